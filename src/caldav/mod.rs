@@ -0,0 +1,213 @@
+#[cfg(test)]
+pub(crate) mod stand_in;
+
+use base64::Engine;
+use std::time::Duration;
+
+const DAV: &str = "DAV:";
+const CALDAV: &str = "urn:ietf:params:xml:ns:caldav";
+
+/// CalDAV collection of tasks (RFC 4791): lista, crea, actualiza y borra `VTODO`s
+pub struct Client {
+    agent: ureq::Agent,
+    /// URL de la colección, terminada en `/`
+    collection: String,
+    /// Header `Authorization` (Basic)
+    auth: Option<String>,
+}
+
+/// Calendar object of the collection (un `.ics` con un VTODO)
+#[derive(Debug, Clone)]
+pub struct Resource {
+    /// URL absoluta del recurso
+    pub url: String,
+    pub etag: Option<String>,
+    /// Contenido iCalendar
+    pub data: String,
+}
+
+impl Client {
+    pub fn new(url: &str, user: Option<&str>, password: Option<&str>) -> Self {
+        let collection = if url.ends_with('/') {
+            url.to_string()
+        } else {
+            format!("{}/", url)
+        };
+        let auth = user.map(|user| {
+            let credentials = format!("{}:{}", user, password.unwrap_or_default());
+            format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(credentials)
+            )
+        });
+        Self {
+            agent: ureq::AgentBuilder::new().timeout(Duration::from_secs(30)).build(),
+            collection,
+            auth,
+        }
+    }
+
+    pub fn collection(&self) -> &str {
+        &self.collection
+    }
+
+    /// Every `VTODO` of the collection (`REPORT` calendar-query)
+    pub fn todos(&self) -> anyhow::Result<Vec<Resource>> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="{DAV}" xmlns:c="{CALDAV}">
+  <d:prop><d:getetag/><c:calendar-data/></d:prop>
+  <c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VTODO"/></c:comp-filter></c:filter>
+</c:calendar-query>"#
+        );
+        let response = self
+            .request("REPORT", &self.collection)
+            .set("Depth", "1")
+            .set("Content-Type", "application/xml; charset=utf-8")
+            .send_string(&body);
+        let xml = check("REPORT", &self.collection, response)?.into_string()?;
+        parse_multistatus(&xml, &self.collection)
+    }
+
+    /// Create `<uid>.ics` in the collection; error si ya existe
+    pub fn create(&self, uid: &str, data: &str) -> anyhow::Result<Resource> {
+        let name: String = uid
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || "-_.@".contains(c) { c } else { '-' })
+            .collect();
+        let url = format!("{}{}.ics", self.collection, name);
+        let response = self
+            .request("PUT", &url)
+            .set("If-None-Match", "*")
+            .set("Content-Type", "text/calendar; charset=utf-8")
+            .send_string(data);
+        let etag = check("PUT", &url, response)?.header("ETag").map(str::to_string);
+        Ok(Resource {
+            url,
+            etag,
+            data: data.to_string(),
+        })
+    }
+
+    /// Replace a resource; con `etag` falla si cambió en el servidor desde que se leyó
+    pub fn update(&self, resource: &Resource, data: &str) -> anyhow::Result<()> {
+        let mut request = self
+            .request("PUT", &resource.url)
+            .set("Content-Type", "text/calendar; charset=utf-8");
+        if let Some(etag) = &resource.etag {
+            request = request.set("If-Match", etag);
+        }
+        check("PUT", &resource.url, request.send_string(data))?;
+        Ok(())
+    }
+
+    pub fn delete(&self, resource: &Resource) -> anyhow::Result<()> {
+        let mut request = self.request("DELETE", &resource.url);
+        if let Some(etag) = &resource.etag {
+            request = request.set("If-Match", etag);
+        }
+        check("DELETE", &resource.url, request.call())?;
+        Ok(())
+    }
+
+    fn request(&self, method: &str, url: &str) -> ureq::Request {
+        let request = self.agent.request(method, url);
+        match &self.auth {
+            Some(auth) => request.set("Authorization", auth),
+            None => request,
+        }
+    }
+}
+
+/// Turn HTTP errors into messages (`412`: cambió en el servidor)
+fn check(
+    method: &str,
+    url: &str,
+    response: Result<ureq::Response, ureq::Error>,
+) -> anyhow::Result<ureq::Response> {
+    match response {
+        Ok(response) => Ok(response),
+        Err(ureq::Error::Status(412, _)) => anyhow::bail!(
+            "{} {}: cambió en el servidor desde la última lectura (412)",
+            method,
+            url
+        ),
+        Err(ureq::Error::Status(401, _)) => {
+            anyhow::bail!("{} {}: usuario o contraseña incorrectos (401)", method, url)
+        }
+        Err(ureq::Error::Status(code, response)) => anyhow::bail!(
+            "{} {}: HTTP {} {}",
+            method,
+            url,
+            code,
+            response.status_text()
+        ),
+        Err(e) => anyhow::bail!("{} {}: {}", method, url, e),
+    }
+}
+
+/// Responses of a `207 Multi-Status` with `calendar-data` (la colección misma se ignora)
+fn parse_multistatus(xml: &str, base: &str) -> anyhow::Result<Vec<Resource>> {
+    let document = roxmltree::Document::parse(xml)
+        .map_err(|e| anyhow::anyhow!("Respuesta CalDAV inválida: {}", e))?;
+    let child_text = |node: roxmltree::Node, namespace: &str, name: &str| {
+        node.descendants()
+            .find(|n| n.has_tag_name((namespace, name)))
+            .and_then(|n| n.text())
+            .map(|t| t.trim().to_string())
+    };
+
+    Ok(document
+        .descendants()
+        .filter(|n| n.has_tag_name((DAV, "response")))
+        .filter_map(|response| {
+            Some(Resource {
+                url: absolute_url(base, &child_text(response, DAV, "href")?),
+                etag: child_text(response, DAV, "getetag"),
+                data: child_text(response, CALDAV, "calendar-data")?,
+            })
+        })
+        .collect())
+}
+
+/// `href` of a response (normalmente un path) as an absolute URL
+fn absolute_url(base: &str, href: &str) -> String {
+    if href.contains("://") {
+        return href.to_string();
+    }
+    let origin_end = base
+        .find("://")
+        .and_then(|scheme| base[scheme + 3..].find('/').map(|i| scheme + 3 + i))
+        .unwrap_or(base.len());
+    format!("{}{}", &base[..origin_end], href)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multistatus() {
+        let xml = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+  <d:response>
+    <d:href>/dav/tareas/</d:href>
+    <d:propstat><d:prop><d:getetag/></d:prop><d:status>HTTP/1.1 404 Not Found</d:status></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/dav/tareas/a.ics</d:href>
+    <d:propstat>
+      <d:prop><d:getetag>"e1"</d:getetag><cal:calendar-data>BEGIN:VCALENDAR&#13;
+END:VCALENDAR&#13;
+</cal:calendar-data></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+        let resources = parse_multistatus(xml, "https://dav.example.com/dav/tareas/").unwrap();
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].url, "https://dav.example.com/dav/tareas/a.ics");
+        assert_eq!(resources[0].etag.as_deref(), Some("\"e1\""));
+        assert_eq!(resources[0].data, "BEGIN:VCALENDAR\r\nEND:VCALENDAR");
+    }
+}
@@ -0,0 +1,121 @@
+//! CalDAV stand-in server for tests: una colección en memoria que entiende `REPORT`,
+//! `PUT` (con `If-Match`/`If-None-Match`) y `DELETE`, como un servidor real.
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+const PATH: &str = "/dav/tareas/";
+
+/// Objeto guardado: (etag, contenido)
+type Store = Arc<Mutex<BTreeMap<String, (String, String)>>>;
+
+pub(crate) struct StandIn {
+    /// URL de la colección
+    pub url: String,
+    store: Store,
+}
+
+impl StandIn {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("http://{}{}", listener.local_addr().expect("addr"), PATH);
+        let store: Store = Arc::default();
+        let shared = store.clone();
+        std::thread::spawn(move || {
+            let mut version = 0u64;
+            for stream in listener.incoming().flatten() {
+                let _ = handle(stream, &shared, &mut version);
+            }
+        });
+        Self { url, store }
+    }
+
+    /// Guardar un objeto como si lo hubiera creado otro cliente
+    pub fn put(&self, name: &str, data: &str) {
+        let mut store = self.store.lock().unwrap();
+        let etag = format!("\"ext-{}\"", store.len());
+        store.insert(format!("{}{}", PATH, name), (etag, data.to_string()));
+    }
+
+    /// Contenido de los objetos de la colección
+    pub fn objects(&self) -> Vec<String> {
+        self.store.lock().unwrap().values().map(|(_, data)| data.clone()).collect()
+    }
+}
+
+fn handle(stream: TcpStream, store: &Store, version: &mut u64) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = BTreeMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let Some((name, value)) = line.trim_end().split_once(':') else {
+            break;
+        };
+        headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+    }
+    let length = headers.get("content-length").and_then(|l| l.parse().ok()).unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8_lossy(&body).to_string();
+
+    let mut store = store.lock().unwrap();
+    let current = store.get(&path).map(|(etag, _)| etag.clone());
+    let if_match = headers.get("if-match");
+    let conflict = (headers.get("if-none-match").is_some_and(|v| v == "*") && current.is_some())
+        || if_match.is_some_and(|etag| Some(etag) != current.as_ref());
+
+    let (status, etag, response) = match method.as_str() {
+        "REPORT" => {
+            let responses: String = store
+                .iter()
+                .map(|(href, (etag, data))| {
+                    format!(
+                        "<d:response><d:href>{}</d:href><d:propstat><d:prop><d:getetag>{}</d:getetag><c:calendar-data>{}</c:calendar-data></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>",
+                        href,
+                        etag.replace('"', "&quot;"),
+                        data.replace('&', "&amp;").replace('<', "&lt;").replace('\r', "&#13;")
+                    )
+                })
+                .collect();
+            let xml = format!(
+                "<?xml version=\"1.0\"?><d:multistatus xmlns:d=\"DAV:\" xmlns:c=\"urn:ietf:params:xml:ns:caldav\">{}</d:multistatus>",
+                responses
+            );
+            ("207 Multi-Status", None, xml)
+        }
+        "PUT" | "DELETE" if conflict => ("412 Precondition Failed", None, String::new()),
+        "PUT" => {
+            *version += 1;
+            let etag = format!("\"v{}\"", version);
+            let status = if current.is_some() { "204 No Content" } else { "201 Created" };
+            store.insert(path, (etag.clone(), body));
+            (status, Some(etag), String::new())
+        }
+        "DELETE" if current.is_some() => {
+            store.remove(&path);
+            ("204 No Content", None, String::new())
+        }
+        _ => ("404 Not Found", None, String::new()),
+    };
+
+    let mut stream = stream;
+    let etag = etag.map(|e| format!("ETag: {}\r\n", e)).unwrap_or_default();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        etag,
+        response.len(),
+        response
+    )?;
+    stream.flush()
+}
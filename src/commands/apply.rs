@@ -0,0 +1,38 @@
+use crate::vault::plan::Plan;
+use std::path::Path;
+
+/// Apply a plan saved with `--plan-out`.
+/// Falla sin tocar nada si el plan es de otro vault o si algún archivo cambió desde que se generó.
+pub fn run(vault: &Path, plan_path: &Path) -> anyhow::Result<()> {
+    let plan = Plan::load(plan_path)?;
+
+    let same_vault = match (plan.vault.canonicalize(), vault.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => plan.vault == vault,
+    };
+    if !same_vault {
+        anyhow::bail!(
+            "El plan es de otro vault: {} (configurado: {})",
+            plan.vault.display(),
+            vault.display()
+        );
+    }
+
+    if plan.is_empty() {
+        println!("ℹ️  El plan no tiene cambios");
+        return Ok(());
+    }
+
+    println!(
+        "Aplicando plan '{}' ({} cambios)...",
+        plan.command,
+        plan.changes.len()
+    );
+    let backups = plan.apply()?;
+
+    println!("✅ Plan aplicado: {} cambios", plan.changes.len());
+    if !backups.is_empty() {
+        println!("   {} backups en {}", backups.len(), plan.vault.join(".arc/backups").display());
+    }
+    Ok(())
+}
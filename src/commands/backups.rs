@@ -0,0 +1,402 @@
+use crate::core::config::Config;
+use crate::core::date_expr;
+use crate::utils::cli::BackupsAction;
+use crate::utils::{diff, file};
+use crate::utils::vault::VaultWalker;
+use crate::vault::plan::Plan;
+use chrono::{Local, NaiveDateTime};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Backup file in `.arc/backups`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Backup {
+    path: PathBuf,
+    /// Nota original, relativa al vault
+    note: PathBuf,
+    taken: NaiveDateTime,
+}
+
+/// `mad backups list|diff|restore|prune`
+pub fn run(vault: &Path, config: &Config, action: BackupsAction) -> anyhow::Result<()> {
+    match action {
+        BackupsAction::List { note } => list(vault, note.as_deref()),
+        BackupsAction::Diff { backup } => show_diff(vault, config, &backup),
+        BackupsAction::Restore { backup } => restore(vault, config, &backup),
+        BackupsAction::Prune {
+            older_than,
+            keep,
+            dry_run,
+        } => prune(vault, older_than, keep, dry_run),
+    }
+}
+
+fn list(vault: &Path, note: Option<&str>) -> anyhow::Result<()> {
+    let mut backups = collect(vault)?;
+    if let Some(query) = note {
+        let query = note_query(vault, query)?;
+        backups.retain(|b| matches_note(b, &query));
+    }
+
+    if backups.is_empty() {
+        println!("No hay backups.");
+        return Ok(());
+    }
+
+    let backups_dir = file::backups_dir(vault);
+    for backup in &backups {
+        println!(
+            "{}  {}  {}",
+            backup.taken.format("%Y-%m-%d %H:%M:%S"),
+            backup.note.display(),
+            backup
+                .path
+                .strip_prefix(&backups_dir)
+                .unwrap_or(&backup.path)
+                .display()
+        );
+    }
+    println!("\n{} backups", backups.len());
+    Ok(())
+}
+
+fn show_diff(vault: &Path, config: &Config, arg: &str) -> anyhow::Result<()> {
+    let backup = find_backup(vault, arg)?;
+    let original = original_path(vault, config, &backup);
+    let saved = fs::read_to_string(&backup.path)?;
+    let current = fs::read_to_string(&original).unwrap_or_default();
+
+    let name = backup.note.display().to_string();
+    let out = diff::unified(
+        &saved,
+        &current,
+        &format!("backup/{}", name),
+        &format!("actual/{}", name),
+    );
+    if out.is_empty() {
+        println!("ℹ️  Sin diferencias con la nota actual");
+    } else {
+        print!("{}", out);
+    }
+    Ok(())
+}
+
+/// Restore through a plan: the current content gets its own backup and `mad undo` reverts it
+fn restore(vault: &Path, config: &Config, arg: &str) -> anyhow::Result<()> {
+    let backup = find_backup(vault, arg)?;
+    let original = original_path(vault, config, &backup);
+    let saved = fs::read_to_string(&backup.path)?;
+
+    let mut plan = Plan::new("backups restore", vault);
+    if original.exists() {
+        if !plan.edit(&original, saved)? {
+            println!("ℹ️  La nota ya tiene ese contenido");
+            return Ok(());
+        }
+    } else {
+        plan.create(&original, saved);
+    }
+    plan.apply()?;
+
+    println!(
+        "✅ Restaurado {} (backup del {})",
+        backup.note.display(),
+        backup.taken.format("%Y-%m-%d %H:%M:%S")
+    );
+    println!("   Para revertir: mad undo");
+    Ok(())
+}
+
+fn prune(
+    vault: &Path,
+    older_than: Option<(i64, char)>,
+    keep: Option<usize>,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let cutoff = match older_than {
+        Some((amount, unit)) => {
+            let today = Local::now().naive_local();
+            let date = date_expr::shift(today.date(), -amount, unit)
+                .ok_or_else(|| anyhow::anyhow!("--older-than fuera de rango"))?;
+            Some(date.and_time(today.time()))
+        }
+        None => None,
+    };
+
+    let backups = collect(vault)?;
+    let prunable = select_prunable(&backups, cutoff, keep.unwrap_or(0));
+
+    if prunable.is_empty() {
+        println!("No hay backups para eliminar.");
+        return Ok(());
+    }
+
+    let backups_dir = file::backups_dir(vault);
+    let mut freed = 0;
+    for backup in &prunable {
+        let rel = backup.path.strip_prefix(&backups_dir).unwrap_or(&backup.path);
+        freed += fs::metadata(&backup.path).map(|m| m.len()).unwrap_or(0);
+        if dry_run {
+            println!("  🗑️  {}", rel.display());
+            continue;
+        }
+        fs::remove_file(&backup.path)?;
+        remove_empty_dirs(&backups_dir, backup.path.parent());
+    }
+
+    if dry_run {
+        println!(
+            "\n🔍 Dry-run: se eliminarían {} backups ({} KB)",
+            prunable.len(),
+            freed / 1024
+        );
+    } else {
+        println!("✅ {} backups eliminados ({} KB)", prunable.len(), freed / 1024);
+    }
+    Ok(())
+}
+
+/// Backups to delete: per note, everything except the newest `keep`,
+/// and only those older than `cutoff` if given
+fn select_prunable(
+    backups: &[Backup],
+    cutoff: Option<NaiveDateTime>,
+    keep: usize,
+) -> Vec<&Backup> {
+    let mut by_note: BTreeMap<&Path, Vec<&Backup>> = BTreeMap::new();
+    for backup in backups {
+        by_note.entry(&backup.note).or_default().push(backup);
+    }
+
+    let mut prunable = Vec::new();
+    for mut group in by_note.into_values() {
+        // `_2` sigue a su par del mismo segundo
+        group.sort_by(|a, b| b.taken.cmp(&a.taken).then_with(|| b.path.cmp(&a.path)));
+        prunable.extend(
+            group
+                .into_iter()
+                .skip(keep)
+                .filter(|b| cutoff.is_none_or(|c| b.taken < c)),
+        );
+    }
+    prunable.sort_by(|a, b| a.path.cmp(&b.path));
+    prunable
+}
+
+/// Every backup, newest first
+fn collect(vault: &Path) -> anyhow::Result<Vec<Backup>> {
+    let backups_dir = file::backups_dir(vault);
+    let mut files = Vec::new();
+    walk_files(&backups_dir, &mut files)?;
+
+    let mut backups: Vec<Backup> = files
+        .into_iter()
+        .filter_map(|path| parse_backup(&backups_dir, path))
+        .collect();
+    backups.sort_by(|a, b| b.taken.cmp(&a.taken).then_with(|| b.path.cmp(&a.path)));
+    Ok(backups)
+}
+
+fn walk_files(dir: &Path, out: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            walk_files(&path, out)?;
+        } else {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// `.arc/backups/Notas/dev/api_20260202_131045.md.bak` → nota `Notas/dev/api.md`
+fn parse_backup(backups_dir: &Path, path: PathBuf) -> Option<Backup> {
+    let rel = path.strip_prefix(backups_dir).ok()?;
+    let name = rel.file_name()?.to_str()?;
+    let (base, ext) = match name.strip_suffix(".md.bak") {
+        Some(base) => (base, ".md"),
+        None => (name.strip_suffix(".bak")?, ""),
+    };
+    let (stem, taken) = split_timestamp(base)?;
+    let note = rel
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(format!("{}{}", stem, ext));
+    Some(Backup { path, note, taken })
+}
+
+/// `api_20260202_131045` o `api_20260202_131045_2` → ("api", 2026-02-02 13:10:45)
+fn split_timestamp(base: &str) -> Option<(&str, NaiveDateTime)> {
+    let base = match base.rsplit_once('_') {
+        Some((head, n)) if (1..6).contains(&n.len()) && n.chars().all(|c| c.is_ascii_digit()) => {
+            head
+        }
+        _ => base,
+    };
+    // "_" + "YYYYMMDD_HHMMSS"
+    let split = base.len().checked_sub(16)?;
+    if !base.is_char_boundary(split) {
+        return None;
+    }
+    let (stem, ts) = base.split_at(split);
+    let taken = NaiveDateTime::parse_from_str(ts.strip_prefix('_')?, "%Y%m%d_%H%M%S").ok()?;
+    (!stem.is_empty()).then_some((stem, taken))
+}
+
+/// Backup given as a path (absolute, relative to cwd or to `.arc/backups`)
+fn find_backup(vault: &Path, arg: &str) -> anyhow::Result<Backup> {
+    let backups_dir = file::backups_dir(vault);
+    let candidates = [
+        std::env::current_dir()?.join(arg),
+        backups_dir.join(arg),
+    ];
+
+    for candidate in candidates {
+        if !candidate.is_file() {
+            continue;
+        }
+        let Some(rel) = file::vault_relative(vault, &candidate) else {
+            continue;
+        };
+        if let Some(backup) = parse_backup(&backups_dir, vault.join(rel)) {
+            return Ok(backup);
+        }
+    }
+    anyhow::bail!("Backup no encontrado en {}: {}", backups_dir.display(), arg)
+}
+
+/// Absolute path of the backed up note. Backups planos (formato anterior) se buscan por nombre.
+fn original_path(vault: &Path, config: &Config, backup: &Backup) -> PathBuf {
+    let direct = vault.join(&backup.note);
+    if direct.exists() || backup.note.parent() != Some(Path::new("")) {
+        return direct;
+    }
+
+    let mut found = Vec::new();
+    let templates_path = vault.join(&config.templates_dir);
+    let _ = VaultWalker::new(vault)
+        .exclude_templates(&templates_path)
+        .walk_paths(|path| {
+            if path.file_name() == backup.note.file_name() {
+                found.push(path.to_path_buf());
+            }
+            Ok(())
+        });
+    match found.as_slice() {
+        [only] => only.clone(),
+        _ => direct,
+    }
+}
+
+/// `[nota]` de `mad backups list`: path a un archivo o nombre/path relativo al vault
+fn note_query(vault: &Path, note: &str) -> anyhow::Result<PathBuf> {
+    let path = Path::new(note);
+    let abs_path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    if abs_path.is_file() {
+        if let Some(rel) = file::vault_relative(vault, &abs_path) {
+            return Ok(rel);
+        }
+    }
+
+    let name = note.trim().trim_start_matches("[[").trim_end_matches("]]");
+    Ok(if name.to_lowercase().ends_with(".md") {
+        PathBuf::from(name)
+    } else {
+        PathBuf::from(format!("{}.md", name))
+    })
+}
+
+fn matches_note(backup: &Backup, query: &Path) -> bool {
+    let by_name = query.parent() == Some(Path::new(""))
+        || backup.note.parent() == Some(Path::new(""));
+    backup.note == query || (by_name && backup.note.file_name() == query.file_name())
+}
+
+/// Remove now-empty directories up to `.arc/backups`
+fn remove_empty_dirs(root: &Path, mut dir: Option<&Path>) {
+    while let Some(d) = dir {
+        if d == root || !d.starts_with(root) || fs::remove_dir(d).is_err() {
+            break;
+        }
+        dir = d.parent();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn backup(note: &str, taken: &str) -> Backup {
+        let taken = NaiveDateTime::parse_from_str(taken, "%Y-%m-%d %H:%M").unwrap();
+        Backup {
+            path: PathBuf::from(format!("{}_{}", note, taken.format("%Y%m%d_%H%M%S"))),
+            note: PathBuf::from(note),
+            taken,
+        }
+    }
+
+    #[test]
+    fn test_backup_names_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::create_dir_all(vault.join("Notas/dev")).unwrap();
+        fs::write(vault.join("Notas/dev/api_v2.md"), "uno").unwrap();
+
+        let first = file::create_backup(vault, &vault.join("Notas/dev/api_v2.md")).unwrap();
+        let second = file::create_backup(vault, &vault.join("Notas/dev/api_v2.md")).unwrap();
+        assert_ne!(first, second);
+        assert!(first.starts_with(vault.join(".arc/backups/Notas/dev")));
+
+        let backups = collect(vault).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(select_prunable(&backups, None, 1)[0].path, first);
+        assert!(backups
+            .iter()
+            .all(|b| b.note == Path::new("Notas/dev/api_v2.md")));
+
+        // Formato plano anterior
+        let legacy = parse_backup(
+            Path::new("/v/.arc/backups"),
+            PathBuf::from("/v/.arc/backups/api_20260202_131045.md.bak"),
+        )
+        .unwrap();
+        assert_eq!(legacy.note, Path::new("api.md"));
+        assert!(matches_note(&legacy, Path::new("Notas/dev/api.md")));
+        assert!(split_timestamp("api_2026").is_none());
+    }
+
+    #[test]
+    fn test_prune_keeps_newest_per_note() {
+        let backups = vec![
+            backup("a.md", "2026-10-15 10:00"),
+            backup("a.md", "2026-09-01 10:00"),
+            backup("a.md", "2026-08-01 10:00"),
+            backup("b.md", "2026-08-01 10:00"),
+        ];
+        let cutoff = NaiveDateTime::parse_from_str("2026-09-16 00:00", "%Y-%m-%d %H:%M").ok();
+
+        let notes = |v: Vec<&Backup>| -> Vec<String> {
+            v.iter()
+                .map(|b| format!("{} {}", b.note.display(), b.taken.format("%m-%d")))
+                .collect()
+        };
+        assert_eq!(
+            notes(select_prunable(&backups, cutoff, 1)),
+            vec!["a.md 08-01", "a.md 09-01"]
+        );
+        assert_eq!(notes(select_prunable(&backups, cutoff, 0)).len(), 3);
+        assert_eq!(
+            notes(select_prunable(&backups, None, 2)),
+            vec!["a.md 08-01"]
+        );
+    }
+}
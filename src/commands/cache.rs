@@ -1,26 +1,94 @@
 use crate::core::config::Config;
-use crate::tags::{cache as tags_cache, primary_cache};
+use crate::tags::primary_cache;
 use crate::utils::cli::CacheKind;
+use crate::utils::output::{self, OutputFormat};
+use crate::vault::index::VaultIndex;
+use serde::Serialize;
+use std::fs;
 use std::path::Path;
 
-pub fn run(vault: &Path, config: &Config, kind: CacheKind) -> anyhow::Result<()> {
-    let config_dir = Config::config_dir()?;
+/// `mad cache --json|--format tsv`
+#[derive(Debug, Serialize)]
+struct Summary {
+    notes: usize,
+    dir_tags: usize,
+}
+
+/// `mad cache dir-tags --json|--format tsv`
+#[derive(Debug, Serialize)]
+struct DirTagRecord {
+    tag: String,
+    dirs: Vec<String>,
+}
+
+/// Regenera desde cero el índice del vault (tags, dir-tags, links y tareas)
+pub fn run(vault: &Path, config: &Config, kind: CacheKind, format: OutputFormat) -> anyhow::Result<()> {
     let templates_path = vault.join(&config.templates_dir);
+    let index = VaultIndex::rebuild(vault, &templates_path)?;
+
+    // Caches anteriores al índice unificado
+    let config_dir = Config::config_dir()?;
+    for legacy in ["tags_cache.json", "primary_tags_cache.json"] {
+        let _ = fs::remove_file(config_dir.join(legacy));
+    }
+
+    if format.is_structured() {
+        return print_records(&index, kind, format);
+    }
 
     match kind {
         CacheKind::All => {
-            let root = tags_cache::collect(vault)?;
-            tags_cache::update(vault, &config_dir, &root)?;
-            let cache = primary_cache::collect(vault, &templates_path)?;
-            primary_cache::update(&config_dir, &cache)?;
-            println!("✅ Cache de tags regenerado (incluye dir-tags).");
+            println!("✅ Índice del vault regenerado ({} notas).", index.len());
         }
         CacheKind::DirTags => {
-            let cache = primary_cache::collect(vault, &templates_path)?;
-            primary_cache::update(&config_dir, &cache)?;
-            println!("✅ Cache de dir-tags regenerado.");
+            let cache = primary_cache::collect(&index);
+            println!(
+                "✅ Cache de dir-tags regenerado ({} dir-tags).",
+                cache.dirs_by_tag.len()
+            );
         }
     }
 
     Ok(())
 }
+
+/// `all`: a summary object (notes, dir-tags); `dir-tags`: one record per dir-tag (one TSV line per directory)
+fn print_records(index: &VaultIndex, kind: CacheKind, format: OutputFormat) -> anyhow::Result<()> {
+    let cache = primary_cache::collect(index);
+    match kind {
+        CacheKind::All => {
+            let summary = Summary {
+                notes: index.len(),
+                dir_tags: cache.dirs_by_tag.len(),
+            };
+            if format == OutputFormat::Json {
+                output::print_json(&summary)?;
+            } else {
+                let cells = [summary.notes.to_string(), summary.dir_tags.to_string()];
+                output::print_lines(&[output::tsv_line(&cells)])?;
+            }
+        }
+        CacheKind::DirTags => {
+            let mut records: Vec<DirTagRecord> = cache
+                .dirs_by_tag
+                .into_iter()
+                .map(|(tag, mut dirs)| {
+                    dirs.sort();
+                    DirTagRecord { tag, dirs }
+                })
+                .collect();
+            records.sort_by(|a, b| a.tag.cmp(&b.tag));
+
+            if format == OutputFormat::Json {
+                output::print_json(&records)?;
+            } else {
+                let lines: Vec<String> = records
+                    .iter()
+                    .flat_map(|r| r.dirs.iter().map(|dir| output::tsv_line(&[&r.tag, dir])))
+                    .collect();
+                output::print_lines(&lines)?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use super::{daily, links, periodic};
+use crate::core::config::Config;
+use crate::core::period::Period;
+use crate::core::section;
+use crate::core::template::TemplateSelection;
+use crate::ui::prompts;
+use crate::utils::cli::CaptureTarget;
+use crate::vault::index::VaultIndex;
+use crate::vault::links::LinkGraph;
+use crate::vault::plan::Plan;
+use chrono::Local;
+use std::io::Read;
+use std::path::Path;
+
+/// Quick capture without opening an editor
+/// - `mad capture "texto"` - `- 10:32 texto` en la daily note de hoy (se crea si no existe)
+/// - `echo ... | mad capture -` - una entrada por línea de stdin
+/// - `--task` captura `- [ ] ...`, `--inbox` va a la nota inbox (`- 2026-10-16 10:32 texto`)
+pub fn run(
+    config: &Config,
+    vault: &Path,
+    text: Option<String>,
+    task: bool,
+    target: Option<CaptureTarget>,
+    heading: Option<String>,
+) -> anyhow::Result<()> {
+    let input = read_input(text, "mad capture \"texto\" o echo ... | mad capture -")?;
+    let entries: Vec<&str> = input
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    if entries.is_empty() {
+        anyhow::bail!("Nada que capturar: el texto está vacío");
+    }
+
+    let target = match target {
+        Some(target) => target,
+        None => match config.capture.target.as_deref() {
+            None | Some("daily") => CaptureTarget::Daily,
+            Some("inbox") => CaptureTarget::Inbox,
+            Some(other) => anyhow::bail!(
+                "capture.target inválido: {} (usa \"daily\" o \"inbox\")",
+                other
+            ),
+        },
+    };
+
+    let now = Local::now();
+    let stamp = match target {
+        CaptureTarget::Daily => now.format(&config.time).to_string(),
+        CaptureTarget::Inbox => format!(
+            "{} {}",
+            now.format(&config.date),
+            now.format(&config.time)
+        ),
+    };
+    let marker = if task { "- [ ] " } else { "- " };
+    let block: Vec<String> = entries
+        .iter()
+        .map(|entry| format!("{}{} {}", marker, stamp, entry))
+        .collect();
+
+    let mut plan = Plan::new("capture", vault);
+    plan.backup = false;
+    let path = match target {
+        CaptureTarget::Daily => {
+            let today = now.date_naive();
+            let path = periodic::note_path(config, vault, Period::Day, today)?;
+            if !path.exists() {
+                // Sin prompts ni editor: template por defecto y sin rollover (no toca otras notas).
+                // La creación va en el mismo plan: `mad undo` deshace la captura entera
+                let Some(created) =
+                    daily::create(config, vault, today, &path, &TemplateSelection::Default, true, false)?
+                else {
+                    anyhow::bail!("No se pudo crear la daily note de hoy");
+                };
+                plan.changes = created.changes;
+            }
+            path
+        }
+        CaptureTarget::Inbox => vault.join(config.capture.inbox.as_deref().unwrap_or("Inbox.md")),
+    };
+
+    let heading = heading.or_else(|| config.capture.heading.clone());
+    let block = block.join("\n");
+    if plan.exists(&path) {
+        let content = plan.read(&path)?;
+        plan.edit(&path, section::append_to_section(&content, heading.as_deref(), &block))?;
+    } else {
+        let content = section::append_to_section("# Inbox\n", heading.as_deref(), &block);
+        plan.create(&path, content);
+    }
+    plan.apply()?;
+
+    println!(
+        "✅ Capturado en {}",
+        path.strip_prefix(vault).unwrap_or(&path).display()
+    );
+    Ok(())
+}
+
+/// Append raw content to a note, under `heading` if given (`mad append <nota> --heading Log`)
+pub fn append(
+    config: &Config,
+    vault: &Path,
+    note: &str,
+    text: Option<String>,
+    heading: Option<String>,
+) -> anyhow::Result<()> {
+    let templates_path = vault.join(&config.templates_dir);
+    let index = VaultIndex::open(vault, &templates_path)?;
+    let graph = LinkGraph::from_items(vault, index.link_items());
+    let path = links::resolve_note_arg(vault, &graph.resolver, note)?;
+
+    let input = read_input(text, "mad append <nota> \"texto\" o echo ... | mad append <nota> -")?;
+    if input.trim().is_empty() {
+        anyhow::bail!("Nada que agregar: el texto está vacío");
+    }
+
+    let mut plan = Plan::new("append", vault);
+    plan.backup = false;
+    let content = plan.read(&path)?;
+    let after = section::append_to_section(&content, heading.as_deref(), &input);
+    plan.edit(&path, after)?;
+    plan.apply()?;
+
+    println!(
+        "✅ Agregado a {}",
+        path.strip_prefix(vault).unwrap_or(&path).display()
+    );
+    Ok(())
+}
+
+/// Text argument, or stdin with `-` (or without text when stdin is piped)
+fn read_input(text: Option<String>, usage: &str) -> anyhow::Result<String> {
+    match text.as_deref() {
+        Some("-") => {}
+        Some(_) => return Ok(text.unwrap_or_default()),
+        None if prompts::is_tty() => anyhow::bail!("Falta el texto: {}", usage),
+        None => {}
+    }
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    Ok(input)
+}
@@ -0,0 +1,409 @@
+use crate::commands::retag;
+use crate::core::config::Config;
+use crate::core::frontmatter;
+use crate::tags::{parser, primary_cache};
+use crate::utils::output::{self, OutputFormat};
+use crate::utils::vault::VaultWalker;
+use crate::vault::index::VaultIndex;
+use crate::vault::links::LinkGraph;
+use crate::vault::plan::{Plan, PlanMode};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Problem found by `mad check`. Paths are relative to the vault.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Issue {
+    /// Nota bajo `tag_root` sin línea `{ #dir-tag }`
+    MissingDirTag { path: PathBuf, expected: String },
+    /// El dir-tag no coincide con el directorio de la nota
+    DirTagMismatch {
+        path: PathBuf,
+        found: String,
+        expected: String,
+    },
+    /// Frontmatter que no se puede parsear (el resto de los comandos lo ignora)
+    MalformedFrontmatter { path: PathBuf, error: String },
+    BrokenLink {
+        path: PathBuf,
+        line: usize,
+        target: String,
+    },
+    /// Varias notas con el mismo nombre: `[[nombre]]` es ambiguo
+    DuplicateName { name: String, paths: Vec<PathBuf> },
+    /// Sin contenido además del frontmatter y el dir-tag
+    EmptyNote { path: PathBuf },
+    /// Dir-tag usado en más de un directorio
+    SpreadTag { tag: String, dirs: Vec<String> },
+}
+
+impl Issue {
+    /// `--fix` corrects the dir-tag issues (same as `mad retag`)
+    pub fn is_fixable(&self) -> bool {
+        matches!(self, Issue::MissingDirTag { .. } | Issue::DirTagMismatch { .. })
+    }
+
+    fn path(&self) -> Option<&Path> {
+        match self {
+            Issue::MissingDirTag { path, .. }
+            | Issue::DirTagMismatch { path, .. }
+            | Issue::MalformedFrontmatter { path, .. }
+            | Issue::BrokenLink { path, .. }
+            | Issue::EmptyNote { path } => Some(path),
+            Issue::DuplicateName { .. } | Issue::SpreadTag { .. } => None,
+        }
+    }
+
+    /// Same as the `kind` field of the JSON output
+    fn kind(&self) -> &'static str {
+        match self {
+            Issue::MissingDirTag { .. } => "missing_dir_tag",
+            Issue::DirTagMismatch { .. } => "dir_tag_mismatch",
+            Issue::MalformedFrontmatter { .. } => "malformed_frontmatter",
+            Issue::BrokenLink { .. } => "broken_link",
+            Issue::DuplicateName { .. } => "duplicate_name",
+            Issue::EmptyNote { .. } => "empty_note",
+            Issue::SpreadTag { .. } => "spread_tag",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Issue::MissingDirTag { .. } => "Sin dir-tag",
+            Issue::DirTagMismatch { .. } => "Dir-tag distinto al directorio",
+            Issue::MalformedFrontmatter { .. } => "Frontmatter inválido",
+            Issue::BrokenLink { .. } => "Enlaces rotos",
+            Issue::DuplicateName { .. } => "Nombres de archivo duplicados",
+            Issue::EmptyNote { .. } => "Notas vacías",
+            Issue::SpreadTag { .. } => "Dir-tags en varios directorios",
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Issue::MissingDirTag { path, expected } => {
+                format!("{} (esperado: {{ #{} }})", path.display(), expected)
+            }
+            Issue::DirTagMismatch {
+                path,
+                found,
+                expected,
+            } => format!("{}: {{ #{} }} → {{ #{} }}", path.display(), found, expected),
+            Issue::MalformedFrontmatter { path, error } => format!("{}: {}", path.display(), error),
+            Issue::BrokenLink { path, line, target } => {
+                format!("{}:{} → {}", path.display(), line, target)
+            }
+            Issue::DuplicateName { name, paths } => {
+                let paths: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+                format!("{}: {}", name, paths.join(", "))
+            }
+            Issue::EmptyNote { path } => path.display().to_string(),
+            Issue::SpreadTag { tag, dirs } => format!("#{}: {}", tag, dirs.join(", ")),
+        }
+    }
+}
+
+/// `mad check --json` (con `--format tsv`: una línea por problema: kind, path, descripción)
+#[derive(Debug, Serialize)]
+struct Report {
+    vault: PathBuf,
+    notes: usize,
+    issues: Vec<Issue>,
+    /// Problemas corregidos con `--fix`
+    fixed: Vec<Issue>,
+}
+
+/// Audit the vault. With `fix`, plan a retag of the notes with dir-tag issues.
+/// Returns true if there are problems left (exit code 1).
+pub fn run(vault: &Path, config: &Config, fix: bool, format: OutputFormat, mode: &PlanMode) -> anyhow::Result<bool> {
+    let templates_path = vault.join(&config.templates_dir);
+    let mut index = VaultIndex::open(vault, &templates_path)?;
+    let mut issues = inspect(vault, config, &index)?;
+
+    let mut fixed = Vec::new();
+    if fix {
+        let mut plan = Plan::new("check --fix", vault);
+        for issue in issues.iter().filter(|i| i.is_fixable()) {
+            let Some(rel) = issue.path() else { continue };
+            let path = vault.join(rel);
+            let content = plan.read(&path)?;
+            match retag::retag_file_inner(vault, config, &path, &content, false, &mut plan) {
+                Ok(true) => fixed.push(issue.clone()),
+                Ok(false) => {}
+                Err(e) => eprintln!("❌ {}: {}", rel.display(), e),
+            }
+        }
+
+        let applied = if format.is_structured() {
+            plan.apply()?;
+            !plan.is_empty()
+        } else {
+            plan.finish(mode)?
+        };
+
+        if applied {
+            // Los dir-tags corregidos también cambian los tags repartidos
+            index = VaultIndex::open(vault, &templates_path)?;
+            issues = inspect(vault, config, &index)?;
+        } else {
+            fixed.clear();
+        }
+    }
+
+    let report = Report {
+        vault: vault.to_path_buf(),
+        notes: index.len(),
+        issues,
+        fixed,
+    };
+
+    match format {
+        OutputFormat::Json => output::print_json(&report)?,
+        OutputFormat::Tsv => output::print(format, &report.issues, |issue| {
+            let path = issue.path().map(|p| p.display().to_string()).unwrap_or_default();
+            vec![issue.kind().to_string(), path, issue.describe()]
+        })?,
+        OutputFormat::Text => print_report(&report),
+    }
+
+    Ok(!report.issues.is_empty())
+}
+
+/// Every problem in the vault, grouped by kind
+pub fn inspect(vault: &Path, config: &Config, index: &VaultIndex) -> anyhow::Result<Vec<Issue>> {
+    let templates_path = vault.join(&config.templates_dir);
+    let rel = |path: &Path| path.strip_prefix(vault).unwrap_or(path).to_path_buf();
+
+    let mut missing = Vec::new();
+    let mut mismatched = Vec::new();
+    let mut malformed = Vec::new();
+    let mut empty = Vec::new();
+    let mut by_name: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+
+    VaultWalker::new(vault)
+        .exclude_templates(&templates_path)
+        .walk(|path, content| {
+            let body = match frontmatter::extract(content) {
+                Ok((_, body)) if !has_unclosed_frontmatter(content) => body,
+                Ok(_) => {
+                    malformed.push(Issue::MalformedFrontmatter {
+                        path: rel(path),
+                        error: "falta el `---` de cierre".to_string(),
+                    });
+                    content.to_string()
+                }
+                Err(e) => {
+                    malformed.push(Issue::MalformedFrontmatter {
+                        path: rel(path),
+                        error: e.to_string().lines().next().unwrap_or_default().to_string(),
+                    });
+                    // Mismo corte que `frontmatter::extract`
+                    content
+                        .strip_prefix("---")
+                        .and_then(|rest| rest.find("\n---").map(|pos| rest[pos + 4..].to_string()))
+                        .unwrap_or_else(|| content.to_string())
+                }
+            };
+
+            let primary = parser::extract_primary_tag(&body);
+            if let Some(expected) = expected_dir_tag(vault, config, path) {
+                match &primary {
+                    None => missing.push(Issue::MissingDirTag {
+                        path: rel(path),
+                        expected,
+                    }),
+                    Some(found) if found.to_slash_string() != expected => {
+                        mismatched.push(Issue::DirTagMismatch {
+                            path: rel(path),
+                            found: found.to_slash_string(),
+                            expected,
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            if is_empty_body(&body, primary.is_some()) {
+                empty.push(Issue::EmptyNote { path: rel(path) });
+            }
+
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                by_name.entry(stem.to_lowercase()).or_default().push(rel(path));
+            }
+            Ok(())
+        })?;
+
+    let graph = LinkGraph::from_items(vault, index.link_items());
+    let broken = graph.forward.iter().flat_map(|(path, links)| {
+        links
+            .iter()
+            .filter(|l| l.target.is_none())
+            .map(move |l| Issue::BrokenLink {
+                path: rel(path),
+                line: l.link.line,
+                target: l.link.target.clone(),
+            })
+    });
+
+    let duplicates = by_name.into_iter().filter(|(_, paths)| paths.len() > 1).map(|(name, mut paths)| {
+        paths.sort();
+        Issue::DuplicateName { name, paths }
+    });
+
+    let spread: BTreeMap<String, Vec<String>> = primary_cache::collect(index)
+        .dirs_by_tag
+        .into_iter()
+        .filter(|(_, dirs)| dirs.len() > 1)
+        .collect();
+
+    let mut issues = Vec::new();
+    for mut group in [missing, mismatched, malformed, empty] {
+        group.sort_by(|a, b| a.path().cmp(&b.path()));
+        issues.extend(group);
+    }
+    issues.extend(broken);
+    issues.extend(duplicates);
+    issues.extend(spread.into_iter().map(|(tag, dirs)| Issue::SpreadTag { tag, dirs }));
+    Ok(issues)
+}
+
+/// Dir-tag a note should have. None outside `tag_root` or directly inside it.
+fn expected_dir_tag(vault: &Path, config: &Config, path: &Path) -> Option<String> {
+    retag::derive_tag_from_path(vault, config, path)
+        .ok()
+        .filter(|tag| !tag.is_empty())
+}
+
+/// `---` at the start with no closing line: `frontmatter::extract` reads it as body
+fn has_unclosed_frontmatter(content: &str) -> bool {
+    content
+        .strip_prefix("---")
+        .is_some_and(|rest| rest.starts_with(['\n', '\r']) && !rest.contains("\n---"))
+}
+
+fn is_empty_body(body: &str, has_dir_tag: bool) -> bool {
+    let body = body.trim_start();
+    let rest = match body.find(" }") {
+        Some(end) if has_dir_tag => &body[end + 2..],
+        _ => body,
+    };
+    rest.trim().is_empty()
+}
+
+fn print_report(report: &Report) {
+    println!("🩺 Revisando {} ({} notas)", report.vault.display(), report.notes);
+
+    if !report.fixed.is_empty() {
+        println!("\n🔧 Corregidos ({}):", report.fixed.len());
+        for issue in &report.fixed {
+            println!("  ✅ {}", issue.describe());
+        }
+    }
+
+    let mut title = "";
+    for issue in &report.issues {
+        if issue.title() != title {
+            title = issue.title();
+            let count = report.issues.iter().filter(|i| i.title() == title).count();
+            println!("\n{} ({}):", title, count);
+        }
+        println!("  ✗ {}", issue.describe());
+    }
+
+    let fixable = report.issues.iter().filter(|i| i.is_fixable()).count();
+    if report.issues.is_empty() {
+        println!("\n✅ Sin problemas");
+    } else if fixable > 0 {
+        println!(
+            "\n⚠️  {} problemas ({} se corrigen con mad check --fix)",
+            report.issues.len(),
+            fixable
+        );
+    } else {
+        println!("\n⚠️  {} problemas", report.issues.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn config(vault: &Path) -> Config {
+        Config {
+            vault: vault.to_str().unwrap().to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_inspect_finds_every_kind() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::create_dir_all(vault.join("Notas/dev")).unwrap();
+        fs::create_dir_all(vault.join("Notas/ops")).unwrap();
+        fs::create_dir_all(vault.join("Templates")).unwrap();
+        fs::write(vault.join("Notas/dev/ok.md"), "{ #dev }\n\nVer [[falta]] y [[api]]\n").unwrap();
+        fs::write(vault.join("Notas/dev/api.md"), "Sin tag\n").unwrap();
+        fs::write(vault.join("Notas/ops/api.md"), "{ #dev }\n").unwrap();
+        fs::write(vault.join("Notas/ops/rota.md"), "---\ntags: [a\n---\n{ #ops }\n\nx\n").unwrap();
+        fs::write(vault.join("Templates/t.md"), "[[nada]]\n").unwrap();
+
+        let config = config(vault);
+        let mut index = VaultIndex::new(vault);
+        index.refresh(vault, &vault.join("Templates")).unwrap();
+        let issues = inspect(vault, &config, &index).unwrap();
+
+        let kinds: Vec<String> = issues
+            .iter()
+            .map(|i| serde_json::to_value(i).unwrap()["kind"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                "missing_dir_tag",
+                "dir_tag_mismatch",
+                "malformed_frontmatter",
+                "empty_note",
+                "broken_link",
+                "duplicate_name",
+                "spread_tag"
+            ]
+        );
+        assert_eq!(
+            issues[1],
+            Issue::DirTagMismatch {
+                path: PathBuf::from("Notas/ops/api.md"),
+                found: "dev".to_string(),
+                expected: "ops".to_string(),
+            }
+        );
+        assert_eq!(issues[3], Issue::EmptyNote { path: PathBuf::from("Notas/ops/api.md") });
+        assert_eq!(
+            issues[4],
+            Issue::BrokenLink {
+                path: PathBuf::from("Notas/dev/ok.md"),
+                line: 3,
+                target: "falta".to_string(),
+            }
+        );
+        assert_eq!(
+            issues[6],
+            Issue::SpreadTag {
+                tag: "dev".to_string(),
+                dirs: vec!["Notas/dev".to_string(), "Notas/ops".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_unclosed_frontmatter() {
+        assert!(has_unclosed_frontmatter("---\ntitle: x\nbody\n"));
+        assert!(!has_unclosed_frontmatter("---\ntitle: x\n---\nbody\n"));
+        assert!(!has_unclosed_frontmatter("--- separador\n"));
+        assert!(is_empty_body("\n{ #dev }\n\n  \n", true));
+        assert!(!is_empty_body("{ #dev }\n\ntexto", true));
+    }
+}
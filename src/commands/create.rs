@@ -1,5 +1,7 @@
 use crate::core::config::Config;
 use crate::core::note::NoteBuilder;
+use crate::core::template::TemplateSelection;
+use crate::utils::cli::NoteAnswers;
 use std::path::PathBuf;
 
 pub fn run(
@@ -8,11 +10,17 @@ pub fn run(
     title: Option<String>,
     target_dir: Option<PathBuf>,
     editor: Option<String>,
+    template: TemplateSelection,
+    answers: NoteAnswers,
 ) -> anyhow::Result<()> {
     let mut builder = NoteBuilder::new(vault, config)
         .title(title)
         .hierarchical_tags(true)
-        .editor(editor);
+        .editor(editor)
+        .template(template)
+        .tag(answers.tag)
+        .aliases(answers.aliases)
+        .assume_yes(answers.yes);
 
     if let Some(dir) = target_dir {
         builder = builder.target_directory(dir);
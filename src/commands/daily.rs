@@ -1,84 +1,311 @@
+use super::{periodic, todo};
 use crate::core::config::Config;
+use crate::core::date_expr;
 use crate::core::note::NoteBuilder;
-use crate::core::template;
-use chrono::Local;
+use crate::core::period::Period;
+use crate::core::section;
+use crate::core::template::TemplateSelection;
+use crate::utils::cli::DailyTarget;
+use crate::vault::index::NoteMeta;
+use crate::vault::plan::Plan;
+use chrono::{Duration, Local, NaiveDate};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-pub fn run(config: Config, vault: PathBuf, editor: Option<String>) -> anyhow::Result<()> {
-    let today = Local::now().format("%Y-%m-%d").to_string();
+pub fn run(
+    config: Config,
+    vault: PathBuf,
+    editor: Option<String>,
+    target: DailyTarget,
+    template_selection: TemplateSelection,
+    assume_yes: bool,
+) -> anyhow::Result<()> {
+    let today = Local::now().date_naive();
+    let date = resolve_date(&config, &vault, &target, today)?;
 
-    // Daily notes go in configured diary directory
-    let diario_dir = vault.join(&config.diary_dir);
-    fs::create_dir_all(&diario_dir)?;
-
-    let daily_file = diario_dir.join(format!("{}.md", today));
+    // Daily notes go in configured diary directory (periodic.day.format may nest them)
+    let daily_file = periodic::note_path(&config, &vault, Period::Day, date)?;
+    let title = date.format("%Y-%m-%d").to_string();
+    let _ = save_last_daily(date);
 
     // If daily note exists, open it with timestamp
     if daily_file.exists() {
-        println!("Abriendo daily note: {}", today);
+        println!("Abriendo daily note: {}", title);
         NoteBuilder::add_timestamp_and_open(&daily_file, &vault, &config, editor)?;
         return Ok(());
     }
 
     // Create new daily note
-    println!("Creando daily note: {}", today);
+    // (Templates/_base.md → Diario → template.txt, o nota genérica)
+    println!("Creando daily note: {}", title);
 
-    // Try to load template from centralized Templates directory first
-    let centralized_template = vault
-        .join(&config.templates_dir)
-        .join(format!("{}.md", &config.diary_dir));
-    let local_template = diario_dir.join("template.txt");
+    let Some(plan) = create(&config, &vault, date, &daily_file, &template_selection, assume_yes, true)? else {
+        println!("\nCreación de daily note cancelada.");
+        return Ok(());
+    };
+    plan.apply()?;
 
-    let template_path = if centralized_template.exists() {
-        centralized_template
-    } else {
-        local_template
+    // Open in editor (as new file, no timestamp)
+    periodic::open_new_note(&daily_file, &vault, editor)
+}
+
+/// Plan a new daily note without opening it; with `rollover`, also brings the open tasks of
+/// earlier daily notes (si `[rollover]` lo permite) and marks them there.
+/// Returns None if the template prompts were cancelled.
+pub(crate) fn create(
+    config: &Config,
+    vault: &Path,
+    date: NaiveDate,
+    daily_file: &Path,
+    template_selection: &TemplateSelection,
+    assume_yes: bool,
+    rollover: bool,
+) -> anyhow::Result<Option<Plan>> {
+    let today = Local::now().date_naive();
+    let Some(content) = periodic::render_new_note(
+        config,
+        vault,
+        Period::Day,
+        date,
+        daily_file,
+        template_selection,
+        assume_yes,
+    )?
+    else {
+        return Ok(None);
     };
 
-    let content = if template_path.exists() {
-        // Load and render template
-        let (frontmatter_map, body) = template::read(&template_path)?;
-
-        // Build variables for template rendering
-        let mut vars = std::collections::BTreeMap::new();
-        let now = Local::now();
-        vars.insert("date".to_string(), now.format(&config.date).to_string());
-        vars.insert("time".to_string(), now.format(&config.time).to_string());
-        vars.insert("title".to_string(), today.clone());
-
-        // Render frontmatter and body
-        let rendered_fm = crate::core::frontmatter::render(frontmatter_map, &vars);
-        let rendered_body = template::render_body(&body, &vars);
-
-        format!(
-            "---\n{}---\n{}",
-            serde_yaml::to_string(&rendered_fm)?,
-            rendered_body
-        )
+    // Open tasks from earlier daily notes (rollover)
+    let move_tasks = rollover_moves(config)?;
+    let rollover = if rollover && date >= today && config.rollover.enabled.unwrap_or(true) {
+        collect_rollover(config, vault, date)?
     } else {
-        // Create generic daily note
-        let now = Local::now();
-        format!(
-            "---\ndate: {}\ntime: {}\n---\n\n# {}\n\n",
-            now.format(&config.date),
-            now.format(&config.time),
-            today
-        )
+        Vec::new()
+    };
+    let heading = config.rollover.heading.as_deref().unwrap_or("## Pendientes");
+    let tasks: Vec<String> = rollover.iter().flat_map(|s| s.lines.clone()).collect();
+    let content = if tasks.is_empty() {
+        content
+    } else {
+        section::append_to_section(&content, Some(heading), &tasks.join("\n"))
     };
 
-    // Write the file
-    fs::write(&daily_file, content)?;
+    let mut plan = Plan::new("dialy", vault);
+    plan.backup = false;
+    plan.create(daily_file, content);
+    mark_rolled_over(&mut plan, &rollover, move_tasks)?;
+    Ok(Some(plan))
+}
 
-    // Open in editor (as new file, no timestamp)
-    if let Some(editor_cmd) = editor {
-        std::process::Command::new(editor_cmd)
-            .arg(&daily_file)
-            .status()?;
+/// Day of the daily note to open.
+/// `--prev`/`--next` van a la daily note existente anterior/siguiente a la última abierta
+/// (o al día adyacente si no hay ninguna).
+fn resolve_date(
+    config: &Config,
+    vault: &Path,
+    target: &DailyTarget,
+    today: NaiveDate,
+) -> anyhow::Result<NaiveDate> {
+    let step = match target {
+        DailyTarget::Today => return Ok(today),
+        DailyTarget::Date(expr) => return date_expr::parse(expr, today),
+        DailyTarget::Prev => -1,
+        DailyTarget::Next => 1,
+    };
+
+    let reference = load_last_daily().unwrap_or(today);
+    let notes = periodic::daily_notes(config, vault)?;
+    Ok(adjacent(&notes, reference, step))
+}
+
+fn adjacent(notes: &[(NaiveDate, PathBuf)], reference: NaiveDate, step: i64) -> NaiveDate {
+    let existing = if step < 0 {
+        notes.iter().rev().map(|(d, _)| *d).find(|d| *d < reference)
     } else {
-        use crate::ui::editor;
-        editor::open(&daily_file, &vault)?;
+        notes.iter().map(|(d, _)| *d).find(|d| *d > reference)
+    };
+    existing.unwrap_or(reference + Duration::days(step))
+}
+
+/// Open tasks of an earlier daily note
+struct RolloverSource {
+    path: PathBuf,
+    line_numbers: Vec<usize>,
+    lines: Vec<String>,
+}
+
+/// Open `- [ ]` tasks of the daily notes before `date`.
+/// `rollover.scope`: "last" (la daily note anterior, default) o "all" (todas las anteriores).
+/// Tareas con el mismo texto se arrastran una sola vez.
+fn collect_rollover(
+    config: &Config,
+    vault: &Path,
+    date: NaiveDate,
+) -> anyhow::Result<Vec<RolloverSource>> {
+    let earlier: Vec<PathBuf> = periodic::daily_notes(config, vault)?
+        .into_iter()
+        .filter(|(d, _)| *d < date)
+        .map(|(_, path)| path)
+        .collect();
+    let earlier = match config.rollover.scope.as_deref().unwrap_or("last") {
+        "all" => earlier,
+        "last" => earlier.into_iter().last().into_iter().collect(),
+        other => anyhow::bail!("rollover.scope desconocido: '{}'. Usa: last, all", other),
+    };
+
+    let mut seen = HashSet::new();
+    let mut sources = Vec::new();
+    for path in earlier {
+        let content = fs::read_to_string(&path)?;
+        let note = NoteMeta::parse(&content, 0, 0);
+        let mut source = RolloverSource {
+            path,
+            line_numbers: Vec::new(),
+            lines: Vec::new(),
+        };
+        // Las subtareas abiertas de una tarea abierta se arrastran anidadas
+        let mut depths: HashMap<usize, usize> = HashMap::new();
+        for task in note.tasks.iter().filter(|t| t.status == ' ') {
+            let depth = task
+                .parent
+                .and_then(|p| depths.get(&p))
+                .map_or(0, |d| d + 1);
+            depths.insert(task.line, depth);
+            source.line_numbers.push(task.line);
+            if seen.insert(task.text.clone()) {
+                source.lines.push(format!("{}- [ ] {}", "  ".repeat(depth), task.text));
+            }
+        }
+        if !source.line_numbers.is_empty() {
+            sources.push(source);
+        }
     }
 
+    Ok(sources)
+}
+
+/// `rollover.mode = "move"` (default) marca las tareas de origen como `[>]`; "copy" las deja abiertas
+fn rollover_moves(config: &Config) -> anyhow::Result<bool> {
+    match config.rollover.mode.as_deref().unwrap_or("move") {
+        "move" => Ok(true),
+        "copy" => Ok(false),
+        other => anyhow::bail!("rollover.mode desconocido: '{}'. Usa: move, copy", other),
+    }
+}
+
+fn mark_rolled_over(plan: &mut Plan, sources: &[RolloverSource], mark: bool) -> anyhow::Result<()> {
+    let mut count = 0;
+    for source in sources {
+        if mark {
+            todo::mark_tasks_in_file(plan, &source.path, &source.line_numbers, '>')?;
+        }
+        count += source.lines.len();
+    }
+    if count > 0 {
+        println!("↪ Tareas arrastradas: {}", count);
+    }
     Ok(())
 }
+
+/// Save the day of the last opened daily note
+fn save_last_daily(date: NaiveDate) -> anyhow::Result<()> {
+    fs::create_dir_all(Config::config_dir()?)?;
+    fs::write(Config::last_daily_path()?, date.format("%Y-%m-%d").to_string())?;
+    Ok(())
+}
+
+fn load_last_daily() -> Option<NaiveDate> {
+    let raw = fs::read_to_string(Config::last_daily_path().ok()?).ok()?;
+    NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::journal;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_adjacent_daily_notes_in_nested_layout() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        let config: Config = toml::from_str(&format!(
+            "vault = \"{}\"\ndate = \"%Y-%m-%d\"\ntime = \"%H:%M\"\n\n[periodic.day]\nformat = \"%Y/%m/%Y-%m-%d\"\n",
+            vault.display()
+        ))
+        .unwrap();
+
+        let d = |m, day| NaiveDate::from_ymd_opt(2026, m, day).unwrap();
+        for date in [d(9, 28), d(10, 2), d(10, 16)] {
+            let path = periodic::note_path(&config, vault, Period::Day, date).unwrap();
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        assert_eq!(
+            periodic::note_path(&config, vault, Period::Day, d(10, 2)).unwrap(),
+            vault.join("Diario/2026/10/2026-10-02.md")
+        );
+
+        let notes = periodic::daily_notes(&config, vault).unwrap();
+        assert_eq!(notes.len(), 3);
+        assert_eq!(adjacent(&notes, d(10, 16), -1), d(10, 2));
+        assert_eq!(adjacent(&notes, d(10, 2), -1), d(9, 28));
+        assert_eq!(adjacent(&notes, d(9, 28), -1), d(9, 27));
+        assert_eq!(adjacent(&notes, d(10, 3), 1), d(10, 16));
+        assert_eq!(adjacent(&notes, d(10, 16), 1), d(10, 17));
+    }
+
+    #[test]
+    fn test_rollover_moves_open_tasks_under_heading() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        let config: Config = toml::from_str(&format!(
+            "vault = \"{}\"\ndate = \"%Y-%m-%d\"\ntime = \"%H:%M\"\n\n[rollover]\nscope = \"all\"\n",
+            vault.display()
+        ))
+        .unwrap();
+
+        fs::create_dir_all(vault.join("Diario")).unwrap();
+        fs::write(
+            vault.join("Diario/2026-10-14.md"),
+            "# 14\n- [ ] Llamar a Ana\n- [x] Hecho\n```\n- [ ] en código\n```\n",
+        )
+        .unwrap();
+        fs::write(
+            vault.join("Diario/2026-10-15.md"),
+            "# 15\n- [ ] Llamar a Ana\n- [ ] Revisar PR\n",
+        )
+        .unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let sources = collect_rollover(&config, vault, date).unwrap();
+        let tasks: Vec<String> = sources.iter().flat_map(|s| s.lines.clone()).collect();
+        assert_eq!(tasks, vec!["- [ ] Llamar a Ana", "- [ ] Revisar PR"]);
+
+        let content = section::append_to_section("# 16\n\n## Pendientes\n\n## Notas\n", Some("## Pendientes"), &tasks.join("\n"));
+        assert_eq!(
+            content,
+            "# 16\n\n## Pendientes\n- [ ] Llamar a Ana\n- [ ] Revisar PR\n\n## Notas\n"
+        );
+
+        let mut plan = Plan::new("dialy", vault);
+        mark_rolled_over(&mut plan, &sources, rollover_moves(&config).unwrap()).unwrap();
+        plan.apply().unwrap();
+        assert_eq!(
+            fs::read_to_string(vault.join("Diario/2026-10-14.md")).unwrap(),
+            "# 14\n- [>] Llamar a Ana\n- [x] Hecho\n```\n- [ ] en código\n```\n"
+        );
+        assert_eq!(
+            fs::read_to_string(vault.join("Diario/2026-10-15.md")).unwrap(),
+            "# 15\n- [>] Llamar a Ana\n- [>] Revisar PR\n"
+        );
+
+        // Queda en el journal: mad undo devuelve las tareas a su daily note
+        journal::undo(vault).unwrap().unwrap();
+        assert_eq!(
+            fs::read_to_string(vault.join("Diario/2026-10-15.md")).unwrap(),
+            "# 15\n- [ ] Llamar a Ana\n- [ ] Revisar PR\n"
+        );
+    }
+}
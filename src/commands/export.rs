@@ -0,0 +1,34 @@
+use crate::core::config::Config;
+use crate::export;
+use crate::tags::parser::TagPath;
+use std::path::Path;
+
+/// Static HTML export
+/// - `mad export html --out site/` - todo el vault
+/// - `mad export html --tag dev/magic-documents --out site/` - solo ese subárbol de dir-tags
+pub fn run(vault: &Path, config: &Config, tag: Option<&str>, out: &Path) -> anyhow::Result<()> {
+    let templates_path = vault.join(&config.templates_dir);
+    let tag = tag.map(TagPath::from_slash).filter(|t| !t.0.is_empty());
+
+    let stats = export::html(vault, &templates_path, tag.as_ref(), out)?;
+    if stats.notes == 0 {
+        match &tag {
+            Some(tag) => println!("No hay notas con dir-tag #{} para exportar", tag.to_slash_string()),
+            None => println!("No hay notas para exportar"),
+        }
+        return Ok(());
+    }
+
+    println!(
+        "✅ Exportadas {} notas, {} páginas de tags y {} adjuntos en {}",
+        stats.notes,
+        stats.tags,
+        stats.attachments,
+        out.display()
+    );
+    if stats.unpublished > 0 {
+        println!("   {} notas con publish: false omitidas", stats.unpublished);
+    }
+    println!("   Inicio: {}", out.join("index.html").display());
+    Ok(())
+}
@@ -1,17 +1,42 @@
 use crate::core::config::Config;
 use crate::core::note::NoteBuilder;
+use crate::ui::prompts;
+use crate::utils::output::{self, OutputFormat};
+use crate::vault::index::VaultIndex;
+use chrono::{DateTime, Local};
 use dialoguer::{theme::ColorfulTheme, FuzzySelect};
-use std::fs;
+use serde::Serialize;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+/// `mad last N --json|--format tsv`
+#[derive(Debug, Serialize)]
+struct NoteRecord {
+    path: String,
+    modified: String,
+}
+
 pub fn run(
     vault: PathBuf,
     config: Config,
     count: usize,
     editor: Option<String>,
+    format: OutputFormat,
 ) -> anyhow::Result<()> {
-    let mut notes = collect_notes(&vault)?;
+    let mut notes = collect_notes(&vault, &config)?;
+
+    if format.is_structured() {
+        notes.sort_by_key(|n| std::cmp::Reverse(n.1));
+        notes.truncate(count);
+        let records: Vec<NoteRecord> = notes
+            .iter()
+            .map(|(path, mtime)| NoteRecord {
+                path: path.strip_prefix(&vault).unwrap_or(path).display().to_string(),
+                modified: DateTime::<Local>::from(*mtime).to_rfc3339(),
+            })
+            .collect();
+        return output::print(format, &records, |r| vec![r.path.clone(), r.modified.clone()]);
+    }
 
     if notes.is_empty() {
         println!("No se encontraron notas en el vault.");
@@ -19,7 +44,7 @@ pub fn run(
     }
 
     // Sort by modification time (most recent first)
-    notes.sort_by(|a, b| b.1.cmp(&a.1));
+    notes.sort_by_key(|n| std::cmp::Reverse(n.1));
 
     // Limit to requested count
     notes.truncate(count);
@@ -42,6 +67,7 @@ pub fn run(
         })
         .collect();
 
+    prompts::ensure_tty("usa --json o --format tsv para listar las notas")?;
     println!(
         "\nÚltimas {} notas editadas (ESC para cancelar):\n",
         notes.len()
@@ -62,24 +88,17 @@ pub fn run(
     Ok(())
 }
 
-fn collect_notes(vault: &Path) -> anyhow::Result<Vec<(PathBuf, SystemTime)>> {
-    let mut notes = Vec::new();
+fn collect_notes(vault: &Path, config: &Config) -> anyhow::Result<Vec<(PathBuf, SystemTime)>> {
+    let templates_path = vault.join(&config.templates_dir);
+    let index = VaultIndex::open(vault, &templates_path)?;
 
-    crate::utils::vault::VaultWalker::new(vault).walk_paths(|path| {
-        if let Ok(metadata) = fs::metadata(path) {
-            if let Ok(modified) = metadata.modified() {
-                notes.push((path.to_path_buf(), modified));
-            }
-        }
-        Ok(())
-    })?;
-
-    Ok(notes)
+    Ok(index
+        .iter()
+        .map(|(path, note)| (path, note.modified()))
+        .collect())
 }
 
 fn format_time(time: SystemTime) -> String {
-    use chrono::{DateTime, Local};
-
     let datetime: DateTime<Local> = time.into();
     let now = Local::now();
 
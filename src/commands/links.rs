@@ -0,0 +1,125 @@
+use crate::core::config::Config;
+use crate::core::note::NoteBuilder;
+use crate::ui::prompts;
+use crate::utils::cli::LinkDirection;
+use crate::vault::index::VaultIndex;
+use crate::vault::links::{LinkGraph, NoteResolver};
+use dialoguer::{theme::ColorfulTheme, FuzzySelect};
+use std::path::{Path, PathBuf};
+
+/// Show outgoing links (`mad links <nota>`) or incoming links (`mad backlinks <nota>`)
+/// and open the selected note.
+/// `<nota>` puede ser un path a un archivo o un nombre de nota como en `[[nombre]]`.
+pub fn run(
+    vault: PathBuf,
+    config: Config,
+    note: &str,
+    direction: LinkDirection,
+    editor: Option<String>,
+) -> anyhow::Result<()> {
+    let templates_path = vault.join(&config.templates_dir);
+    let index = VaultIndex::open(&vault, &templates_path)?;
+    let graph = LinkGraph::from_items(&vault, index.link_items());
+    let note_path = resolve_note_arg(&vault, &graph.resolver, note)?;
+    let display_note = note_path.strip_prefix(&vault).unwrap_or(&note_path);
+
+    // (destino a abrir, texto a mostrar)
+    let entries: Vec<(PathBuf, String)> = match direction {
+        LinkDirection::Forward => {
+            let links = graph.links_from(&note_path);
+            if links.is_empty() {
+                println!("No hay enlaces en: {}", display_note.display());
+                return Ok(());
+            }
+
+            println!("\n🔗 Enlaces desde {}:", display_note.display());
+            let broken: Vec<_> = links.iter().filter(|l| l.target.is_none()).collect();
+            for link in &broken {
+                println!(
+                    "  ✗ {} (no encontrado, línea {})",
+                    link.link.target, link.link.line
+                );
+            }
+            if !broken.is_empty() {
+                println!();
+            }
+
+            links
+                .iter()
+                .filter_map(|l| {
+                    let target = l.target.clone()?;
+                    let relative = target.strip_prefix(&vault).unwrap_or(&target);
+                    let display = format!("{} (línea {})", relative.display(), l.link.line);
+                    Some((target, display))
+                })
+                .collect()
+        }
+        LinkDirection::Backward => {
+            let backlinks = graph.backlinks_to(&note_path);
+            if backlinks.is_empty() {
+                println!("No hay backlinks hacia: {}", display_note.display());
+                return Ok(());
+            }
+
+            println!("\n🔙 Backlinks hacia {}:\n", display_note.display());
+            backlinks
+                .iter()
+                .map(|b| {
+                    let relative = b.source.strip_prefix(&vault).unwrap_or(&b.source);
+                    let display = format!("{} (línea {})", relative.display(), b.link.line);
+                    (b.source.clone(), display)
+                })
+                .collect()
+        }
+    };
+
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let display_items: Vec<&str> = entries.iter().map(|(_, d)| d.as_str()).collect();
+
+    prompts::ensure_tty("el selector de notas necesita una terminal")?;
+    let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Selecciona una nota para abrir (ESC para cancelar)")
+        .items(&display_items)
+        .default(0)
+        .interact_opt()?;
+
+    if let Some(idx) = selection {
+        let selected_path = &entries[idx].0;
+        println!("\nAbriendo: {}", selected_path.display());
+        NoteBuilder::add_timestamp_and_open(selected_path, &vault, &config, editor)?;
+    }
+
+    Ok(())
+}
+
+/// Resolve the `<nota>` argument: existing file path first, then note name
+pub fn resolve_note_arg(
+    vault: &Path,
+    resolver: &NoteResolver,
+    note: &str,
+) -> anyhow::Result<PathBuf> {
+    let path = Path::new(note);
+    let abs_path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+
+    if abs_path.is_file() {
+        let canonical_vault = vault.canonicalize()?;
+        let canonical = abs_path.canonicalize()?;
+        let relative = canonical.strip_prefix(&canonical_vault).map_err(|_| {
+            anyhow::anyhow!("La nota no está dentro del vault: {}", abs_path.display())
+        })?;
+        return Ok(vault.join(relative));
+    }
+
+    let name = note.trim().trim_start_matches("[[").trim_end_matches("]]");
+
+    resolver
+        .resolve_name(name, None)
+        .ok_or_else(|| anyhow::anyhow!("Nota no encontrada: {}", note))
+}
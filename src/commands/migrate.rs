@@ -1,14 +1,15 @@
 use crate::core::config::Config;
 use crate::core::frontmatter;
 use crate::utils::vault::VaultWalker;
+use crate::vault::plan::{Plan, PlanMode};
 use serde_yaml::Value;
-use std::fs;
 use std::path::Path;
 
 /// One-time migration: Convert array-style tags to slash-separated format
 /// Example: `tags: ["padre", "hijo"]` → `tags: ["padre/hijo"]`
-pub fn run(vault: &Path, config: &Config) -> anyhow::Result<()> {
+pub fn run(vault: &Path, config: &Config, mode: &PlanMode) -> anyhow::Result<()> {
     let templates_path = vault.join(&config.templates_dir);
+    let mut plan = Plan::new("migrate", vault);
 
     let mut converted = 0;
     let mut skipped = 0;
@@ -20,7 +21,7 @@ pub fn run(vault: &Path, config: &Config) -> anyhow::Result<()> {
     VaultWalker::new(vault)
         .exclude_templates(&templates_path)
         .walk(|path, content| {
-            match migrate_file_inner(path, content) {
+            match migrate_file_inner(path, content, &mut plan) {
                 Ok(Some(changes)) => {
                     println!("  ✅ {} ({})", path.display(), changes);
                     converted += 1;
@@ -41,21 +42,21 @@ pub fn run(vault: &Path, config: &Config) -> anyhow::Result<()> {
         converted, skipped, errors
     );
 
-    if converted > 0 {
-        println!("\n💡 Se crearon archivos .bak como respaldo.");
-        println!("   Para eliminarlos: find {} -name '*.bak' -delete", vault.display());
+    if plan.finish(mode)? && converted > 0 {
+        println!("\n💡 Respaldos en {}", vault.join(".arc/backups").display());
     }
 
     Ok(())
 }
 
-fn migrate_file_inner(path: &Path, content: &str) -> anyhow::Result<Option<String>> {
-    let (mut fm, body) = frontmatter::extract(content)?;
+fn migrate_file_inner(path: &Path, content: &str, plan: &mut Plan) -> anyhow::Result<Option<String>> {
+    let mut doc = frontmatter::Document::parse(content)?;
+    let fm = doc.mapping()?;
 
     let mut changes = Vec::new();
 
     for key in ["tags", "tag", "Tags", "Tag"] {
-        if let Some(Value::Sequence(tag_list)) = fm.get(&Value::String(key.to_string())) {
+        if let Some(Value::Sequence(tag_list)) = fm.get(Value::String(key.to_string())) {
             // Check if migration is needed:
             // - More than one element in the array (old format)
             // - Or single element without slash that could be part of old format
@@ -130,7 +131,7 @@ fn migrate_file_inner(path: &Path, content: &str) -> anyhow::Result<Option<Strin
 
             // Update frontmatter
             let new_value = Value::Sequence(vec![Value::String(new_tag)]);
-            fm.insert(Value::String(key.to_string()), new_value);
+            doc.set(key, new_value)?;
             break; // Only process first matching key
         }
     }
@@ -139,13 +140,8 @@ fn migrate_file_inner(path: &Path, content: &str) -> anyhow::Result<Option<Strin
         return Ok(None);
     }
 
-    // Create backup
-    let backup_path = path.with_extension("md.bak");
-    fs::copy(path, &backup_path)?;
-
-    // Write updated content
-    let new_content = format!("---\n{}---{}", serde_yaml::to_string(&fm)?, body);
-    fs::write(path, new_content)?;
+    // Plan updated content (frontmatter edited in place, with backup)
+    plan.edit(path, doc.into_string())?;
 
     Ok(Some(changes.join(", ")))
 }
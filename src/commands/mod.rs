@@ -1,13 +1,24 @@
+pub mod apply;
+pub mod backups;
+pub mod check;
 pub mod create;
 pub mod cache;
+pub mod capture;
 pub mod daily;
+pub mod export;
 pub mod last;
+pub mod links;
 pub mod migrate;
 pub mod obsidian;
+pub mod periodic;
+pub mod query;
 pub mod recent;
 pub mod redir;
 pub mod rename;
 pub mod retag;
+pub mod search;
+pub mod sync;
 pub mod tman;
 pub mod rcal_tasks;
 pub mod todo;
+pub mod undo;
@@ -1,5 +1,8 @@
 use crate::core::config::Config;
 use crate::core::note::NoteBuilder;
+use crate::core::template::TemplateSelection;
+use crate::ui::prompts;
+use crate::utils::cli::NoteAnswers;
 use dialoguer::{theme::ColorfulTheme, Confirm};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -13,6 +16,8 @@ pub fn run(
     config: Config,
     title: String,
     editor_cmd: Option<String>,
+    template: TemplateSelection,
+    answers: NoteAnswers,
 ) -> anyhow::Result<()> {
     let current_dir = std::env::current_dir()?;
     let current_canonical = current_dir.canonicalize()?;
@@ -62,10 +67,15 @@ pub fn run(
     if !dest_dir.exists() {
         println!("\x1b[32m{}\x1b[0m", dest_dir.display());
 
-        let should_create = Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt("Crear directorio?")
-            .default(true)
-            .interact_opt()?;
+        let should_create = if answers.yes {
+            Some(true)
+        } else {
+            prompts::ensure_tty("usa --yes para crear el directorio")?;
+            Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Crear directorio?")
+                .default(true)
+                .interact_opt()?
+        };
 
         match should_create {
             Some(true) => {
@@ -83,6 +93,9 @@ pub fn run(
         .target_directory(dest_dir)
         .hierarchical_tags(true)
         .editor(editor_cmd)
+        .template(template)
+        .aliases(answers.aliases)
+        .assume_yes(answers.yes)
         .create()?;
 
     Ok(())
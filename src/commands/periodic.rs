@@ -0,0 +1,408 @@
+use crate::core::config::Config;
+use crate::core::note::NoteBuilder;
+use crate::core::period::{self, Period};
+use crate::core::template::{self, Context, TemplateSelection};
+use crate::ui::prompts;
+use crate::utils::vault::VaultWalker;
+use crate::vault::plan::Plan;
+use chrono::{Local, NaiveDate};
+use serde_yaml::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// `mad week|month|quarter|year`: open or create the periodic note of the current period
+pub fn run(
+    config: Config,
+    vault: PathBuf,
+    editor: Option<String>,
+    period: Period,
+    template_selection: TemplateSelection,
+    assume_yes: bool,
+) -> anyhow::Result<()> {
+    let today = Local::now().date_naive();
+    let note_file = note_path(&config, &vault, period, today)?;
+    let title = stem(&note_file);
+
+    if note_file.exists() {
+        println!("Abriendo {}: {}", period.label(), title);
+        NoteBuilder::add_timestamp_and_open(&note_file, &vault, &config, editor)?;
+        return Ok(());
+    }
+
+    println!("Creando {}: {}", period.label(), title);
+    let Some(content) =
+        render_new_note(&config, &vault, period, today, &note_file, &template_selection, assume_yes)?
+    else {
+        println!("\nCreación de {} cancelada.", period.label());
+        return Ok(());
+    };
+
+    let mut plan = Plan::new(period.key(), &vault);
+    plan.backup = false;
+    plan.create(&note_file, content);
+    plan.apply()?;
+    open_new_note(&note_file, &vault, editor)
+}
+
+/// Path of the periodic note containing `date`: `<dir>/<format>.md`
+pub fn note_path(
+    config: &Config,
+    vault: &Path,
+    period: Period,
+    date: NaiveDate,
+) -> anyhow::Result<PathBuf> {
+    let settings = config.period(period);
+    let name = period::format_date(period.start(date), &settings.format)?;
+    Ok(vault.join(&settings.dir).join(format!("{}.md", name)))
+}
+
+/// Existing daily notes, sorted by date.
+/// La fecha se lee del path relativo al directorio con el formato configurado.
+pub fn daily_notes(config: &Config, vault: &Path) -> anyhow::Result<Vec<(NaiveDate, PathBuf)>> {
+    let settings = config.period(Period::Day);
+    let dir = vault.join(&settings.dir);
+    let mut notes = Vec::new();
+
+    VaultWalker::new(&dir).walk_paths(|path| {
+        if let Some(date) = date_in(&dir, &settings.format, path) {
+            notes.push((date, path.to_path_buf()));
+        }
+        Ok(())
+    })?;
+
+    notes.sort();
+    Ok(notes)
+}
+
+/// Day of a daily note (según `[periodic.day]`), or None for any other note
+pub fn daily_note_date(config: &Config, vault: &Path, path: &Path) -> Option<NaiveDate> {
+    let settings = config.period(Period::Day);
+    date_in(&vault.join(&settings.dir), &settings.format, path)
+}
+
+/// Date of `path` from its path relative to `dir` (`2026/10/16.md` con `%Y/%m/%d`)
+fn date_in(dir: &Path, format: &str, path: &Path) -> Option<NaiveDate> {
+    let relative = path.strip_prefix(dir).ok()?.with_extension("");
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    NaiveDate::parse_from_str(&relative, format).ok()
+}
+
+/// Wiki link to the periodic note containing `date`: `[[2026-W42]]`
+pub fn note_link(
+    config: &Config,
+    vault: &Path,
+    period: Period,
+    date: NaiveDate,
+) -> anyhow::Result<String> {
+    Ok(format!("[[{}]]", stem(&note_path(config, vault, period, date)?)))
+}
+
+/// Render a new periodic note (template cascade or generic note).
+/// Creates the note directory. Returns None if the user cancels.
+pub(crate) fn render_new_note(
+    config: &Config,
+    vault: &Path,
+    period: Period,
+    date: NaiveDate,
+    note_file: &Path,
+    template_selection: &TemplateSelection,
+    assume_yes: bool,
+) -> anyhow::Result<Option<String>> {
+    let settings = config.period(period);
+    let note_dir = note_file.parent().unwrap_or(vault);
+    fs::create_dir_all(note_dir)?;
+
+    let title = stem(note_file);
+    let templates_root = vault.join(&config.templates_dir);
+    let Some(template_path) = NoteBuilder::resolve_template(
+        &templates_root,
+        &settings.template,
+        &vault.join(&settings.dir),
+        template_selection,
+    )?
+    else {
+        return Ok(None);
+    };
+
+    let cascade = template::load_cascade(
+        &templates_root,
+        &settings.template,
+        &template_path,
+        vault,
+        note_dir,
+    )?;
+    // `date` es el día de la nota (`mad dialy ayer`), no el de hoy
+    let now = Local::now();
+
+    let Some((frontmatter_map, body)) = cascade else {
+        // Nota genérica
+        return Ok(Some(format!(
+            "---\ndate: {}\ntime: {}\n---\n\n# {}\n\n{}",
+            date.format(&config.date),
+            now.format(&config.time),
+            title,
+            generic_body(config, vault, period, date)?
+        )));
+    };
+
+    let Some(answers) = NoteBuilder::ask_template_inputs(&frontmatter_map, &body, assume_yes)? else {
+        return Ok(None);
+    };
+
+    let mut vars = answers;
+    vars.insert("date".to_string(), date.format(&config.date).to_string());
+    vars.insert("time".to_string(), now.format(&config.time).to_string());
+    vars.insert("title".to_string(), title);
+
+    let mut ctx = Context::new(&vars).date_format(&config.date).today(date);
+    fill_context(&mut ctx, config, vault, period, date)?;
+
+    let rendered_fm = crate::core::frontmatter::render(frontmatter_map, &ctx)?;
+    let ctx = ctx.with_fields(&rendered_fm);
+    let rendered_body = template::render_body(&body, &ctx)?;
+
+    Ok(Some(format!(
+        "---\n{}---\n{}",
+        serde_yaml::to_string(&rendered_fm)?,
+        rendered_body
+    )))
+}
+
+/// Open a just-created note (no timestamp)
+pub(crate) fn open_new_note(
+    note_file: &Path,
+    vault: &Path,
+    editor: Option<String>,
+) -> anyhow::Result<()> {
+    if let Some(editor_cmd) = editor {
+        std::process::Command::new(editor_cmd)
+            .arg(note_file)
+            .status()?;
+    } else if !prompts::is_tty() {
+        // Sin terminal (scripts, cron) la nota queda creada sin abrir el editor
+        println!("Creado: {}", note_file.display());
+    } else {
+        use crate::ui::editor;
+        editor::open(note_file, vault)?;
+    }
+    Ok(())
+}
+
+/// Template variables of a periodic note:
+/// - `period_start`, `period_end`, `<period>_start`, `<period>_end` (fechas, admiten `+7d`)
+/// - `prev_link`, `next_link`, `prev_<period>_link`, `next_<period>_link`
+/// - `days`: links a las daily notes del período; `daily_links`: la misma lista en Markdown
+/// - `year`, `quarter`, `month`, `week` según el período
+fn fill_context(
+    ctx: &mut Context,
+    config: &Config,
+    vault: &Path,
+    period: Period,
+    date: NaiveDate,
+) -> anyhow::Result<()> {
+    let key = period.key();
+    let start = period.start(date);
+    let end = period.end(date);
+
+    for (name, value) in [("start", start), ("end", end)] {
+        ctx.set_date(&format!("period_{}", name), value);
+        ctx.set_date(&format!("{}_{}", key, name), value);
+    }
+
+    let prev = note_link(config, vault, period, period.prev(date))?;
+    let next = note_link(config, vault, period, period.next(date))?;
+    for (name, link) in [("prev", prev), ("next", next)] {
+        ctx.set(&format!("{}_link", name), Value::String(link.clone()));
+        ctx.set(&format!("{}_{}_link", name, key), Value::String(link));
+    }
+
+    let numbers = match period {
+        Period::Day => vec![],
+        Period::Week => vec![
+            ("year", period::format_date(start, "%G")?),
+            ("week", period::format_date(start, "%V")?),
+        ],
+        Period::Month => vec![
+            ("year", period::format_date(start, "%Y")?),
+            ("month", period::format_date(start, "%m")?),
+        ],
+        Period::Quarter => vec![
+            ("year", period::format_date(start, "%Y")?),
+            ("quarter", period::format_date(start, "%q")?),
+        ],
+        Period::Year => vec![("year", period::format_date(start, "%Y")?)],
+    };
+    for (name, value) in numbers {
+        ctx.set(name, Value::String(value));
+    }
+
+    if period != Period::Day {
+        let days = daily_links(config, vault, period, date)?;
+        ctx.set(
+            "daily_links",
+            Value::String(markdown_list(&days).trim_end().to_string()),
+        );
+        ctx.set(
+            "days",
+            Value::Sequence(days.into_iter().map(Value::String).collect()),
+        );
+    }
+
+    Ok(())
+}
+
+/// Links to the daily notes covered by the period.
+/// Semanas y meses enlazan todos sus días; trimestres y años solo las daily notes existentes.
+fn daily_links(
+    config: &Config,
+    vault: &Path,
+    period: Period,
+    date: NaiveDate,
+) -> anyhow::Result<Vec<String>> {
+    let only_existing = matches!(period, Period::Quarter | Period::Year);
+    let mut links = Vec::new();
+
+    for day in period.days(date) {
+        let path = note_path(config, vault, Period::Day, day)?;
+        if !only_existing || path.exists() {
+            links.push(format!("[[{}]]", stem(&path)));
+        }
+    }
+
+    Ok(links)
+}
+
+/// Body of a periodic note without template: navigation and covered days
+fn generic_body(
+    config: &Config,
+    vault: &Path,
+    period: Period,
+    date: NaiveDate,
+) -> anyhow::Result<String> {
+    if period == Period::Day {
+        return Ok(String::new());
+    }
+
+    let mut body = format!(
+        "← {} | {} →\n",
+        note_link(config, vault, period, period.prev(date))?,
+        note_link(config, vault, period, period.next(date))?
+    );
+
+    let days = daily_links(config, vault, period, date)?;
+    if !days.is_empty() {
+        body.push_str("\n## Días\n\n");
+        body.push_str(&markdown_list(&days));
+    }
+
+    Ok(body)
+}
+
+fn markdown_list(items: &[String]) -> String {
+    items.iter().map(|i| format!("- {}\n", i)).collect()
+}
+
+fn stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config(vault: &Path) -> Config {
+        let mut config = Config {
+            vault: vault.display().to_string(),
+            ..Default::default()
+        };
+        config.periodic.week.dir = Some("Semanas".to_string());
+        config
+    }
+
+    #[test]
+    fn test_weekly_note_context() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        let config = config(vault);
+        let date = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+
+        assert_eq!(
+            note_path(&config, vault, Period::Week, date).unwrap(),
+            vault.join("Semanas/2026-W42.md")
+        );
+        assert_eq!(
+            note_path(&config, vault, Period::Quarter, date).unwrap(),
+            vault.join("Periodicas/Trimestral/2026-Q4.md")
+        );
+
+        let mut ctx = Context::new(&Default::default());
+        fill_context(&mut ctx, &config, vault, Period::Week, date).unwrap();
+        let out = template::render_body(
+            "{{week_start}}..{{week_end}} {{prev_week_link}} {{next_link}} S{{week}}\n{{daily_links}}",
+            &ctx,
+        )
+        .unwrap();
+        assert!(out.starts_with("2026-10-12..2026-10-18 [[2026-W41]] [[2026-W43]] S42\n"));
+        assert!(out.contains("- [[2026-10-12]]\n"));
+        assert!(out.ends_with("- [[2026-10-18]]"));
+    }
+
+    #[test]
+    fn test_quarter_links_existing_daily_notes() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        let config = config(vault);
+        fs::create_dir_all(vault.join("Diario")).unwrap();
+        fs::write(vault.join("Diario/2026-11-03.md"), "").unwrap();
+        fs::write(vault.join("Diario/2026-09-30.md"), "").unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let body = generic_body(&config, vault, Period::Quarter, date).unwrap();
+        assert_eq!(
+            body,
+            "← [[2026-Q3]] | [[2027-Q1]] →\n\n## Días\n\n- [[2026-11-03]]\n"
+        );
+    }
+
+    #[test]
+    fn test_daily_note_date_follows_periodic_day() {
+        let vault = Path::new("/vault");
+        let mut config = config(vault);
+        config.periodic.day.dir = Some("Diario".to_string());
+        config.periodic.day.format = Some("%Y/%m/%d".to_string());
+
+        let date = daily_note_date(&config, vault, Path::new("/vault/Diario/2026/10/16.md"));
+        assert_eq!(date, NaiveDate::from_ymd_opt(2026, 10, 16));
+        assert_eq!(daily_note_date(&config, vault, Path::new("/vault/Diario/2026-10-16.md")), None);
+        assert_eq!(daily_note_date(&config, vault, Path::new("/vault/Notas/2026/10/16.md")), None);
+    }
+
+    #[test]
+    fn test_new_note_uses_the_note_date() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        let config = config(vault);
+        let date = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let note_file = vault.join("Diario/2026-03-01.md");
+
+        let generic = render_new_note(&config, vault, Period::Day, date, &note_file, &TemplateSelection::Default, true)
+            .unwrap()
+            .unwrap();
+        assert!(generic.starts_with("---\ndate: 2026-03-01\n"));
+
+        fs::create_dir_all(vault.join("Templates")).unwrap();
+        fs::write(
+            vault.join(format!("Templates/{}.md", config.period(Period::Day).template)),
+            "---\ndia: \"{{date}}\"\n---\n{{date | date:\"%A\"}} {{today-1d}}\n",
+        )
+        .unwrap();
+        let rendered = render_new_note(&config, vault, Period::Day, date, &note_file, &TemplateSelection::Default, true)
+            .unwrap()
+            .unwrap();
+        assert!(rendered.contains("dia: 2026-03-01\n"), "{}", rendered);
+        assert!(rendered.ends_with("Sunday 2026-02-28\n"), "{}", rendered);
+    }
+}
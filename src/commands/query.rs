@@ -0,0 +1,147 @@
+use crate::core::config::Config;
+use crate::utils::cli::QueryFormat;
+use crate::utils::output::{self, OutputFormat};
+use crate::vault::index::VaultIndex;
+use crate::vault::query::{self, QueryHit};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use serde_yaml::Mapping;
+use std::path::Path;
+
+/// `mad query --format json|tsv`
+#[derive(Debug, Serialize)]
+struct Record<'a> {
+    path: String,
+    dir_tag: Option<String>,
+    tags: Vec<String>,
+    modified: String,
+    tasks: TaskCounts,
+    links: usize,
+    backlinks: usize,
+    frontmatter: &'a Mapping,
+}
+
+#[derive(Debug, Serialize)]
+struct TaskCounts {
+    total: usize,
+    open: usize,
+}
+
+/// Structured query over the vault index
+/// - `mad query 'tag:proj/acme and status:open'` - paths, one per line
+/// - `mad query '...' --format table` - tabla con dir-tag, fecha, tareas y links
+/// - `mad query '...' --format json` - registros con el frontmatter completo
+/// - `mad query '...' --format tsv` - path, dir-tag, fecha, tareas abiertas/total, links, backlinks
+pub fn run(vault: &Path, config: &Config, query_str: &str, format: QueryFormat) -> anyhow::Result<()> {
+    let expr = query::parse(query_str, Local::now().date_naive())?;
+    let templates_path = vault.join(&config.templates_dir);
+    let index = VaultIndex::open(vault, &templates_path)?;
+    let hits = query::run(vault, &index, &expr);
+
+    match format {
+        QueryFormat::Paths => {
+            for hit in &hits {
+                println!("{}", relative(vault, hit));
+            }
+        }
+        QueryFormat::Table => print_table(vault, &hits),
+        QueryFormat::Json | QueryFormat::Tsv => {
+            let records: Vec<Record> = hits
+                .iter()
+                .map(|hit| {
+                    let meta = hit.meta;
+                    Record {
+                        path: relative(vault, hit),
+                        dir_tag: meta.primary_tag.as_ref().map(|t| t.to_slash_string()),
+                        tags: meta.secondary_tags.iter().map(|t| t.to_slash_string()).collect(),
+                        modified: modified(hit).to_rfc3339(),
+                        tasks: TaskCounts {
+                            total: meta.tasks.len(),
+                            open: meta.tasks.iter().filter(|t| t.status == ' ').count(),
+                        },
+                        links: hit.links,
+                        backlinks: hit.backlinks,
+                        frontmatter: &meta.frontmatter,
+                    }
+                })
+                .collect();
+            let format = match format {
+                QueryFormat::Tsv => OutputFormat::Tsv,
+                _ => OutputFormat::Json,
+            };
+            output::print(format, &records, |r| {
+                vec![
+                    r.path.clone(),
+                    r.dir_tag.clone().unwrap_or_default(),
+                    r.modified.clone(),
+                    r.tasks.open.to_string(),
+                    r.tasks.total.to_string(),
+                    r.links.to_string(),
+                    r.backlinks.to_string(),
+                ]
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+fn print_table(vault: &Path, hits: &[QueryHit]) {
+    if hits.is_empty() {
+        println!("Sin resultados");
+        return;
+    }
+
+    let rows: Vec<[String; 5]> = hits
+        .iter()
+        .map(|hit| {
+            let meta = hit.meta;
+            let open = meta.tasks.iter().filter(|t| t.status == ' ').count();
+            [
+                relative(vault, hit),
+                meta.primary_tag
+                    .as_ref()
+                    .map(|t| t.to_slash_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                modified(hit).format("%Y-%m-%d %H:%M").to_string(),
+                format!("{}/{}", open, meta.tasks.len()),
+                format!("{}/{}", hit.links, hit.backlinks),
+            ]
+        })
+        .collect();
+
+    let header = ["Nota", "Dir-tag", "Modificada", "Tareas", "Links/Back"];
+    let mut widths = header.map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let print_row = |cells: &[&str]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    };
+
+    print_row(&header);
+    for row in &rows {
+        print_row(&row.each_ref().map(|c| c.as_str()));
+    }
+    println!("\n{} notas", rows.len());
+}
+
+fn relative(vault: &Path, hit: &QueryHit) -> String {
+    hit.path
+        .strip_prefix(vault)
+        .unwrap_or(&hit.path)
+        .display()
+        .to_string()
+}
+
+fn modified(hit: &QueryHit) -> DateTime<Local> {
+    hit.meta.modified().into()
+}
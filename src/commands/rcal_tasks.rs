@@ -1,6 +1,11 @@
-use chrono::{Duration, NaiveDateTime};
+use crate::core::config::Config;
+use crate::core::ical::{self, Component, DateValue, Property};
+use crate::core::task::Priority;
+use chrono::{Duration, Local, Utc};
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Config mínima de un calendario rcal (solo lo que necesitamos)
 #[derive(Debug, Clone)]
@@ -17,16 +22,123 @@ pub struct RcalConfig {
     pub time_forward: Duration,
 }
 
-/// Tarea extraída de un archivo .ics
+impl Default for RcalConfig {
+    fn default() -> Self {
+        Self {
+            calendars: Vec::new(),
+            time_backward: Duration::days(2),
+            time_forward: Duration::days(7),
+        }
+    }
+}
+
+/// Tarea extraída de un archivo .ics: un `VTODO`, o un `VEVENT` con `DESCRIPTION:#TODO`/`#DONE`
+/// (la convención de rcal, que se sigue leyendo)
 #[derive(Debug, Clone)]
 pub struct IcalTask {
     pub summary: String,
-    pub start: Option<NaiveDateTime>,
+    pub start: Option<DateValue>,
+    pub due: Option<DateValue>,
+    pub priority: Priority,
+    /// `CATEGORIES`
+    pub categories: Vec<String>,
     pub completed: bool,
+    /// `VEVENT` con `#TODO` en vez de `VTODO`
+    pub legacy: bool,
     pub file_path: PathBuf,
     pub calendar_name: String,
 }
 
+/// Task to create in a calendar
+#[derive(Debug, Clone)]
+pub struct NewTask {
+    pub summary: String,
+    pub due: Option<DateValue>,
+    pub priority: Priority,
+    /// `CATEGORIES` (los tags de la tarea)
+    pub categories: Vec<String>,
+}
+
+/// `STATUS` of a VTODO
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TodoStatus {
+    Open,
+    Completed,
+    Cancelled,
+}
+
+impl TodoStatus {
+    /// Status of a VTODO: `STATUS`, o `COMPLETED` presente
+    pub fn of(todo: &Component) -> Self {
+        match todo.property("STATUS").map(|s| s.value.trim().to_ascii_uppercase()).as_deref() {
+            Some("COMPLETED") => TodoStatus::Completed,
+            Some("CANCELLED") => TodoStatus::Cancelled,
+            _ if todo.property("COMPLETED").is_some() => TodoStatus::Completed,
+            _ => TodoStatus::Open,
+        }
+    }
+}
+
+impl NewTask {
+    /// `VCALENDAR` with this task as its only `VTODO`
+    pub fn to_calendar(&self, uid: &str) -> Component {
+        let stamp = DateValue::Utc(Utc::now());
+        let mut todo = Component::new("VTODO");
+        todo.set(Property::new("UID", uid));
+        todo.set(stamp.to_property("DTSTAMP"));
+        todo.set(stamp.to_property("CREATED"));
+        todo.set(Property::text("SUMMARY", &self.summary));
+        todo.set(Property::new("STATUS", "NEEDS-ACTION"));
+        if let Some(due) = self.due {
+            todo.set(due.to_property("DUE"));
+        }
+        if let Some(priority) = self.priority.to_ical() {
+            todo.set(Property::new("PRIORITY", priority.to_string()));
+        }
+        if !self.categories.is_empty() {
+            let categories: Vec<String> = self.categories.iter().map(|c| ical::escape_text(c)).collect();
+            todo.set(Property::new("CATEGORIES", categories.join(",")));
+        }
+
+        let mut calendar = Component::new("VCALENDAR");
+        calendar.set(Property::new("VERSION", "2.0"));
+        calendar.set(Property::new("PRODID", "-//magic-documents//mad//ES"));
+        calendar.components.push(todo);
+        calendar
+    }
+}
+
+/// Unique ID for a new task (`20261016T093000123456789-4242-0@mad`)
+pub fn new_uid() -> String {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    format!(
+        "{}-{}-{}@mad",
+        Utc::now().format("%Y%m%dT%H%M%S%9f"),
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Set `STATUS` of a VTODO, with `COMPLETED`/`PERCENT-COMPLETE` when completed
+pub fn set_status(todo: &mut Component, status: TodoStatus) {
+    let now = DateValue::Utc(Utc::now());
+    match status {
+        TodoStatus::Completed => {
+            todo.set(Property::new("STATUS", "COMPLETED"));
+            todo.set(now.to_property("COMPLETED"));
+            todo.set(Property::new("PERCENT-COMPLETE", "100"));
+        }
+        TodoStatus::Cancelled => todo.set(Property::new("STATUS", "CANCELLED")),
+        TodoStatus::Open => {
+            todo.set(Property::new("STATUS", "NEEDS-ACTION"));
+            todo.remove("COMPLETED");
+            todo.remove("PERCENT-COMPLETE");
+        }
+    }
+    todo.set(now.to_property("LAST-MODIFIED"));
+    todo.set(now.to_property("DTSTAMP"));
+}
+
 /// Busca config de rcal.
 /// Orden: mad config.rcal_config → ~/.config/rcal/config.toml → ~/.rcal/config.toml
 pub fn find_rcal_config(mad_override: Option<&str>) -> Option<PathBuf> {
@@ -78,17 +190,18 @@ pub fn read_rcal_config(config_path: &Path) -> anyhow::Result<RcalConfig> {
     }
 
     // Parsear ventana de tiempo de [default]
+    let window = RcalConfig::default();
     let defaults = config.get("default");
     let time_backward = defaults
         .and_then(|d| d.get("timebackward"))
         .and_then(|v| v.as_str())
         .map(parse_duration)
-        .unwrap_or(Duration::days(2));
+        .unwrap_or(window.time_backward);
     let time_forward = defaults
         .and_then(|d| d.get("timeforward"))
         .and_then(|v| v.as_str())
         .map(parse_duration)
-        .unwrap_or(Duration::days(7));
+        .unwrap_or(window.time_forward);
 
     Ok(RcalConfig {
         calendars,
@@ -97,6 +210,45 @@ pub fn read_rcal_config(config_path: &Path) -> anyhow::Result<RcalConfig> {
     })
 }
 
+/// Task calendars: the rcal ones plus `[tasks] calendar`, which goes first (destino de las
+/// tareas nuevas). None si no hay ninguno configurado.
+pub fn load(config: &Config) -> Option<RcalConfig> {
+    let mut rcal = find_rcal_config(config.rcal_config.as_deref())
+        .and_then(|path| read_rcal_config(&path).ok())
+        .unwrap_or_default();
+
+    if let Some(dir) = &config.tasks.calendar {
+        let path = PathBuf::from(dir);
+        rcal.calendars.retain(|cal| cal.path != path);
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("tareas")
+            .to_string();
+        rcal.calendars.insert(0, RcalCalendar { name, path });
+    }
+
+    (!rcal.calendars.is_empty()).then_some(rcal)
+}
+
+impl RcalConfig {
+    /// Calendar by name, or the default one (el primero) without name
+    pub fn calendar(&self, name: Option<&str>) -> anyhow::Result<&RcalCalendar> {
+        let found = match name {
+            Some(name) => self.calendars.iter().find(|cal| cal.name == name),
+            None => self.calendars.first(),
+        };
+        found.ok_or_else(|| {
+            let names: Vec<&str> = self.calendars.iter().map(|cal| cal.name.as_str()).collect();
+            anyhow::anyhow!(
+                "Calendario no encontrado: {} (disponibles: {})",
+                name.unwrap_or_default(),
+                names.join(", ")
+            )
+        })
+    }
+}
+
 /// Parsea string de duración de rcal: "2d", "7d", "60m", "3h"
 fn parse_duration(s: &str) -> Duration {
     let s = s.trim();
@@ -118,10 +270,11 @@ fn parse_duration(s: &str) -> Duration {
     Duration::zero()
 }
 
-/// Escanea todos los calendarios y retorna tareas #TODO dentro de la ventana de tiempo de rcal.
-/// Tareas sin DTSTART se incluyen siempre.
+/// Escanea todos los calendarios y retorna las tareas pendientes.
+/// Los `#TODO` de rcal (eventos) se filtran con la ventana de tiempo de rcal; los `VTODO`
+/// solo se ocultan si empiezan después de la ventana (los vencidos siguen apareciendo).
 pub fn read_pending_tasks(rcal_cfg: &RcalConfig) -> anyhow::Result<Vec<IcalTask>> {
-    let now = chrono::Local::now().naive_local();
+    let now = Local::now().naive_local();
     let window_start = now - rcal_cfg.time_backward;
     let window_end = now + rcal_cfg.time_forward;
 
@@ -143,8 +296,8 @@ pub fn read_pending_tasks(rcal_cfg: &RcalConfig) -> anyhow::Result<Vec<IcalTask>
                         continue;
                     }
                     // Filtro de ventana: sin DTSTART → incluir siempre
-                    if let Some(start) = task.start {
-                        if start < window_start || start > window_end {
+                    if let Some(start) = task.start.map(|s| s.local()) {
+                        if start > window_end || (task.legacy && start < window_start) {
                             continue;
                         }
                     }
@@ -154,145 +307,132 @@ pub fn read_pending_tasks(rcal_cfg: &RcalConfig) -> anyhow::Result<Vec<IcalTask>
         }
     }
 
-    // Ordenar por fecha de inicio (None al final)
-    tasks.sort_by(|a, b| match (&a.start, &b.start) {
-        (Some(a_start), Some(b_start)) => a_start.cmp(b_start),
-        (Some(_), None) => std::cmp::Ordering::Less,
-        (None, Some(_)) => std::cmp::Ordering::Greater,
-        (None, None) => std::cmp::Ordering::Equal,
+    // Ordenar por vencimiento o fecha de inicio (None al final)
+    tasks.sort_by_key(|task| {
+        let date = task.due.or(task.start).map(|d| d.local());
+        (date.is_none(), date)
     });
 
     Ok(tasks)
 }
 
-/// Toggle #TODO ↔ #DONE en un archivo .ics
-pub fn toggle_task(file_path: &Path) -> anyhow::Result<()> {
+/// Complete the task of a .ics file: `STATUS:COMPLETED` + `COMPLETED` en un `VTODO`,
+/// `#TODO` → `#DONE` en un evento de rcal
+pub fn complete_task(file_path: &Path) -> anyhow::Result<()> {
     let content = fs::read_to_string(file_path)?;
-    let new_content = if content.contains("DESCRIPTION:#TODO") {
-        content.replacen("DESCRIPTION:#TODO", "DESCRIPTION:#DONE", 1)
-    } else if content.contains("DESCRIPTION:#DONE") {
-        content.replacen("DESCRIPTION:#DONE", "DESCRIPTION:#TODO", 1)
+    let mut components = ical::parse(&content)?;
+
+    let task = components
+        .iter_mut()
+        .flat_map(|c| {
+            if c.name == "VCALENDAR" {
+                c.components.iter_mut()
+            } else {
+                std::slice::from_mut(c).iter_mut()
+            }
+        })
+        .find(|c| is_task(c))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No se encontró un VTODO ni #TODO/#DONE en {}",
+                file_path.display()
+            )
+        })?;
+
+    if legacy_status(task).is_some() {
+        task.set(Property::text("DESCRIPTION", "#DONE"));
     } else {
-        return Err(anyhow::anyhow!(
-            "No se encontró #TODO ni #DONE en {}",
-            file_path.display()
-        ));
-    };
-    fs::write(file_path, new_content)?;
+        set_status(task, TodoStatus::Completed);
+    }
+
+    fs::write(file_path, ical::to_ics(&components))?;
     Ok(())
 }
 
-/// Parse un archivo .ics y retorna un IcalTask si contiene DESCRIPTION:#TODO o #DONE
-fn parse_ics_task(content: &str, file_path: &Path, calendar_name: &str) -> Option<IcalTask> {
-    let mut summary: Option<String> = None;
-    let mut is_task = false;
-    let mut completed = false;
-    let mut start: Option<NaiveDateTime> = None;
-
-    for line in content.lines() {
-        if line.starts_with("SUMMARY:") {
-            summary = Some(line.strip_prefix("SUMMARY:")?.to_string());
-        } else if line == "DESCRIPTION:#TODO" {
-            is_task = true;
-            completed = false;
-        } else if line == "DESCRIPTION:#DONE" {
-            is_task = true;
-            completed = true;
-        } else if line.starts_with("DTSTART") {
-            start = parse_dtstart(line);
-        }
-    }
+/// Write a new `VTODO` as `<UID>.ics` in the calendar directory (se crea si no existe)
+pub fn create_task(calendar: &RcalCalendar, task: &NewTask) -> anyhow::Result<PathBuf> {
+    let uid = new_uid();
+    fs::create_dir_all(&calendar.path)?;
+    let path = calendar.path.join(format!("{}.ics", uid));
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    file.write_all(task.to_calendar(&uid).to_ics().as_bytes())?;
+    Ok(path)
+}
 
-    if !is_task {
-        return None;
-    }
+/// Parse un archivo .ics y retorna un IcalTask si contiene un VTODO o un evento #TODO/#DONE
+pub(crate) fn parse_ics_task(content: &str, file_path: &Path, calendar_name: &str) -> Option<IcalTask> {
+    let mut components = ical::parse(content).ok()?;
+    // Un VEVENT suelto (sin VCALENDAR) también se acepta
+    let calendar = match components.iter().position(|c| c.name == "VCALENDAR") {
+        Some(i) => components.swap_remove(i),
+        None => Component {
+            components,
+            ..Component::new("VCALENDAR")
+        },
+    };
+    let task = calendar.components.iter().find(|c| is_task(c))?;
+
+    let legacy = legacy_status(task);
+    let completed = legacy.unwrap_or_else(|| TodoStatus::of(task) != TodoStatus::Open);
+    let priority = task
+        .property("PRIORITY")
+        .and_then(|p| p.value.trim().parse().ok())
+        .map(Priority::from_ical)
+        .unwrap_or_default();
+    let categories = task
+        .properties
+        .iter()
+        .filter(|p| p.name == "CATEGORIES")
+        .flat_map(|p| p.value.split(',').map(ical::unescape_text))
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect();
 
     Some(IcalTask {
-        summary: summary.unwrap_or_else(|| "(sin título)".to_string()),
-        start,
+        summary: task
+            .text("SUMMARY")
+            .unwrap_or_else(|| "(sin título)".to_string()),
+        start: task.date("DTSTART", &calendar),
+        due: task.date("DUE", &calendar),
+        priority,
+        categories,
         completed,
+        legacy: legacy.is_some(),
         file_path: file_path.to_path_buf(),
         calendar_name: calendar_name.to_string(),
     })
 }
 
-/// Parsea línea DTSTART con formatos:
-/// DTSTART:YYYYMMDDTHHmmss
-/// DTSTART;VALUE=DATE-TIME:YYYYMMDDTHHmmss
-/// DTSTART;VALUE=DATE:YYYYMMDD
-/// DTSTART;TZID=...:YYYYMMDDTHHmmss
-fn parse_dtstart(line: &str) -> Option<NaiveDateTime> {
-    // Obtener el valor después del último ':'
-    let value = line.rsplit(':').next()?.trim();
-
-    // Formato datetime: YYYYMMDDTHHmmss
-    if value.len() == 15 && value.contains('T') {
-        return NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok();
-    }
-
-    // Formato date: YYYYMMDD → convertir a datetime en medianoche
-    if value.len() == 8 && value.chars().all(|c| c.is_ascii_digit()) {
-        let date = chrono::NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
-        return Some(date.and_hms_opt(0, 0, 0).unwrap());
-    }
-
-    None
-}
-
-/// Verifica que el binario `rcal` esté disponible en PATH
-pub fn rcal_available() -> bool {
-    std::process::Command::new("rcal")
-        .arg("--help")
-        .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null())
-        .status()
-        .map(|s| s.success())
-        .unwrap_or(false)
+fn is_task(component: &Component) -> bool {
+    component.name == "VTODO" || (component.name == "VEVENT" && legacy_status(component).is_some())
 }
 
-/// Ejecuta `rcal todo` con título y flags opcionales.
-/// Retorna Ok(()) si el proceso terminó con exit code 0.
-pub fn run_rcal_todo(
-    title: &str,
-    calendar: Option<&str>,
-    date: Option<&str>,
-    time: Option<&str>,
-    duration: Option<&str>,
-) -> anyhow::Result<()> {
-    let mut cmd = std::process::Command::new("rcal");
-    cmd.arg("todo").arg(title);
-
-    if let Some(c) = calendar {
-        cmd.arg("-c").arg(c);
-    }
-    if let Some(d) = date {
-        cmd.arg("-f").arg(d);
-    }
-    if let Some(t) = time {
-        cmd.arg("-t").arg(t);
+/// rcal convention: `DESCRIPTION:#TODO` (pendiente) o `#DONE` (completada) en un VEVENT
+fn legacy_status(component: &Component) -> Option<bool> {
+    if component.name != "VEVENT" {
+        return None;
     }
-    if let Some(dur) = duration {
-        cmd.arg("-d").arg(dur);
+    match component.text("DESCRIPTION")?.trim() {
+        "#TODO" => Some(false),
+        "#DONE" => Some(true),
+        _ => None,
     }
-
-    let status = cmd
-        .stdout(std::process::Stdio::null())
-        .status()
-        .map_err(|e| anyhow::anyhow!("No se pudo ejecutar `rcal`: {}", e))?;
-
-    if !status.success() {
-        return Err(anyhow::anyhow!(
-            "`rcal todo` terminó con código de salida {}",
-            status.code().unwrap_or(-1)
-        ));
-    }
-
-    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::NaiveDateTime;
+
+    /// DTSTART de una línea suelta, leída dentro de un VTODO
+    fn parse_dtstart(line: &str) -> Option<NaiveDateTime> {
+        let ics = format!("BEGIN:VTODO\nSUMMARY:x\n{}\nEND:VTODO", line);
+        let task = parse_ics_task(&ics, Path::new("/tmp/test.ics"), "cal")?;
+        task.start.map(|start| start.local())
+    }
 
     #[test]
     fn test_parse_dtstart_datetime() {
@@ -377,4 +517,60 @@ mod tests {
         assert_eq!(parse_duration("abc"), Duration::zero());
         assert_eq!(parse_duration(""), Duration::zero());
     }
+
+    #[test]
+    fn test_parse_vtodo() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nUID:1\r\nSUMMARY:Renovar\r\n  pasaporte\r\nDUE;VALUE=DATE:20261020\r\nPRIORITY:1\r\nCATEGORIES:casa,papeles\r\nSTATUS:NEEDS-ACTION\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+        let task = parse_ics_task(ics, Path::new("/tmp/a.ics"), "cal").unwrap();
+        assert_eq!(task.summary, "Renovar pasaporte");
+        assert_eq!(task.due.unwrap().date().to_string(), "2026-10-20");
+        assert_eq!(task.priority, Priority::Highest);
+        assert_eq!(task.categories, vec!["casa", "papeles"]);
+        assert!(!task.completed && !task.legacy);
+
+        let done = ics.replace("STATUS:NEEDS-ACTION", "STATUS:COMPLETED");
+        assert!(parse_ics_task(&done, Path::new("/tmp/a.ics"), "cal").unwrap().completed);
+    }
+
+    #[test]
+    fn test_create_and_complete_task() {
+        let dir = tempfile::tempdir().unwrap();
+        let calendar = RcalCalendar {
+            name: "tareas".to_string(),
+            path: dir.path().join("tareas"),
+        };
+        let path = create_task(
+            &calendar,
+            &NewTask {
+                summary: "Pagar luz, agua".to_string(),
+                due: Some(DateValue::Date(chrono::NaiveDate::from_ymd_opt(2026, 10, 20).unwrap())),
+                priority: Priority::High,
+                categories: Vec::new(),
+            },
+        )
+        .unwrap();
+
+        let rcal = RcalConfig {
+            calendars: vec![calendar],
+            ..RcalConfig::default()
+        };
+        let tasks = read_pending_tasks(&rcal).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].summary, "Pagar luz, agua");
+        assert_eq!(tasks[0].priority, Priority::High);
+
+        complete_task(&path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("STATUS:COMPLETED\r\n"));
+        assert!(content.contains("COMPLETED:20"));
+        assert!(read_pending_tasks(&rcal).unwrap().is_empty());
+
+        // Un evento de rcal con #TODO sigue siendo una tarea y se completa con #DONE
+        let legacy = rcal.calendars[0].path.join("evento.ics");
+        fs::write(&legacy, "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:Viejo\nDESCRIPTION:#TODO\nEND:VEVENT\nEND:VCALENDAR\n").unwrap();
+        assert_eq!(read_pending_tasks(&rcal).unwrap().len(), 1);
+        complete_task(&legacy).unwrap();
+        assert!(fs::read_to_string(&legacy).unwrap().contains("DESCRIPTION:#DONE"));
+        assert!(read_pending_tasks(&rcal).unwrap().is_empty());
+    }
 }
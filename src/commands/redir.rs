@@ -1,19 +1,32 @@
 use crate::core::config::Config;
 use crate::core::frontmatter;
 use crate::tags::TagPath;
+use crate::ui::prompts;
 use crate::utils::vault::VaultWalker;
-use chrono::Local;
+use crate::vault::plan::{Plan, PlanMode};
+use crate::vault::rewrite::{self, LinkRewriter};
 use dialoguer::{theme::ColorfulTheme, Select};
-use std::fs;
 use std::path::{Path, PathBuf};
 
 /// Move files to directories matching their tags
 /// - `md --redir file.md` - move single file
 /// - `md --redir .` - move all files recursively in current directory
 /// - `md --redir file.md --no-bak` - move without creating backup
-pub fn run(vault: &Path, config: &Config, target: &str, no_backup: bool) -> anyhow::Result<()> {
-    if target == "." {
-        redir_recursive(vault, config, no_backup)
+/// - `md --redir . --dry-run` - list the moves and link edits without touching the vault
+/// - `md --redir . --pick-first` - with several frontmatter tags, use the first one instead of asking
+pub fn run(
+    vault: &Path,
+    config: &Config,
+    target: &str,
+    no_backup: bool,
+    pick_first: bool,
+    mode: &PlanMode,
+) -> anyhow::Result<()> {
+    let mut plan = Plan::new("redir", vault);
+    plan.backup = !no_backup;
+
+    let touched = if target == "." {
+        redir_recursive(vault, config, pick_first, &mut plan)?
     } else {
         let path = Path::new(target);
         let abs_path = if path.is_absolute() {
@@ -21,11 +34,21 @@ pub fn run(vault: &Path, config: &Config, target: &str, no_backup: bool) -> anyh
         } else {
             std::env::current_dir()?.join(target)
         };
-        redir_file(vault, config, &abs_path, no_backup)
+        redir_file(vault, config, &abs_path, pick_first, &mut plan)?
+    };
+
+    if plan.finish(mode)? {
+        rewrite::print_summary(vault, &touched);
     }
+    Ok(())
 }
 
-fn redir_recursive(vault: &Path, config: &Config, no_backup: bool) -> anyhow::Result<()> {
+fn redir_recursive(
+    vault: &Path,
+    config: &Config,
+    pick_first: bool,
+    plan: &mut Plan,
+) -> anyhow::Result<Vec<rewrite::RewrittenFile>> {
     let current_dir = std::env::current_dir()?;
     let templates_path = vault.join(&config.templates_dir);
 
@@ -45,14 +68,18 @@ fn redir_recursive(vault: &Path, config: &Config, no_backup: bool) -> anyhow::Re
         current_dir.display()
     );
 
+    // Links graph is built from the vault before any planned move
+    let mut rewriter = LinkRewriter::new(vault, &templates_path)?;
+
     let mut moved = 0;
     let mut skipped = 0;
     let mut errors = 0;
 
     for path in files_to_process {
-        match redir_file_inner(vault, config, &path, no_backup) {
+        match redir_file_inner(vault, config, &path, pick_first, plan) {
             Ok(Some(dest)) => {
                 println!("  ✅ {} → {}", path.display(), dest.display());
+                rewriter.record_move(&path, &dest);
                 moved += 1;
             }
             Ok(None) => {
@@ -69,24 +96,44 @@ fn redir_recursive(vault: &Path, config: &Config, no_backup: bool) -> anyhow::Re
         "\nRedir completado: {} movidos, {} sin cambios, {} errores",
         moved, skipped, errors
     );
-    Ok(())
+
+    rewriter.plan(plan)
 }
 
-fn redir_file(vault: &Path, config: &Config, path: &Path, no_backup: bool) -> anyhow::Result<()> {
+fn redir_file(
+    vault: &Path,
+    config: &Config,
+    path: &Path,
+    pick_first: bool,
+    plan: &mut Plan,
+) -> anyhow::Result<Vec<rewrite::RewrittenFile>> {
     if !path.exists() {
         anyhow::bail!("Archivo no encontrado: {}", path.display());
     }
 
-    match redir_file_inner(vault, config, path, no_backup) {
-        Ok(Some(dest)) => println!("✅ Movido: {} → {}", path.display(), dest.display()),
+    let templates_path = vault.join(&config.templates_dir);
+    let mut rewriter = LinkRewriter::new(vault, &templates_path)?;
+
+    match redir_file_inner(vault, config, path, pick_first, plan) {
+        Ok(Some(dest)) => {
+            println!("✅ Movido: {} → {}", path.display(), dest.display());
+            rewriter.record_move(path, &dest);
+            return rewriter.plan(plan);
+        }
         Ok(None) => println!("ℹ️  Sin cambios (ya está en ubicación correcta o sin tags)"),
         Err(e) => eprintln!("❌ Error: {}", e),
     }
-    Ok(())
+    Ok(Vec::new())
 }
 
-fn redir_file_inner(vault: &Path, config: &Config, path: &Path, no_backup: bool) -> anyhow::Result<Option<PathBuf>> {
-    let content = fs::read_to_string(path)?;
+fn redir_file_inner(
+    vault: &Path,
+    config: &Config,
+    path: &Path,
+    pick_first: bool,
+    plan: &mut Plan,
+) -> anyhow::Result<Option<PathBuf>> {
+    let content = plan.read(path)?;
     let (_fm, body) = frontmatter::extract(&content)?;
 
     // Extract primary tag from body (first line: { #tag/path })
@@ -103,9 +150,10 @@ fn redir_file_inner(vault: &Path, config: &Config, path: &Path, no_backup: bool)
         }
 
         // If multiple tags in frontmatter, prompt user to select
-        if fm_tags.len() == 1 {
+        if fm_tags.len() == 1 || pick_first {
             fm_tags.into_iter().next().unwrap()
         } else {
+            prompts::ensure_tty("el archivo tiene varios tags en el frontmatter, usa --pick-first")?;
             let tag_strings: Vec<String> = fm_tags.iter().map(|t| t.to_slash_string()).collect();
 
             println!("\nEl archivo tiene {} tags en frontmatter:", fm_tags.len());
@@ -142,56 +190,53 @@ fn redir_file_inner(vault: &Path, config: &Config, path: &Path, no_backup: bool)
         }
     }
 
-    // Create backup if not disabled
-    if !no_backup {
-        create_backup(vault, path)?;
-    }
-
-    // Create destination directory
-    fs::create_dir_all(&dest_dir)?;
-
     // Build destination file path
     let filename = path.file_name().ok_or_else(|| anyhow::anyhow!("Invalid filename"))?;
     let dest_path = dest_dir.join(filename);
 
-    // Check for collision
-    if dest_path.exists() {
+    // Check for collision (including files already planned to move there)
+    if plan.exists(&dest_path) {
         anyhow::bail!(
             "Archivo destino ya existe: {}",
             dest_path.display()
         );
     }
 
-    // Move file
-    fs::rename(path, &dest_path)?;
+    // Plan destination directory and move (backup per plan.backup)
+    plan.create_dir(&dest_dir);
+    plan.move_path(path, &dest_path);
 
     Ok(Some(dest_path))
 }
 
-/// Create backup in vault/.arc/backups/ with timestamp
-/// Backups are stored flat (no directory structure) with format: filename_YYYYMMDD_HHMMSS.md.bak
-fn create_backup(vault: &Path, file_path: &Path) -> anyhow::Result<()> {
-    let backup_dir = vault.join(".arc").join("backups");
-    fs::create_dir_all(&backup_dir)?;
-
-    // Get filename without path
-    let filename = file_path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .ok_or_else(|| anyhow::anyhow!("Invalid filename"))?;
-
-    // Generate timestamp
-    let timestamp = Local::now().format("%Y%m%d_%H%M%S").to_string();
-
-    // Build backup filename: original_20260202_131045.md.bak
-    let backup_filename = if let Some(stem) = filename.strip_suffix(".md") {
-        format!("{}_{}.md.bak", stem, timestamp)
-    } else {
-        format!("{}_{}.bak", filename, timestamp)
-    };
-
-    let backup_path = backup_dir.join(backup_filename);
-    fs::copy(file_path, &backup_path)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pick_first_uses_the_first_frontmatter_tag() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        let note = vault.join("nota.md");
+        std::fs::write(&note, "---\ntags:\n- proj/acme\n- otro\n---\nTexto\n").unwrap();
+        let config = Config {
+            vault: vault.to_str().unwrap().to_string(),
+            ..Default::default()
+        };
 
-    Ok(())
+        let mut plan = Plan::new("redir", vault);
+        let dest = redir_file_inner(vault, &config, &note, true, &mut plan).unwrap();
+        let expected = vault.join(&config.notes_dir).join("proj/acme/nota.md");
+        assert_eq!(dest.as_deref(), Some(expected.as_path()));
+        assert!(plan.exists(&expected));
+
+        // Sin --pick-first y sin terminal falla en vez de esperar el selector
+        if !prompts::is_tty() {
+            let mut plan = Plan::new("redir", vault);
+            let err = redir_file_inner(vault, &config, &note, false, &mut plan).unwrap_err();
+            assert!(err.to_string().contains("--pick-first"));
+            assert!(plan.is_empty());
+        }
+    }
 }
@@ -1,13 +1,14 @@
 use crate::commands::retag;
 use crate::core::config::Config;
+use crate::vault::plan::{Plan, PlanMode};
+use crate::vault::rewrite::{self, LinkRewriter};
 use anyhow::{Context, Result};
 use std::env;
-use std::fs;
 use std::path::{Path, PathBuf};
 
 /// Renombra directorios sincronizando bidireccionalmente entre workspace productivo y vault
 /// Ejecuta retag automáticamente después del rename
-pub fn run(config: &Config, new_name: &str, no_retag: bool) -> Result<()> {
+pub fn run(config: &Config, new_name: &str, no_retag: bool, mode: &PlanMode) -> Result<()> {
     let current_dir = env::current_dir()?.canonicalize()?;
     let vault = PathBuf::from(&config.vault).canonicalize()?;
     let tag_root = vault.join(&config.tag_root);
@@ -15,20 +16,21 @@ pub fn run(config: &Config, new_name: &str, no_retag: bool) -> Result<()> {
     // Determine if we're in productive workspace or vault
     if current_dir.starts_with(&vault) {
         // We're in vault → rename both productive and vault dirs
-        rename_from_vault(config, &current_dir, &vault, &tag_root, new_name, no_retag)
+        rename_from_vault(config, &current_dir, &vault, &tag_root, new_name, no_retag, mode)
     } else {
         // We're in productive dir → rename both vault and productive dirs
-        rename_from_productive(config, &current_dir, &vault, &tag_root, new_name, no_retag)
+        rename_from_productive(config, &current_dir, &vault, &tag_root, new_name, no_retag, mode)
     }
 }
 
 fn rename_from_productive(
     config: &Config,
     current_dir: &Path,
-    _vault: &Path,
+    vault: &Path,
     tag_root: &Path,
     new_name: &str,
     no_retag: bool,
+    mode: &PlanMode,
 ) -> Result<()> {
     // Find matching dir_mapping
     let dir_mappings = config
@@ -95,35 +97,22 @@ fn rename_from_productive(
         .and_then(|s| s.to_str())
         .unwrap_or("?");
 
-    // Rename vault directory
-    fs::rename(&vault_dir, &new_vault_dir).with_context(|| {
-        format!(
-            "Error renombrando vault '{}' → '{}'",
-            vault_dir.display(),
-            new_vault_dir.display()
-        )
-    })?;
+    // Links graph is built from the vault before the planned renames
+    let mut rewriter = LinkRewriter::new(vault, &vault.join(&config.templates_dir))?;
 
-    // Rename productive directory
-    fs::rename(current_dir, &new_productive_dir).with_context(|| {
-        format!(
-            "Error renombrando productivo '{}' → '{}'",
-            current_dir.display(),
-            new_productive_dir.display()
-        )
-    })?;
+    let mut plan = Plan::new("rename", vault);
+    plan.move_path(&vault_dir, &new_vault_dir);
+    plan.move_path(current_dir, &new_productive_dir);
 
-    println!(
-        "\x1b[33m{}\x1b[0m → \x1b[32m{}\x1b[0m",
-        old_name, new_name
-    );
-
-    // Execute retag if not disabled
-    if !no_retag {
-        let vault = PathBuf::from(&config.vault);
-        // Change to new vault dir to run retag
-        std::env::set_current_dir(&new_vault_dir)?;
-        retag::run(&vault, config, ".", true, false)?; // no_backup=true, no_alias=false (keep old tags as aliases)
+    rewriter.record_dir_move(&vault_dir, &new_vault_dir);
+    if let Some(touched) =
+        finish_rename(config, vault, &rewriter, &vault_dir, &new_vault_dir, no_retag, plan, mode)?
+    {
+        println!(
+            "\x1b[33m{}\x1b[0m → \x1b[32m{}\x1b[0m",
+            old_name, new_name
+        );
+        rewrite::print_summary(vault, &touched);
     }
 
     Ok(())
@@ -132,10 +121,11 @@ fn rename_from_productive(
 fn rename_from_vault(
     config: &Config,
     current_dir: &Path,
-    _vault: &Path,
+    vault: &Path,
     tag_root: &Path,
     new_name: &str,
     no_retag: bool,
+    mode: &PlanMode,
 ) -> Result<()> {
     // Calculate relative path from tag_root to current_dir
     let relative = current_dir
@@ -207,38 +197,50 @@ fn rename_from_vault(
         .and_then(|s| s.to_str())
         .unwrap_or("?");
 
-    // Rename productive directory first
-    fs::rename(&productive_dir, &new_productive_dir).with_context(|| {
-        format!(
-            "Error renombrando productivo '{}' → '{}'",
-            productive_dir.display(),
-            new_productive_dir.display()
-        )
-    })?;
-
-    // Rename vault directory
-    fs::rename(current_dir, &new_vault_dir).with_context(|| {
-        format!(
-            "Error renombrando vault '{}' → '{}'",
-            current_dir.display(),
-            new_vault_dir.display()
-        )
-    })?;
+    // Links graph is built from the vault before the planned renames
+    let mut rewriter = LinkRewriter::new(vault, &vault.join(&config.templates_dir))?;
+
+    // Productive directory first
+    let mut plan = Plan::new("rename", vault);
+    plan.move_path(&productive_dir, &new_productive_dir);
+    plan.move_path(current_dir, &new_vault_dir);
+
+    rewriter.record_dir_move(current_dir, &new_vault_dir);
+    if let Some(touched) =
+        finish_rename(config, vault, &rewriter, current_dir, &new_vault_dir, no_retag, plan, mode)?
+    {
+        println!(
+            "\x1b[33m{}\x1b[0m → \x1b[32m{}\x1b[0m",
+            old_name, new_name
+        );
+        rewrite::print_summary(vault, &touched);
+    }
+
+    Ok(())
+}
 
-    println!(
-        "\x1b[33m{}\x1b[0m → \x1b[32m{}\x1b[0m",
-        old_name, new_name
-    );
+/// Add link rewrites and the retag of the renamed vault dir to the plan, then apply it
+/// (or print it with `--dry-run`). Returns the rewritten files if the plan was applied.
+#[allow(clippy::too_many_arguments)]
+fn finish_rename(
+    config: &Config,
+    vault: &Path,
+    rewriter: &LinkRewriter,
+    vault_dir: &Path,
+    new_vault_dir: &Path,
+    no_retag: bool,
+    mut plan: Plan,
+    mode: &PlanMode,
+) -> Result<Option<Vec<rewrite::RewrittenFile>>> {
+    let touched = rewriter.plan(&mut plan)?;
 
-    // Execute retag if not disabled
+    // Retag without backups, keeping old tags as aliases
     if !no_retag {
-        let vault = PathBuf::from(&config.vault);
-        // Change to new vault dir to run retag
-        std::env::set_current_dir(&new_vault_dir)?;
-        retag::run(&vault, config, ".", true, false)?; // no_backup=true, no_alias=false (keep old tags as aliases)
+        plan.backup = false;
+        retag::plan_tree(vault, config, vault_dir, new_vault_dir, false, &mut plan)?;
     }
 
-    Ok(())
+    Ok(plan.finish(mode)?.then_some(touched))
 }
 
 #[cfg(test)]
@@ -272,18 +274,8 @@ mod tests {
 
         let config = Config {
             vault: vault.to_str().unwrap().to_string(),
-            date: "%Y-%m-%d".to_string(),
-            time: "%H:%M".to_string(),
-            tag_root: "Notas".to_string(),
-            notes_dir: "Notas".to_string(),
-            diary_dir: "Diario".to_string(),
-            templates_dir: "Templates".to_string(),
             dir_mappings: Some(dir_mappings),
-            default_nametype: None,
-            editor: None,
-            editor_mode: None,
-            timeprint: None,
-            rcal_config: None,
+            ..Default::default()
         };
 
         // Change to project dir and rename both dirs
@@ -298,6 +290,7 @@ mod tests {
             &tag_root_canonical,
             "new-name",
             true, // no_retag for test
+            &PlanMode::default(),
         )
         .unwrap();
 
@@ -336,18 +329,8 @@ mod tests {
 
         let config = Config {
             vault: vault.to_str().unwrap().to_string(),
-            date: "%Y-%m-%d".to_string(),
-            time: "%H:%M".to_string(),
-            tag_root: "Notas".to_string(),
-            notes_dir: "Notas".to_string(),
-            diary_dir: "Diario".to_string(),
-            templates_dir: "Templates".to_string(),
             dir_mappings: Some(dir_mappings),
-            default_nametype: None,
-            editor: None,
-            editor_mode: None,
-            timeprint: None,
-            rcal_config: None,
+            ..Default::default()
         };
 
         // Change to vault dir and rename both dirs
@@ -362,6 +345,7 @@ mod tests {
             &tag_root_canonical,
             "new-name",
             true, // no_retag for test
+            &PlanMode::default(),
         )
         .unwrap();
 
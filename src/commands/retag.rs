@@ -2,19 +2,31 @@ use crate::core::config::Config;
 use crate::core::frontmatter;
 use crate::tags;
 use crate::utils::vault::VaultWalker;
+use crate::vault::plan::{Plan, PlanMode};
 use chrono::Local;
 use serde_yaml::Value;
-use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Re-tag files based on their directory location
 /// - `md --retag file.md` - retag single file
 /// - `md --retag .` - retag all files recursively in current directory
 /// - `md --retag file.md --no-bak` - retag without creating backup
 /// - `md --retag file.md --no-alias` - retag without adding old tag to aliases
-pub fn run(vault: &Path, config: &Config, target: &str, no_backup: bool, no_alias: bool) -> anyhow::Result<()> {
+/// - `md --retag . --dry-run` - show the diffs without writing
+pub fn run(
+    vault: &Path,
+    config: &Config,
+    target: &str,
+    no_backup: bool,
+    no_alias: bool,
+    mode: &PlanMode,
+) -> anyhow::Result<()> {
+    let mut plan = Plan::new("retag", vault);
+    plan.backup = !no_backup;
+
     if target == "." {
-        retag_recursive(vault, config, no_backup, no_alias)
+        let current_dir = std::env::current_dir()?;
+        plan_tree(vault, config, &current_dir, &current_dir, no_alias, &mut plan)?;
     } else {
         let path = Path::new(target);
         let abs_path = if path.is_absolute() {
@@ -22,39 +34,60 @@ pub fn run(vault: &Path, config: &Config, target: &str, no_backup: bool, no_alia
         } else {
             std::env::current_dir()?.join(target)
         };
-        retag_file(vault, config, &abs_path, no_backup, no_alias)
+        retag_file(vault, config, &abs_path, no_alias, &mut plan)?;
     }
+
+    plan.finish(mode)?;
+    Ok(())
 }
 
-fn retag_recursive(vault: &Path, config: &Config, no_backup: bool, no_alias: bool) -> anyhow::Result<()> {
-    let current_dir = std::env::current_dir()?;
+/// Plan the retag of every note under `disk_dir`, as if it were already at `dir`
+/// (`rename` plans the retag before moving the directory).
+pub(crate) fn plan_tree(
+    vault: &Path,
+    config: &Config,
+    disk_dir: &Path,
+    dir: &Path,
+    no_alias: bool,
+    plan: &mut Plan,
+) -> anyhow::Result<()> {
     let templates_path = vault.join(&config.templates_dir);
 
     let mut updated = 0;
     let mut skipped = 0;
     let mut errors = 0;
 
-    println!("Re-tagging archivos en: {}", current_dir.display());
+    println!("Re-tagging archivos en: {}", dir.display());
 
-    VaultWalker::new(&current_dir)
+    let mut paths: Vec<PathBuf> = Vec::new();
+    VaultWalker::new(disk_dir)
         .exclude_templates(&templates_path)
-        .walk(|path, content| {
-            match retag_file_inner(vault, config, path, content, no_backup, no_alias) {
-                Ok(true) => {
-                    println!("  ✅ {}", path.display());
-                    updated += 1;
-                }
-                Ok(false) => {
-                    skipped += 1;
-                }
-                Err(e) => {
-                    eprintln!("  ❌ {}: {}", path.display(), e);
-                    errors += 1;
-                }
+        .walk_paths(|path| {
+            if let Ok(rel) = path.strip_prefix(disk_dir) {
+                paths.push(dir.join(rel));
             }
             Ok(())
         })?;
 
+    for path in paths {
+        let result = plan
+            .read(&path)
+            .and_then(|content| retag_file_inner(vault, config, &path, &content, no_alias, plan));
+        match result {
+            Ok(true) => {
+                println!("  ✅ {}", path.display());
+                updated += 1;
+            }
+            Ok(false) => {
+                skipped += 1;
+            }
+            Err(e) => {
+                eprintln!("  ❌ {}: {}", path.display(), e);
+                errors += 1;
+            }
+        }
+    }
+
     println!(
         "\nRetag completado: {} actualizados, {} sin cambios, {} errores",
         updated, skipped, errors
@@ -62,13 +95,13 @@ fn retag_recursive(vault: &Path, config: &Config, no_backup: bool, no_alias: boo
     Ok(())
 }
 
-fn retag_file(vault: &Path, config: &Config, path: &Path, no_backup: bool, no_alias: bool) -> anyhow::Result<()> {
+fn retag_file(vault: &Path, config: &Config, path: &Path, no_alias: bool, plan: &mut Plan) -> anyhow::Result<()> {
     if !path.exists() {
         anyhow::bail!("Archivo no encontrado: {}", path.display());
     }
 
-    let content = fs::read_to_string(path)?;
-    match retag_file_inner(vault, config, path, &content, no_backup, no_alias) {
+    let content = plan.read(path)?;
+    match retag_file_inner(vault, config, path, &content, no_alias, plan) {
         Ok(true) => println!("✅ Actualizado: {}", path.display()),
         Ok(false) => println!("ℹ️  Sin cambios: {}", path.display()),
         Err(e) => eprintln!("❌ Error: {}", e),
@@ -76,15 +109,16 @@ fn retag_file(vault: &Path, config: &Config, path: &Path, no_backup: bool, no_al
     Ok(())
 }
 
-fn retag_file_inner(
+pub(crate) fn retag_file_inner(
     vault: &Path,
     config: &Config,
     path: &Path,
     content: &str,
-    no_backup: bool,
     no_alias: bool,
+    plan: &mut Plan,
 ) -> anyhow::Result<bool> {
-    let (mut fm, body) = frontmatter::extract(content)?;
+    let mut doc = frontmatter::Document::parse(content)?;
+    let body = doc.body().to_string();
 
     // Derive new primary tag from path
     let new_tag_str = derive_tag_from_path(vault, config, path)?;
@@ -121,8 +155,8 @@ fn retag_file_inner(
                 let alias_entry = format!("{} {}", date, old_tag_str);
 
                 // Get or create aliases array
-                let aliases = fm
-                    .get(&Value::String("aliases".to_string()))
+                let aliases = doc
+                    .get("aliases")
                     .and_then(|v| {
                         if let Value::Sequence(seq) = v {
                             Some(seq.clone())
@@ -134,12 +168,10 @@ fn retag_file_inner(
 
                 // Add new alias
                 let mut new_aliases = aliases;
-                new_aliases.push(Value::String(alias_entry));
+                new_aliases.push(Value::String(alias_entry.clone()));
 
-                fm.insert(
-                    Value::String("aliases".to_string()),
-                    Value::Sequence(new_aliases),
-                );
+                doc.set("aliases", Value::Sequence(new_aliases))?;
+                plan.note_alias(path, &alias_entry);
             }
         }
     }
@@ -147,50 +179,16 @@ fn retag_file_inner(
     // Update primary tag in body
     let new_body = tags::parser::replace_primary_tag(&body, &new_tag);
 
-    // Create backup if not disabled
-    if !no_backup {
-        create_backup(vault, path)?;
-    }
-
-    // Write updated file
-    let new_content = format!("---\n{}---\n{}", serde_yaml::to_string(&fm)?, new_body);
-    fs::write(path, new_content)?;
-
-    Ok(true)
+    // Plan updated file (frontmatter edited in place, backup per plan.backup)
+    doc.set_body(&new_body);
+    plan.edit(path, doc.into_string())
 }
 
-/// Create backup in vault/.arc/backups/ with timestamp
-/// Backups are stored flat (no directory structure) with format: filename_YYYYMMDD_HHMMSS.md.bak
-fn create_backup(vault: &Path, file_path: &Path) -> anyhow::Result<()> {
-    let backup_dir = vault.join(".arc").join("backups");
-    fs::create_dir_all(&backup_dir)?;
-
-    // Get filename without path
-    let filename = file_path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .ok_or_else(|| anyhow::anyhow!("Invalid filename"))?;
-
-    // Generate timestamp
-    let timestamp = Local::now().format("%Y%m%d_%H%M%S").to_string();
-
-    // Build backup filename: original_20260202_131045.md.bak
-    let backup_filename = if let Some(stem) = filename.strip_suffix(".md") {
-        format!("{}_{}.md.bak", stem, timestamp)
-    } else {
-        format!("{}_{}.bak", filename, timestamp)
-    };
-
-    let backup_path = backup_dir.join(backup_filename);
-    fs::copy(file_path, &backup_path)?;
-
-    Ok(())
-}
 
 /// Derive tag from file path relative to vault
 /// Example: vault/Notas/proyecto/cliente/nota.md -> "proyecto/cliente"
 /// tag_root (ej: "Notas") se excluye del tag generado
-fn derive_tag_from_path(vault: &Path, config: &Config, path: &Path) -> anyhow::Result<String> {
+pub(crate) fn derive_tag_from_path(vault: &Path, config: &Config, path: &Path) -> anyhow::Result<String> {
     let tag_root = vault.join(&config.tag_root);
 
     // Get path relative to tag_root
@@ -0,0 +1,110 @@
+use crate::core::config::Config;
+use crate::core::note::NoteBuilder;
+use crate::search::index::tokenize;
+use crate::search::{query, Clause, SearchIndex};
+use crate::ui::prompts;
+use dialoguer::{theme::ColorfulTheme, FuzzySelect};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SNIPPET_WIDTH: usize = 60;
+
+/// Full-text search over the vault
+/// - `mad search "texto"` - busca notas que contienen todos los términos
+/// - `mad search "\"frase exacta\""` - busca la frase literal
+/// - `mad search "pref*"` - busca por prefijo
+/// - `mad search "texto" --rebuild` - regenera el índice desde cero
+pub fn run(
+    vault: PathBuf,
+    config: Config,
+    query_str: &str,
+    limit: usize,
+    rebuild: bool,
+    editor: Option<String>,
+) -> anyhow::Result<()> {
+    let index = load_index(&vault, &config, rebuild)?;
+
+    let mut hits = index.search(&vault, query_str);
+
+    if hits.is_empty() {
+        println!("Sin resultados para: {}", query_str);
+        return Ok(());
+    }
+
+    let total = hits.len();
+    hits.truncate(limit);
+
+    let clauses = query::parse(query_str);
+    let display_items: Vec<String> = hits
+        .iter()
+        .map(|hit| {
+            let relative = hit.path.strip_prefix(&vault).unwrap_or(&hit.path);
+            match snippet(&hit.path, &clauses) {
+                Some(s) => format!("{} — {}", relative.display(), s),
+                None => relative.display().to_string(),
+            }
+        })
+        .collect();
+
+    println!(
+        "\n{} resultados (mostrando {}, ESC para cancelar):\n",
+        total,
+        hits.len()
+    );
+
+    prompts::ensure_tty("el selector de resultados necesita una terminal")?;
+    let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Selecciona una nota para abrir")
+        .items(&display_items)
+        .default(0)
+        .interact_opt()?;
+
+    if let Some(idx) = selection {
+        let selected_path = &hits[idx].path;
+        println!("\nAbriendo: {}", selected_path.display());
+        NoteBuilder::add_timestamp_and_open(selected_path, &vault, &config, editor)?;
+    }
+
+    Ok(())
+}
+
+/// Carga el índice persistido y lo actualiza incrementalmente (solo archivos modificados)
+fn load_index(vault: &Path, config: &Config, rebuild: bool) -> anyhow::Result<SearchIndex> {
+    let index_path = Config::search_index_path()?;
+    let templates_path = vault.join(&config.templates_dir);
+
+    let mut index = if rebuild {
+        SearchIndex::new(vault)
+    } else {
+        SearchIndex::load(&index_path, vault)
+    };
+
+    let stats = index.refresh(vault, &templates_path)?;
+    if stats.changed() || rebuild {
+        index.save(&index_path)?;
+    }
+
+    Ok(index)
+}
+
+/// Primera línea de la nota que contiene algún término de la consulta
+fn snippet(path: &Path, clauses: &[Clause]) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+
+    let line = content.lines().find(|line| {
+        let tokens = tokenize(line);
+        clauses.iter().any(|clause| match clause {
+            Clause::Term(t) => tokens.contains(t),
+            Clause::Prefix(p) => tokens.iter().any(|t| t.starts_with(p.as_str())),
+            Clause::Phrase(words) => words.iter().any(|w| tokens.contains(w)),
+        })
+    })?;
+
+    let trimmed = line.trim();
+    if trimmed.chars().count() > SNIPPET_WIDTH {
+        let truncated: String = trimmed.chars().take(SNIPPET_WIDTH - 3).collect();
+        Some(format!("{}...", truncated))
+    } else {
+        Some(trimmed.to_string())
+    }
+}
@@ -0,0 +1,786 @@
+use crate::caldav::{Client, Resource};
+use crate::commands::rcal_tasks::{self, IcalTask, NewTask, TodoStatus};
+use crate::core::config::{CaldavConfig, Config};
+use crate::core::ical::{self, Component, DateValue};
+use crate::core::section;
+use crate::core::task::{self, TaskLine, TaskMeta};
+use crate::vault::index::VaultIndex;
+use crate::vault::plan::{Plan, PlanMode};
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Tasks linked in the last sync, para distinguir "borrada de un lado" de "nueva del otro"
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    /// Colección a la que corresponden los IDs
+    pub url: String,
+    pub ids: BTreeSet<String>,
+}
+
+impl SyncState {
+    /// State of `url`; empieza de cero si no existe o es de otra colección
+    pub fn load(path: &Path, url: &str) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<SyncState>(&content).ok())
+            .filter(|state| state.url == url)
+            .unwrap_or_else(|| SyncState {
+                url: url.to_string(),
+                ids: BTreeSet::new(),
+            })
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// What a sync did (o haría, en dry-run)
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub created: usize,
+    pub imported: usize,
+    pub completed_remote: usize,
+    pub completed_local: usize,
+    pub deleted: usize,
+    pub unlinked: usize,
+    pub errors: usize,
+}
+
+/// Lines of the vault that carry the same task ID
+struct LocalTask {
+    /// (archivo, línea, estado) de cada copia, sin las `[>]` ni `[M]`; puede haber copias en
+    /// varias notas (rollover con `mode = "copy"`)
+    lines: Vec<(PathBuf, usize, char)>,
+    meta: TaskMeta,
+}
+
+impl LocalTask {
+    fn status(&self) -> TodoStatus {
+        if self.lines.iter().any(|(_, _, s)| matches!(s, 'x' | 'X')) {
+            TodoStatus::Completed
+        } else if self.lines.iter().any(|(_, _, s)| *s == '-') {
+            TodoStatus::Cancelled
+        } else {
+            TodoStatus::Open
+        }
+    }
+}
+
+/// VTODO of the collection
+struct RemoteTask {
+    resource: Resource,
+    calendar: Vec<Component>,
+    status: TodoStatus,
+}
+
+impl RemoteTask {
+    fn task(&self) -> Option<IcalTask> {
+        rcal_tasks::parse_ics_task(&self.resource.data, Path::new(&self.resource.url), "")
+    }
+
+    fn summary(&self) -> String {
+        self.task().map(|task| task.summary).unwrap_or_default()
+    }
+}
+
+/// Change to the server, se hace después de escribir el vault
+enum RemoteOp {
+    Create(String),
+    Complete(String, TodoStatus),
+    Delete(String),
+}
+
+/// Planned changes to the markdown side, por archivo
+#[derive(Default)]
+struct LocalEdits {
+    /// Línea (1-based) → contenido nuevo (puede traer una línea insertada debajo)
+    lines: BTreeMap<PathBuf, BTreeMap<usize, String>>,
+    /// Líneas a agregar a la nota de tareas importadas
+    imports: Vec<String>,
+}
+
+/// `mad tasks sync`: sync Markdown tasks and the `[tasks.caldav]` collection both ways
+pub fn run(vault: &Path, config: &Config, dry_run: bool) -> anyhow::Result<()> {
+    let caldav = caldav_config(config)?;
+    let client = client(caldav)?;
+    let state_path = Config::caldav_state_path()?;
+    let mut state = SyncState::load(&state_path, client.collection());
+    let templates_path = vault.join(&config.templates_dir);
+    let index = VaultIndex::open(vault, &templates_path)?;
+
+    let report = sync(vault, config, &index, &client, &mut state, dry_run)?;
+    if !dry_run {
+        state.save(&state_path)?;
+    }
+
+    println!(
+        "\n{} creadas, {} importadas, {} completadas en el servidor, {} en el vault, {} borradas, {} desvinculadas",
+        report.created,
+        report.imported,
+        report.completed_remote,
+        report.completed_local,
+        report.deleted,
+        report.unlinked
+    );
+    if report.errors > 0 {
+        anyhow::bail!("{} tareas no se pudieron sincronizar", report.errors);
+    }
+    Ok(())
+}
+
+/// Link a markdown task to the collection: le agrega un ID y la crea en el servidor
+pub fn link_task(vault: &Path, config: &Config, path: &Path, line_number: usize) -> anyhow::Result<()> {
+    let client = client(caldav_config(config)?)?;
+    let mut plan = Plan::new("tasks sync", vault);
+    plan.backup = false;
+    let content = plan.read(path)?;
+    let mut lines: Vec<String> = content.split('\n').map(str::to_string).collect();
+    let Some(task) = line_number
+        .checked_sub(1)
+        .and_then(|idx| lines.get(idx))
+        .and_then(|line| TaskLine::parse(line))
+    else {
+        anyhow::bail!("La línea {} de {} no es una tarea", line_number, path.display());
+    };
+    if let Some(id) = task::task_id(task.text) {
+        anyhow::bail!("La tarea ya está sincronizada ({})", id);
+    }
+
+    let uid = rcal_tasks::new_uid();
+    let meta = TaskMeta::parse(task.text);
+    let line = task.with_text(task.status, &task::with_task_id(task.text, &uid));
+    lines[line_number - 1] = line;
+    plan.edit(path, lines.join("\n"))?;
+    plan.apply()?;
+
+    // El ID ya está en el vault: si el servidor falla, el próximo sync la crea
+    if let Err(e) = client.create(&uid, &ical::to_ics(&[new_task(&meta).to_calendar(&uid)])) {
+        anyhow::bail!(
+            "{}: {}. La tarea quedó vinculada y se creará con `mad tasks sync`",
+            meta.title,
+            e
+        );
+    }
+
+    let state_path = Config::caldav_state_path()?;
+    let mut state = SyncState::load(&state_path, client.collection());
+    state.ids.insert(uid);
+    state.save(&state_path)?;
+    println!("↑ {}: creada en el servidor", meta.title);
+    Ok(())
+}
+
+/// Reconcile the vault and the collection. Solo se sincroniza el estado: el título, las
+/// fechas y la prioridad se copian al crear la tarea del otro lado. Si una está completada
+/// de un lado y abierta del otro, se completa en ambos.
+pub fn sync(
+    vault: &Path,
+    config: &Config,
+    index: &VaultIndex,
+    client: &Client,
+    state: &mut SyncState,
+    dry_run: bool,
+) -> anyhow::Result<Report> {
+    let caldav = caldav_config(config)?;
+    let today = Local::now().date_naive();
+    let mut local = local_tasks(index);
+    let mut edits = LocalEdits::default();
+
+    // Tareas abiertas con el tag de sincronización: se vinculan con un ID nuevo
+    if let Some(tag) = caldav.tag.as_deref().map(|t| t.trim_start_matches('#')) {
+        for (path, note) in index.iter() {
+            for indexed in note.tasks.iter().filter(|t| t.status == ' ') {
+                let meta = TaskMeta::parse(&indexed.text);
+                if meta.id.is_some() || !meta.tags.iter().any(|t| t == tag) {
+                    continue;
+                }
+                let uid = rcal_tasks::new_uid();
+                local.insert(
+                    uid,
+                    LocalTask {
+                        lines: vec![(path.clone(), indexed.line, ' ')],
+                        meta,
+                    },
+                );
+            }
+        }
+    }
+
+    let mut remote = BTreeMap::new();
+    for resource in client.todos()? {
+        match parse_remote(resource) {
+            Ok(Some((uid, task))) => {
+                remote.insert(uid, task);
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("⚠️  {}", e),
+        }
+    }
+
+    // Un vault o una colección sin ninguna de las tareas vinculadas es más probable que sea
+    // el equivocado que un borrado masivo
+    if state.ids.len() > 1 {
+        for (side, ids) in [
+            ("el vault", local.keys().collect::<BTreeSet<_>>()),
+            ("el servidor", remote.keys().collect()),
+        ] {
+            if !state.ids.iter().any(|id| ids.contains(id)) {
+                anyhow::bail!(
+                    "Ninguna de las {} tareas sincronizadas está en {}. Si es correcto, borra {} y vuelve a sincronizar",
+                    state.ids.len(),
+                    side,
+                    Config::caldav_state_path()?.display()
+                );
+            }
+        }
+    }
+
+    let prefix = if dry_run { "(dry-run) " } else { "" };
+    let mut report = Report::default();
+    let mut linked = BTreeSet::new();
+    let mut ops = Vec::new();
+    let ids: BTreeSet<String> = local.keys().chain(remote.keys()).cloned().collect();
+
+    for id in ids {
+        let known = state.ids.contains(&id);
+        match (local.get(&id), remote.get(&id)) {
+            (Some(task), Some(remote)) => {
+                linked.insert(id.clone());
+                match (task.status(), remote.status) {
+                    (TodoStatus::Open, TodoStatus::Open) => {}
+                    (TodoStatus::Open, status) => {
+                        let mark = if status == TodoStatus::Cancelled { '-' } else { 'x' };
+                        for (path, line, _) in task.lines.iter().filter(|(_, _, s)| *s == ' ') {
+                            complete_line(&mut edits, path, *line, mark, today);
+                        }
+                        println!("{}✓ {}: completada en el vault", prefix, task.meta.title);
+                        report.completed_local += 1;
+                    }
+                    (status, TodoStatus::Open) => {
+                        // Las copias que siguen abiertas (rollover con `mode = "copy"`) también
+                        let mark = if status == TodoStatus::Cancelled { '-' } else { 'x' };
+                        for (path, line, _) in task.lines.iter().filter(|(_, _, s)| *s == ' ') {
+                            complete_line(&mut edits, path, *line, mark, today);
+                        }
+                        ops.push(RemoteOp::Complete(id.clone(), status));
+                    }
+                    _ => {}
+                }
+            }
+            (Some(task), None) if known => {
+                for (path, line, _) in &task.lines {
+                    unlink_line(&mut edits, path, *line);
+                }
+                println!("{}⊘ {}: borrada en el servidor, se desvincula", prefix, task.meta.title);
+                report.unlinked += 1;
+            }
+            (Some(task), None) => {
+                // Recién vinculada por el tag: el ID se escribe antes de crearla, así una
+                // creación fallida se reintenta en el próximo sync
+                if task.meta.id.is_none() {
+                    let (path, line, _) = &task.lines[0];
+                    link_line(&mut edits, path, *line, &id);
+                }
+                ops.push(RemoteOp::Create(id.clone()));
+            }
+            (None, Some(_)) if known => ops.push(RemoteOp::Delete(id.clone())),
+            (None, Some(remote)) if remote.status == TodoStatus::Open => {
+                let Some(task) = remote.task() else {
+                    continue;
+                };
+                edits.imports.push(import_line(&task, &id));
+                linked.insert(id.clone());
+                report.imported += 1;
+            }
+            _ => {}
+        }
+    }
+
+    let inbox = vault.join(
+        caldav
+            .note
+            .as_deref()
+            .or(config.capture.inbox.as_deref())
+            .unwrap_or("Inbox.md"),
+    );
+    if report.imported > 0 {
+        println!(
+            "{}↓ {} tareas nuevas del servidor: importadas a {}",
+            prefix,
+            report.imported,
+            inbox.strip_prefix(vault).unwrap_or(&inbox).display()
+        );
+    }
+
+    // Primero el vault: si no se puede escribir, el servidor queda como estaba y no hay
+    // tareas creadas cuyo ID no llegó al Markdown
+    let plan = plan_edits(vault, edits, &inbox, caldav.heading.as_deref())?;
+    let plan_empty = plan.is_empty();
+    if !plan_empty {
+        plan.finish(&PlanMode {
+            dry_run,
+            plan_out: None,
+        })?;
+    }
+
+    for op in ops {
+        let (title, result) = match op {
+            RemoteOp::Create(id) => {
+                let task = &local[&id];
+                let mut calendar = new_task(&task.meta).to_calendar(&id);
+                let status = task.status();
+                if status != TodoStatus::Open {
+                    if let Some(todo) = calendar.components.iter_mut().find(|c| c.name == "VTODO") {
+                        rcal_tasks::set_status(todo, status);
+                    }
+                }
+                let result = if dry_run {
+                    Ok(())
+                } else {
+                    client.create(&id, &ical::to_ics(&[calendar])).map(|_| ())
+                };
+                if result.is_ok() {
+                    println!("{}↑ {}: creada en el servidor", prefix, task.meta.title);
+                    linked.insert(id);
+                    report.created += 1;
+                }
+                (task.meta.title.clone(), result)
+            }
+            RemoteOp::Complete(id, status) => {
+                let title = local[&id].meta.title.clone();
+                let result = if dry_run {
+                    Ok(())
+                } else {
+                    update_status(client, &remote[&id], status)
+                };
+                if result.is_ok() {
+                    println!("{}✓ {}: completada en el servidor", prefix, title);
+                    report.completed_remote += 1;
+                }
+                (title, result)
+            }
+            RemoteOp::Delete(id) => {
+                let remote = &remote[&id];
+                let result = if dry_run {
+                    Ok(())
+                } else {
+                    client.delete(&remote.resource)
+                };
+                match &result {
+                    Ok(()) => {
+                        println!("{}✗ {}: borrada del vault, se borra del servidor", prefix, remote.summary());
+                        report.deleted += 1;
+                    }
+                    // Sigue vinculada para reintentar el borrado
+                    Err(_) => {
+                        linked.insert(id);
+                    }
+                }
+                (remote.summary(), result)
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("✗ {}: {}", title, e);
+            report.errors += 1;
+        }
+    }
+
+    if dry_run && plan_empty {
+        println!("\n🔍 Dry-run: no se modificó el vault ni el servidor");
+    }
+
+    if !dry_run {
+        state.ids = linked;
+    }
+    Ok(report)
+}
+
+fn caldav_config(config: &Config) -> anyhow::Result<&CaldavConfig> {
+    config.tasks.caldav.as_ref().ok_or_else(|| {
+        anyhow::anyhow!("Falta la colección CalDAV: configura [tasks.caldav] url = \"...\" en config.toml")
+    })
+}
+
+/// Client of the collection, con la contraseña de `password` o de `password_command`
+fn client(caldav: &CaldavConfig) -> anyhow::Result<Client> {
+    let password = match (&caldav.password, &caldav.password_command) {
+        (Some(password), _) => Some(password.clone()),
+        (None, Some(command)) => {
+            let output = Command::new("sh").arg("-c").arg(command).output()?;
+            if !output.status.success() {
+                anyhow::bail!("tasks.caldav.password_command falló: {}", command);
+            }
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            Some(stdout.lines().next().unwrap_or_default().to_string())
+        }
+        (None, None) => None,
+    };
+    Ok(Client::new(&caldav.url, caldav.user.as_deref(), password.as_deref()))
+}
+
+/// Tasks of the vault with an ID, agrupadas por ID
+fn local_tasks(index: &VaultIndex) -> BTreeMap<String, LocalTask> {
+    let mut tasks: BTreeMap<String, LocalTask> = BTreeMap::new();
+    for (path, note) in index.iter() {
+        // `[>]` (arrastrada a otra nota) y `[M]` (migrada) son copias viejas de la tarea
+        for indexed in note.tasks.iter().filter(|t| !matches!(t.status, '>' | 'M')) {
+            let meta = TaskMeta::parse(&indexed.text);
+            let Some(id) = meta.id.clone() else {
+                continue;
+            };
+            let line = (path.clone(), indexed.line, indexed.status);
+            match tasks.get_mut(&id) {
+                Some(task) => task.lines.push(line),
+                None => {
+                    tasks.insert(
+                        id,
+                        LocalTask {
+                            lines: vec![line],
+                            meta,
+                        },
+                    );
+                }
+            }
+        }
+    }
+    tasks
+}
+
+/// UID and task of a resource; None si no es un VTODO
+fn parse_remote(resource: Resource) -> anyhow::Result<Option<(String, RemoteTask)>> {
+    let calendar = ical::parse(&resource.data)
+        .map_err(|e| anyhow::anyhow!("{}: {}", resource.url, e))?;
+    let Some(todo) = calendar
+        .iter()
+        .flat_map(|c| c.components.iter())
+        .find(|c| c.name == "VTODO")
+    else {
+        return Ok(None);
+    };
+    let Some(uid) = todo.property("UID").map(|p| p.value.trim().to_string()) else {
+        return Ok(None);
+    };
+    let status = TodoStatus::of(todo);
+    Ok(Some((
+        uid,
+        RemoteTask {
+            resource,
+            calendar,
+            status,
+        },
+    )))
+}
+
+fn update_status(client: &Client, remote: &RemoteTask, status: TodoStatus) -> anyhow::Result<()> {
+    let mut calendar = remote.calendar.clone();
+    for todo in calendar
+        .iter_mut()
+        .flat_map(|c| c.components.iter_mut())
+        .filter(|c| c.name == "VTODO")
+    {
+        rcal_tasks::set_status(todo, status);
+    }
+    client.update(&remote.resource, &ical::to_ics(&calendar))
+}
+
+/// VTODO for a markdown task: el título sin `#tags`, que van como `CATEGORIES`
+fn new_task(meta: &TaskMeta) -> NewTask {
+    let summary: Vec<&str> = meta
+        .title
+        .split_whitespace()
+        .filter(|word| !word.starts_with('#'))
+        .collect();
+    NewTask {
+        summary: if summary.is_empty() {
+            meta.title.clone()
+        } else {
+            summary.join(" ")
+        },
+        due: meta.due.map(DateValue::Date),
+        priority: meta.priority,
+        categories: meta.tags.clone(),
+    }
+}
+
+/// Markdown line for a task of the server: `- [ ] título 📅 2026-10-20 ⏫ #tag <!-- mad-id:UID -->`
+fn import_line(task: &IcalTask, uid: &str) -> String {
+    let mut text = task.summary.trim().to_string();
+    if let Some(due) = task.due {
+        text.push_str(&format!(" 📅 {}", due.date().format("%Y-%m-%d")));
+    }
+    if let Some(start) = task.start {
+        text.push_str(&format!(" 🛫 {}", start.date().format("%Y-%m-%d")));
+    }
+    if let Some(emoji) = task.priority.emoji() {
+        text.push_str(&format!(" {}", emoji));
+    }
+    for category in &task.categories {
+        text.push_str(&format!(" #{}", category.replace(' ', "-")));
+    }
+    format!("- [ ] {}", task::with_task_id(&text, uid))
+}
+
+/// Current text of a line: la ya editada o la del archivo
+fn current_line(edits: &LocalEdits, path: &Path, line_number: usize) -> Option<String> {
+    if let Some(line) = edits.lines.get(path).and_then(|lines| lines.get(&line_number)) {
+        return Some(line.clone());
+    }
+    let content = fs::read_to_string(path).ok()?;
+    content.split('\n').nth(line_number.checked_sub(1)?).map(str::to_string)
+}
+
+fn edit_line(
+    edits: &mut LocalEdits,
+    path: &Path,
+    line_number: usize,
+    edit: impl FnOnce(&TaskLine) -> String,
+) {
+    let Some(line) = current_line(edits, path, line_number) else {
+        return;
+    };
+    let Some(task) = TaskLine::parse(&line) else {
+        return;
+    };
+    let line = edit(&task);
+    edits
+        .lines
+        .entry(path.to_path_buf())
+        .or_default()
+        .insert(line_number, line);
+}
+
+/// Complete a line like `mad tasks` (con la próxima ocurrencia si se repite)
+fn complete_line(
+    edits: &mut LocalEdits,
+    path: &Path,
+    line_number: usize,
+    mark: char,
+    today: NaiveDate,
+) {
+    edit_line(edits, path, line_number, |task| {
+        let line = task.with_status(mark);
+        match (mark == 'x').then(|| task::next_occurrence(task.text, today)).flatten() {
+            Some(next) => format!("{}\n{}", line, task.with_text(' ', &next)),
+            None => line,
+        }
+    });
+}
+
+fn link_line(edits: &mut LocalEdits, path: &Path, line_number: usize, id: &str) {
+    edit_line(edits, path, line_number, |task| {
+        task.with_text(task.status, &task::with_task_id(task.text, id))
+    });
+}
+
+fn unlink_line(edits: &mut LocalEdits, path: &Path, line_number: usize) {
+    edit_line(edits, path, line_number, |task| {
+        task.with_text(task.status, &task::without_task_id(task.text))
+    });
+}
+
+/// Plan with every markdown change: líneas editadas por archivo y las tareas importadas
+fn plan_edits(vault: &Path, edits: LocalEdits, inbox: &Path, heading: Option<&str>) -> anyhow::Result<Plan> {
+    let mut plan = Plan::new("tasks sync", vault);
+    plan.backup = false;
+
+    for (path, changed) in edits.lines {
+        let content = plan.read(&path)?;
+        let mut lines: Vec<String> = content.split('\n').map(str::to_string).collect();
+        for (line_number, line) in changed {
+            if let Some(slot) = line_number.checked_sub(1).and_then(|idx| lines.get_mut(idx)) {
+                *slot = line;
+            }
+        }
+        plan.edit(&path, lines.join("\n"))?;
+    }
+
+    if !edits.imports.is_empty() {
+        let block = edits.imports.join("\n");
+        if plan.exists(inbox) {
+            let content = plan.read(inbox)?;
+            plan.edit(inbox, section::append_to_section(&content, heading, &block))?;
+        } else {
+            plan.create(inbox, section::append_to_section("# Inbox\n", heading, &block));
+        }
+    }
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::caldav::stand_in::StandIn;
+    use crate::core::config::TasksConfig;
+    use tempfile::TempDir;
+
+    fn config(vault: &Path, url: &str) -> Config {
+        Config {
+            vault: vault.to_str().unwrap().to_string(),
+            tasks: TasksConfig {
+                calendar: None,
+                caldav: Some(CaldavConfig {
+                    url: url.to_string(),
+                    user: None,
+                    password: None,
+                    password_command: None,
+                    note: None,
+                    heading: Some("Tareas".to_string()),
+                    tag: Some("caldav".to_string()),
+                }),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn run_sync(vault: &Path, config: &Config, client: &Client, state: &mut SyncState) -> Report {
+        let mut index = VaultIndex::new(vault);
+        index.refresh(vault, &vault.join("Templates")).unwrap();
+        sync(vault, config, &index, client, state, false).unwrap()
+    }
+
+    fn remote_todo(uid: &str, summary: &str, status: &str) -> String {
+        format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VTODO\r\nUID:{}\r\nSUMMARY:{}\r\nDUE;VALUE=DATE:20261020\r\nPRIORITY:1\r\nSTATUS:{}\r\nEND:VTODO\r\nEND:VCALENDAR\r\n",
+            uid, summary, status
+        )
+    }
+
+    #[test]
+    fn test_sync_creates_imports_and_completes_both_ways() {
+        let server = StandIn::start();
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::create_dir_all(vault.join("Notas")).unwrap();
+        let note = vault.join("Notas/casa.md");
+        fs::write(&note, "# Casa\n\n- [ ] Pagar luz 📅 2026-10-18 #caldav\n- [ ] Sin sincronizar\n").unwrap();
+        server.put("remota.ics", &remote_todo("remota", "Renovar pasaporte", "NEEDS-ACTION"));
+        server.put("vieja.ics", &remote_todo("vieja", "Ya hecha", "COMPLETED"));
+
+        let config = config(vault, &server.url);
+        let client = Client::new(&server.url, None, None);
+        let mut state = SyncState::default();
+
+        // La tarea con el tag se crea en el servidor, la abierta del servidor se importa
+        let report = run_sync(vault, &config, &client, &mut state);
+        assert_eq!((report.created, report.imported, report.errors), (1, 1, 0));
+        let content = fs::read_to_string(&note).unwrap();
+        let id = task::task_id(content.lines().nth(2).unwrap()).unwrap().to_string();
+        assert!(content.contains("- [ ] Sin sincronizar\n"));
+        assert!(server.objects().iter().any(|o| o.contains(&id) && o.contains("SUMMARY:Pagar luz")));
+        assert_eq!(
+            fs::read_to_string(vault.join("Inbox.md")).unwrap(),
+            "# Inbox\n\n## Tareas\n- [ ] Renovar pasaporte 📅 2026-10-20 🔺 <!-- mad-id:remota -->\n"
+        );
+        assert_eq!(state.ids, BTreeSet::from([id.clone(), "remota".to_string()]));
+
+        // Completada en el vault → en el servidor; completada en el servidor → en el vault
+        fs::write(&note, content.replace("- [ ] Pagar", "- [x] Pagar")).unwrap();
+        server.put("remota.ics", &remote_todo("remota", "Renovar pasaporte", "COMPLETED"));
+        let report = run_sync(vault, &config, &client, &mut state);
+        assert_eq!((report.completed_remote, report.completed_local), (1, 1));
+        assert!(server
+            .objects()
+            .iter()
+            .any(|o| o.contains(&id) && o.contains("STATUS:COMPLETED")));
+        assert!(fs::read_to_string(vault.join("Inbox.md")).unwrap().contains("- [x] Renovar pasaporte"));
+
+        // Sin cambios: nada que hacer
+        assert_eq!(run_sync(vault, &config, &client, &mut state), Report::default());
+    }
+
+    #[test]
+    fn test_sync_propagates_deletions() {
+        let server = StandIn::start();
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::create_dir_all(vault.join("Notas")).unwrap();
+        let note = vault.join("Notas/lista.md");
+        fs::write(
+            &note,
+            "- [ ] Uno <!-- mad-id:uno -->\n- [ ] Dos <!-- mad-id:dos -->\n- [ ] Tres <!-- mad-id:tres -->\n",
+        )
+        .unwrap();
+
+        let config = config(vault, &server.url);
+        let client = Client::new(&server.url, None, None);
+        let mut state = SyncState::default();
+        assert_eq!(run_sync(vault, &config, &client, &mut state).created, 3);
+
+        // "dos" se borra del vault y "tres" del servidor (como otro cliente)
+        fs::write(&note, "- [ ] Uno <!-- mad-id:uno -->\n- [ ] Tres <!-- mad-id:tres -->\n").unwrap();
+        let objects = server.objects();
+        let tres = objects.iter().find(|o| o.contains("UID:tres")).unwrap();
+        let resource = Resource {
+            url: format!("{}tres.ics", server.url),
+            etag: None,
+            data: tres.clone(),
+        };
+        client.delete(&resource).unwrap();
+
+        let report = run_sync(vault, &config, &client, &mut state);
+        assert_eq!((report.deleted, report.unlinked, report.errors), (1, 1, 0));
+        assert_eq!(fs::read_to_string(&note).unwrap(), "- [ ] Uno <!-- mad-id:uno -->\n- [ ] Tres\n");
+        assert_eq!(server.objects().len(), 1);
+        assert_eq!(state.ids, BTreeSet::from(["uno".to_string()]));
+    }
+
+    #[test]
+    fn test_sync_leaves_the_server_alone_when_the_vault_cannot_be_written() {
+        let server = StandIn::start();
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        let note = vault.join("casa.md");
+        fs::write(&note, "- [ ] Pagar luz #caldav\n").unwrap();
+        // La nota de importadas no se puede escribir
+        fs::create_dir_all(vault.join("Inbox.md")).unwrap();
+        server.put("remota.ics", &remote_todo("remota", "Renovar pasaporte", "NEEDS-ACTION"));
+
+        let config = config(vault, &server.url);
+        let client = Client::new(&server.url, None, None);
+        let mut state = SyncState::default();
+        let mut index = VaultIndex::new(vault);
+        index.refresh(vault, &vault.join("Templates")).unwrap();
+        assert!(sync(vault, &config, &index, &client, &mut state, false).is_err());
+
+        // Ni la tarea del tag en el servidor sin su ID en el Markdown, ni IDs nuevos en el estado
+        assert_eq!(server.objects().len(), 1);
+        assert_eq!(fs::read_to_string(&note).unwrap(), "- [ ] Pagar luz #caldav\n");
+        assert!(state.ids.is_empty());
+    }
+
+    #[test]
+    fn test_sync_completes_copies_in_other_notes() {
+        let server = StandIn::start();
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::create_dir_all(vault.join("Diario")).unwrap();
+        // Rollover con mode = "copy": la de ayer sigue abierta y se completa la de hoy
+        fs::write(vault.join("Diario/2026-10-16.md"), "- [ ] Llamar <!-- mad-id:llamar -->\n").unwrap();
+        fs::write(vault.join("Diario/2026-10-17.md"), "- [x] Llamar <!-- mad-id:llamar -->\n").unwrap();
+        server.put("llamar.ics", &remote_todo("llamar", "Llamar", "NEEDS-ACTION"));
+
+        let config = config(vault, &server.url);
+        let client = Client::new(&server.url, None, None);
+        let mut state = SyncState {
+            url: server.url.clone(),
+            ids: BTreeSet::from(["llamar".to_string()]),
+        };
+        let report = run_sync(vault, &config, &client, &mut state);
+        assert_eq!((report.completed_remote, report.errors), (1, 0));
+        assert!(server.objects()[0].contains("STATUS:COMPLETED"));
+        assert_eq!(
+            fs::read_to_string(vault.join("Diario/2026-10-16.md")).unwrap(),
+            "- [x] Llamar <!-- mad-id:llamar -->\n"
+        );
+        assert_eq!(run_sync(vault, &config, &client, &mut state), Report::default());
+    }
+}
@@ -1,18 +1,51 @@
 use crate::core::config::Config;
 use crate::core::frontmatter;
-use crate::vault::scan;
+use crate::tags::TagPath;
+use crate::ui::prompts;
+use crate::utils::output::{self, OutputFormat};
+use crate::vault::index::VaultIndex;
+use crate::vault::plan::{Plan, PlanMode};
+use chrono::{DateTime, Local};
 use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input, Select};
+use serde::Serialize;
 use serde_yaml::Value;
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// `mad tag list --json|--format tsv`
+#[derive(Debug, Serialize)]
+struct TagRecord {
+    tag: String,
+    /// Notas con exactamente este tag (0 para los niveles intermedios)
+    count: usize,
+    files: Vec<String>,
+}
+
+/// `mad tag find [tag] --json|--format tsv`
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+struct TaggedNote {
+    tag: String,
+    path: String,
+    modified: String,
+}
+
+/// Tag a renombrar y tag nuevo, como listas de niveles
+struct TagRename {
+    old_path: Vec<String>,
+    new_path: Vec<String>,
+    /// También los sub-tags: `a/b/x` → `c/x`
+    recursive: bool,
+}
+
 pub fn run(vault: &Path) -> anyhow::Result<()> {
     interactive_menu(vault)
 }
 
-pub fn list_tags(vault: &Path, include_archived: bool) -> anyhow::Result<()> {
-    if include_archived {
+pub fn list_tags(vault: &Path, include_archived: bool, format: OutputFormat) -> anyhow::Result<()> {
+    if format.is_structured() {
+        print_tag_records(vault, format)
+    } else if include_archived {
         // For list-all, use interactive mode to filter archived tags
         list_tags_interactive(vault, include_archived)
     } else {
@@ -21,12 +54,77 @@ pub fn list_tags(vault: &Path, include_archived: bool) -> anyhow::Result<()> {
     }
 }
 
-pub fn rename_tags(vault: &Path) -> anyhow::Result<()> {
-    rename_tag(vault)
+/// Rename a tag in every note's frontmatter.
+/// With `from`/`to` there is no selector; `yes` skips the confirmation.
+pub fn rename_tags(
+    vault: &Path,
+    mode: &PlanMode,
+    from: Option<&str>,
+    to: Option<&str>,
+    recursive: bool,
+    yes: bool,
+) -> anyhow::Result<()> {
+    let tag_map = collect_all_tags(vault)?;
+
+    let rename = match (from, to) {
+        (Some(from), Some(to)) => rename_from_args(&tag_map, from, to, recursive)?,
+        _ => {
+            prompts::ensure_tty("usa: mad tag rename --from <tag> --to <tag> [--recursive] --yes")?;
+            match pick_rename(&tag_map)? {
+                Some(picked) => picked,
+                None => return Ok(()),
+            }
+        }
+    };
+
+    rename_tag(vault, mode, &tag_map, &rename, yes)
+}
+
+/// Rename given by `--from`/`--to`, checked against the tags of the vault
+fn rename_from_args(
+    tag_map: &HashMap<Vec<String>, Vec<PathBuf>>,
+    from: &str,
+    to: &str,
+    recursive: bool,
+) -> anyhow::Result<TagRename> {
+    let old_path = parse_tag_arg(from).0;
+    let new_path = parse_tag_arg(to).0;
+    if old_path.is_empty() || new_path.is_empty() {
+        anyhow::bail!("Tag inválido: usa --from a/b --to c");
+    }
+    if !tag_map.keys().any(|path| path.starts_with(&old_path)) {
+        anyhow::bail!("No hay notas con el tag '{}'", old_path.join("/"));
+    }
+    // Solo sub-tags: sin --recursive el plan quedaría vacío
+    if !recursive && !tag_map.contains_key(&old_path) {
+        anyhow::bail!(
+            "Ninguna nota tiene exactamente el tag '{}', solo sub-tags: usa --recursive para renombrarlos",
+            old_path.join("/")
+        );
+    }
+    Ok(TagRename {
+        old_path,
+        new_path,
+        recursive,
+    })
 }
 
-pub fn find_by_tag(vault: &Path) -> anyhow::Result<()> {
-    search_files_by_tag(vault)
+/// Without a tag (and in text mode) opens the fuzzy selector
+pub fn find_by_tag(vault: &Path, tag: Option<&str>, format: OutputFormat) -> anyhow::Result<()> {
+    if format.is_structured() {
+        return print_tagged_notes(vault, tag.map(parse_tag_arg).as_ref(), format);
+    }
+    match tag {
+        Some(tag) => {
+            let tag = parse_tag_arg(tag);
+            let tag_map = collect_all_tags(vault)?;
+            let mut files = tag_map.get(&tag.0).cloned().unwrap_or_default();
+            files.sort();
+            print_tag_files(&tag.0.join(" → "), &files);
+            Ok(())
+        }
+        None => search_files_by_tag(vault),
+    }
 }
 
 pub fn visual_selector() -> anyhow::Result<()> {
@@ -34,6 +132,7 @@ pub fn visual_selector() -> anyhow::Result<()> {
 }
 
 fn interactive_menu(vault: &Path) -> anyhow::Result<()> {
+    prompts::ensure_tty("usa: mad tag list | find <tag> | rename --from <tag> --to <tag>")?;
     println!("🏷️  Gestor de Tags para: {}\n", vault.display());
     println!("Tip: Presiona ESC para salir en cualquier momento\n");
 
@@ -53,7 +152,7 @@ fn interactive_menu(vault: &Path) -> anyhow::Result<()> {
 
         match selection {
             Some(0) => list_flat_tags(vault)?,
-            Some(1) => rename_tag(vault)?,
+            Some(1) => rename_tags(vault, &PlanMode::default(), None, None, false, false)?,
             Some(2) => search_files_by_tag(vault)?,
             Some(3) | None => break,
             _ => {}
@@ -72,13 +171,13 @@ fn collect_all_tags(vault: &Path) -> anyhow::Result<HashMap<Vec<String>, Vec<Pat
 
     let mut tag_map: HashMap<Vec<String>, HashSet<PathBuf>> = HashMap::new();
 
-    let items = scan::scan_tags(vault, &templates_path)?;
-    for item in items {
-        for tag in item.secondary_tags {
+    let index = VaultIndex::open(vault, &templates_path)?;
+    for (path, note) in index.iter() {
+        for tag in &note.secondary_tags {
             tag_map
-                .entry(tag.0)
-                .or_insert_with(HashSet::new)
-                .insert(item.path.clone());
+                .entry(tag.0.clone())
+                .or_default()
+                .insert(path.clone());
         }
     }
 
@@ -91,6 +190,7 @@ fn collect_all_tags(vault: &Path) -> anyhow::Result<HashMap<Vec<String>, Vec<Pat
 }
 
 fn list_tags_interactive(vault: &Path, include_archived: bool) -> anyhow::Result<()> {
+    prompts::ensure_tty("usa: mad tag list --json")?;
     let tag_map = collect_all_tags(vault)?;
 
     if tag_map.is_empty() {
@@ -237,6 +337,7 @@ fn list_flat_tags(vault: &Path) -> anyhow::Result<()> {
 }
 
 fn search_files_by_tag(vault: &Path) -> anyhow::Result<()> {
+    prompts::ensure_tty("usa: mad tag find <tag>")?;
     let tag_map = collect_all_tags(vault)?;
 
     let mut tag_paths: Vec<String> = tag_map.keys().map(|path| path.join(" → ")).collect();
@@ -258,20 +359,101 @@ fn search_files_by_tag(vault: &Path) -> anyhow::Result<()> {
             tag_paths[idx].split(" → ").map(|s| s.to_string()).collect();
 
         if let Some(files) = tag_map.get(&selected_path) {
-            println!("\n📁 Archivos con tag '{}':", tag_paths[idx]);
-            for file in files {
-                println!("  → {}", file.display());
-            }
-            println!("\nTotal: {} archivos", files.len());
+            print_tag_files(&tag_paths[idx], files);
         }
     }
 
     Ok(())
 }
 
-fn rename_tag(vault: &Path) -> anyhow::Result<()> {
-    let tag_map = collect_all_tags(vault)?;
+fn print_tag_files(tag: &str, files: &[PathBuf]) {
+    if files.is_empty() {
+        println!("\n📁 No hay archivos con el tag '{}'", tag);
+        return;
+    }
+    println!("\n📁 Archivos con tag '{}':", tag);
+    for file in files {
+        println!("  → {}", file.display());
+    }
+    println!("\nTotal: {} archivos", files.len());
+}
+
+/// `proj/acme`, `#proj/acme` o `proj → acme`
+fn parse_tag_arg(raw: &str) -> TagPath {
+    TagPath::from_slash(&raw.replace('→', "/"))
+}
+
+/// Same tags as the flat list (archived excluded, intermediate levels included)
+fn tag_records(vault: &Path, tag_map: HashMap<Vec<String>, Vec<PathBuf>>) -> Vec<TagRecord> {
+    let tag_map: HashMap<Vec<String>, Vec<PathBuf>> = tag_map
+        .into_iter()
+        .filter(|(path, _)| !path.iter().any(|component| component == "Archived"))
+        .collect();
+
+    let mut all_paths = HashSet::new();
+    for path in tag_map.keys() {
+        for i in 1..=path.len() {
+            all_paths.insert(path[..i].to_vec());
+        }
+    }
+
+    let mut records: Vec<TagRecord> = all_paths
+        .into_iter()
+        .map(|path| {
+            let mut files: Vec<String> = tag_map
+                .get(&path)
+                .into_iter()
+                .flatten()
+                .map(|f| f.strip_prefix(vault).unwrap_or(f).display().to_string())
+                .collect();
+            files.sort();
+            TagRecord {
+                tag: path.join("/"),
+                count: files.len(),
+                files,
+            }
+        })
+        .collect();
+    records.sort_by(|a, b| a.tag.cmp(&b.tag));
+    records
+}
+
+fn print_tag_records(vault: &Path, format: OutputFormat) -> anyhow::Result<()> {
+    let records = tag_records(vault, collect_all_tags(vault)?);
+    output::print(format, &records, |r| vec![r.tag.clone(), r.count.to_string()])
+}
+
+/// One record per (tag, note); only `tag` when given
+fn print_tagged_notes(vault: &Path, tag: Option<&TagPath>, format: OutputFormat) -> anyhow::Result<()> {
+    let config = Config::load_default()?;
+    let templates_path = vault.join(&config.templates_dir);
+    let index = VaultIndex::open(vault, &templates_path)?;
+
+    let mut records = Vec::new();
+    for (path, note) in index.iter() {
+        let modified: DateTime<Local> = note.modified().into();
+        for note_tag in &note.secondary_tags {
+            if tag.is_some_and(|t| t != note_tag) {
+                continue;
+            }
+            records.push(TaggedNote {
+                tag: note_tag.to_slash_string(),
+                path: path.strip_prefix(vault).unwrap_or(&path).display().to_string(),
+                modified: modified.to_rfc3339(),
+            });
+        }
+    }
+    records.sort();
+    records.dedup();
 
+    output::print(format, &records, |r| {
+        vec![r.tag.clone(), r.path.clone(), r.modified.clone()]
+    })
+}
+
+/// Selector of the tag, single/recursive mode and new name.
+/// Returns None if the user cancelled.
+fn pick_rename(tag_map: &HashMap<Vec<String>, Vec<PathBuf>>) -> anyhow::Result<Option<TagRename>> {
     let mut all_tag_paths = HashSet::new();
 
     for path in tag_map.keys() {
@@ -286,7 +468,7 @@ fn rename_tag(vault: &Path) -> anyhow::Result<()> {
 
     if tag_paths.is_empty() {
         println!("No hay tags en el vault.");
-        return Ok(());
+        return Ok(None);
     }
 
     let selection = Select::with_theme(&ColorfulTheme::default())
@@ -297,7 +479,7 @@ fn rename_tag(vault: &Path) -> anyhow::Result<()> {
 
     let idx = match selection {
         Some(i) => i,
-        None => return Ok(()),
+        None => return Ok(None),
     };
 
     let old_path: Vec<String> = tag_paths[idx].split(" → ").map(|s| s.to_string()).collect();
@@ -327,7 +509,7 @@ fn rename_tag(vault: &Path) -> anyhow::Result<()> {
 
     println!("\nTag actual: {}", tag_paths[idx]);
 
-    let rename_mode = if has_children {
+    let recursive = if has_children {
         let options = vec![
             "Solo este nivel (sin afectar sub-tags)",
             "Este nivel y todos sus sub-tags",
@@ -340,13 +522,12 @@ fn rename_tag(vault: &Path) -> anyhow::Result<()> {
             .interact_opt()?;
 
         match mode_sel {
-            Some(0) => "single",
-            Some(1) => "recursive",
-            None => return Ok(()),
-            _ => "single",
+            Some(1) => true,
+            None => return Ok(None),
+            _ => false,
         }
     } else {
-        "single"
+        false
     };
 
     println!("Ingresa el nuevo tag (usa '/' para mantener jerarquía, vacío para cancelar):");
@@ -358,7 +539,7 @@ fn rename_tag(vault: &Path) -> anyhow::Result<()> {
 
     if new_tag.trim().is_empty() {
         println!("Operación cancelada");
-        return Ok(());
+        return Ok(None);
     }
 
     let new_path: Vec<String> = new_tag
@@ -369,45 +550,92 @@ fn rename_tag(vault: &Path) -> anyhow::Result<()> {
 
     if new_path.is_empty() {
         println!("❌ Tag inválido");
-        return Ok(());
+        return Ok(None);
     }
 
+    Ok(Some(TagRename {
+        old_path,
+        new_path,
+        recursive,
+    }))
+}
+
+fn rename_tag(
+    vault: &Path,
+    mode: &PlanMode,
+    tag_map: &HashMap<Vec<String>, Vec<PathBuf>>,
+    rename: &TagRename,
+    yes: bool,
+) -> anyhow::Result<()> {
+    let TagRename {
+        old_path,
+        new_path,
+        recursive,
+    } = rename;
+    let recursive = *recursive;
     let mut affected_files = HashSet::new();
-    if rename_mode == "recursive" {
+    if recursive {
         for (path, files) in tag_map.iter() {
-            if path.starts_with(&old_path) {
+            if path.starts_with(old_path) {
                 for file in files {
                     affected_files.insert(file.clone());
                 }
             }
         }
     } else {
-        if let Some(files) = tag_map.get(&old_path) {
+        if let Some(files) = tag_map.get(old_path) {
             for file in files {
                 affected_files.insert(file.clone());
             }
         }
     }
 
-    let confirm = dialoguer::Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(format!(
-            "¿Renombrar '{}' a '{}' en {} archivos?",
-            tag_paths[idx],
-            new_path.join(" → "),
-            affected_files.len()
-        ))
-        .default(false)
-        .interact_opt()?;
+    // Con --dry-run no hay nada que confirmar
+    if !mode.is_dry_run() && !yes {
+        prompts::ensure_tty("usa --yes para confirmar el renombrado")?;
+        let confirm = dialoguer::Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!(
+                "¿Renombrar '{}' a '{}' en {} archivos?",
+                old_path.join(" → "),
+                new_path.join(" → "),
+                affected_files.len()
+            ))
+            .default(false)
+            .interact_opt()?;
 
-    if !confirm.unwrap_or(false) {
-        println!("Operación cancelada");
-        return Ok(());
+        if !confirm.unwrap_or(false) {
+            println!("Operación cancelada");
+            return Ok(());
+        }
     }
 
-    let mut updated = 0;
-    for file_path in &affected_files {
+    let plan = plan_rename(vault, &affected_files, rename)?;
+    let updated = plan.changes.len();
+    if plan.finish(mode)? {
+        println!("✅ {} archivos actualizados (backups en .arc/backups)", updated);
+
+        println!("Actualizando índice del vault...");
+        regenerate_tag_cache(vault)?;
+    }
+
+    Ok(())
+}
+
+/// Plan with the frontmatter of `files` rewritten to the new tag
+fn plan_rename(vault: &Path, files: &HashSet<PathBuf>, rename: &TagRename) -> anyhow::Result<Plan> {
+    let TagRename {
+        old_path,
+        new_path,
+        recursive,
+    } = rename;
+    let recursive = *recursive;
+    let mut plan = Plan::new("tag rename", vault);
+    let mut affected_files: Vec<&PathBuf> = files.iter().collect();
+    affected_files.sort();
+    for file_path in affected_files {
         if let Ok(content) = fs::read_to_string(file_path) {
-            if let Ok((mut fm, body)) = frontmatter::extract(&content) {
+            if let Ok(mut doc) = frontmatter::Document::parse(&content) {
+                let fm = doc.mapping()?;
                 for key in ["tags", "tag", "Tags", "Tag"] {
                     let key_val = Value::String((*key).to_string());
                     if let Some(Value::Sequence(tag_list)) = fm.get(&key_val) {
@@ -426,16 +654,16 @@ fn rename_tag(vault: &Path) -> anyhow::Result<()> {
                                     .filter(|p| !p.is_empty())
                                     .collect();
 
-                                let should_update = if rename_mode == "recursive" {
-                                    current_path.starts_with(&old_path)
+                                let should_update = if recursive {
+                                    current_path.starts_with(old_path)
                                 } else {
-                                    current_path == old_path
+                                    current_path == *old_path
                                 };
 
                                 if should_update {
                                     // Build updated path
-                                    let mut updated_path = new_path.clone();
-                                    if rename_mode == "recursive" && current_path.len() > old_path.len() {
+                                    let mut updated_path = new_path.to_vec();
+                                    if recursive && current_path.len() > old_path.len() {
                                         updated_path.extend_from_slice(&current_path[old_path.len()..]);
                                     }
 
@@ -451,16 +679,8 @@ fn rename_tag(vault: &Path) -> anyhow::Result<()> {
                         }
 
                         if any_updated {
-                            fm.insert(Value::String(key.to_string()), Value::Sequence(new_list));
-
-                            let new_content =
-                                format!("---\n{}---{}", serde_yaml::to_string(&fm)?, body);
-
-                            let backup_path = file_path.with_extension("md.bak");
-                            fs::copy(file_path, &backup_path)?;
-
-                            fs::write(file_path, new_content)?;
-                            updated += 1;
+                            doc.set(key, Value::Sequence(new_list))?;
+                            plan.edit(file_path, doc.as_str().to_string())?;
                         }
                         break;
                     }
@@ -468,16 +688,66 @@ fn rename_tag(vault: &Path) -> anyhow::Result<()> {
             }
         }
     }
-    println!("✅ {} archivos actualizados", updated);
-
-    println!("Regenerando caché de tags...");
-    regenerate_tag_cache()?;
+    Ok(plan)
+}
 
+/// Re-parsea en el índice las notas modificadas por el renombrado
+fn regenerate_tag_cache(vault: &Path) -> anyhow::Result<()> {
+    let config = Config::load_default()?;
+    VaultIndex::open(vault, &vault.join(&config.templates_dir))?;
     Ok(())
 }
 
-fn regenerate_tag_cache() -> anyhow::Result<()> {
-    let cache_path = Config::cache_path()?;
-    let _ = std::fs::remove_file(&cache_path);
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_records_include_parents_and_skip_archived() {
+        let vault = Path::new("/vault");
+        let tags = |t: &str| t.split('/').map(String::from).collect::<Vec<_>>();
+        let mut tag_map = HashMap::new();
+        tag_map.insert(tags("proj/acme"), vec![vault.join("b.md"), vault.join("a.md")]);
+        tag_map.insert(tags("Archived/old"), vec![vault.join("c.md")]);
+
+        let records = tag_records(vault, tag_map);
+        let summary: Vec<(&str, usize)> = records.iter().map(|r| (r.tag.as_str(), r.count)).collect();
+        assert_eq!(summary, vec![("proj", 0), ("proj/acme", 2)]);
+        assert_eq!(records[1].files, vec!["a.md", "b.md"]);
+        assert_eq!(parse_tag_arg("#proj → acme"), TagPath::from_slash("proj/acme"));
+    }
+
+    #[test]
+    fn test_rename_from_args_plans_frontmatter_edits() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let vault = temp.path();
+        let a = vault.join("a.md");
+        let b = vault.join("b.md");
+        fs::write(&a, "---\ntags:\n- proj/acme\n- otro\n---\nA\n").unwrap();
+        fs::write(&b, "---\ntags:\n- proj/acme/web\n---\nB\n").unwrap();
+        let tags = |t: &str| t.split('/').map(String::from).collect::<Vec<_>>();
+        let mut tag_map = HashMap::new();
+        tag_map.insert(tags("proj/acme"), vec![a.clone()]);
+        tag_map.insert(tags("otro"), vec![a.clone()]);
+        tag_map.insert(tags("proj/acme/web"), vec![b.clone()]);
+        let files: HashSet<PathBuf> = HashSet::from([a.clone(), b.clone()]);
+
+        assert!(rename_from_args(&tag_map, "nada", "x", false).is_err());
+        assert!(rename_from_args(&tag_map, "proj", "", true).is_err());
+        // `proj` solo existe como padre: sin --recursive no renombraría nada
+        let err = rename_from_args(&tag_map, "proj", "cliente", false).err().unwrap();
+        assert!(err.to_string().contains("--recursive"));
+
+        let rename = rename_from_args(&tag_map, "#proj", "cliente", true).unwrap();
+        let plan = plan_rename(vault, &files, &rename).unwrap();
+        assert_eq!(plan.changes.len(), 2);
+        assert!(plan.read(&a).unwrap().contains("- cliente/acme\n- otro\n"));
+        assert!(plan.read(&b).unwrap().contains("- cliente/acme/web\n"));
+
+        // Sin --recursive solo el tag exacto
+        let rename = rename_from_args(&tag_map, "proj/acme", "acme", false).unwrap();
+        let plan = plan_rename(vault, &files, &rename).unwrap();
+        assert_eq!(plan.changes.len(), 1);
+        assert!(plan.read(&a).unwrap().contains("- acme\n"));
+    }
 }
@@ -1,12 +1,22 @@
-use crate::commands::rcal_tasks;
+use crate::commands::rcal_tasks::{self, NewTask, RcalCalendar, RcalConfig};
+use crate::commands::{periodic, sync};
 use crate::core::config::Config;
-use crate::utils::vault::VaultWalker;
-use chrono::{DateTime, Local, NaiveDate};
+use crate::core::date_expr;
+use crate::core::ical::DateValue;
+use crate::core::task::{self, Priority, TaskLine, TaskMeta};
+use crate::tags::parser::TagPath;
+use crate::ui::prompts;
+use crate::utils::cli::TaskFilter;
+use crate::utils::output::{self, OutputFormat};
+use crate::vault::index::VaultIndex;
+use crate::vault::plan::Plan;
+use chrono::{DateTime, Local, NaiveDate, NaiveTime};
 use crossterm::{
     event::{self, Event, KeyCode},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use console::style;
 use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
 use ratatui::{
     backend::CrosstermBackend,
@@ -15,8 +25,9 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
     Terminal,
 };
+use serde::Serialize;
+use std::cmp::Reverse;
 use std::collections::HashMap;
-use std::fs;
 use std::path::{Path, PathBuf};
 
 // ─── Structs unificados ─────────────────────────────────────────────────────
@@ -36,44 +47,160 @@ enum TaskSource {
 struct Task {
     title: String,
     source: TaskSource,
+    /// Fecha ISO (`2026-03-01` o `2026-03-01T09:30`) para la salida estructurada
+    date: Option<String>,
+    due: Option<NaiveDate>,
+    scheduled: Option<NaiveDate>,
+    start: Option<NaiveDate>,
+    priority: Priority,
+    tags: Vec<String>,
+    /// Regla `🔁 every week`
+    recurrence: Option<String>,
+    /// Línea de la tarea padre en el mismo archivo
+    parent: Option<usize>,
+    /// Nivel de anidación en la lista (las subtareas van debajo de su padre)
+    depth: usize,
     meta_date: String,
     meta_label: String,
 }
 
+/// Filtros de la línea de comandos con la fecha ya resuelta
+struct Filter {
+    today: NaiveDate,
+    due_until: Option<NaiveDate>,
+    overdue: bool,
+    priority: Option<Priority>,
+    tag: Option<TagPath>,
+}
+
+/// Vencimiento de una tarea respecto de hoy (para colorear)
+enum DueState {
+    Overdue,
+    Today,
+    Upcoming,
+    NoDue,
+}
+
+/// `mad tasks --json|--format tsv`
+#[derive(Debug, Serialize)]
+struct TaskRecord {
+    /// `markdown` | `ical`
+    source: &'static str,
+    path: String,
+    line: Option<usize>,
+    date: Option<String>,
+    text: String,
+    due: Option<String>,
+    scheduled: Option<String>,
+    start: Option<String>,
+    priority: Priority,
+    tags: Vec<String>,
+    recurrence: Option<String>,
+    /// Línea de la tarea padre (subtareas)
+    parent: Option<usize>,
+}
+
 /// Acción retornada por el TUI
 enum Action {
     MarkDone(usize),
-    Migrate(usize),
+    Sync(usize),
     CreateNew,
     Quit,
 }
 
 // ─── Entry point ─────────────────────────────────────────────────────────────
 
-pub fn run(vault: PathBuf, config: Config, mark_all: bool, full: bool) -> anyhow::Result<()> {
+pub fn run(
+    vault: PathBuf,
+    config: Config,
+    mark_all: bool,
+    full: bool,
+    format: OutputFormat,
+    filter: &TaskFilter,
+    yes: bool,
+) -> anyhow::Result<()> {
+    let filter = Filter::new(filter, Local::now().date_naive())?;
+    if format.is_structured() {
+        return print_records(&vault, &config, &filter, format);
+    }
     if mark_all {
-        return run_mark_all(&vault, &config);
+        return run_mark_all(&vault, &config, &filter, yes);
     }
+    prompts::ensure_tty("usa --json o --format tsv para listar las tareas")?;
     if full {
-        return run_tui(&vault, &config);
+        return run_tui(&vault, &config, &filter);
     }
-    run_simple(&vault, &config)
+    run_simple(&vault, &config, &filter)
+}
+
+// ─── Salida estructurada: sin prompts ni TUI ────────────────────────────────
+
+fn print_records(
+    vault: &Path,
+    config: &Config,
+    filter: &Filter,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let iso = |d: Option<NaiveDate>| d.map(|d| d.format("%Y-%m-%d").to_string());
+    let records: Vec<TaskRecord> = collect_all_tasks(vault, config, filter)?
+        .into_iter()
+        .map(|task| {
+            let (source, path, line) = match task.source {
+                TaskSource::Markdown { path, line_number } => ("markdown", path, Some(line_number)),
+                TaskSource::Ical { file_path } => ("ical", file_path, None),
+            };
+            TaskRecord {
+                source,
+                path: path.strip_prefix(vault).unwrap_or(&path).display().to_string(),
+                line,
+                date: task.date,
+                text: task.title,
+                due: iso(task.due),
+                scheduled: iso(task.scheduled),
+                start: iso(task.start),
+                priority: task.priority,
+                tags: task.tags,
+                recurrence: task.recurrence,
+                parent: task.parent,
+            }
+        })
+        .collect();
+
+    output::print(format, &records, |r| {
+        vec![
+            r.source.to_string(),
+            r.path.clone(),
+            r.line.map(|l| l.to_string()).unwrap_or_default(),
+            r.date.clone().unwrap_or_default(),
+            r.text.clone(),
+            r.due.clone().unwrap_or_default(),
+            r.scheduled.clone().unwrap_or_default(),
+            r.start.clone().unwrap_or_default(),
+            r.priority.as_str().to_string(),
+            r.tags.join(","),
+            r.recurrence.clone().unwrap_or_default(),
+            r.parent.map(|l| l.to_string()).unwrap_or_default(),
+        ]
+    })
 }
 
 // ─── Path mark_all: dialoguer original, solo tareas md ──────────────────────
 
-fn run_mark_all(vault: &Path, config: &Config) -> anyhow::Result<()> {
-    let md_tasks = collect_md_tasks(vault, config)?;
+fn run_mark_all(vault: &Path, config: &Config, filter: &Filter, yes: bool) -> anyhow::Result<()> {
+    let md_tasks = collect_md_tasks(vault, config, filter)?;
 
     if md_tasks.is_empty() {
         println!("No se encontraron tareas pendientes en el vault.");
         return Ok(());
     }
 
-    let mark = Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt("¿Quieres marcar TODAS las tareas como listas?")
-        .default(false)
-        .interact()?;
+    let mark = yes || {
+        prompts::ensure_tty("usa --yes para confirmar")?;
+        Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("¿Quieres marcar TODAS las tareas como listas?")
+            .default(false)
+            .interact()?
+    };
 
     if !mark {
         return Ok(());
@@ -86,10 +213,12 @@ fn run_mark_all(vault: &Path, config: &Config) -> anyhow::Result<()> {
         }
     }
 
+    let mut plan = task_plan(vault);
     let mut updated = 0usize;
     for (path, line_numbers) in by_file {
-        updated += mark_tasks_in_file(&path, &line_numbers, "- [x] ")?;
+        updated += mark_tasks_in_file(&mut plan, &path, &line_numbers, 'x')?;
     }
+    plan.apply()?;
 
     println!("✅ Tareas marcadas como listas: {}", updated);
     Ok(())
@@ -97,9 +226,9 @@ fn run_mark_all(vault: &Path, config: &Config) -> anyhow::Result<()> {
 
 // ─── Path simple: dialoguer Select, md + ical ───────────────────────────────
 
-fn run_simple(vault: &Path, config: &Config) -> anyhow::Result<()> {
+fn run_simple(vault: &Path, config: &Config, filter: &Filter) -> anyhow::Result<()> {
     loop {
-        let tasks = collect_all_tasks(vault, config)?;
+        let tasks = collect_all_tasks(vault, config, filter)?;
 
         if tasks.is_empty() {
             println!("No se encontraron tareas pendientes.");
@@ -118,7 +247,8 @@ fn run_simple(vault: &Path, config: &Config) -> anyhow::Result<()> {
                     format!("({} {})", task.meta_date, task.meta_label)
                 };
                 let meta_len = meta.chars().count();
-                let checkbox_width = 4; // "[ ] "
+                let indent = "  ".repeat(task.depth);
+                let checkbox_width = 4 + indent.len(); // "[ ] "
                 let available = term_width.saturating_sub(checkbox_width + meta_len + 2);
 
                 let title: String = if task.title.chars().count() > available {
@@ -130,7 +260,19 @@ fn run_simple(vault: &Path, config: &Config) -> anyhow::Result<()> {
                 };
 
                 let padding = available.saturating_sub(title.chars().count());
-                format!("[ ] {}{:width$}{}", title, "", meta, width = padding)
+                let title = match task.priority {
+                    Priority::Highest | Priority::High => style(title).red().bold(),
+                    Priority::Medium => style(title).yellow(),
+                    Priority::Low | Priority::Lowest => style(title).dim(),
+                    Priority::Normal => style(title),
+                };
+                let meta = match filter.due_state(task) {
+                    DueState::Overdue => style(meta).red(),
+                    DueState::Today => style(meta).yellow(),
+                    DueState::Upcoming => style(meta).green(),
+                    DueState::NoDue => style(meta).dim(),
+                };
+                format!("{}[ ] {}{:width$}{}", indent, title, "", meta, width = padding)
             })
             .collect();
 
@@ -146,34 +288,10 @@ fn run_simple(vault: &Path, config: &Config) -> anyhow::Result<()> {
         let task = &tasks[idx];
         match &task.source {
             TaskSource::Ical { file_path } => {
-                rcal_tasks::toggle_task(file_path)?;
+                rcal_tasks::complete_task(file_path)?;
                 println!("✓ Tarea marcada como lista: {}", task.title);
             }
-            TaskSource::Markdown { path, line_number } => {
-                if rcal_tasks::rcal_available() {
-                    let migrate = Confirm::with_theme(&ColorfulTheme::default())
-                        .with_prompt("¿Migrar a rcal?")
-                        .default(true)
-                        .interact()?;
-
-                    if migrate {
-                        mark_tasks_in_file(path, &[*line_number], "- [M] ")?;
-                        match rcal_tasks::run_rcal_todo(&task.title, None, None, None, None) {
-                            Ok(()) => println!("✓ Tarea migrada a rcal: {}", task.title),
-                            Err(e) => {
-                                let _ = rollback_migrate(path, *line_number);
-                                eprintln!("✗ Error en rcal todo: {}", e);
-                            }
-                        }
-                    } else {
-                        mark_tasks_in_file(path, &[*line_number], "- [x] ")?;
-                        println!("✓ Tarea marcada como lista: {}", task.title);
-                    }
-                } else {
-                    mark_tasks_in_file(path, &[*line_number], "- [x] ")?;
-                    println!("✓ Tarea marcada como lista: {}", task.title);
-                }
-            }
+            TaskSource::Markdown { .. } => mark_done(vault, &tasks, idx)?,
         }
 
         std::thread::sleep(std::time::Duration::from_millis(600));
@@ -182,16 +300,16 @@ fn run_simple(vault: &Path, config: &Config) -> anyhow::Result<()> {
 
 // ─── Path TUI: ratatui con tareas md + ical ─────────────────────────────────
 
-fn run_tui(vault: &Path, config: &Config) -> anyhow::Result<()> {
+fn run_tui(vault: &Path, config: &Config, filter: &Filter) -> anyhow::Result<()> {
     loop {
-        let tasks = collect_all_tasks(vault, config)?;
+        let tasks = collect_all_tasks(vault, config, filter)?;
 
         if tasks.is_empty() {
             println!("No se encontraron tareas pendientes.");
             return Ok(());
         }
 
-        let action = run_task_tui(&tasks)?;
+        let action = run_task_tui(&tasks, filter)?;
 
         match action {
             Action::Quit => return Ok(()),
@@ -199,83 +317,40 @@ fn run_tui(vault: &Path, config: &Config) -> anyhow::Result<()> {
             Action::MarkDone(idx) => {
                 let task = &tasks[idx];
                 match &task.source {
-                    TaskSource::Markdown { path, line_number } => {
-                        mark_tasks_in_file(path, &[*line_number], "- [x] ")?;
-                        println!("✓ Tarea marcada como lista: {}", task.title);
+                    TaskSource::Markdown { .. } => {
+                        mark_done(vault, &tasks, idx)?;
                         std::thread::sleep(std::time::Duration::from_millis(600));
                     }
                     TaskSource::Ical { file_path } => {
-                        rcal_tasks::toggle_task(file_path)?;
+                        rcal_tasks::complete_task(file_path)?;
                         println!("✓ Tarea marcada como lista: {}", task.title);
                         std::thread::sleep(std::time::Duration::from_millis(600));
                     }
                 }
             }
 
-            Action::Migrate(idx) => {
-                let task = &tasks[idx];
-                let TaskSource::Markdown { path, line_number } = &task.source else {
+            Action::Sync(idx) => {
+                let TaskSource::Markdown { path, line_number } = &tasks[idx].source else {
                     continue;
                 };
-
-                if !rcal_tasks::rcal_available() {
-                    println!("✗ `rcal` no encontrado en PATH. No se puede migrar.");
-                    std::thread::sleep(std::time::Duration::from_millis(800));
-                    continue;
+                if let Err(e) = sync::link_task(vault, config, path, *line_number) {
+                    eprintln!("✗ {}", e);
                 }
-
-                // Marcar [M] primero
-                mark_tasks_in_file(path, &[*line_number], "- [M] ")?;
-
-                // Prompts fuera del TUI
-                match prompt_rcal_flags(Some(&task.title))? {
-                    Some((title, cal, date, time, dur)) => {
-                        match rcal_tasks::run_rcal_todo(
-                            &title,
-                            cal.as_deref(),
-                            date.as_deref(),
-                            time.as_deref(),
-                            dur.as_deref(),
-                        ) {
-                            Ok(()) => {
-                                println!("✓ Tarea migrada a rcal: {}", title);
-                            }
-                            Err(e) => {
-                                // Rollback: restaurar [ ]
-                                let _ = rollback_migrate(path, *line_number);
-                                eprintln!("✗ Error en rcal todo: {}", e);
-                            }
-                        }
-                    }
-                    None => {
-                        // Cancelado → rollback
-                        let _ = rollback_migrate(path, *line_number);
-                        println!("Migración cancelada.");
-                    }
-                }
-                std::thread::sleep(std::time::Duration::from_millis(600));
+                std::thread::sleep(std::time::Duration::from_millis(800));
             }
 
             Action::CreateNew => {
-                if !rcal_tasks::rcal_available() {
-                    println!("✗ `rcal` no encontrado en PATH. No se puede crear tarea.");
+                let Some(rcal) = rcal_tasks::load(config) else {
+                    println!("✗ No hay calendario configurado ([tasks] calendar o rcal). No se puede crear tarea.");
                     std::thread::sleep(std::time::Duration::from_millis(800));
                     continue;
-                }
+                };
 
-                match prompt_rcal_flags(None)? {
-                    Some((title, cal, date, time, dur)) => {
-                        match rcal_tasks::run_rcal_todo(
-                            &title,
-                            cal.as_deref(),
-                            date.as_deref(),
-                            time.as_deref(),
-                            dur.as_deref(),
-                        ) {
-                            Ok(()) => println!("✓ Tarea creada en rcal: {}", title),
-                            Err(e) => eprintln!("✗ Error en rcal todo: {}", e),
-                        }
-                    }
+                match prompt_new_task(&rcal)? {
+                    Some((calendar, new_task)) => match rcal_tasks::create_task(calendar, &new_task) {
+                        Ok(_) => println!("✓ Tarea creada en {}: {}", calendar.name, new_task.summary),
+                        Err(e) => eprintln!("✗ No se pudo crear la tarea en {}: {}", calendar.name, e),
+                    },
                     None => println!("Creación cancelada."),
                 }
                 std::thread::sleep(std::time::Duration::from_millis(600));
@@ -286,7 +361,7 @@ fn run_tui(vault: &Path, config: &Config) -> anyhow::Result<()> {
 
 // ─── TUI ratatui ─────────────────────────────────────────────────────────────
 
-fn run_task_tui(tasks: &[Task]) -> anyhow::Result<Action> {
+fn run_task_tui(tasks: &[Task], filter: &Filter) -> anyhow::Result<Action> {
     enable_raw_mode()?;
     let mut stdout = std::io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
@@ -308,7 +383,7 @@ fn run_task_tui(tasks: &[Task]) -> anyhow::Result<Action> {
                 .split(f.area());
 
             // Construir items de la lista
-            let items = build_list_items(tasks, separator_idx, list_state.selected());
+            let items = build_list_items(tasks, filter, separator_idx, list_state.selected());
             let item_count = items.len();
 
             let list = List::new(items)
@@ -329,7 +404,7 @@ fn run_task_tui(tasks: &[Task]) -> anyhow::Result<Action> {
             f.render_stateful_widget(list, chunks[0], &mut list_state);
 
             let hints = if item_count > 0 {
-                " ↑↓ Navegar | Enter: Marcar lista | n: Nueva | c: Migrar a rcal | ESC: Salir "
+                " ↑↓ Navegar | Enter: Marcar lista | n: Nueva | c: Sincronizar con CalDAV | ESC: Salir "
             } else {
                 " ESC: Salir "
             };
@@ -365,9 +440,9 @@ fn run_task_tui(tasks: &[Task]) -> anyhow::Result<Action> {
                 KeyCode::Char('c') | KeyCode::Char('C') => {
                     if let Some(sel) = list_state.selected() {
                         if let Some(task_idx) = visible_to_task_idx(sel, separator_idx) {
-                            // Solo permitir migrar tareas md
+                            // Solo las tareas md se vinculan a la colección CalDAV
                             if matches!(tasks[task_idx].source, TaskSource::Markdown { .. }) {
-                                break Action::Migrate(task_idx);
+                                break Action::Sync(task_idx);
                             }
                         }
                     }
@@ -384,9 +459,10 @@ fn run_task_tui(tasks: &[Task]) -> anyhow::Result<Action> {
     Ok(action)
 }
 
-/// Construye los ListItems, insertando un separador visual entre vault y rcal
+/// Construye los ListItems, insertando un separador visual entre vault y calendarios
 fn build_list_items(
     tasks: &[Task],
+    filter: &Filter,
     separator_idx: Option<usize>,
     selected: Option<usize>,
 ) -> Vec<ListItem<'static>> {
@@ -396,7 +472,7 @@ fn build_list_items(
         // Insertar separador antes del primer task ical
         if separator_idx == Some(i) {
             let sep = ListItem::new(
-                ratatui::text::Line::from("─── rcal ────────────────────────────────────────")
+                ratatui::text::Line::from("─── calendario ──────────────────────────────────")
                     .style(Style::default().fg(Color::DarkGray)),
             );
             items.push(sep);
@@ -411,17 +487,27 @@ fn build_list_items(
         let is_selected = selected == Some(visible_idx);
         let prefix = if is_selected { "" } else { "[ ] " };
 
-        let label = format!(
-            "{}{}",
-            prefix,
-            task.title
-        );
+        let label = format!("{}{}{}", "  ".repeat(task.depth), prefix, task.title);
 
         let meta = format!("({} {})", task.meta_date, task.meta_label);
+        let label_style = match task.priority {
+            Priority::Highest | Priority::High => {
+                Style::default().fg(Color::LightRed).add_modifier(Modifier::BOLD)
+            }
+            Priority::Medium => Style::default().fg(Color::Yellow),
+            Priority::Low | Priority::Lowest => Style::default().fg(Color::Gray),
+            Priority::Normal => Style::default().fg(Color::White),
+        };
+        let meta_color = match filter.due_state(task) {
+            DueState::Overdue => Color::Red,
+            DueState::Today => Color::Yellow,
+            DueState::Upcoming => Color::Green,
+            DueState::NoDue => Color::DarkGray,
+        };
         let line = ratatui::text::Line::from(vec![
-            ratatui::text::Span::styled(label, Style::default().fg(Color::White)),
+            ratatui::text::Span::styled(label, label_style),
             ratatui::text::Span::raw("  "),
-            ratatui::text::Span::styled(meta, Style::default().fg(Color::DarkGray)),
+            ratatui::text::Span::styled(meta, Style::default().fg(meta_color)),
         ]);
 
         items.push(ListItem::new(line));
@@ -499,96 +585,114 @@ fn move_selection(
     state.select(Some(next));
 }
 
-// ─── Prompts de flags (dialoguer, fuera del TUI) ────────────────────────────
-
-/// (título, calendario, fecha, hora, duración)
-type RcalFlags = (String, Option<String>, Option<String>, Option<String>, Option<String>);
-
-/// Muestra prompts para crear/migrar tarea en rcal.
-/// Si `prefill_title` es Some, se usa como default del campo título.
-/// Retorna None si el usuario cancela (ESC / Ctrl+C en dialoguer).
-fn prompt_rcal_flags(prefill_title: Option<&str>) -> anyhow::Result<Option<RcalFlags>> {
+// ─── Prompts de tarea nueva (dialoguer, fuera del TUI) ──────────────────────
 
-    let title: String = if let Some(default) = prefill_title {
-        Input::with_theme(&ColorfulTheme::default())
-            .with_prompt("Título")
-            .default(default.to_string())
-            .interact()?
-    } else {
-        Input::with_theme(&ColorfulTheme::default())
-            .with_prompt("Título")
-            .interact()?
-    };
+/// Muestra prompts para crear una tarea en un calendario.
+/// Retorna None si el usuario cancela o algún dato no es válido.
+fn prompt_new_task(rcal: &RcalConfig) -> anyhow::Result<Option<(&RcalCalendar, NewTask)>> {
+    let title: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Título")
+        .interact()?;
 
     if title.trim().is_empty() {
         return Ok(None);
     }
 
+    let default_calendar = rcal.calendar(None)?;
     let calendar: String = Input::with_theme(&ColorfulTheme::default())
-        .with_prompt("Calendario (vacío = default)")
+        .with_prompt(format!("Calendario (vacío = {})", default_calendar.name))
         .default(String::new())
         .interact()?;
+    let calendar = match rcal.calendar(Some(calendar.trim()).filter(|c| !c.is_empty())) {
+        Ok(calendar) => calendar,
+        Err(e) => {
+            eprintln!("✗ {}", e);
+            return Ok(None);
+        }
+    };
 
-    let date: String = Input::with_theme(&ColorfulTheme::default())
-        .with_prompt("Fecha (vacío = hoy)")
+    let due: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Vence (vacío = sin fecha)")
         .default(String::new())
         .interact()?;
 
     let time: String = Input::with_theme(&ColorfulTheme::default())
-        .with_prompt("Hora (vacío = auto)")
+        .with_prompt("Hora (vacío = todo el día)")
         .default(String::new())
         .interact()?;
 
-    let duration: String = Input::with_theme(&ColorfulTheme::default())
-        .with_prompt("Duración (vacío = default)")
-        .default(String::new())
-        .interact()?;
+    let today = Local::now().date_naive();
+    let date = match due.trim() {
+        "" if time.trim().is_empty() => None,
+        "" => Some(today),
+        expr => match date_expr::parse(expr, today) {
+            Ok(date) => Some(date),
+            Err(e) => {
+                eprintln!("✗ {}", e);
+                return Ok(None);
+            }
+        },
+    };
+    let due = match (date, time.trim()) {
+        (None, _) => None,
+        (Some(date), "") => Some(DateValue::Date(date)),
+        (Some(date), time) => match NaiveTime::parse_from_str(time, "%H:%M") {
+            Ok(time) => Some(DateValue::Floating(date.and_time(time))),
+            Err(_) => {
+                eprintln!("✗ Hora inválida: {} (usa HH:MM)", time);
+                return Ok(None);
+            }
+        },
+    };
 
     Ok(Some((
-        title,
-        if calendar.is_empty() { None } else { Some(calendar) },
-        if date.is_empty() { None } else { Some(date) },
-        if time.is_empty() { None } else { Some(time) },
-        if duration.is_empty() { None } else { Some(duration) },
+        calendar,
+        NewTask {
+            summary: title.trim().to_string(),
+            due,
+            priority: Priority::Normal,
+            categories: Vec::new(),
+        },
     )))
 }
 
-// ─── Rollback migración ──────────────────────────────────────────────────────
-
-fn rollback_migrate(path: &Path, line_number: usize) -> anyhow::Result<()> {
-    let content = fs::read_to_string(path)?;
-    let mut lines: Vec<String> = content.split('\n').map(|s| s.to_string()).collect();
-
-    let idx = line_number - 1;
-    if idx < lines.len() && lines[idx].starts_with("- [M] ") {
-        lines[idx] = lines[idx].replacen("- [M] ", "- [ ] ", 1);
-        fs::write(path, lines.join("\n"))?;
-    }
-    Ok(())
-}
-
 // ─── Recolección de tareas ───────────────────────────────────────────────────
 
-/// Recolecta tareas md + ical. Degradación gracil: si no hay config rcal, solo md.
-fn collect_all_tasks(vault: &Path, config: &Config) -> anyhow::Result<Vec<Task>> {
-    let mut tasks = collect_md_tasks(vault, config)?;
+/// Recolecta tareas md + ical. Degradación gracil: si no hay calendarios, solo md.
+fn collect_all_tasks(vault: &Path, config: &Config, filter: &Filter) -> anyhow::Result<Vec<Task>> {
+    let mut tasks = collect_md_tasks(vault, config, filter)?;
 
     // Intentar cargar tareas ical (degradación gracil)
-    if let Some(rcal_cfg_path) = rcal_tasks::find_rcal_config(config.rcal_config.as_deref()) {
-        if let Ok(rcal_cfg) = rcal_tasks::read_rcal_config(&rcal_cfg_path) {
-            if let Ok(ical_tasks) = rcal_tasks::read_pending_tasks(&rcal_cfg) {
-                for it in ical_tasks {
-                    tasks.push(Task {
-                        title: it.summary,
-                        source: TaskSource::Ical {
-                            file_path: it.file_path,
-                        },
-                        meta_date: it
-                            .start
-                            .map(|dt| dt.format("%d/%m %H:%M").to_string())
-                            .unwrap_or_else(|| "--/-- --:--".to_string()),
-                        meta_label: String::new(),
-                    });
+    if let Some(rcal_cfg) = rcal_tasks::load(config) {
+        if let Ok(ical_tasks) = rcal_tasks::read_pending_tasks(&rcal_cfg) {
+            for it in ical_tasks {
+                let meta_date = match (it.due, it.start) {
+                    (Some(due), _) => format!("vence {}", ical_meta_date(due)),
+                    (None, Some(start)) => ical_meta_date(start),
+                    (None, None) => "--/-- --:--".to_string(),
+                };
+                let task = Task {
+                    title: it.summary,
+                    source: TaskSource::Ical {
+                        file_path: it.file_path,
+                    },
+                    date: it.start.map(|start| match start {
+                        DateValue::Date(date) => date.format("%Y-%m-%d").to_string(),
+                        _ => start.local().format("%Y-%m-%dT%H:%M").to_string(),
+                    }),
+                    due: it.due.map(|due| due.date()),
+                    scheduled: None,
+                    start: it.start.map(|start| start.date()),
+                    priority: it.priority,
+                    tags: it.categories,
+                    recurrence: None,
+                    parent: None,
+                    depth: 0,
+                    meta_date,
+                    meta_label: it.calendar_name,
+                };
+                if filter.matches(&task) {
+                    tasks.push(task);
                 }
             }
         }
@@ -597,86 +701,261 @@ fn collect_all_tasks(vault: &Path, config: &Config) -> anyhow::Result<Vec<Task>>
     Ok(tasks)
 }
 
-/// Recolecta solo tareas markdown del vault
-fn collect_md_tasks(vault: &Path, config: &Config) -> anyhow::Result<Vec<Task>> {
+/// `16/10 09:30`, o `16/10` si es de todo el día
+fn ical_meta_date(value: DateValue) -> String {
+    if value.has_time() {
+        value.local().format("%d/%m %H:%M").to_string()
+    } else {
+        value.date().format("%d/%m").to_string()
+    }
+}
+
+/// Recolecta solo tareas markdown del vault (leídas del índice del vault),
+/// ordenadas por vencimiento y prioridad
+fn collect_md_tasks(vault: &Path, config: &Config, filter: &Filter) -> anyhow::Result<Vec<Task>> {
     let templates_path = vault.join(&config.templates_dir);
-    let diario_dir = vault.join(&config.diary_dir);
+    let index = VaultIndex::open(vault, &templates_path)?;
     let mut tasks = Vec::new();
 
-    VaultWalker::new(vault)
-        .exclude_templates(&templates_path)
-        .walk(|path, content| {
-            let mut in_code_block = false;
-            let file_stem = path
-                .file_stem()
+    for (path, note) in index.iter() {
+        let pending: Vec<_> = note.tasks.iter().filter(|t| t.status == ' ').collect();
+        if pending.is_empty() {
+            continue;
+        }
+
+        let daily = periodic::daily_note_date(config, vault, &path);
+        let meta_label = if daily.is_some() {
+            "diario".to_string()
+        } else {
+            path.file_stem()
                 .and_then(|s| s.to_str())
                 .unwrap_or("sin-titulo")
-                .to_string();
-            let meta_label = if path.starts_with(&diario_dir) {
-                "diario".to_string()
-            } else {
-                file_stem
+                .to_string()
+        };
+        let date = daily.unwrap_or_else(|| {
+            let modified: DateTime<Local> = note.modified().into();
+            modified.date_naive()
+        });
+        let meta_date = date.format("%d/%m").to_string();
+
+        for task in pending {
+            let meta = TaskMeta::parse(&task.text);
+            let mut meta_date = match (meta.due, meta.scheduled) {
+                (Some(due), _) => format!("vence {}", due.format("%d/%m")),
+                (None, Some(scheduled)) => format!("para {}", scheduled.format("%d/%m")),
+                (None, None) => meta_date.clone(),
             };
-            let meta_date = task_meta_date(path, &diario_dir)?;
+            if meta.recurrence.is_some() {
+                meta_date = format!("↻ {}", meta_date);
+            }
+            let task = Task {
+                title: meta.title,
+                source: TaskSource::Markdown {
+                    path: path.clone(),
+                    line_number: task.line,
+                },
+                date: Some(date.format("%Y-%m-%d").to_string()),
+                due: meta.due,
+                scheduled: meta.scheduled,
+                start: meta.start,
+                priority: meta.priority,
+                tags: meta.tags,
+                recurrence: meta.recurrence,
+                parent: task.parent,
+                depth: 0,
+                meta_date,
+                meta_label: meta_label.clone(),
+            };
+            if filter.matches(&task) {
+                tasks.push(task);
+            }
+        }
+    }
 
-            for (idx, line) in content.split('\n').enumerate() {
-                let trimmed = line.trim_start();
+    // Primero las que vencen antes, después las de mayor prioridad
+    tasks.sort_by_key(|t| {
+        (
+            t.due.is_none(),
+            t.due,
+            Reverse(t.priority),
+            t.scheduled.is_none(),
+            t.scheduled,
+        )
+    });
+    Ok(nest(tasks))
+}
 
-                if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
-                    in_code_block = !in_code_block;
-                    continue;
-                }
+/// Put each subtask right below its parent (in file order), keeping the order of the roots.
+/// Una subtarea cuyo padre no está en la lista (completado o filtrado) queda como raíz.
+fn nest(tasks: Vec<Task>) -> Vec<Task> {
+    let key = |task: &Task| match &task.source {
+        TaskSource::Markdown { path, line_number } => Some((path.clone(), *line_number)),
+        TaskSource::Ical { .. } => None,
+    };
+    let index: HashMap<(PathBuf, usize), usize> = tasks
+        .iter()
+        .enumerate()
+        .filter_map(|(i, task)| Some((key(task)?, i)))
+        .collect();
+    let parent_of = |task: &Task| {
+        let (path, _) = key(task)?;
+        index.get(&(path, task.parent?)).copied()
+    };
 
-                if !in_code_block && line.starts_with("- [ ] ") {
-                    let title = line
-                        .trim_start()
-                        .strip_prefix("- [ ] ")
-                        .unwrap_or(line)
-                        .trim_end()
-                        .to_string();
-
-                    tasks.push(Task {
-                        title,
-                        source: TaskSource::Markdown {
-                            path: path.to_path_buf(),
-                            line_number: idx + 1,
-                        },
-                        meta_date: meta_date.clone(),
-                        meta_label: meta_label.clone(),
-                    });
-                }
-            }
-            Ok(())
-        })?;
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut roots = Vec::new();
+    for (i, task) in tasks.iter().enumerate() {
+        match parent_of(task) {
+            Some(parent) => children.entry(parent).or_default().push(i),
+            None => roots.push(i),
+        }
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|&i| key(&tasks[i]).map(|(_, line)| line));
+    }
 
-    Ok(tasks)
+    let mut order: Vec<(usize, usize)> = Vec::new();
+    let mut stack: Vec<(usize, usize)> = roots.into_iter().rev().map(|i| (i, 0)).collect();
+    while let Some((i, depth)) = stack.pop() {
+        order.push((i, depth));
+        if let Some(siblings) = children.get(&i) {
+            stack.extend(siblings.iter().rev().map(|&c| (c, depth + 1)));
+        }
+    }
+
+    order
+        .into_iter()
+        .map(|(i, depth)| Task {
+            depth,
+            ..tasks[i].clone()
+        })
+        .collect()
 }
 
-// ─── Utilidades de archivo ───────────────────────────────────────────────────
+/// Pending subtasks of `tasks[idx]`: the tasks right below it with more depth
+fn subtasks(tasks: &[Task], idx: usize) -> &[Task] {
+    let depth = tasks[idx].depth;
+    let count = tasks[idx + 1..]
+        .iter()
+        .take_while(|t| t.depth > depth)
+        .count();
+    &tasks[idx + 1..idx + 1 + count]
+}
+
+/// Complete a markdown task; if it has pending subtasks, offer to complete them too
+fn mark_done(vault: &Path, tasks: &[Task], idx: usize) -> anyhow::Result<()> {
+    let task = &tasks[idx];
+    let TaskSource::Markdown { path, line_number } = &task.source else {
+        return Ok(());
+    };
+
+    let mut lines = vec![*line_number];
+    let children = subtasks(tasks, idx);
+    if !children.is_empty()
+        && Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("¿Completar también sus {} subtareas?", children.len()))
+            .default(true)
+            .interact()?
+    {
+        lines.extend(children.iter().filter_map(|t| match &t.source {
+            TaskSource::Markdown { line_number, .. } => Some(*line_number),
+            TaskSource::Ical { .. } => None,
+        }));
+    }
+
+    let mut plan = task_plan(vault);
+    let count = mark_tasks_in_file(&mut plan, path, &lines, 'x')?;
+    plan.apply()?;
+    if count > 1 {
+        println!("✓ Tarea marcada como lista: {} (y {} subtareas)", task.title, count - 1);
+    } else {
+        println!("✓ Tarea marcada como lista: {}", task.title);
+    }
+    Ok(())
+}
 
-fn task_meta_date(path: &Path, diario_dir: &Path) -> anyhow::Result<String> {
-    if path.starts_with(diario_dir) {
-        if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-            if let Ok(date) = NaiveDate::parse_from_str(stem, "%Y-%m-%d") {
-                return Ok(date.format("%d/%m").to_string());
+/// Plan for completing tasks (sin backups: el journal guarda el antes y el después)
+fn task_plan(vault: &Path) -> Plan {
+    let mut plan = Plan::new("tasks", vault);
+    plan.backup = false;
+    plan
+}
+
+// ─── Filtros ─────────────────────────────────────────────────────────────────
+
+impl Filter {
+    fn new(filter: &TaskFilter, today: NaiveDate) -> anyhow::Result<Self> {
+        Ok(Self {
+            today,
+            due_until: match &filter.due {
+                Some(expr) => Some(date_expr::parse(expr, today)?),
+                None => None,
+            },
+            overdue: filter.overdue,
+            priority: filter.priority,
+            tag: filter
+                .tag
+                .as_deref()
+                .map(|t| TagPath::from_slash(t.trim_start_matches('#'))),
+        })
+    }
+
+    fn matches(&self, task: &Task) -> bool {
+        if let Some(until) = self.due_until {
+            if task.due.is_none_or(|due| due > until) {
+                return false;
+            }
+        }
+        if self.overdue && task.due.is_none_or(|due| due >= self.today) {
+            return false;
+        }
+        if let Some(min) = self.priority {
+            if task.priority < min {
+                return false;
+            }
+        }
+        if let Some(tag) = &self.tag {
+            if !task.tags.iter().any(|t| TagPath::from_slash(t).starts_with(tag)) {
+                return false;
             }
         }
+        true
     }
 
-    let metadata = fs::metadata(path)?;
-    let modified = metadata.modified()?;
-    let datetime: DateTime<Local> = modified.into();
-    Ok(datetime.format("%d/%m").to_string())
+    fn due_state(&self, task: &Task) -> DueState {
+        match task.due {
+            Some(due) if due < self.today => DueState::Overdue,
+            Some(due) if due == self.today => DueState::Today,
+            Some(_) => DueState::Upcoming,
+            None => DueState::NoDue,
+        }
+    }
 }
 
-/// Marca líneas en un archivo md reemplazando `- [ ] ` por `replacement`.
+// ─── Utilidades de archivo ───────────────────────────────────────────────────
+
+/// Marca tareas abiertas (`- [ ] `, `* [ ] `, `1. [ ] `...) de un archivo md con `status`,
+/// como un cambio más de `plan` (se aplica y queda en el journal con `plan.apply()`).
+/// Al completar (`x`) una tarea recurrente (`🔁 every week`), inserta la próxima
+/// ocurrencia justo debajo.
 /// Retorna la cantidad de líneas reemplazadas.
-fn mark_tasks_in_file(path: &Path, line_numbers: &[usize], replacement: &str) -> anyhow::Result<usize> {
-    let content = fs::read_to_string(path)?;
+pub(crate) fn mark_tasks_in_file(
+    plan: &mut Plan,
+    path: &Path,
+    line_numbers: &[usize],
+    status: char,
+) -> anyhow::Result<usize> {
+    let content = plan.read(path)?;
     let mut lines: Vec<String> = content.split('\n').map(|s| s.to_string()).collect();
+    let today = Local::now().date_naive();
+
+    // De abajo hacia arriba: las líneas insertadas no corren los números pendientes
+    let mut line_numbers = line_numbers.to_vec();
+    line_numbers.sort_unstable_by(|a, b| b.cmp(a));
+    line_numbers.dedup();
 
     let mut updated = 0usize;
-    for &line_number in line_numbers {
+    for line_number in line_numbers {
         if line_number == 0 {
             continue;
         }
@@ -684,15 +963,23 @@ fn mark_tasks_in_file(path: &Path, line_numbers: &[usize], replacement: &str) ->
         if idx >= lines.len() {
             continue;
         }
-        let line = &lines[idx];
-        if line.starts_with("- [ ] ") {
-            lines[idx] = line.replacen("- [ ] ", replacement, 1);
-            updated += 1;
+        let Some(task) = TaskLine::parse(&lines[idx]).filter(|t| t.status == ' ') else {
+            continue;
+        };
+        let next = (status == 'x')
+            .then(|| task::next_occurrence(task.text, today))
+            .flatten()
+            .map(|next| task.with_text(' ', &next));
+        lines[idx] = task.with_status(status);
+        updated += 1;
+        if let Some(next) = next {
+            println!("↻ Próxima ocurrencia: {}", next.trim_start());
+            lines.insert(idx + 1, next);
         }
     }
 
     if updated > 0 {
-        fs::write(path, lines.join("\n"))?;
+        plan.edit(path, lines.join("\n"))?;
     }
 
     Ok(updated)
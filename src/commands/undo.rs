@@ -0,0 +1,64 @@
+use crate::vault::journal::{self, Entry};
+use std::path::Path;
+
+/// Revert the last operation recorded in `.arc/journal`
+/// - `mad undo` - deshacer la última operación
+/// - `mad undo --list` - listar el journal (la más reciente primero)
+pub fn run(vault: &Path, list: bool) -> anyhow::Result<()> {
+    if list {
+        return list_entries(vault);
+    }
+
+    match journal::undo(vault)? {
+        Some(entry) => {
+            println!("↩️  Deshecho: {} ({})", entry.plan.command, entry.date);
+            entry.plan.inverse().print_changes();
+        }
+        None => println!("No hay operaciones para deshacer."),
+    }
+    Ok(())
+}
+
+/// Re-apply the last undone operation
+pub fn redo(vault: &Path) -> anyhow::Result<()> {
+    match journal::redo(vault)? {
+        Some(entry) => {
+            println!("↪️  Rehecho: {} ({})", entry.plan.command, entry.date);
+            entry.plan.print_changes();
+        }
+        None => println!("No hay operaciones para rehacer."),
+    }
+    Ok(())
+}
+
+fn list_entries(vault: &Path) -> anyhow::Result<()> {
+    let entries = journal::list(vault)?;
+    if entries.is_empty() {
+        println!("El journal está vacío.");
+        return Ok(());
+    }
+
+    for (_, entry) in entries.iter().rev() {
+        print_entry(entry);
+    }
+    Ok(())
+}
+
+fn print_entry(entry: &Entry) {
+    let state = if entry.undone { " [deshecho]" } else { "" };
+    println!(
+        "\n{}  {}  {} cambios{}",
+        entry.date,
+        entry.plan.command,
+        entry.plan.changes.len(),
+        state
+    );
+    entry.plan.print_changes();
+    for (path, backup) in &entry.backups {
+        println!(
+            "  💾 {} → {}",
+            entry.plan.display(path),
+            entry.plan.display(backup)
+        );
+    }
+}
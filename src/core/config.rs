@@ -1,3 +1,4 @@
+use crate::core::period::Period;
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::fs;
@@ -28,6 +29,128 @@ pub struct Config {
     /// Path al config de rcal (por defecto: ~/.config/rcal/config.toml)
     #[serde(default)]
     pub rcal_config: Option<String>,
+    /// Notas periódicas: `[periodic.day]`, `[periodic.week]`, `[periodic.month]`, `[periodic.quarter]`, `[periodic.year]`
+    #[serde(default)]
+    pub periodic: PeriodicConfig,
+    /// Arrastre de tareas abiertas a la nueva daily note: `[rollover]`
+    #[serde(default)]
+    pub rollover: RolloverConfig,
+    /// Captura rápida sin editor (`mad capture`): `[capture]`
+    #[serde(default)]
+    pub capture: CaptureConfig,
+    /// Tareas en calendarios iCalendar (`mad tasks`): `[tasks]`
+    #[serde(default)]
+    pub tasks: TasksConfig,
+}
+
+/// Same values as a `config.toml` with an empty `vault`: base para los tests
+/// (`Config { vault, ..Default::default() }`)
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            vault: String::new(),
+            date: "%Y-%m-%d".to_string(),
+            time: "%H:%M".to_string(),
+            default_nametype: None,
+            editor: None,
+            editor_mode: None,
+            timeprint: None,
+            notes_dir: default_notes_dir(),
+            diary_dir: default_diary_dir(),
+            templates_dir: default_templates_dir(),
+            tag_root: default_tag_root(),
+            dir_mappings: None,
+            rcal_config: None,
+            periodic: Default::default(),
+            rollover: Default::default(),
+            capture: Default::default(),
+            tasks: Default::default(),
+        }
+    }
+}
+
+/// Rollover of open tasks into a new daily note (todos opcionales)
+#[derive(Debug, Default, Deserialize)]
+pub struct RolloverConfig {
+    /// Activado por defecto
+    pub enabled: Option<bool>,
+    /// "move" (marca el origen como `[>]`, default) o "copy"
+    pub mode: Option<String>,
+    /// "last" (daily note anterior, default) o "all" (todas las anteriores)
+    pub scope: Option<String>,
+    /// Heading bajo el que se insertan (default: "## Pendientes")
+    pub heading: Option<String>,
+}
+
+/// Destination of `mad capture` (todos opcionales)
+#[derive(Debug, Default, Deserialize)]
+pub struct CaptureConfig {
+    /// "daily" (daily note de hoy, default) o "inbox"
+    pub target: Option<String>,
+    /// Nota inbox relativa al vault (default: "Inbox.md")
+    pub inbox: Option<String>,
+    /// Heading bajo el que se capturan (default: al final de la nota)
+    pub heading: Option<String>,
+}
+
+/// iCalendar tasks of `mad tasks` (todos opcionales)
+#[derive(Debug, Default, Deserialize)]
+pub struct TasksConfig {
+    /// Directorio de `.ics` donde se crean las tareas nuevas (default: primer calendario de rcal)
+    pub calendar: Option<String>,
+    /// Colección CalDAV de `mad tasks sync`: `[tasks.caldav]`
+    pub caldav: Option<CaldavConfig>,
+}
+
+/// CalDAV task list synced with `mad tasks sync`
+#[derive(Debug, Deserialize)]
+pub struct CaldavConfig {
+    /// URL de la colección (`https://dav.example.com/calendars/ana/tareas/`)
+    pub url: String,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    /// Comando que imprime la contraseña (`pass show caldav`), en vez de `password`
+    pub password_command: Option<String>,
+    /// Nota donde se agregan las tareas nuevas del servidor (default: la inbox de `[capture]`)
+    pub note: Option<String>,
+    /// Heading bajo el que se agregan (default: al final de la nota)
+    pub heading: Option<String>,
+    /// Las tareas abiertas con este tag se vinculan solas al sincronizar
+    pub tag: Option<String>,
+}
+
+/// Configuración de las notas periódicas (diaria, semanal, mensual, trimestral, anual)
+#[derive(Debug, Default, Deserialize)]
+pub struct PeriodicConfig {
+    #[serde(default)]
+    pub day: PeriodConfig,
+    #[serde(default)]
+    pub week: PeriodConfig,
+    #[serde(default)]
+    pub month: PeriodConfig,
+    #[serde(default)]
+    pub quarter: PeriodConfig,
+    #[serde(default)]
+    pub year: PeriodConfig,
+}
+
+/// Directory, filename format and template of a periodic note (todos opcionales)
+#[derive(Debug, Default, Deserialize)]
+pub struct PeriodConfig {
+    /// Directorio relativo al vault
+    pub dir: Option<String>,
+    /// Formato strftime del nombre de archivo (`%q` = trimestre, `/` = subdirectorios)
+    pub format: Option<String>,
+    /// Nombre del template: `Templates/<template>.md` y `Templates/<template>/*.md`
+    pub template: Option<String>,
+}
+
+/// Resolved settings of a periodic note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeriodSettings {
+    pub dir: String,
+    pub format: String,
+    pub template: String,
 }
 
 fn default_notes_dir() -> String {
@@ -47,6 +170,37 @@ fn default_tag_root() -> String {
 }
 
 impl Config {
+    /// Settings of a periodic note, with defaults for missing keys.
+    /// Daily notes default to `diary_dir` and `%Y-%m-%d`.
+    pub fn period(&self, period: Period) -> PeriodSettings {
+        let (configured, dir, format, template) = match period {
+            Period::Day => (
+                &self.periodic.day,
+                self.diary_dir.as_str(),
+                "%Y-%m-%d",
+                self.diary_dir.as_str(),
+            ),
+            Period::Week => (&self.periodic.week, "Periodicas/Semanal", "%G-W%V", "Semanal"),
+            Period::Month => (&self.periodic.month, "Periodicas/Mensual", "%Y-%m", "Mensual"),
+            Period::Quarter => (
+                &self.periodic.quarter,
+                "Periodicas/Trimestral",
+                "%Y-Q%q",
+                "Trimestral",
+            ),
+            Period::Year => (&self.periodic.year, "Periodicas/Anual", "%Y", "Anual"),
+        };
+
+        PeriodSettings {
+            dir: configured.dir.clone().unwrap_or_else(|| dir.to_string()),
+            format: configured.format.clone().unwrap_or_else(|| format.to_string()),
+            template: configured
+                .template
+                .clone()
+                .unwrap_or_else(|| template.to_string()),
+        }
+    }
+
     /// Returns the config directory path (~/.config/magic-documents or $XDG_CONFIG_HOME/magic-documents)
     pub fn config_dir() -> anyhow::Result<PathBuf> {
         let config_base = std::env::var("XDG_CONFIG_HOME")
@@ -64,14 +218,14 @@ impl Config {
         Ok(Self::config_dir()?.join("config.toml"))
     }
 
-    /// Returns the tags cache file path (~/.config/magic-documents/tags_cache.json)
-    pub fn cache_path() -> anyhow::Result<PathBuf> {
-        Ok(Self::config_dir()?.join("tags_cache.json"))
+    /// Returns the vault metadata index path (~/.config/magic-documents/vault_index.json)
+    pub fn vault_index_path() -> anyhow::Result<PathBuf> {
+        Ok(Self::config_dir()?.join("vault_index.json"))
     }
 
-    /// Returns the primary tags cache file path (~/.config/magic-documents/primary_tags_cache.json)
-    pub fn primary_cache_path() -> anyhow::Result<PathBuf> {
-        Ok(Self::config_dir()?.join("primary_tags_cache.json"))
+    /// Returns the full-text search index path (~/.config/magic-documents/search_index.json)
+    pub fn search_index_path() -> anyhow::Result<PathBuf> {
+        Ok(Self::config_dir()?.join("search_index.json"))
     }
 
     /// Returns the last note file path (~/.config/magic-documents/.last_note)
@@ -79,6 +233,16 @@ impl Config {
         Ok(Self::config_dir()?.join(".last_note"))
     }
 
+    /// Returns the last daily note file path (~/.config/magic-documents/.last_daily)
+    pub fn last_daily_path() -> anyhow::Result<PathBuf> {
+        Ok(Self::config_dir()?.join(".last_daily"))
+    }
+
+    /// Returns the CalDAV sync state path (~/.config/magic-documents/caldav_sync.json)
+    pub fn caldav_state_path() -> anyhow::Result<PathBuf> {
+        Ok(Self::config_dir()?.join("caldav_sync.json"))
+    }
+
     /// Returns the aliases file path (~/.config/magic-documents/aliases.json)
     pub fn aliases_path() -> anyhow::Result<PathBuf> {
         Ok(Self::config_dir()?.join("aliases.json"))
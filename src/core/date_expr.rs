@@ -0,0 +1,218 @@
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+
+/// Parse a date expression relative to `today`.
+/// - `2026-03-01`, `01/03/2026`
+/// - `today`/`hoy`, `yesterday`/`ayer`, `tomorrow`/`mañana`, `anteayer`, `pasado mañana`
+/// - `+3d`, `-1w`, `+1m-2d` (d, w, m, y)
+/// - `3 days ago`/`hace 3 días`, `in 2 weeks`/`en 2 semanas`
+/// - `friday`/`viernes`: ese día de la semana actual (semanas de lunes a domingo)
+/// - `last friday`/`viernes pasado`, `next friday`/`próximo viernes`/`viernes que viene`
+/// - `last week`/`semana pasada`, `next month`/`mes que viene`...
+pub fn parse(input: &str, today: NaiveDate) -> anyhow::Result<NaiveDate> {
+    let normalized: String = input
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(crate::search::index::fold_accent)
+        .collect();
+    let mut words: Vec<&str> = normalized.split_whitespace().collect();
+    if matches!(words.first(), Some(&("el" | "la" | "the"))) {
+        words.remove(0);
+    }
+
+    parse_words(&words, today)
+        .ok_or_else(|| anyhow::anyhow!("Fecha no reconocida: '{}'", input.trim()))
+}
+
+fn parse_words(words: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    match words {
+        [] => Some(today),
+        [word] => parse_word(word, today),
+        ["pasado", "manana"] => shift(today, 2, 'd'),
+        ["day", "after", "tomorrow"] => shift(today, 2, 'd'),
+        ["day", "before", "yesterday"] => shift(today, -2, 'd'),
+        ["last" | "past" | "previous", target] | [target, "pasado" | "pasada" | "anterior"] => {
+            relative(target, -1, today)
+        }
+        ["next" | "proximo" | "proxima" | "siguiente", target]
+        | [target, "proximo" | "proxima" | "siguiente"]
+        | [target, "que", "viene"] => relative(target, 1, today),
+        ["hace", amount, unit] | [amount, unit, "ago"] => {
+            shift(today, amount.parse::<i64>().ok()?.checked_neg()?, unit_of(unit)?)
+        }
+        ["in" | "en", amount, unit] => shift(today, amount.parse().ok()?, unit_of(unit)?),
+        _ => None,
+    }
+}
+
+fn parse_word(word: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match word {
+        "today" | "hoy" => return Some(today),
+        "yesterday" | "ayer" => return shift(today, -1, 'd'),
+        "tomorrow" | "manana" => return shift(today, 1, 'd'),
+        "anteayer" => return shift(today, -2, 'd'),
+        _ => {}
+    }
+
+    if let Some(weekday) = weekday_of(word) {
+        let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        return Some(monday + Duration::days(weekday.num_days_from_monday() as i64));
+    }
+
+    if word.starts_with(['+', '-']) {
+        return parse_offsets(word, today);
+    }
+
+    ["%Y-%m-%d", "%d/%m/%Y", "%Y/%m/%d"]
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(word, fmt).ok())
+}
+
+/// `last friday` (-1) / `next friday` (+1), or `last week` / `next month`
+fn relative(target: &str, direction: i64, today: NaiveDate) -> Option<NaiveDate> {
+    if let Some(weekday) = weekday_of(target) {
+        let mut date = today;
+        loop {
+            date = shift(date, direction, 'd')?;
+            if date.weekday() == weekday {
+                return Some(date);
+            }
+        }
+    }
+    shift(today, direction, unit_of(target)?)
+}
+
+/// `+1m-2d` → today + 1 month - 2 days
+fn parse_offsets(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let mut date = today;
+    let mut rest = s;
+
+    while !rest.is_empty() {
+        let sign = match rest.chars().next()? {
+            '+' => 1,
+            '-' => -1,
+            _ => return None,
+        };
+        rest = &rest[1..];
+        let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+        let amount: i64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit = rest.chars().next()?;
+        rest = &rest[unit.len_utf8()..];
+        date = shift(date, sign * amount, unit)?;
+    }
+
+    Some(date)
+}
+
+/// Shift a date by `amount` days (`d`), weeks (`w`), months (`m`) or years (`y`).
+/// Months are clamped to the last day (31/01 + 1m → 28/02). None if out of range.
+pub fn shift(date: NaiveDate, amount: i64, unit: char) -> Option<NaiveDate> {
+    match unit {
+        'd' => date.checked_add_signed(Duration::try_days(amount)?),
+        'w' => date.checked_add_signed(Duration::try_weeks(amount)?),
+        'm' | 'y' => {
+            let months = if unit == 'y' { amount.checked_mul(12)? } else { amount };
+            let delta = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+            if months >= 0 {
+                date.checked_add_months(delta)
+            } else {
+                date.checked_sub_months(delta)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Age like `30d`, `2w`, `6m`, `1y` → (amount, unit); None if it is not one
+pub fn parse_age(input: &str) -> Option<(i64, char)> {
+    let unit = input.chars().last().filter(|c| matches!(c, 'd' | 'w' | 'm' | 'y'))?;
+    let amount = input[..input.len() - unit.len_utf8()].parse::<i64>().ok()?;
+    (amount >= 0).then_some((amount, unit))
+}
+
+pub(crate) fn unit_of(word: &str) -> Option<char> {
+    match word {
+        "day" | "days" | "dia" | "dias" => Some('d'),
+        "week" | "weeks" | "semana" | "semanas" => Some('w'),
+        "month" | "months" | "mes" | "meses" => Some('m'),
+        "year" | "years" | "ano" | "anos" => Some('y'),
+        _ => None,
+    }
+}
+
+pub(crate) fn weekday_of(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" | "mon" | "lunes" | "lun" => Some(Weekday::Mon),
+        "tuesday" | "tue" | "martes" | "mar" => Some(Weekday::Tue),
+        "wednesday" | "wed" | "miercoles" | "mie" => Some(Weekday::Wed),
+        "thursday" | "thu" | "jueves" | "jue" => Some(Weekday::Thu),
+        "friday" | "fri" | "viernes" | "vie" => Some(Weekday::Fri),
+        "saturday" | "sat" | "sabado" | "sab" => Some(Weekday::Sat),
+        "sunday" | "sun" | "domingo" | "dom" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    #[test]
+    fn test_keywords_and_absolute_dates() {
+        // Viernes 16 de octubre de 2026
+        let today = d(2026, 10, 16);
+
+        assert_eq!(parse("hoy", today).unwrap(), today);
+        assert_eq!(parse("yesterday", today).unwrap(), d(2026, 10, 15));
+        assert_eq!(parse("Mañana", today).unwrap(), d(2026, 10, 17));
+        assert_eq!(parse("anteayer", today).unwrap(), d(2026, 10, 14));
+        assert_eq!(parse("pasado mañana", today).unwrap(), d(2026, 10, 18));
+        assert_eq!(parse("2026-03-01", today).unwrap(), d(2026, 3, 1));
+        assert_eq!(parse("01/03/2026", today).unwrap(), d(2026, 3, 1));
+        assert_eq!(parse("+1m-2d", today).unwrap(), d(2026, 11, 14));
+        assert!(parse("el otro día", today).is_err());
+        assert!(parse("2026-02-30", today).is_err());
+    }
+
+    #[test]
+    fn test_relative_weekdays_and_units() {
+        let today = d(2026, 10, 16);
+
+        assert_eq!(parse("monday", today).unwrap(), d(2026, 10, 12));
+        assert_eq!(parse("last friday", today).unwrap(), d(2026, 10, 9));
+        assert_eq!(parse("el viernes pasado", today).unwrap(), d(2026, 10, 9));
+        assert_eq!(parse("lunes pasado", today).unwrap(), d(2026, 10, 12));
+        assert_eq!(parse("next monday", today).unwrap(), d(2026, 10, 19));
+        assert_eq!(parse("miércoles que viene", today).unwrap(), d(2026, 10, 21));
+        assert_eq!(parse("semana pasada", today).unwrap(), d(2026, 10, 9));
+        assert_eq!(parse("next month", today).unwrap(), d(2026, 11, 16));
+        assert_eq!(parse("hace 3 días", today).unwrap(), d(2026, 10, 13));
+        assert_eq!(parse("2 weeks ago", today).unwrap(), d(2026, 10, 2));
+        assert_eq!(parse("en 1 año", today).unwrap(), d(2027, 10, 16));
+    }
+
+    #[test]
+    fn test_huge_offsets_are_out_of_range() {
+        let today = d(2026, 10, 16);
+
+        assert!(parse("+999999999999999d", today).is_err());
+        assert!(parse("in 99999999999999 weeks", today).is_err());
+        assert!(parse("hace -9223372036854775808 días", today).is_err());
+        assert!(parse("+999999999999999999y", today).is_err());
+        assert_eq!(shift(today, i64::MAX, 'w'), None);
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("30d"), Some((30, 'd')));
+        assert_eq!(parse_age("1y"), Some((1, 'y')));
+        assert_eq!(parse_age("-3d"), None);
+        assert_eq!(parse_age("3h"), None);
+        assert_eq!(parse_age("d"), None);
+    }
+}
@@ -0,0 +1,635 @@
+use serde_yaml::{Mapping, Value};
+use std::ops::Range;
+
+/// Note whose frontmatter is edited key by key, without re-serializing the mapping.
+/// Todo lo que no se toca se conserva byte a byte: comentarios, orden de claves,
+/// estilo de comillas y secuencias flow (`[a, b]`) o block (`- a`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document {
+    text: String,
+    /// Rango del YAML dentro de `text` (sin los delimitadores `---`)
+    yaml: Option<Range<usize>>,
+}
+
+impl Document {
+    /// Parse a note. Fails if the frontmatter is not valid YAML.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let doc = Self {
+            text: text.to_string(),
+            yaml: yaml_range(text),
+        };
+        doc.mapping()?;
+        Ok(doc)
+    }
+
+    /// Frontmatter as a mapping (empty if the note has none)
+    pub fn mapping(&self) -> anyhow::Result<Mapping> {
+        match serde_yaml::from_str::<Value>(self.yaml_text())? {
+            Value::Mapping(map) => Ok(map),
+            Value::Null => Ok(Mapping::new()),
+            _ => anyhow::bail!("El frontmatter no es un mapa YAML"),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.mapping().ok()?.get(key).cloned()
+    }
+
+    /// Body after the frontmatter (same as `frontmatter::extract`)
+    pub fn body(&self) -> &str {
+        match &self.yaml {
+            Some(range) => &self.text[range.end + 3..],
+            None => &self.text,
+        }
+    }
+
+    /// Replace the body. The line break after the closing `---` is kept whether or not
+    /// `body` starts with one (como el que devuelve `body()`).
+    pub fn set_body(&mut self, body: &str) {
+        let Some(range) = &self.yaml else {
+            self.text = body.to_string();
+            return;
+        };
+        let start = range.end + 3;
+        let eol = if self.text[start..].starts_with("\r\n") { "\r\n" } else { "\n" };
+        let body = body
+            .strip_prefix("\r\n")
+            .or_else(|| body.strip_prefix('\n'))
+            .unwrap_or(body);
+        self.text.replace_range(start.., &format!("{}{}", eol, body));
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_string(self) -> String {
+        self.text
+    }
+
+    /// Set a top-level key. Existing keys are rewritten in place keeping their style
+    /// (los ítems sin cambios de una secuencia conservan su línea original);
+    /// new keys are appended at the end of the frontmatter.
+    pub fn set(&mut self, key: &str, value: Value) -> anyhow::Result<()> {
+        let old = self.get(key);
+        if old.as_ref() == Some(&value) {
+            return Ok(());
+        }
+
+        let yaml = self.yaml_text().to_string();
+        let lines: Vec<&str> = yaml.split_inclusive('\n').collect();
+        let entries = entries(&lines);
+
+        let new_yaml = match entries.iter().find(|(line, _)| line.key == key) {
+            Some((key_line, range)) => {
+                let rewritten = rewrite_entry(
+                    key_line,
+                    &lines[range.clone()],
+                    &old.unwrap_or(Value::Null),
+                    &value,
+                    &block_prefix(&lines),
+                )?;
+                format!(
+                    "{}{}{}",
+                    lines[..range.start].concat(),
+                    rewritten,
+                    lines[range.end..].concat()
+                )
+            }
+            None => {
+                let eol = if yaml.contains("\r\n") { "\r\n" } else { "\n" };
+                format!(
+                    "{}{}",
+                    yaml,
+                    new_entry(key, &value, &block_prefix(&lines), eol)?
+                )
+            }
+        };
+
+        self.replace_yaml(&new_yaml)
+    }
+
+    /// Remove a top-level key. Returns false if it did not exist.
+    pub fn remove(&mut self, key: &str) -> anyhow::Result<bool> {
+        let yaml = self.yaml_text().to_string();
+        let lines: Vec<&str> = yaml.split_inclusive('\n').collect();
+        let Some((_, range)) = entries(&lines).into_iter().find(|(line, _)| line.key == key)
+        else {
+            return Ok(false);
+        };
+
+        let new_yaml = format!(
+            "{}{}",
+            lines[..range.start].concat(),
+            lines[range.end..].concat()
+        );
+        self.replace_yaml(&new_yaml)?;
+        Ok(true)
+    }
+
+    fn yaml_text(&self) -> &str {
+        match &self.yaml {
+            Some(range) => &self.text[range.clone()],
+            None => "",
+        }
+    }
+
+    fn replace_yaml(&mut self, yaml: &str) -> anyhow::Result<()> {
+        let mut updated = self.clone();
+        match &self.yaml {
+            Some(range) => {
+                updated.text.replace_range(range.clone(), yaml);
+                updated.yaml = Some(range.start..range.start + yaml.len());
+            }
+            None => {
+                updated.text = format!("---\n{}---\n{}", yaml, self.text);
+                updated.yaml = Some(4..4 + yaml.len());
+            }
+        }
+
+        updated
+            .mapping()
+            .map_err(|e| anyhow::anyhow!("Frontmatter inválido tras la edición: {}", e))?;
+        *self = updated;
+        Ok(())
+    }
+}
+
+/// `---\n<yaml>\n---` → range of `<yaml>` (incluye el salto de línea final)
+fn yaml_range(text: &str) -> Option<Range<usize>> {
+    let start = if text.starts_with("---\n") {
+        4
+    } else if text.starts_with("---\r\n") {
+        5
+    } else {
+        return None;
+    };
+    let end = text[start - 1..].find("\n---")? + start;
+    Some(start..end)
+}
+
+/// Top-level key line: `key: value  # comment`
+#[derive(Debug)]
+struct KeyLine {
+    key: String,
+    /// `key:` más los espacios que siguen
+    head: String,
+    /// Valor en línea, sin comentario
+    value: String,
+    /// Comentario con sus espacios previos
+    comment: String,
+    eol: String,
+}
+
+fn parse_key_line(line: &str) -> Option<KeyLine> {
+    let content = line.trim_end_matches(['\n', '\r']);
+    let eol = line[content.len()..].to_string();
+    let first = content.chars().next()?;
+    if first.is_whitespace() || matches!(first, '#' | '-' | '[' | '{') {
+        return None;
+    }
+
+    let (key, colon) = if first == '"' || first == '\'' {
+        let close = content[1..].find(first)? + 1;
+        if !content[close + 1..].starts_with(':') {
+            return None;
+        }
+        (content[1..close].to_string(), close + 1)
+    } else {
+        let colon = content
+            .find(": ")
+            .or_else(|| content.ends_with(':').then(|| content.len() - 1))?;
+        (content[..colon].trim_end().to_string(), colon)
+    };
+
+    let after = &content[colon + 1..];
+    let spaces = after.len() - after.trim_start().len();
+    let head = content[..colon + 1 + spaces].to_string();
+    let (value, comment) = split_comment(&content[head.len()..]);
+
+    Some(KeyLine {
+        key,
+        head,
+        value: value.to_string(),
+        comment: comment.to_string(),
+        eol,
+    })
+}
+
+/// `valor  # comentario` → ("valor", "  # comentario"), ignoring `#` inside quotes
+fn split_comment(s: &str) -> (&str, &str) {
+    let mut quote: Option<char> = None;
+    let mut prev = ' ';
+    for (idx, c) in s.char_indices() {
+        match quote {
+            Some(q) if c == q && !(q == '"' && prev == '\\') => quote = None,
+            Some(_) => {}
+            None if (c == '"' || c == '\'') && opens_scalar(prev) => quote = Some(c),
+            None if c == '#' && prev.is_whitespace() => {
+                let value = s[..idx].trim_end();
+                return (value, &s[value.len()..]);
+            }
+            None => {}
+        }
+        prev = c;
+    }
+    (s, "")
+}
+
+/// A quote only starts a quoted scalar at the beginning of a value (`it's` no abre comillas)
+fn opens_scalar(prev: char) -> bool {
+    prev.is_whitespace() || matches!(prev, '[' | '{' | ',')
+}
+
+/// Top-level entries: key line plus its continuation lines (indented or `- item`).
+/// Los comentarios y líneas en blanco entre claves no pertenecen a ninguna entrada.
+fn entries(lines: &[&str]) -> Vec<(KeyLine, Range<usize>)> {
+    let mut out = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let Some(key_line) = parse_key_line(lines[i]) else {
+            i += 1;
+            continue;
+        };
+
+        let mut end = i + 1;
+        let mut j = i + 1;
+        while j < lines.len() {
+            if is_continuation(lines[j]) {
+                j += 1;
+                end = j;
+            } else if lines[j].trim().is_empty() {
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        out.push((key_line, i..end));
+        i = end;
+    }
+
+    out
+}
+
+fn is_continuation(line: &str) -> bool {
+    let trimmed = line.trim_end();
+    (line.starts_with([' ', '\t']) && !trimmed.trim_start().is_empty())
+        || trimmed.starts_with("- ")
+        || trimmed == "-"
+}
+
+/// Block sequence item: `  - valor` → ("  - ", "valor")
+fn split_item(line: &str) -> Option<(&str, &str)> {
+    let content = line.trim_end_matches(['\n', '\r']);
+    let indent = content.len() - content.trim_start().len();
+    let rest = &content[indent..];
+    if rest == "-" {
+        return Some((content, ""));
+    }
+    let rest = rest.strip_prefix('-')?;
+    let spaces = rest.len() - rest.trim_start().len();
+    if spaces == 0 {
+        return None;
+    }
+    let prefix_len = indent + 1 + spaces;
+    Some((&content[..prefix_len], &content[prefix_len..]))
+}
+
+/// Indentation used by the block sequences of the document (default: `- `)
+fn block_prefix(lines: &[&str]) -> String {
+    lines
+        .iter()
+        .find_map(|line| split_item(line).map(|(prefix, _)| prefix.to_string()))
+        .unwrap_or_else(|| "- ".to_string())
+}
+
+fn rewrite_entry(
+    key_line: &KeyLine,
+    lines: &[&str],
+    old: &Value,
+    new: &Value,
+    default_prefix: &str,
+) -> anyhow::Result<String> {
+    let eol = key_line.eol.as_str();
+    let value = key_line.value.as_str();
+
+    if let Value::Sequence(items) = new {
+        let old_items = match old {
+            Value::Sequence(seq) => seq.as_slice(),
+            _ => &[],
+        };
+
+        // Flow: `tags: [a, "b"]`
+        if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            let raw = split_flow(inner);
+            let quote = raw.first().map(|r| quote_of(r)).unwrap_or(Quote::Plain);
+            let mut reuse = pair_items(old_items, &raw);
+            let formatted: Option<Vec<String>> = items
+                .iter()
+                .map(|item| take_reused(&mut reuse, item).or_else(|| format_scalar(item, quote, true)))
+                .collect();
+            if let Some(formatted) = formatted {
+                return Ok(format!(
+                    "{}[{}]{}{}",
+                    key_line.head,
+                    formatted.join(", "),
+                    key_line.comment,
+                    eol
+                ));
+            }
+        }
+
+        // Block: `tags:` + `- a` (o clave vacía)
+        if value.is_empty() && matches!(old, Value::Sequence(_) | Value::Null) {
+            if items.is_empty() {
+                return Ok(format!(
+                    "{} []{}{}",
+                    key_line.head.trim_end(),
+                    key_line.comment,
+                    eol
+                ));
+            }
+
+            let item_lines: Vec<&str> = lines[1..]
+                .iter()
+                .copied()
+                .filter(|l| split_item(l).is_some())
+                .collect();
+            let prefix = item_lines
+                .first()
+                .and_then(|l| split_item(l))
+                .map(|(p, _)| p.to_string())
+                .unwrap_or_else(|| default_prefix.to_string());
+            let quote = item_lines
+                .first()
+                .and_then(|l| split_item(l))
+                .map(|(_, v)| quote_of(v))
+                .unwrap_or(Quote::Plain);
+            let mut reuse = pair_items(old_items, &item_lines);
+
+            let mut out = lines[0].to_string();
+            if !out.ends_with('\n') {
+                out.push('\n');
+            }
+            for item in items {
+                match take_reused(&mut reuse, item) {
+                    Some(line) => {
+                        out.push_str(line.trim_end_matches(['\n', '\r']));
+                        out.push_str(line_ending(&line, eol));
+                    }
+                    None => match format_scalar(item, quote, false) {
+                        Some(formatted) => {
+                            out.push_str(&prefix);
+                            out.push_str(&formatted);
+                            out.push_str(line_ending("", eol));
+                        }
+                        None => return serialize_entry(&key_line.key, new),
+                    },
+                }
+            }
+            return Ok(out);
+        }
+
+        return serialize_entry(&key_line.key, new);
+    }
+
+    // Scalar in place, keeping quote style and comment
+    let is_scalar = |v: &Value| !matches!(v, Value::Sequence(_) | Value::Mapping(_));
+    if is_scalar(old) && is_scalar(new) && !value.is_empty() && !value.starts_with(['|', '>']) {
+        if let Some(formatted) = format_scalar(new, quote_of(value), false) {
+            return Ok(format!(
+                "{}{}{}{}",
+                key_line.head, formatted, key_line.comment, eol
+            ));
+        }
+    }
+
+    serialize_entry(&key_line.key, new)
+}
+
+fn new_entry(key: &str, value: &Value, prefix: &str, eol: &str) -> anyhow::Result<String> {
+    let key_text = format_string(key, Quote::Plain, false);
+    match value {
+        Value::Sequence(items) if items.is_empty() => Ok(format!("{}: []{}", key_text, eol)),
+        Value::Sequence(items) => {
+            let mut out = format!("{}:{}", key_text, eol);
+            for item in items {
+                let Some(formatted) = format_scalar(item, Quote::Plain, false) else {
+                    return serialize_entry(key, value);
+                };
+                out.push_str(&format!("{}{}{}", prefix, formatted, eol));
+            }
+            Ok(out)
+        }
+        _ => match format_scalar(value, Quote::Plain, false) {
+            Some(formatted) => Ok(format!("{}: {}{}", key_text, formatted, eol)),
+            None => serialize_entry(key, value),
+        },
+    }
+}
+
+/// Fallback for values without an in-place representation (mapas, cambios de tipo)
+fn serialize_entry(key: &str, value: &Value) -> anyhow::Result<String> {
+    let mut map = Mapping::new();
+    map.insert(Value::String(key.to_string()), value.clone());
+    Ok(serde_yaml::to_string(&map)?)
+}
+
+fn line_ending<'a>(line: &'a str, default: &'a str) -> &'a str {
+    if line.ends_with("\r\n") {
+        "\r\n"
+    } else if line.ends_with('\n') {
+        "\n"
+    } else {
+        default
+    }
+}
+
+/// Pair old sequence items with their raw text (solo si hay uno por línea/ítem)
+fn pair_items<'a>(old: &[Value], raw: &[&'a str]) -> Vec<(Value, &'a str)> {
+    if old.len() != raw.len() {
+        return Vec::new();
+    }
+    old.iter().cloned().zip(raw.iter().copied()).collect()
+}
+
+fn take_reused(reuse: &mut Vec<(Value, &str)>, item: &Value) -> Option<String> {
+    let idx = reuse.iter().position(|(value, _)| value == item)?;
+    Some(reuse.remove(idx).1.to_string())
+}
+
+/// `a, "b, c", 'd'` → ["a", "\"b, c\"", "'d'"]
+fn split_flow(inner: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut prev = ' ';
+
+    for (idx, c) in inner.char_indices() {
+        match quote {
+            Some(q) if c == q && !(q == '"' && prev == '\\') => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' if opens_scalar(prev) => quote = Some(c),
+                '[' | '{' => depth += 1,
+                ']' | '}' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    items.push(inner[start..idx].trim());
+                    start = idx + 1;
+                }
+                _ => {}
+            },
+        }
+        prev = c;
+    }
+
+    let last = inner[start..].trim();
+    if !last.is_empty() || !items.is_empty() {
+        items.push(last);
+    }
+    items
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quote {
+    Plain,
+    Single,
+    Double,
+}
+
+fn quote_of(raw: &str) -> Quote {
+    match raw.trim_start().chars().next() {
+        Some('"') => Quote::Double,
+        Some('\'') => Quote::Single,
+        _ => Quote::Plain,
+    }
+}
+
+fn format_scalar(value: &Value, quote: Quote, in_flow: bool) -> Option<String> {
+    match value {
+        Value::String(s) => Some(format_string(s, quote, in_flow)),
+        Value::Null | Value::Bool(_) | Value::Number(_) => {
+            Some(serde_yaml::to_string(value).ok()?.trim_end().to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Format a string in the requested style; plain strings that YAML would read
+/// differently (`2026-02-02`, `true`, `a: b`, `#x`) are double-quoted
+fn format_string(s: &str, quote: Quote, in_flow: bool) -> String {
+    match quote {
+        Quote::Double => double_quoted(s),
+        Quote::Single if !s.contains(['\n', '\r']) => format!("'{}'", s.replace('\'', "''")),
+        _ if is_plain_safe(s, in_flow) => s.to_string(),
+        _ => double_quoted(s),
+    }
+}
+
+fn is_plain_safe(s: &str, in_flow: bool) -> bool {
+    if s.is_empty() || s.trim() != s || s.contains(['\n', '\r']) {
+        return false;
+    }
+    let expected = Value::String(s.to_string());
+    if in_flow {
+        matches!(
+            serde_yaml::from_str::<Value>(&format!("[{}]", s)),
+            Ok(Value::Sequence(seq)) if seq == [expected]
+        )
+    } else {
+        matches!(
+            serde_yaml::from_str::<Value>(&format!("k: {}", s)),
+            Ok(Value::Mapping(map)) if map.len() == 1 && map.get("k") == Some(&expected)
+        )
+    }
+}
+
+fn double_quoted(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seq(items: &[&str]) -> Value {
+        Value::Sequence(items.iter().map(|s| Value::String(s.to_string())).collect())
+    }
+
+    #[test]
+    fn test_untouched_keys_are_preserved_byte_for_byte() {
+        let text = "---\n# Nota de proyecto\ntitle: \"Reunión: cliente\"   # título\ncreated: \"2026-02-02\"\ntags:\n  - proyecto/acme\n  - 'reunion'\naliases: [uno, \"dos\"]\n---\n\n# Cuerpo\n";
+        let mut doc = Document::parse(text).unwrap();
+
+        // Same value: no change at all
+        doc.set("created", Value::String("2026-02-02".into())).unwrap();
+        assert_eq!(doc.as_str(), text);
+
+        doc.set("tags", seq(&["proyecto/acme", "reunion", "2026"])).unwrap();
+        assert_eq!(
+            doc.as_str(),
+            "---\n# Nota de proyecto\ntitle: \"Reunión: cliente\"   # título\ncreated: \"2026-02-02\"\ntags:\n  - proyecto/acme\n  - 'reunion'\n  - \"2026\"\naliases: [uno, \"dos\"]\n---\n\n# Cuerpo\n"
+        );
+        assert_eq!(doc.body(), "\n\n# Cuerpo\n");
+    }
+
+    #[test]
+    fn test_flow_sequences_and_scalars_keep_their_style() {
+        let text = "---\naliases: [uno, \"dos\"]  # viejos\ntitle: 'Hola'\ndraft: true\nnote: it's  # c\n---\nbody";
+        let mut doc = Document::parse(text).unwrap();
+
+        doc.set("aliases", seq(&["dos", "tres: x"])).unwrap();
+        doc.set("title", Value::String("It's".into())).unwrap();
+        doc.set("draft", Value::Bool(false)).unwrap();
+        doc.set("note", Value::String("ok".into())).unwrap();
+        assert_eq!(
+            doc.as_str(),
+            "---\naliases: [\"dos\", \"tres: x\"]  # viejos\ntitle: 'It''s'\ndraft: false\nnote: ok  # c\n---\nbody"
+        );
+        assert_eq!(doc.get("title"), Some(Value::String("It's".into())));
+    }
+
+    #[test]
+    fn test_add_and_remove_keys() {
+        let mut doc = Document::parse("# Sin frontmatter\n").unwrap();
+        doc.set("tags", seq(&["a/b"])).unwrap();
+        assert_eq!(doc.as_str(), "---\ntags:\n- a/b\n---\n# Sin frontmatter\n");
+
+        doc.set("date", Value::String("2026-10-16".into())).unwrap();
+        assert_eq!(
+            doc.as_str(),
+            "---\ntags:\n- a/b\ndate: 2026-10-16\n---\n# Sin frontmatter\n"
+        );
+
+        assert!(doc.remove("tags").unwrap());
+        assert!(!doc.remove("tags").unwrap());
+        assert_eq!(doc.as_str(), "---\ndate: 2026-10-16\n---\n# Sin frontmatter\n");
+    }
+
+    #[test]
+    fn test_block_sequence_to_empty_and_body_edit() {
+        let mut doc = Document::parse("---\ntags:\n- a\n- b\nx: 1\n---\nuno\n").unwrap();
+        doc.set("tags", seq(&[])).unwrap();
+        doc.set_body("\ndos\n");
+        assert_eq!(doc.as_str(), "---\ntags: []\nx: 1\n---\ndos\n");
+        doc.set_body("{ #tag }\n\ntres\n");
+        assert_eq!(doc.as_str(), "---\ntags: []\nx: 1\n---\n{ #tag }\n\ntres\n");
+    }
+}
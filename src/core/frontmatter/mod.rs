@@ -0,0 +1,35 @@
+pub mod edit;
+
+pub use edit::Document;
+
+use crate::core::template::{engine, Context};
+use serde_yaml::{Mapping, Value};
+
+/// Extract front matter (---yaml---) returning (map, body_after)
+pub fn extract(text: &str) -> anyhow::Result<(Mapping, String)> {
+    if let Some(rest) = text.strip_prefix("---") {
+        if let Some(pos) = rest.find("\n---") {
+            let fm_raw = &rest[..pos + 1];
+            let body = &rest[pos + 4..];
+            let fm = serde_yaml::from_str::<Mapping>(fm_raw)?;
+            return Ok((fm, body.to_string()));
+        }
+    }
+    Ok((Mapping::new(), text.to_string()))
+}
+
+/// Merge two YAML mappings (dst overwritten by src)
+pub fn merge(mut base: Mapping, override_map: Mapping) -> Mapping {
+    for (k, v) in override_map.into_iter() {
+        base.insert(k, v);
+    }
+    base
+}
+
+/// Render every string of the mapping (keys, values and nested sequences/mappings)
+pub fn render(m: Mapping, ctx: &Context) -> anyhow::Result<Mapping> {
+    match engine::render_value(Value::Mapping(m), ctx)? {
+        Value::Mapping(rendered) => Ok(rendered),
+        _ => Ok(Mapping::new()),
+    }
+}
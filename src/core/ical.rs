@@ -0,0 +1,493 @@
+use crate::core::recurrence;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+
+/// Property of an iCalendar component (`DTSTART;TZID=Europe/Madrid:20261016T093000`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    /// Nombre en mayúsculas (`DTSTART`)
+    pub name: String,
+    /// Parámetros en orden; los valores entre comillas conservan las comillas
+    pub params: Vec<(String, String)>,
+    /// Valor crudo (los TEXT siguen escapados)
+    pub value: String,
+}
+
+/// Component (`VCALENDAR`, `VTODO`, `VEVENT`, `VTIMEZONE`...) with its properties and subcomponents
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Component {
+    pub name: String,
+    pub properties: Vec<Property>,
+    pub components: Vec<Component>,
+}
+
+/// Value of a `DATE` or `DATE-TIME` property
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateValue {
+    /// `VALUE=DATE`: todo el día
+    Date(NaiveDate),
+    /// Sin `Z` ni `TZID` (o con un `TZID` sin `VTIMEZONE`): hora local de quien lo lea
+    Floating(NaiveDateTime),
+    /// Con `Z`, o con `TZID` resuelto con el `VTIMEZONE` del calendario
+    Utc(DateTime<Utc>),
+}
+
+impl Property {
+    pub fn new(name: &str, value: impl Into<String>) -> Self {
+        Self {
+            name: name.to_ascii_uppercase(),
+            params: Vec::new(),
+            value: value.into(),
+        }
+    }
+
+    /// TEXT property, escaping `\`, `;`, `,` and newlines
+    pub fn text(name: &str, text: &str) -> Self {
+        Self::new(name, escape_text(text))
+    }
+
+    pub fn with_param(mut self, name: &str, value: &str) -> Self {
+        self.params.push((name.to_ascii_uppercase(), value.to_string()));
+        self
+    }
+
+    /// Parameter value without quotes (`TZID`, `VALUE`...)
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim_matches('"'))
+    }
+
+    /// Value of a TEXT property with escapes resolved
+    pub fn text_value(&self) -> String {
+        unescape_text(&self.value)
+    }
+
+    /// Content line without folding: `NAME;PARAM=V:value`
+    fn content_line(&self) -> String {
+        let mut line = self.name.clone();
+        for (key, value) in &self.params {
+            line.push(';');
+            line.push_str(key);
+            line.push('=');
+            line.push_str(value);
+        }
+        line.push(':');
+        line.push_str(&self.value);
+        line
+    }
+}
+
+impl Component {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_ascii_uppercase(),
+            ..Self::default()
+        }
+    }
+
+    pub fn property(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Unescaped value of a TEXT property
+    pub fn text(&self, name: &str) -> Option<String> {
+        self.property(name).map(Property::text_value)
+    }
+
+    /// Replace every `name` property with `property` (in place of the first one, or at the end)
+    pub fn set(&mut self, property: Property) {
+        let Some(first) = self.properties.iter().position(|p| p.name == property.name) else {
+            self.properties.push(property);
+            return;
+        };
+        let mut index = 0;
+        self.properties.retain(|p| {
+            let keep = index <= first || p.name != property.name;
+            index += 1;
+            keep
+        });
+        self.properties[first] = property;
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.properties.retain(|p| !p.name.eq_ignore_ascii_case(name));
+    }
+
+    /// Date or date-time of property `name`; `TZID` is resolved with the `VTIMEZONE`s of `calendar`
+    pub fn date(&self, name: &str, calendar: &Component) -> Option<DateValue> {
+        parse_date(self.property(name)?, calendar)
+    }
+
+    /// Serialize with CRLF line endings, folding lines longer than 75 octets
+    pub fn to_ics(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out);
+        out
+    }
+
+    fn write(&self, out: &mut String) {
+        fold(&format!("BEGIN:{}", self.name), out);
+        for property in &self.properties {
+            fold(&property.content_line(), out);
+        }
+        for component in &self.components {
+            component.write(out);
+        }
+        fold(&format!("END:{}", self.name), out);
+    }
+}
+
+impl DateValue {
+    pub fn date(&self) -> NaiveDate {
+        self.local().date()
+    }
+
+    /// Wall time in the local timezone (midnight for `DATE`)
+    pub fn local(&self) -> NaiveDateTime {
+        match self {
+            DateValue::Date(date) => date.and_hms_opt(0, 0, 0).unwrap_or_default(),
+            DateValue::Floating(datetime) => *datetime,
+            DateValue::Utc(datetime) => datetime.with_timezone(&Local).naive_local(),
+        }
+    }
+
+    pub fn has_time(&self) -> bool {
+        !matches!(self, DateValue::Date(_))
+    }
+
+    /// Property `name` with this value (`DUE;VALUE=DATE:20261020`, `DUE:20261020T093000Z`)
+    pub fn to_property(&self, name: &str) -> Property {
+        match self {
+            DateValue::Date(date) => {
+                Property::new(name, date.format("%Y%m%d").to_string()).with_param("VALUE", "DATE")
+            }
+            DateValue::Floating(datetime) => {
+                Property::new(name, datetime.format("%Y%m%dT%H%M%S").to_string())
+            }
+            DateValue::Utc(datetime) => {
+                Property::new(name, datetime.format("%Y%m%dT%H%M%SZ").to_string())
+            }
+        }
+    }
+}
+
+/// Parse an iCalendar stream into its top-level components (usually one `VCALENDAR`)
+pub fn parse(input: &str) -> anyhow::Result<Vec<Component>> {
+    let mut roots = Vec::new();
+    let mut stack: Vec<Component> = Vec::new();
+
+    for line in unfold(input) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        // Líneas sin `:` no son content lines: se ignoran
+        let Some(property) = parse_line(&line) else {
+            continue;
+        };
+        match property.name.as_str() {
+            "BEGIN" => stack.push(Component::new(property.value.trim())),
+            "END" => {
+                let name = property.value.trim().to_ascii_uppercase();
+                let Some(component) = stack.pop() else {
+                    anyhow::bail!("iCalendar inválido: END:{} sin BEGIN", name);
+                };
+                if component.name != name {
+                    anyhow::bail!(
+                        "iCalendar inválido: END:{} cierra BEGIN:{}",
+                        name,
+                        component.name
+                    );
+                }
+                match stack.last_mut() {
+                    Some(parent) => parent.components.push(component),
+                    None => roots.push(component),
+                }
+            }
+            _ => match stack.last_mut() {
+                Some(component) => component.properties.push(property),
+                None => anyhow::bail!("iCalendar inválido: {} fuera de un BEGIN/END", property.name),
+            },
+        }
+    }
+
+    if let Some(component) = stack.last() {
+        anyhow::bail!("iCalendar inválido: falta END:{}", component.name);
+    }
+    Ok(roots)
+}
+
+/// Serialize several top-level components (the contents of a `.ics` file)
+pub fn to_ics(components: &[Component]) -> String {
+    components.iter().map(Component::to_ics).collect()
+}
+
+/// Join folded lines: a line starting with a space or tab continues the previous one
+fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in input.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(previous)) => previous.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Fold at 75 octets without splitting UTF-8 characters
+fn fold(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// `NAME;PARAM=V;PARAM="a:b":value` (los `:` y `;` entre comillas no cortan)
+fn parse_line(line: &str) -> Option<Property> {
+    let name_end = line.find([';', ':'])?;
+    let mut property = Property::new(line[..name_end].trim(), "");
+    let mut rest = &line[name_end..];
+
+    while let Some(param) = rest.strip_prefix(';') {
+        let eq = param.find('=')?;
+        let value = &param[eq + 1..];
+        let mut quoted = false;
+        let mut end = value.len();
+        for (i, c) in value.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                ';' | ':' if !quoted => {
+                    end = i;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        property
+            .params
+            .push((param[..eq].to_ascii_uppercase(), value[..end].to_string()));
+        rest = &value[end..];
+    }
+
+    property.value = rest.strip_prefix(':')?.to_string();
+    Some(property)
+}
+
+pub fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+pub fn unescape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn parse_date(property: &Property, calendar: &Component) -> Option<DateValue> {
+    let value = property.value.trim();
+    if property.param("VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE")) || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d").ok().map(DateValue::Date);
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let datetime = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(DateValue::Utc(Utc.from_utc_datetime(&datetime)));
+    }
+    let datetime = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let offset = property
+        .param("TZID")
+        .and_then(|tzid| timezone_offset(calendar, tzid, datetime));
+    Some(match offset {
+        Some(offset) => DateValue::Utc(Utc.from_utc_datetime(&(datetime - offset))),
+        None => DateValue::Floating(datetime),
+    })
+}
+
+/// UTC offset of `tzid` at wall time `local`, from the `VTIMEZONE` of the calendar:
+/// rige la observancia (`STANDARD`/`DAYLIGHT`) con el inicio más reciente
+fn timezone_offset(calendar: &Component, tzid: &str, local: NaiveDateTime) -> Option<Duration> {
+    let timezone = calendar.components.iter().find(|c| {
+        c.name == "VTIMEZONE" && c.property("TZID").is_some_and(|p| p.value.trim() == tzid)
+    })?;
+
+    timezone
+        .components
+        .iter()
+        .filter_map(|observance| {
+            let start = observance.property("DTSTART")?.value.trim().to_string();
+            let start = NaiveDateTime::parse_from_str(&start, "%Y%m%dT%H%M%S").ok()?;
+            let offset = parse_offset(&observance.property("TZOFFSETTO")?.value)?;
+            let onset = last_onset(observance, start, local)?;
+            Some((onset, offset))
+        })
+        .max_by_key(|(onset, _)| *onset)
+        .map(|(_, offset)| offset)
+}
+
+/// Last start of an observance at or before `local`. Solo entiende las reglas anuales de los
+/// VTIMEZONE (`FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU`); otras reglas cuentan solo el DTSTART.
+fn last_onset(observance: &Component, start: NaiveDateTime, local: NaiveDateTime) -> Option<NaiveDateTime> {
+    if start > local {
+        return None;
+    }
+    let Some(rule) = observance.property("RRULE") else {
+        return Some(start);
+    };
+
+    let parts: Vec<(&str, &str)> = rule.value.split(';').filter_map(|p| p.split_once('=')).collect();
+    let part = |key: &str| parts.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| *v);
+    let (Some("YEARLY"), Some(month), Some(byday)) = (part("FREQ"), part("BYMONTH"), part("BYDAY"))
+    else {
+        return Some(start);
+    };
+    let month: u32 = month.parse().ok()?;
+    // `BYDAY` viene del servidor: puede no terminar en un código ASCII
+    let (nth, weekday) = byday.split_at_checked(byday.len().checked_sub(2)?)?;
+    let nth: i32 = if nth.is_empty() { 1 } else { nth.trim_start_matches('+').parse().ok()? };
+    let weekday = weekday_code(weekday)?;
+    let until = part("UNTIL").and_then(|u| {
+        NaiveDateTime::parse_from_str(u.trim_end_matches('Z'), "%Y%m%dT%H%M%S").ok()
+    });
+
+    [local.year(), local.year() - 1].into_iter().find_map(|year| {
+        let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let onset = recurrence::nth_weekday(first, nth, weekday)?.and_time(start.time());
+        (onset <= local && onset >= start && until.is_none_or(|u| onset <= u)).then_some(onset)
+    })
+}
+
+/// `+0100`, `-0500`, `+053000`
+fn parse_offset(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (sign, digits) = match value.split_at_checked(1)? {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return None,
+    };
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        digits.get(range).map_or(Some(0), |d| d.parse().ok())
+    };
+    if digits.len() != 4 && digits.len() != 6 {
+        return None;
+    }
+    let seconds = number(0..2)? * 3600 + number(2..4)? * 60 + number(4..6)?;
+    Some(Duration::seconds(sign * seconds))
+}
+
+fn weekday_code(code: &str) -> Option<Weekday> {
+    Some(match code.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unfolds_params_and_roundtrips() {
+        let input = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VTODO\r\nSUMMARY:Llamar al banco\\, y pedir\r\n  el extracto\r\nATTENDEE;CN=\"Pérez; Ana\";ROLE=REQ-PARTICIPANT:mailto:ana@example.com\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+        let calendars = parse(input).unwrap();
+        let todo = &calendars[0].components[0];
+        assert_eq!(todo.name, "VTODO");
+        assert_eq!(todo.text("SUMMARY").unwrap(), "Llamar al banco, y pedir el extracto");
+        let attendee = todo.property("ATTENDEE").unwrap();
+        assert_eq!(attendee.param("CN"), Some("Pérez; Ana"));
+        assert_eq!(attendee.param("role"), Some("REQ-PARTICIPANT"));
+        assert_eq!(attendee.value, "mailto:ana@example.com");
+
+        // Las líneas largas se pliegan a 75 octetos y vuelven a leerse igual
+        let mut todo = todo.clone();
+        todo.set(Property::text("DESCRIPTION", &"ñ".repeat(60)));
+        let ics = todo.to_ics();
+        assert!(ics.split("\r\n").all(|l| l.len() <= 75));
+        assert_eq!(parse(&ics).unwrap()[0], todo);
+
+        assert!(parse("BEGIN:VCALENDAR\nBEGIN:VTODO\nEND:VCALENDAR\n").is_err());
+    }
+
+    #[test]
+    fn test_dates_and_timezones() {
+        let input = "BEGIN:VCALENDAR
+BEGIN:VTIMEZONE
+TZID:Europe/Madrid
+BEGIN:DAYLIGHT
+DTSTART:19810329T020000
+TZOFFSETFROM:+0100
+TZOFFSETTO:+0200
+RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU
+END:DAYLIGHT
+BEGIN:STANDARD
+DTSTART:19961027T030000
+TZOFFSETFROM:+0200
+TZOFFSETTO:+0100
+RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU
+END:STANDARD
+END:VTIMEZONE
+BEGIN:VTODO
+DUE;TZID=Europe/Madrid:20261016T093000
+DTSTART;TZID=Europe/Madrid:20261201T093000
+COMPLETED:20261016T080000Z
+X-DAY;VALUE=DATE:20261020
+X-FLOATING:20261020T090000
+X-UNKNOWN;TZID=Mars/Olympus:20261020T090000
+END:VTODO
+END:VCALENDAR
+";
+        let calendar = &parse(input).unwrap()[0];
+        let todo = calendar.components.iter().find(|c| c.name == "VTODO").unwrap();
+        let utc = |s: &str| {
+            DateValue::Utc(Utc.from_utc_datetime(
+                &NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap(),
+            ))
+        };
+        // Horario de verano (+02:00) en octubre, de invierno (+01:00) en diciembre
+        assert_eq!(todo.date("DUE", calendar), Some(utc("2026-10-16 07:30")));
+        assert_eq!(todo.date("DTSTART", calendar), Some(utc("2026-12-01 08:30")));
+        assert_eq!(todo.date("COMPLETED", calendar), Some(utc("2026-10-16 08:00")));
+        assert_eq!(
+            todo.date("X-DAY", calendar),
+            Some(DateValue::Date(NaiveDate::from_ymd_opt(2026, 10, 20).unwrap()))
+        );
+        assert!(matches!(todo.date("X-FLOATING", calendar), Some(DateValue::Floating(_))));
+        assert!(matches!(todo.date("X-UNKNOWN", calendar), Some(DateValue::Floating(_))));
+    }
+
+    #[test]
+    fn test_malformed_byday_is_ignored() {
+        let input = "BEGIN:VCALENDAR\nBEGIN:DAYLIGHT\nDTSTART:19810329T020000\nRRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=éU\nEND:DAYLIGHT\nEND:VCALENDAR\n";
+        let observance = &parse(input).unwrap()[0].components[0];
+        let start = NaiveDateTime::parse_from_str("1981-03-29 02:00", "%Y-%m-%d %H:%M").unwrap();
+        let local = NaiveDateTime::parse_from_str("2026-10-16 09:30", "%Y-%m-%d %H:%M").unwrap();
+        assert_eq!(last_onset(observance, start, local), None);
+    }
+}
@@ -1,4 +1,10 @@
 pub mod config;
+pub mod date_expr;
 pub mod frontmatter;
+pub mod ical;
 pub mod note;
+pub mod period;
+pub mod recurrence;
+pub mod section;
+pub mod task;
 pub mod template;
@@ -1,10 +1,11 @@
 use crate::core::config::Config;
 use crate::core::frontmatter;
-use crate::core::template;
+use crate::core::template::{self, TemplateSelection};
 use crate::tags;
 use crate::ui::editor;
 use crate::ui::prompts;
 use crate::utils::file;
+use crate::vault::index::VaultIndex;
 use chrono::Local;
 use serde_yaml::Value;
 use slug::slugify;
@@ -20,6 +21,13 @@ pub struct NoteBuilder {
     target_dir: Option<PathBuf>,
     use_hierarchical_tags: bool,
     editor_override: Option<String>,
+    template: TemplateSelection,
+    /// Dir-tag dado con `--tag` (sin selector)
+    tag: Option<String>,
+    /// Aliases dados con `--alias` (sin prompt)
+    aliases: Vec<String>,
+    /// `--yes`: sin aliases y valores por defecto del template en vez de preguntar
+    assume_yes: bool,
 }
 
 impl NoteBuilder {
@@ -31,6 +39,10 @@ impl NoteBuilder {
             target_dir: None,
             use_hierarchical_tags: false,
             editor_override: None,
+            template: TemplateSelection::Default,
+            tag: None,
+            aliases: Vec::new(),
+            assume_yes: false,
         }
     }
 
@@ -54,6 +66,26 @@ impl NoteBuilder {
         self
     }
 
+    pub fn template(mut self, template: TemplateSelection) -> Self {
+        self.template = template;
+        self
+    }
+
+    pub fn tag(mut self, tag: Option<String>) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    pub fn aliases(mut self, aliases: Vec<String>) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    pub fn assume_yes(mut self, yes: bool) -> Self {
+        self.assume_yes = yes;
+        self
+    }
+
     pub fn create(self) -> anyhow::Result<()> {
         // Determine target directory
         let notas_dir = if let Some(ref dir) = self.target_dir {
@@ -146,6 +178,11 @@ impl NoteBuilder {
             let editor_mode = config.editor_mode.as_deref().unwrap_or("integrated");
 
             if editor_mode == "integrated" {
+                if !prompts::is_tty() {
+                    // Sin terminal (scripts, cron) no se abre el editor integrado
+                    println!("{}", target_file.display());
+                    return Ok(());
+                }
                 editor::open(target_file, vault)?;
             } else {
                 let editor = config.editor.as_deref().unwrap_or("vi");
@@ -159,57 +196,81 @@ impl NoteBuilder {
     }
 
     fn create_new_note(&self, target_file: &Path, notas_dir: &Path) -> anyhow::Result<()> {
-        // Try to load template from centralized Templates directory first
-        let centralized_template = self
-            .vault
-            .join(&self.config.templates_dir)
-            .join(format!("{}.md", &self.config.notes_dir));
-        let local_template = notas_dir.join("template.txt");
-
-        let template_path = if centralized_template.exists() {
-            centralized_template
-        } else {
-            local_template
+        if !self.write_new_note(target_file, notas_dir)? {
+            println!("\nCreación de nota cancelada.");
+            return Ok(());
+        }
+        self.open_editor_new_file(target_file)
+    }
+
+    /// Render the template into `target_file`. Returns Ok(false) if the user cancelled a prompt.
+    fn write_new_note(&self, target_file: &Path, notas_dir: &Path) -> anyhow::Result<bool> {
+        let templates_root = self.vault.join(&self.config.templates_dir);
+        let Some(template_path) = Self::resolve_template(
+            &templates_root,
+            &self.config.notes_dir,
+            notas_dir,
+            &self.template,
+        )?
+        else {
+            return Ok(false);
         };
 
-        let (frontmatter_map, body) = template::read(&template_path)?;
+        // Cascade: Templates/_base.md → notebook template → template.txt per directory
+        let (frontmatter_map, body) = template::load_cascade(
+            &templates_root,
+            &self.config.notes_dir,
+            &template_path,
+            &self.vault,
+            notas_dir,
+        )?
+        .unwrap_or_default();
 
         // Select tags - now returns slash-separated string (e.g., "padre/hijo/nieto")
         let selected_tag = if self.target_dir.is_some() {
             // Derive tag from directory path relative to vault
             self.derive_tag_from_dir(notas_dir)?
-        } else if self.use_hierarchical_tags {
-            match tags::selector::select_hierarchical(&self.vault) {
-                Ok(tag) => tag,
-                Err(_) => {
-                    println!("\nCreación de nota cancelada.");
-                    return Ok(());
-                }
-            }
+        } else if let Some(ref tag) = self.tag {
+            tags::TagPath::from_slash(tag).to_slash_string()
         } else {
-            match tags::selector::select_with_fuzzy(&self.vault) {
+            prompts::ensure_tty("usa --tag <tag> o un DIR")?;
+            let selected = if self.use_hierarchical_tags {
+                tags::selector::select_hierarchical(&self.vault)
+            } else {
+                tags::selector::select_with_fuzzy(&self.vault)
+            };
+            match selected {
                 Ok(tag) => tag,
                 Err(_) => {
-                    println!("\nCreación de nota cancelada.");
-                    return Ok(());
+                    return Ok(false);
                 }
             }
         };
 
         // Select aliases
-        let selected_aliases = match prompts::select_aliases()? {
-            Some(aliases) => aliases,
-            None => {
-                println!("\nCreación de nota cancelada.");
-                return Ok(());
+        let selected_aliases = if !self.aliases.is_empty() || self.assume_yes {
+            self.aliases.clone()
+        } else {
+            match prompts::select_aliases()? {
+                Some(aliases) => aliases,
+                None => {
+                    return Ok(false);
+                }
             }
         };
 
+        // Values declared by the template ({{prompt:x}}, {{choice:x|a,b}})
+        let Some(answers) = Self::ask_template_inputs(&frontmatter_map, &body, self.assume_yes)? else {
+            return Ok(false);
+        };
+
         // Build variables
-        let vars = self.build_variables()?;
+        let mut vars = self.build_variables()?;
+        vars.extend(answers);
 
         // Render and build frontmatter
-        let mut rendered_map = frontmatter::render(frontmatter_map, &vars);
+        let ctx = template::Context::new(&vars).date_format(&self.config.date);
+        let mut rendered_map = frontmatter::render(frontmatter_map, &ctx)?;
 
         // NOTE: Primary tag is NOT added to frontmatter.tags anymore
         // It will be in the body as first line: { #tag/path }
@@ -225,8 +286,9 @@ impl NoteBuilder {
             rendered_map.insert(Value::String("aliases".to_string()), aliases_value);
         }
 
-        // Render body
-        let rendered_body = template::render_body(&body, &vars);
+        // Render body (frontmatter fields are available as variables)
+        let ctx = ctx.with_fields(&rendered_map);
+        let rendered_body = template::render_body(&body, &ctx)?;
 
         // Prepend primary tag to body as first line: { #tag/path }
         // Format: { #tag }\n\n{content}
@@ -238,9 +300,61 @@ impl NoteBuilder {
 
         // Write file
         file::write_note(target_file, &rendered_map, &body_with_tag)?;
+        Ok(true)
+    }
 
-        // Open editor
-        self.open_editor_new_file(target_file)
+    /// Template file for a new note of `notebook`.
+    /// Returns Ok(None) if the user cancelled the template picker.
+    pub fn resolve_template(
+        templates_root: &Path,
+        notebook: &str,
+        notebook_dir: &Path,
+        selection: &TemplateSelection,
+    ) -> anyhow::Result<Option<PathBuf>> {
+        let default = template::default_path(templates_root, notebook, notebook_dir);
+
+        match selection {
+            TemplateSelection::Default => Ok(Some(default)),
+            TemplateSelection::Named(name) => {
+                Ok(Some(template::find_named(templates_root, notebook, name)?))
+            }
+            TemplateSelection::Pick => {
+                let named = template::named_templates(templates_root, notebook);
+                if named.is_empty() {
+                    println!(
+                        "ℹ️  No hay templates con nombre para {}, se usa el template por defecto",
+                        notebook
+                    );
+                    return Ok(Some(default));
+                }
+
+                let mut names = vec!["(por defecto)".to_string()];
+                names.extend(named.iter().map(|t| t.name.clone()));
+
+                Ok(prompts::select_template(&names)?.map(|idx| match idx {
+                    0 => default,
+                    i => named[i - 1].path.clone(),
+                }))
+            }
+        }
+    }
+
+    /// Ask the values declared by the template (`assume_yes`: take the defaults).
+    /// Returns Ok(None) if the user cancelled.
+    pub fn ask_template_inputs(
+        frontmatter_map: &serde_yaml::Mapping,
+        body: &str,
+        assume_yes: bool,
+    ) -> anyhow::Result<Option<BTreeMap<String, String>>> {
+        let inputs = template::inputs(frontmatter_map, body);
+        if inputs.is_empty() {
+            return Ok(Some(BTreeMap::new()));
+        }
+        if assume_yes {
+            return Ok(Some(prompts::default_template_inputs(&inputs)));
+        }
+        println!();
+        prompts::ask_template_inputs(&inputs)
     }
 
     fn build_variables(&self) -> anyhow::Result<BTreeMap<String, String>> {
@@ -290,7 +404,11 @@ impl NoteBuilder {
         } else {
             let editor_mode = self.config.editor_mode.as_deref().unwrap_or("integrated");
 
-            if editor_mode == "integrated" {
+            if editor_mode == "integrated" && !prompts::is_tty() {
+                // Sin terminal (scripts, cron) la nota queda creada sin abrir el editor
+                println!("Creado: {}", target_file.display());
+                self.update_tag_cache()?;
+            } else if editor_mode == "integrated" {
                 let saved = editor::open(target_file, &self.vault)?;
                 if saved {
                     println!("Creado: {}", target_file.display());
@@ -336,9 +454,50 @@ impl NoteBuilder {
         Ok(tag)
     }
 
+    /// Refresh the vault index so the new note shows up in tags, tasks and links
     fn update_tag_cache(&self) -> anyhow::Result<()> {
-        // Skip cache update - causes issues when vault path has special chars
-        // TODO: Fix cache update for paths with spaces
+        let templates_path = self.vault.join(&self.config.templates_dir);
+        VaultIndex::open(&self.vault, &templates_path)?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_flags_answer_every_prompt() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path().to_path_buf();
+        let config = Config {
+            vault: vault.to_str().unwrap().to_string(),
+            ..Default::default()
+        };
+        let templates = vault.join(&config.templates_dir);
+        std::fs::create_dir_all(&templates).unwrap();
+        std::fs::write(
+            templates.join(format!("{}.md", config.notes_dir)),
+            "# {{title}}\n\nCliente: {{prompt:cliente | default:\"Acme\"}}\n",
+        )
+        .unwrap();
+        let notes = vault.join(&config.notes_dir);
+        std::fs::create_dir_all(&notes).unwrap();
+        let note = notes.join("mi-nota.md");
+
+        // --tag, --alias y --yes: ni selector de tags, ni aliases, ni valores del template.
+        // Sin abrir el editor ni refrescar el índice (que vive fuera del vault)
+        let builder = NoteBuilder::new(vault.clone(), config)
+            .title(Some("Mi nota".to_string()))
+            .tag(Some("#proj/acme".to_string()))
+            .aliases(vec!["MN".to_string()])
+            .assume_yes(true);
+        assert_eq!(builder.build_target_path(&notes).unwrap(), note);
+        assert!(builder.write_new_note(&note, &notes).unwrap());
+
+        let content = std::fs::read_to_string(&note).unwrap();
+        assert!(content.contains("aliases:\n- MN\n"));
+        assert!(content.contains("{ #proj/acme }\n\n# mi-nota\n\nCliente: Acme\n"));
+    }
+}
@@ -0,0 +1,147 @@
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use std::fmt::Write;
+
+/// Período de una nota periódica
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Period {
+    /// Nombre usado en variables de template (`week_start`, `prev_month_link`...)
+    pub fn key(self) -> &'static str {
+        match self {
+            Period::Day => "day",
+            Period::Week => "week",
+            Period::Month => "month",
+            Period::Quarter => "quarter",
+            Period::Year => "year",
+        }
+    }
+
+    /// Nombre para mensajes ("nota semanal")
+    pub fn label(self) -> &'static str {
+        match self {
+            Period::Day => "daily note",
+            Period::Week => "nota semanal",
+            Period::Month => "nota mensual",
+            Period::Quarter => "nota trimestral",
+            Period::Year => "nota anual",
+        }
+    }
+
+    /// First day of the period containing `date` (weeks start on Monday, ISO 8601)
+    pub fn start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Period::Month => date.with_day(1).unwrap_or(date),
+            Period::Quarter => {
+                let month = (date.month0() / 3) * 3 + 1;
+                NaiveDate::from_ymd_opt(date.year(), month, 1).unwrap_or(date)
+            }
+            Period::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap_or(date),
+        }
+    }
+
+    /// Last day of the period containing `date`
+    pub fn end(self, date: NaiveDate) -> NaiveDate {
+        self.next(date) - Duration::days(1)
+    }
+
+    /// First day of the next period
+    pub fn next(self, date: NaiveDate) -> NaiveDate {
+        let start = self.start(date);
+        match self {
+            Period::Day => start + Duration::days(1),
+            Period::Week => start + Duration::weeks(1),
+            Period::Month => start + Months::new(1),
+            Period::Quarter => start + Months::new(3),
+            Period::Year => start + Months::new(12),
+        }
+    }
+
+    /// First day of the previous period
+    pub fn prev(self, date: NaiveDate) -> NaiveDate {
+        self.start(self.start(date) - Duration::days(1))
+    }
+
+    /// Días del período, en orden
+    pub fn days(self, date: NaiveDate) -> Vec<NaiveDate> {
+        let end = self.end(date);
+        self.start(date)
+            .iter_days()
+            .take_while(|d| *d <= end)
+            .collect()
+    }
+}
+
+/// Format a date with a strftime pattern plus `%q` (trimestre 1-4).
+/// Invalid patterns return an error instead of panicking.
+pub fn format_date(date: NaiveDate, fmt: &str) -> anyhow::Result<String> {
+    let quarter = (date.month0() / 3 + 1).to_string();
+    let mut pattern = String::with_capacity(fmt.len());
+    let mut chars = fmt.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('%', Some('q')) => {
+                chars.next();
+                pattern.push_str(&quarter);
+            }
+            ('%', Some('%')) => {
+                chars.next();
+                pattern.push_str("%%");
+            }
+            _ => pattern.push(c),
+        }
+    }
+
+    let mut out = String::new();
+    write!(out, "{}", date.format(&pattern))
+        .map_err(|_| anyhow::anyhow!("Formato de fecha inválido: '{}'", fmt))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    #[test]
+    fn test_period_bounds() {
+        // Jueves 16 de octubre de 2026
+        let date = d(2026, 10, 16);
+
+        assert_eq!(Period::Week.start(date), d(2026, 10, 12));
+        assert_eq!(Period::Week.end(date), d(2026, 10, 18));
+        assert_eq!(Period::Week.prev(date), d(2026, 10, 5));
+        assert_eq!(Period::Week.next(date), d(2026, 10, 19));
+        assert_eq!(Period::Week.days(date).len(), 7);
+
+        assert_eq!(Period::Month.end(d(2026, 2, 10)), d(2026, 2, 28));
+        assert_eq!(Period::Month.prev(d(2026, 1, 10)), d(2025, 12, 1));
+
+        assert_eq!(Period::Quarter.start(date), d(2026, 10, 1));
+        assert_eq!(Period::Quarter.end(date), d(2026, 12, 31));
+        assert_eq!(Period::Quarter.next(date), d(2027, 1, 1));
+        assert_eq!(Period::Quarter.prev(date), d(2026, 7, 1));
+
+        assert_eq!(Period::Year.days(date).len(), 365);
+    }
+
+    #[test]
+    fn test_format_date() {
+        let date = d(2026, 10, 16);
+        assert_eq!(format_date(date, "%G-W%V").unwrap(), "2026-W42");
+        assert_eq!(format_date(date, "%Y-Q%q").unwrap(), "2026-Q4");
+        assert_eq!(format_date(date, "100%% %Y").unwrap(), "100% 2026");
+        assert!(format_date(date, "%Y-%!").is_err());
+    }
+}
@@ -0,0 +1,194 @@
+use crate::core::date_expr;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Recurrence rule of a task (`🔁 every week`), Obsidian Tasks syntax:
+/// - `every day`, `every 3 days`, `every 2 weeks`, `every month`, `every year`
+/// - `every monday`, `every monday, thursday`, `every week on friday`
+/// - `every month on the 1st`, `every month on the last`
+/// - `every 2nd monday`, `every month on the last friday`: n-ésimo día de semana del mes
+/// - sufijo `when done`: la próxima se calcula desde el día en que se completó
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub rule: Rule,
+    pub when_done: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// Cada `n` unidades (`d`, `w`, `m`, `y`)
+    Interval { n: i64, unit: char },
+    /// Próximo de estos días de la semana
+    Weekdays(Vec<Weekday>),
+    /// Día del mes (-1: último)
+    MonthDay(i32),
+    /// n-ésimo día de semana del mes (-1: último)
+    MonthWeekday { nth: i32, weekday: Weekday },
+}
+
+impl Recurrence {
+    pub fn parse(input: &str) -> Option<Self> {
+        let normalized: String = input
+            .trim()
+            .to_lowercase()
+            .replace(',', " ")
+            .chars()
+            .map(crate::search::index::fold_accent)
+            .collect();
+        let mut words: Vec<&str> = normalized.split_whitespace().collect();
+
+        let when_done = words.ends_with(&["when", "done"]);
+        if when_done {
+            words.truncate(words.len() - 2);
+        }
+        let words = match words.split_first() {
+            Some((&("every" | "cada"), rest)) => rest,
+            _ => return None,
+        };
+
+        Some(Self {
+            rule: parse_rule(words)?,
+            when_done,
+        })
+    }
+
+    /// First occurrence strictly after `from`
+    pub fn next(&self, from: NaiveDate) -> Option<NaiveDate> {
+        match &self.rule {
+            Rule::Interval { n, unit } => date_expr::shift(from, *n, *unit),
+            Rule::Weekdays(days) => (1..=7)
+                .map(|i| from + Duration::days(i))
+                .find(|d| days.contains(&d.weekday())),
+            Rule::MonthDay(day) => (0..=12).find_map(|i| {
+                let first = first_of_month(from, i)?;
+                let date = if *day < 0 {
+                    last_of_month(first)
+                } else {
+                    // 31 en un mes de 30 días → último día
+                    first.with_day(*day as u32).unwrap_or_else(|| last_of_month(first))
+                };
+                (date > from).then_some(date)
+            }),
+            Rule::MonthWeekday { nth, weekday } => (0..=12).find_map(|i| {
+                let first = first_of_month(from, i)?;
+                let date = nth_weekday(first, *nth, *weekday)?;
+                (date > from).then_some(date)
+            }),
+        }
+    }
+}
+
+fn parse_rule(words: &[&str]) -> Option<Rule> {
+    match words {
+        // every week on friday
+        ["week" | "semana", "on" | "el" | "los", days @ ..] => weekdays(days),
+        // every month on the 1st / on the last friday
+        ["month" | "mes", "on" | "el", rest @ ..] => {
+            let rest = match rest {
+                ["the" | "el", rest @ ..] => rest,
+                rest => rest,
+            };
+            match rest {
+                [nth] => Some(Rule::MonthDay(ordinal(nth)?)),
+                [nth, day] => Some(Rule::MonthWeekday {
+                    nth: ordinal(nth)?,
+                    weekday: date_expr::weekday_of(day)?,
+                }),
+                _ => None,
+            }
+        }
+        // every 2nd monday
+        [nth, day] if ordinal(nth).is_some() && date_expr::weekday_of(day).is_some() => {
+            Some(Rule::MonthWeekday {
+                nth: ordinal(nth)?,
+                weekday: date_expr::weekday_of(day)?,
+            })
+        }
+        [unit] if date_expr::unit_of(unit).is_some() => Some(Rule::Interval {
+            n: 1,
+            unit: date_expr::unit_of(unit)?,
+        }),
+        [n, unit] if n.parse::<i64>().is_ok_and(|n| n > 0) => Some(Rule::Interval {
+            n: n.parse().ok()?,
+            unit: date_expr::unit_of(unit)?,
+        }),
+        days => weekdays(days),
+    }
+}
+
+fn weekdays(words: &[&str]) -> Option<Rule> {
+    let days: Option<Vec<Weekday>> = words
+        .iter()
+        .filter(|w| !matches!(**w, "and" | "y"))
+        .map(|w| date_expr::weekday_of(w))
+        .collect();
+    let days = days?;
+    (!days.is_empty()).then_some(Rule::Weekdays(days))
+}
+
+/// `1st`, `2nd`, `3rd`, `15th`, `1`, `last`/`ultimo` → -1
+fn ordinal(word: &str) -> Option<i32> {
+    if matches!(word, "last" | "ultimo" | "ultima") {
+        return Some(-1);
+    }
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == 'º');
+    let n: i32 = digits.parse().ok()?;
+    (1..=31).contains(&n).then_some(n)
+}
+
+fn first_of_month(date: NaiveDate, months_ahead: u32) -> Option<NaiveDate> {
+    date.with_day(1)?
+        .checked_add_months(chrono::Months::new(months_ahead))
+}
+
+fn last_of_month(first: NaiveDate) -> NaiveDate {
+    first
+        .checked_add_months(chrono::Months::new(1))
+        .and_then(|d| d.pred_opt())
+        .unwrap_or(first)
+}
+
+pub(crate) fn nth_weekday(first: NaiveDate, nth: i32, weekday: Weekday) -> Option<NaiveDate> {
+    if nth < 0 {
+        let last = last_of_month(first);
+        let back = (last.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+        return Some(last - Duration::days(back as i64));
+    }
+    let ahead = (weekday.num_days_from_monday() + 7 - first.weekday().num_days_from_monday()) % 7;
+    let date = first + Duration::days(ahead as i64 + 7 * (nth as i64 - 1));
+    (date.month() == first.month()).then_some(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    fn next(rule: &str, from: NaiveDate) -> NaiveDate {
+        Recurrence::parse(rule).unwrap().next(from).unwrap()
+    }
+
+    #[test]
+    fn test_intervals_and_weekdays() {
+        let friday = d(2026, 10, 16);
+        assert_eq!(next("every day", friday), d(2026, 10, 17));
+        assert_eq!(next("every 2 weeks", friday), d(2026, 10, 30));
+        assert_eq!(next("every month", d(2026, 1, 31)), d(2026, 2, 28));
+        assert_eq!(next("every monday, thursday", friday), d(2026, 10, 19));
+        assert_eq!(next("every week on friday", friday), d(2026, 10, 23));
+        assert!(Recurrence::parse("every week when done").unwrap().when_done);
+        assert!(Recurrence::parse("whenever").is_none());
+    }
+
+    #[test]
+    fn test_days_of_month() {
+        let from = d(2026, 10, 16);
+        assert_eq!(next("every month on the 1st", from), d(2026, 11, 1));
+        assert_eq!(next("every month on the last", from), d(2026, 10, 31));
+        assert_eq!(next("every 2nd monday", from), d(2026, 11, 9));
+        assert_eq!(next("every month on the last friday", from), d(2026, 10, 30));
+        assert_eq!(next("every month on the 31st", d(2026, 10, 31)), d(2026, 11, 30));
+    }
+}
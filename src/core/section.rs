@@ -0,0 +1,138 @@
+/// Heading as written in the note: `Log` → `## Log`, `### Log` se deja igual
+pub fn heading_line(heading: &str) -> String {
+    let heading = heading.trim();
+    if heading.starts_with('#') {
+        heading.to_string()
+    } else {
+        format!("## {}", heading)
+    }
+}
+
+/// Append `block` at the end of the section under `heading` (before the next heading of the
+/// same or higher level), creating the heading at the end of the note if missing.
+/// Without heading the block goes at the end of the note.
+pub fn append_to_section(content: &str, heading: Option<&str>, block: &str) -> String {
+    let block = block.trim_end_matches('\n');
+    let Some(heading) = heading.map(heading_line) else {
+        return append_at_end(content, block);
+    };
+
+    let lines: Vec<&str> = content.lines().collect();
+    let Some(start) = find_heading(&lines, &heading) else {
+        return append_at_end(content, &format!("{}\n{}", heading, block));
+    };
+
+    let level = heading_level(&heading).unwrap_or(2);
+    let mut end = lines.len();
+    let mut in_fence = false;
+    for (i, line) in lines.iter().enumerate().skip(start + 1) {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        } else if !in_fence && heading_level(line).is_some_and(|l| l <= level) {
+            end = i;
+            break;
+        }
+    }
+    // Después de la última línea con contenido de la sección (las líneas vacías quedan debajo)
+    while end > start + 1 && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+
+    let mut out: Vec<&str> = lines[..end].to_vec();
+    out.extend(block.lines());
+    out.extend(&lines[end..]);
+    let mut out = out.join("\n");
+    out.push('\n');
+    out
+}
+
+fn append_at_end(content: &str, block: &str) -> String {
+    let content = content.trim_end();
+    let continues_list = content.lines().last().is_some_and(is_list_item)
+        && block.lines().next().is_some_and(is_list_item);
+    if content.is_empty() {
+        format!("{}\n", block)
+    } else if continues_list {
+        // Sin línea vacía: la lista sigue siendo una sola (no "loose")
+        format!("{}\n{}\n", content, block)
+    } else {
+        format!("{}\n\n{}\n", content, block)
+    }
+}
+
+/// `- x`, `* x`, `+ x`, `1. x`, `1) x` (también tareas e items anidados)
+fn is_list_item(line: &str) -> bool {
+    let line = line.trim_start();
+    if line.starts_with("- ") || line.starts_with("* ") || line.starts_with("+ ") {
+        return true;
+    }
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    digits > 0 && (line[digits..].starts_with(". ") || line[digits..].starts_with(") "))
+}
+
+fn find_heading(lines: &[&str], heading: &str) -> Option<usize> {
+    let mut in_fence = false;
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        } else if !in_fence && line.trim_end() == heading {
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// Level of an ATX heading (`## x` → 2)
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let rest = &line[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    Some(level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_appends_at_end_of_existing_section() {
+        let content = "# Día\n\n## Log\n- 09:00 uno\n\n## Notas\ntexto\n";
+        assert_eq!(
+            append_to_section(content, Some("Log"), "- 10:00 dos"),
+            "# Día\n\n## Log\n- 09:00 uno\n- 10:00 dos\n\n## Notas\ntexto\n"
+        );
+        // Un subheading no cierra la sección
+        let content = "## Log\n### Mañana\n- a\n## Fin\n";
+        assert_eq!(
+            append_to_section(content, Some("## Log"), "- b"),
+            "## Log\n### Mañana\n- a\n- b\n## Fin\n"
+        );
+    }
+
+    #[test]
+    fn test_creates_missing_heading_or_appends_at_end() {
+        let content = "# Día\n\ntexto\n\n";
+        assert_eq!(
+            append_to_section(content, Some("Log"), "- uno\n"),
+            "# Día\n\ntexto\n\n## Log\n- uno\n"
+        );
+        assert_eq!(append_to_section(content, None, "- uno"), "# Día\n\ntexto\n\n- uno\n");
+        // Un heading dentro de un bloque de código no cuenta
+        assert_eq!(
+            append_to_section("```\n## Log\n```\n", Some("Log"), "- x"),
+            "```\n## Log\n```\n\n## Log\n- x\n"
+        );
+    }
+
+    #[test]
+    fn test_captures_in_a_row_stay_one_list() {
+        let note = append_to_section("# Inbox\n", None, "- 00:31 primera idea");
+        let note = append_to_section(&note, None, "- 00:31 desde pipe");
+        assert_eq!(note, "# Inbox\n\n- 00:31 primera idea\n- 00:31 desde pipe\n");
+        // Después de un párrafo sigue haciendo falta la línea vacía
+        assert_eq!(append_to_section("texto\n", None, "- [ ] tarea"), "texto\n\n- [ ] tarea\n");
+        assert_eq!(append_to_section("1. uno\n", None, "texto"), "1. uno\n\ntexto\n");
+    }
+}
@@ -0,0 +1,497 @@
+use crate::core::recurrence::Recurrence;
+use crate::vault::scan;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::ops::Range;
+
+/// Priority of a task. Como en Obsidian Tasks, sin prioridad va entre medium y low.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Lowest,
+    Low,
+    #[default]
+    Normal,
+    Medium,
+    High,
+    Highest,
+}
+
+impl Priority {
+    /// `high`, `alta`... (también en `!high` dentro de la tarea)
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.to_lowercase().as_str() {
+            "highest" | "urgente" => Some(Priority::Highest),
+            "high" | "alta" => Some(Priority::High),
+            "medium" | "media" => Some(Priority::Medium),
+            "normal" | "none" => Some(Priority::Normal),
+            "low" | "baja" => Some(Priority::Low),
+            "lowest" | "minima" | "mínima" => Some(Priority::Lowest),
+            _ => None,
+        }
+    }
+
+    fn from_emoji(token: &str) -> Option<Self> {
+        match token {
+            "🔺" => Some(Priority::Highest),
+            "⏫" => Some(Priority::High),
+            "🔼" => Some(Priority::Medium),
+            "🔽" => Some(Priority::Low),
+            "⏬" => Some(Priority::Lowest),
+            _ => None,
+        }
+    }
+
+    /// Obsidian Tasks emoji (`⏫`), None for `Normal`
+    pub fn emoji(self) -> Option<&'static str> {
+        match self {
+            Priority::Highest => Some("🔺"),
+            Priority::High => Some("⏫"),
+            Priority::Medium => Some("🔼"),
+            Priority::Normal => None,
+            Priority::Low => Some("🔽"),
+            Priority::Lowest => Some("⏬"),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Priority::Highest => "highest",
+            Priority::High => "high",
+            Priority::Medium => "medium",
+            Priority::Normal => "normal",
+            Priority::Low => "low",
+            Priority::Lowest => "lowest",
+        }
+    }
+
+    /// iCalendar `PRIORITY`: 1-4 alta, 5 media, 6-9 baja, 0 sin prioridad
+    pub fn from_ical(value: u8) -> Self {
+        match value {
+            1 => Priority::Highest,
+            2..=4 => Priority::High,
+            5 => Priority::Medium,
+            6..=8 => Priority::Low,
+            9 => Priority::Lowest,
+            _ => Priority::Normal,
+        }
+    }
+
+    pub fn to_ical(self) -> Option<u8> {
+        match self {
+            Priority::Highest => Some(1),
+            Priority::High => Some(3),
+            Priority::Medium => Some(5),
+            Priority::Normal => None,
+            Priority::Low => Some(7),
+            Priority::Lowest => Some(9),
+        }
+    }
+}
+
+/// Metadata of a task line: Obsidian Tasks emojis (`📅 2026-10-20`, `⏳`, `🛫`, `⏫`...)
+/// o su equivalente en texto (`due:2026-10-20`, `scheduled:`, `start:`, `!high`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskMeta {
+    /// Texto sin los metadatos (los `#tags` se conservan)
+    pub title: String,
+    pub due: Option<NaiveDate>,
+    pub scheduled: Option<NaiveDate>,
+    pub start: Option<NaiveDate>,
+    pub priority: Priority,
+    /// `#tags` de la línea, sin `#`
+    pub tags: Vec<String>,
+    /// Regla de `🔁 every week` (ver `core::recurrence`)
+    pub recurrence: Option<String>,
+    /// ID oculto de una tarea sincronizada (`<!-- mad-id:UID -->`)
+    pub id: Option<String>,
+}
+
+/// List item of any CommonMark form: `- x`, `* x`, `+ x`, `1. x`, `1) x`,
+/// indentado o dentro de citas y callouts (`> - x`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListItem<'a> {
+    /// Niveles de cita (`>`)
+    pub quote: usize,
+    /// Columna del marcador dentro de la cita (tab = 4)
+    pub indent: usize,
+    /// Todo lo anterior al contenido: `  > 1. `
+    pub prefix: &'a str,
+    pub content: &'a str,
+}
+
+impl<'a> ListItem<'a> {
+    pub fn parse(line: &'a str) -> Option<Self> {
+        let mut rest = line;
+        let mut quote = 0;
+        while let Some(after) = rest.trim_start_matches([' ', '\t']).strip_prefix('>') {
+            quote += 1;
+            rest = after.strip_prefix(' ').unwrap_or(after);
+        }
+
+        let marker = rest.trim_start_matches([' ', '\t']);
+        let indent = rest[..rest.len() - marker.len()]
+            .chars()
+            .map(|c| if c == '\t' { 4 } else { 1 })
+            .sum();
+
+        let digits = marker.chars().take_while(|c| c.is_ascii_digit()).count();
+        let after_marker = if digits == 0 {
+            marker.strip_prefix(['-', '*', '+'])?
+        } else if digits <= 9 {
+            marker[digits..].strip_prefix(['.', ')'])?
+        } else {
+            return None;
+        };
+        let content = after_marker.trim_start_matches([' ', '\t']);
+        if content.len() == after_marker.len() && !content.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            quote,
+            indent,
+            prefix: &line[..line.len() - content.len()],
+            content,
+        })
+    }
+}
+
+/// Task list item: `- [ ] x`, `* [x] x`, `1. [ ] x`, `> - [ ] x`...
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskLine<'a> {
+    pub item: ListItem<'a>,
+    /// Carácter entre corchetes (' ', 'x', 'M', '>'...)
+    pub status: char,
+    /// Texto después de `] `
+    pub text: &'a str,
+}
+
+impl<'a> TaskLine<'a> {
+    pub fn parse(line: &'a str) -> Option<Self> {
+        let item = ListItem::parse(line)?;
+        let rest = item.content.strip_prefix('[')?;
+        let mut chars = rest.chars();
+        let status = chars.next()?;
+        let after = chars.as_str().strip_prefix(']')?;
+        let text = match after.strip_prefix([' ', '\t']) {
+            Some(text) => text,
+            None if after.is_empty() => after,
+            None => return None,
+        };
+        Some(Self { item, status, text })
+    }
+
+    /// The same line with another status
+    pub fn with_status(&self, status: char) -> String {
+        self.with_text(status, self.text)
+    }
+
+    /// A task at the same place (prefix and indentation) with other status and text
+    pub fn with_text(&self, status: char, text: &str) -> String {
+        format!("{}[{}] {}", self.item.prefix, status, text)
+            .trim_end()
+            .to_string()
+    }
+}
+
+/// Date field a token sets
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DateField {
+    Due,
+    Scheduled,
+    Start,
+    /// `➕` creada / `✅` completada: se quitan del título
+    Ignored,
+}
+
+/// Where a date of the task is written (bytes of `YYYY-MM-DD` in the text)
+struct DateSpan {
+    field: DateField,
+    range: Range<usize>,
+}
+
+impl TaskMeta {
+    /// Parse the text after `- [ ] `. Tokens that look like metadata but do not parse
+    /// (`📅 mañana`) stay in the title.
+    pub fn parse(text: &str) -> Self {
+        scan(text).0
+    }
+
+    fn set(&mut self, field: DateField, date: NaiveDate) {
+        match field {
+            DateField::Due => self.due = Some(date),
+            DateField::Scheduled => self.scheduled = Some(date),
+            DateField::Start => self.start = Some(date),
+            DateField::Ignored => {}
+        }
+    }
+}
+
+/// ID of a synced task, from its hidden marker `<!-- mad-id:UID -->`
+pub fn task_id(text: &str) -> Option<&str> {
+    let range = marker_range(text)?;
+    let id = text[range].trim_start_matches(ID_MARKER).trim_end_matches("-->").trim();
+    (!id.is_empty()).then_some(id)
+}
+
+/// Text without the ID marker
+pub fn without_task_id(text: &str) -> String {
+    let Some(range) = marker_range(text) else {
+        return text.to_string();
+    };
+    let before = text[..range.start].trim_end();
+    let after = text[range.end..].trim_start();
+    if after.is_empty() {
+        before.to_string()
+    } else {
+        format!("{} {}", before, after)
+    }
+}
+
+/// Text with `id` in its hidden marker, at the end (reemplaza el anterior)
+pub fn with_task_id(text: &str, id: &str) -> String {
+    format!("{} {}{} -->", without_task_id(text), ID_MARKER, id)
+}
+
+const ID_MARKER: &str = "<!-- mad-id:";
+
+fn marker_range(text: &str) -> Option<Range<usize>> {
+    let start = text.find(ID_MARKER)?;
+    let end = text[start..].find("-->").map_or(text.len(), |e| start + e + 3);
+    Some(start..end)
+}
+
+/// Text of the next occurrence of a recurring task, or None if it does not recur.
+/// Las fechas (vencimiento, programada, inicio) avanzan lo mismo que la de referencia
+/// (la de vencimiento, si no la programada o la de inicio); sin fechas, la próxima vence
+/// según la regla contada desde `today`. La próxima no lleva el ID de sincronización.
+pub fn next_occurrence(text: &str, today: NaiveDate) -> Option<String> {
+    let (meta, spans) = scan(text);
+    let recurrence = Recurrence::parse(meta.recurrence.as_deref()?)?;
+
+    let Some(reference) = meta.due.or(meta.scheduled).or(meta.start) else {
+        let due = recurrence.next(today)?;
+        return Some(format!(
+            "{} 📅 {}",
+            without_task_id(text).trim_end(),
+            due.format("%Y-%m-%d")
+        ));
+    };
+    let base = if recurrence.when_done { today } else { reference };
+    let delta = recurrence.next(base)? - reference;
+
+    let mut out = String::new();
+    let mut last = 0;
+    for span in spans.iter().filter(|s| s.field != DateField::Ignored) {
+        let date = NaiveDate::parse_from_str(&text[span.range.clone()], "%Y-%m-%d").ok()?;
+        out.push_str(&text[last..span.range.start]);
+        out.push_str(&date.checked_add_signed(delta)?.format("%Y-%m-%d").to_string());
+        last = span.range.end;
+    }
+    out.push_str(&text[last..]);
+    Some(without_task_id(&out))
+}
+
+fn scan(text: &str) -> (TaskMeta, Vec<DateSpan>) {
+    let mut meta = TaskMeta::default();
+    let mut spans = Vec::new();
+    let mut title: Vec<&str> = Vec::new();
+    // (offset en bytes, token)
+    let tokens: Vec<(usize, &str)> = text
+        .split_whitespace()
+        .map(|t| (t.as_ptr() as usize - text.as_ptr() as usize, t))
+        .collect();
+    let marker = marker_range(text);
+    let in_marker = |offset: usize| marker.as_ref().is_some_and(|m| m.contains(&offset));
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let (offset, token) = tokens[i];
+        let bare = token.trim_end_matches('\u{FE0F}');
+        if in_marker(offset) {
+            i += 1;
+            continue;
+        }
+
+        if let Some(priority) = Priority::from_emoji(bare) {
+            meta.priority = priority;
+        } else if let Some(priority) = bare.strip_prefix('!').and_then(Priority::parse) {
+            meta.priority = priority;
+        } else if let Some((field, date, start)) = text_date(bare) {
+            meta.set(field, date);
+            spans.push(DateSpan {
+                field,
+                range: offset + start..offset + bare.len(),
+            });
+        } else if bare.starts_with('🔁') {
+            // La regla sigue hasta el próximo metadato: `🔁 every week 📅 2026-10-20`
+            let mut rule: Vec<&str> = Vec::new();
+            let first = bare.trim_start_matches('🔁').trim_start_matches('\u{FE0F}');
+            if !first.is_empty() {
+                rule.push(first);
+            }
+            while let Some((next_offset, next)) = tokens.get(i + 1) {
+                if is_metadata(next) || in_marker(*next_offset) {
+                    break;
+                }
+                rule.push(next);
+                i += 1;
+            }
+            meta.recurrence = Some(rule.join(" "));
+        } else if let Some((field, rest)) = emoji_date(bare) {
+            // `📅 2026-10-20` o `📅2026-10-20`
+            let (raw, raw_offset, used) = if rest.is_empty() {
+                let (next_offset, next) = tokens.get(i + 1).copied().unwrap_or((0, ""));
+                (next, next_offset, 2)
+            } else {
+                (rest, offset + bare.len() - rest.len(), 1)
+            };
+            match NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+                Ok(date) => {
+                    meta.set(field, date);
+                    spans.push(DateSpan {
+                        field,
+                        range: raw_offset..raw_offset + raw.len(),
+                    });
+                    i += used;
+                    continue;
+                }
+                Err(_) => title.push(token),
+            }
+        } else {
+            title.push(token);
+        }
+        i += 1;
+    }
+
+    meta.title = title.join(" ");
+    meta.id = task_id(text).map(str::to_string);
+    meta.tags = scan::extract_hash_tags_from_line(&meta.title)
+        .iter()
+        .map(|t| t.to_slash_string())
+        .collect();
+    (meta, spans)
+}
+
+/// Token that starts another piece of metadata (ends a `🔁` rule)
+fn is_metadata(token: &str) -> bool {
+    let bare = token.trim_end_matches('\u{FE0F}');
+    Priority::from_emoji(bare).is_some()
+        || bare.strip_prefix('!').and_then(Priority::parse).is_some()
+        || text_date(bare).is_some()
+        || emoji_date(bare).is_some()
+        || bare.starts_with('#')
+}
+
+fn emoji_date(token: &str) -> Option<(DateField, &str)> {
+    let emojis = [
+        ("📅", DateField::Due),
+        ("📆", DateField::Due),
+        ("🗓", DateField::Due),
+        ("⏳", DateField::Scheduled),
+        ("🛫", DateField::Start),
+        ("➕", DateField::Ignored),
+        ("✅", DateField::Ignored),
+    ];
+    emojis.into_iter().find_map(|(emoji, field)| {
+        let rest = token.strip_prefix(emoji)?;
+        Some((field, rest.trim_start_matches('\u{FE0F}')))
+    })
+}
+
+/// `due:2026-10-20`, `scheduled:...`, `start:...` → (campo, fecha, offset de la fecha)
+fn text_date(token: &str) -> Option<(DateField, NaiveDate, usize)> {
+    let (key, value) = token.split_once(':')?;
+    let field = match key {
+        "due" => DateField::Due,
+        "scheduled" => DateField::Scheduled,
+        "start" => DateField::Start,
+        _ => return None,
+    };
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    Some((field, date, key.len() + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_parses_obsidian_tasks_emojis() {
+        let meta = TaskMeta::parse("pagar #casa/cuentas factura ⏫ 🛫 2026-10-01 ⏳2026-10-10 📅 2026-10-20");
+        assert_eq!(meta.title, "pagar #casa/cuentas factura");
+        assert_eq!(meta.priority, Priority::High);
+        assert_eq!(meta.start, Some(date("2026-10-01")));
+        assert_eq!(meta.scheduled, Some(date("2026-10-10")));
+        assert_eq!(meta.due, Some(date("2026-10-20")));
+        assert_eq!(meta.tags, vec!["casa/cuentas"]);
+    }
+
+    #[test]
+    fn test_hidden_id_marker() {
+        let text = "pagar luz 📅 2026-10-20 <!-- mad-id:mad-k3j9x@mad -->";
+        let meta = TaskMeta::parse(text);
+        assert_eq!(meta.title, "pagar luz");
+        assert_eq!(meta.id.as_deref(), Some("mad-k3j9x@mad"));
+        assert_eq!(without_task_id(text), "pagar luz 📅 2026-10-20");
+        assert_eq!(with_task_id("pagar luz <!-- mad-id:viejo -->", "nuevo"), "pagar luz <!-- mad-id:nuevo -->");
+        assert_eq!(task_id("sin marcador"), None);
+    }
+
+    #[test]
+    fn test_parses_plain_text_metadata_and_keeps_the_rest() {
+        let meta = TaskMeta::parse("llamar a Ana due:2026-10-20 !low ✅ 2026-10-21 📅 mañana");
+        assert_eq!(meta.title, "llamar a Ana 📅 mañana");
+        assert_eq!(meta.priority, Priority::Low);
+        assert_eq!(meta.due, Some(date("2026-10-20")));
+        assert!(Priority::Normal > Priority::Low && Priority::Normal < Priority::Medium);
+    }
+
+    #[test]
+    fn test_parses_every_task_list_form() {
+        for line in ["- [ ] a", "* [ ] a", "+ [ ] a", "1. [ ] a", "12) [ ] a", "> - [ ] a", "\t- [ ] a"] {
+            let task = TaskLine::parse(line).unwrap_or_else(|| panic!("{}", line));
+            assert_eq!((task.status, task.text), (' ', "a"));
+            assert_eq!(task.with_status('x'), line.replace("[ ]", "[x]"));
+        }
+        let nested = TaskLine::parse("> >   - [x] hecho").unwrap();
+        assert_eq!((nested.item.quote, nested.item.indent), (2, 2));
+        assert_eq!(TaskLine::parse("- [ ]").unwrap().text, "");
+        for line in ["-[ ] a", "- [ ]a", "[ ] a", "- a", "a. [ ] b", "**negrita**"] {
+            assert!(TaskLine::parse(line).is_none(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_next_occurrence_moves_every_date() {
+        let today = date("2026-10-16");
+        let text = "ops semanal 🔁 every week ⏳ 2026-10-12 📅 2026-10-14";
+        assert_eq!(TaskMeta::parse(text).recurrence.as_deref(), Some("every week"));
+        assert_eq!(
+            next_occurrence(text, today).as_deref(),
+            Some("ops semanal 🔁 every week ⏳ 2026-10-19 📅 2026-10-21")
+        );
+        assert_eq!(
+            next_occurrence("backup 🔁 every month when done due:2026-10-01", today).as_deref(),
+            Some("backup 🔁 every month when done due:2026-11-16")
+        );
+        assert_eq!(
+            next_occurrence("regar 🔁 every 2 days", today).as_deref(),
+            Some("regar 🔁 every 2 days 📅 2026-10-18")
+        );
+        assert_eq!(next_occurrence("sin regla 📅 2026-10-14", today), None);
+        // La próxima ocurrencia no hereda el ID de sincronización
+        assert_eq!(
+            next_occurrence("regar 🔁 every day 📅 2026-10-16 <!-- mad-id:abc -->", today).as_deref(),
+            Some("regar 🔁 every day 📅 2026-10-17")
+        );
+        // Fuera del rango de fechas: sin próxima ocurrencia, sin pánico
+        assert_eq!(next_occurrence("regar 🔁 every 99999999999999 days 📅 2026-10-16", today), None);
+        assert_eq!(next_occurrence("regar 🔁 every 99999999999999 weeks", today), None);
+    }
+}
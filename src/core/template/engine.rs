@@ -0,0 +1,898 @@
+use crate::core::date_expr;
+use chrono::{Local, NaiveDate};
+use serde_yaml::{Mapping, Value};
+use slug::slugify;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Variables available to a template.
+/// - `vars`: valores escalares o YAML (listas, mapas) accesibles por nombre o `a.b`
+/// - `dates`: variables de fecha (`date`, `today`) que admiten aritmética: `{{date+7d}}`
+#[derive(Debug, Clone)]
+pub struct Context {
+    vars: Mapping,
+    dates: BTreeMap<String, NaiveDate>,
+    date_format: String,
+}
+
+impl Context {
+    /// Context with string variables; `date`/`today` default to the current day
+    pub fn new(vars: &BTreeMap<String, String>) -> Self {
+        let mut ctx = Self {
+            vars: Mapping::new(),
+            dates: BTreeMap::new(),
+            date_format: "%Y-%m-%d".to_string(),
+        };
+        for (k, v) in vars {
+            ctx.set(k, Value::String(v.clone()));
+        }
+        ctx.today(Local::now().date_naive())
+    }
+
+    /// Format used to print date values (config `date`)
+    pub fn date_format(mut self, fmt: &str) -> Self {
+        self.date_format = fmt.to_string();
+        self
+    }
+
+    /// Day used for `date` and `today`
+    pub fn today(mut self, date: NaiveDate) -> Self {
+        self.set_date("date", date);
+        self.set_date("today", date);
+        self
+    }
+
+    pub fn set(&mut self, key: &str, value: Value) {
+        self.vars.insert(Value::String(key.to_string()), value);
+    }
+
+    pub fn set_date(&mut self, key: &str, date: NaiveDate) {
+        self.dates.insert(key.to_string(), date);
+    }
+
+    /// Add fields (e.g. the rendered frontmatter) without overriding existing variables
+    pub fn with_fields(mut self, fields: &Mapping) -> Self {
+        for (k, v) in fields {
+            if let Value::String(key) = k {
+                if !self.vars.contains_key(k) && !self.dates.contains_key(key) {
+                    self.vars.insert(k.clone(), v.clone());
+                }
+            }
+        }
+        self
+    }
+}
+
+/// Render a template string.
+/// - `{{var}}`, `{{a.b}}`: variables (las desconocidas se dejan tal cual)
+/// - `{{var | upper}}`, `{{title | slug}}`, `{{date | date:"%A"}}`, `{{x | default:"-"}}`
+/// - `{{date+7d}}`, `{{date-1m}}`: aritmética de fechas (d, w, m, y)
+/// - `{{#if x}}...{{else}}...{{/if}}`, `{{#unless x}}...{{/unless}}`
+/// - `{{#each lista}}{{this}} {{@index}}{{/each}}`
+pub fn render(template: &str, ctx: &Context) -> anyhow::Result<String> {
+    let tokens = tokenize(template);
+    let mut pos = 0;
+    let (nodes, _) = parse_block(&tokens, &mut pos, None)?;
+
+    let mut out = String::new();
+    Renderer {
+        ctx,
+        scopes: Vec::new(),
+    }
+    .render_nodes(&nodes, &mut out)?;
+    Ok(out)
+}
+
+/// Render every string of a YAML tree (keys, values, nested sequences and mappings).
+/// A string that is a single `{{expr}}` resolving to a list or mapping keeps its structure.
+pub fn render_value(value: Value, ctx: &Context) -> anyhow::Result<Value> {
+    Ok(match value {
+        Value::String(s) => render_scalar(&s, ctx)?,
+        Value::Sequence(seq) => Value::Sequence(
+            seq.into_iter()
+                .map(|v| render_value(v, ctx))
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        Value::Mapping(map) => match unquoted_placeholder(&map) {
+            Some(placeholder) => render_scalar(&placeholder, ctx)?,
+            None => {
+                let mut out = Mapping::new();
+                for (k, v) in map {
+                    out.insert(render_value(k, ctx)?, render_value(v, ctx)?);
+                }
+                Value::Mapping(out)
+            }
+        },
+        Value::Tagged(mut tagged) => {
+            tagged.value = render_value(tagged.value, ctx)?;
+            Value::Tagged(tagged)
+        }
+        other => other,
+    })
+}
+
+fn render_scalar(s: &str, ctx: &Context) -> anyhow::Result<Value> {
+    let trimmed = s.trim();
+    if let Some(inner) = trimmed
+        .strip_prefix("{{")
+        .and_then(|r| r.strip_suffix("}}"))
+    {
+        let is_single = !inner.contains("{{") && !inner.contains("}}");
+        let is_block = inner.trim_start().starts_with(['#', '/']);
+        if is_single && !is_block {
+            let renderer = Renderer {
+                ctx,
+                scopes: Vec::new(),
+            };
+            if let Some(Val::Data(v @ (Value::Sequence(_) | Value::Mapping(_)))) =
+                renderer.eval(inner)?
+            {
+                return Ok(v);
+            }
+        }
+    }
+    Ok(Value::String(render(s, ctx)?))
+}
+
+/// Unquoted `key: {{var}}` is parsed by YAML as `{ {var: null}: null }`
+fn unquoted_placeholder(map: &Mapping) -> Option<String> {
+    let (key, value) = map.iter().next()?;
+    if map.len() != 1 || !value.is_null() {
+        return None;
+    }
+    let Value::Mapping(inner) = key else {
+        return None;
+    };
+    let (name, inner_value) = inner.iter().next()?;
+    match (inner.len(), name, inner_value) {
+        (1, Value::String(name), Value::Null) => Some(format!("{{{{{}}}}}", name)),
+        _ => None,
+    }
+}
+
+// ─── Parser ──────────────────────────────────────────────────────────────────
+
+enum Token<'a> {
+    Text(&'a str),
+    /// (contenido sin llaves, tag completo)
+    Tag(&'a str, &'a str),
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    /// (expresión, tag original para dejarlo tal cual si no se resuelve)
+    Expr(String, String),
+    If {
+        cond: String,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Each {
+        expr: String,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    /// `{{#block nombre}}...{{/block}}`
+    Group(Vec<Node>),
+}
+
+/// Split into text and `{{...}}` tags. Block tags alone on their line
+/// (`{{#if}}`, `{{else}}`, `{{/each}}`...) consume the whole line.
+fn tokenize(src: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < src.len() {
+        let Some(start) = src[pos..].find("{{").map(|i| pos + i) else {
+            tokens.push(Token::Text(&src[pos..]));
+            break;
+        };
+        let Some(end) = src[start + 2..].find("}}").map(|i| start + 2 + i) else {
+            tokens.push(Token::Text(&src[pos..]));
+            break;
+        };
+
+        let inner = &src[start + 2..end];
+        let mut text_end = start;
+        let mut next = end + 2;
+
+        let trimmed = inner.trim();
+        let is_block = trimmed.starts_with('#') || trimmed.starts_with('/') || trimmed == "else";
+        if is_block {
+            let line_start = src[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+            let line_end = src[next..].find('\n').map(|i| next + i);
+            let before_blank = line_start >= pos && src[line_start..start].trim().is_empty();
+            let after = &src[next..line_end.unwrap_or(src.len())];
+            if before_blank && after.trim().is_empty() {
+                text_end = line_start;
+                next = line_end.map(|i| i + 1).unwrap_or(src.len());
+            }
+        }
+
+        if text_end > pos {
+            tokens.push(Token::Text(&src[pos..text_end]));
+        }
+        tokens.push(Token::Tag(trimmed, &src[start..end + 2]));
+        pos = next;
+    }
+
+    tokens
+}
+
+/// Parse until `{{/block}}` (or the end when `block` is None).
+/// Returns (nodes, nodes after `{{else}}`).
+fn parse_block(
+    tokens: &[Token],
+    pos: &mut usize,
+    block: Option<&str>,
+) -> anyhow::Result<(Vec<Node>, Vec<Node>)> {
+    let mut nodes = Vec::new();
+    let mut otherwise = Vec::new();
+    let mut in_else = false;
+
+    while *pos < tokens.len() {
+        let token = &tokens[*pos];
+        *pos += 1;
+
+        let target = if in_else { &mut otherwise } else { &mut nodes };
+
+        let (inner, full) = match token {
+            Token::Text(text) => {
+                target.push(Node::Text(text.to_string()));
+                continue;
+            }
+            Token::Tag(inner, full) => (*inner, *full),
+        };
+
+        if let Some(name) = inner.strip_prefix('/') {
+            let name = name.trim();
+            return match block {
+                Some(open) if open == name => Ok((nodes, otherwise)),
+                Some(open) => anyhow::bail!(
+                    "Template: se esperaba {{{{/{}}}}} y se encontró {}",
+                    open,
+                    full
+                ),
+                None => anyhow::bail!("Template: {} sin bloque abierto", full),
+            };
+        }
+
+        if inner == "else" {
+            if block.is_none() || in_else {
+                anyhow::bail!("Template: {{{{else}}}} fuera de un bloque");
+            }
+            in_else = true;
+            continue;
+        }
+
+        if let Some(open) = inner.strip_prefix('#') {
+            let (name, arg) = open.split_once(char::is_whitespace).unwrap_or((open, ""));
+            let arg = arg.trim().to_string();
+            if arg.is_empty() {
+                anyhow::bail!("Template: {} necesita una expresión", full);
+            }
+            let (body, else_body) = parse_block(tokens, pos, Some(name))?;
+            let node = match name {
+                "if" | "unless" => Node::If {
+                    cond: arg,
+                    negate: name == "unless",
+                    then: body,
+                    otherwise: else_body,
+                },
+                "each" => Node::Each {
+                    expr: arg,
+                    body,
+                    otherwise: else_body,
+                },
+                // Bloques de herencia: ya compuestos, se renderiza su contenido
+                "block" if else_body.is_empty() => Node::Group(body),
+                "block" => anyhow::bail!("Template: {{{{else}}}} dentro de {{{{#block {}}}}}", arg),
+                other => anyhow::bail!("Template: bloque desconocido #{}", other),
+            };
+            target.push(node);
+            continue;
+        }
+
+        target.push(Node::Expr(inner.to_string(), full.to_string()));
+    }
+
+    match block {
+        Some(open) => anyhow::bail!("Template: bloque {{{{#{}}}}} sin cerrar", open),
+        None => Ok((nodes, otherwise)),
+    }
+}
+
+// ─── Evaluación ──────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+enum Val {
+    Date(NaiveDate),
+    Data(Value),
+}
+
+struct Renderer<'a> {
+    ctx: &'a Context,
+    /// Variables de cada `{{#each}}` anidado (`this`, `@index`, campos del item)
+    scopes: Vec<Mapping>,
+}
+
+impl Renderer<'_> {
+    fn render_nodes(&mut self, nodes: &[Node], out: &mut String) -> anyhow::Result<()> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Group(body) => self.render_nodes(body, out)?,
+                Node::Expr(expr, original) => {
+                    let (head, filters) = split_expr(expr);
+                    match self.eval(expr)? {
+                        Some(val) => out.push_str(&self.display(&val)),
+                        // Variable desconocida sin filtros: se deja tal cual
+                        None if filters.is_empty() && !head.is_empty() => out.push_str(original),
+                        None => {}
+                    }
+                }
+                Node::If {
+                    cond,
+                    negate,
+                    then,
+                    otherwise,
+                } => {
+                    let value = self.eval(cond)?;
+                    let truthy = value.as_ref().is_some_and(is_truthy);
+                    if truthy != *negate {
+                        self.render_nodes(then, out)?;
+                    } else {
+                        self.render_nodes(otherwise, out)?;
+                    }
+                }
+                Node::Each {
+                    expr,
+                    body,
+                    otherwise,
+                } => {
+                    let items = match self.eval(expr)? {
+                        Some(Val::Data(value)) => items_of(value),
+                        Some(Val::Date(date)) => vec![(None, Val::Date(date))],
+                        None => Vec::new(),
+                    };
+
+                    if items.is_empty() {
+                        self.render_nodes(otherwise, out)?;
+                        continue;
+                    }
+
+                    let last = items.len() - 1;
+                    for (index, (key, item)) in items.into_iter().enumerate() {
+                        let mut scope = Mapping::new();
+                        if let Val::Data(Value::Mapping(fields)) = &item {
+                            for (k, v) in fields {
+                                scope.insert(k.clone(), v.clone());
+                            }
+                        }
+                        let this = match item {
+                            Val::Data(v) => v,
+                            Val::Date(d) => Value::String(self.format_date(d, None)?),
+                        };
+                        scope.insert(key_value("this"), this);
+                        scope.insert(key_value("@index"), Value::from(index as u64));
+                        scope.insert(key_value("@first"), Value::Bool(index == 0));
+                        scope.insert(key_value("@last"), Value::Bool(index == last));
+                        if let Some(key) = key {
+                            scope.insert(key_value("@key"), key);
+                        }
+
+                        self.scopes.push(scope);
+                        let result = self.render_nodes(body, out);
+                        self.scopes.pop();
+                        result?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluate `head | filtro | filtro:"arg"`. None = variable desconocida.
+    fn eval(&self, expr: &str) -> anyhow::Result<Option<Val>> {
+        let (head, filters) = split_expr(expr);
+        let mut value = self.eval_head(head)?;
+        for filter in filters {
+            value = self.apply_filter(value, filter)?;
+        }
+        Ok(value)
+    }
+
+    fn eval_head(&self, head: &str) -> anyhow::Result<Option<Val>> {
+        // `prompt:x` y `choice:x` leen la respuesta guardada en `x`
+        if let Some(name) = input_name(head) {
+            return Ok(self.lookup(name).map(Val::Data));
+        }
+
+        if let Some(literal) = unquote(head) {
+            return Ok(Some(Val::Data(Value::String(literal))));
+        }
+
+        // `date+7d`, `today-1w+2d`
+        if let Some((name, offsets)) = split_date_offsets(head) {
+            if let Some(date) = self.lookup_date(name) {
+                return apply_offsets(date, offsets).map(|d| Some(Val::Date(d)));
+            }
+        }
+
+        if let Some(date) = self.lookup_date(head) {
+            return Ok(Some(Val::Date(date)));
+        }
+
+        Ok(self.lookup(head).map(Val::Data))
+    }
+
+    fn lookup_date(&self, name: &str) -> Option<NaiveDate> {
+        if self.scopes.iter().any(|s| s.contains_key(name)) {
+            return None;
+        }
+        self.ctx.dates.get(name).copied()
+    }
+
+    /// `nombre`, `a.b.c`, `this`, `this.campo`, `@index`
+    fn lookup(&self, path: &str) -> Option<Value> {
+        let mut parts = path.split('.');
+        let first = parts.next()?.trim();
+
+        let mut current = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(first))
+            .or_else(|| self.ctx.vars.get(first))?
+            .clone();
+
+        for part in parts {
+            current = match current {
+                Value::Mapping(map) => map.get(part.trim())?.clone(),
+                Value::Sequence(seq) => seq.get(part.trim().parse::<usize>().ok()?)?.clone(),
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    fn apply_filter(&self, value: Option<Val>, filter: &str) -> anyhow::Result<Option<Val>> {
+        let (name, arg) = match filter.split_once(':') {
+            Some((n, a)) => (
+                n.trim(),
+                Some(unquote(a.trim()).unwrap_or_else(|| a.trim().to_string())),
+            ),
+            None => (filter.trim(), None),
+        };
+
+        if name == "default" {
+            let empty = value.as_ref().map(|v| !is_truthy(v)).unwrap_or(true);
+            return Ok(if empty {
+                Some(Val::Data(Value::String(arg.unwrap_or_default())))
+            } else {
+                value
+            });
+        }
+
+        let Some(value) = value else {
+            return Ok(None);
+        };
+
+        let text = |v: &Val| self.display(v);
+        let result = match name {
+            "upper" => Val::Data(Value::String(text(&value).to_uppercase())),
+            "lower" => Val::Data(Value::String(text(&value).to_lowercase())),
+            "trim" => Val::Data(Value::String(text(&value).trim().to_string())),
+            "slug" => Val::Data(Value::String(slugify(text(&value)))),
+            "capitalize" => {
+                let s = text(&value);
+                let mut chars = s.chars();
+                let capitalized = match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                };
+                Val::Data(Value::String(capitalized))
+            }
+            "join" => {
+                let sep = arg.unwrap_or_else(|| ", ".to_string());
+                match &value {
+                    Val::Data(Value::Sequence(seq)) => Val::Data(Value::String(
+                        seq.iter()
+                            .map(|v| self.display(&Val::Data(v.clone())))
+                            .collect::<Vec<_>>()
+                            .join(&sep),
+                    )),
+                    _ => value,
+                }
+            }
+            "date" => {
+                let date = match &value {
+                    Val::Date(d) => Some(*d),
+                    Val::Data(Value::String(s)) => self.parse_date(s),
+                    _ => None,
+                };
+                match date {
+                    Some(d) => Val::Data(Value::String(self.format_date(d, arg.as_deref())?)),
+                    None => value,
+                }
+            }
+            other => anyhow::bail!("Template: filtro desconocido '{}'", other),
+        };
+
+        Ok(Some(result))
+    }
+
+    fn display(&self, value: &Val) -> String {
+        match value {
+            Val::Date(d) => self.format_date(*d, None).unwrap_or_else(|_| d.to_string()),
+            Val::Data(Value::String(s)) => s.clone(),
+            Val::Data(Value::Null) => String::new(),
+            Val::Data(Value::Bool(b)) => b.to_string(),
+            Val::Data(Value::Number(n)) => n.to_string(),
+            Val::Data(Value::Sequence(seq)) => seq
+                .iter()
+                .map(|v| self.display(&Val::Data(v.clone())))
+                .collect::<Vec<_>>()
+                .join(", "),
+            Val::Data(other) => serde_yaml::to_string(other)
+                .unwrap_or_default()
+                .trim_end()
+                .to_string(),
+        }
+    }
+
+    fn format_date(&self, date: NaiveDate, fmt: Option<&str>) -> anyhow::Result<String> {
+        let fmt = fmt.unwrap_or(&self.ctx.date_format);
+        let mut out = String::new();
+        write!(out, "{}", date.format(fmt))
+            .map_err(|_| anyhow::anyhow!("Template: formato de fecha inválido '{}'", fmt))?;
+        Ok(out)
+    }
+
+    fn parse_date(&self, s: &str) -> Option<NaiveDate> {
+        [self.ctx.date_format.as_str(), "%Y-%m-%d", "%d/%m/%Y"]
+            .iter()
+            .find_map(|fmt| NaiveDate::parse_from_str(s.trim(), fmt).ok())
+    }
+}
+
+/// Like `split_filters`, but `choice:x|a,b` keeps its options out of the filters
+fn split_expr(expr: &str) -> (&str, Vec<&str>) {
+    let (head, mut filters) = split_filters(expr);
+    if head.starts_with("choice:") && !filters.is_empty() {
+        filters.remove(0);
+    }
+    (head, filters)
+}
+
+/// `prompt:cliente` / `choice:estado` → `cliente` / `estado`
+fn input_name(head: &str) -> Option<&str> {
+    head.strip_prefix("prompt:")
+        .or_else(|| head.strip_prefix("choice:"))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+}
+
+/// Value the user is asked for before rendering
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateInput {
+    pub name: String,
+    pub kind: InputKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputKind {
+    /// `{{prompt:cliente}}` o `{{prompt:cliente | default:"Acme"}}`
+    Text { default: Option<String> },
+    /// `{{choice:estado|abierto,cerrado}}`
+    Choice(Vec<String>),
+}
+
+/// Inputs declared in a template string, in order of appearance
+pub fn inputs(template: &str) -> Vec<TemplateInput> {
+    let mut found: Vec<TemplateInput> = Vec::new();
+
+    for token in tokenize(template) {
+        let Token::Tag(inner, _) = token else {
+            continue;
+        };
+        let (head, mut filters) = split_filters(inner);
+        let Some(name) = input_name(head) else {
+            continue;
+        };
+        if found.iter().any(|i| i.name == name) {
+            continue;
+        }
+
+        let kind = if head.starts_with("choice:") {
+            let options = if filters.is_empty() {
+                Vec::new()
+            } else {
+                filters
+                    .remove(0)
+                    .split(',')
+                    .map(|o| o.trim().to_string())
+                    .collect()
+            };
+            InputKind::Choice(options.into_iter().filter(|o| !o.is_empty()).collect())
+        } else {
+            let default = filters.iter().find_map(|f| {
+                let (name, arg) = f.split_once(':')?;
+                (name.trim() == "default")
+                    .then(|| unquote(arg).unwrap_or_else(|| arg.trim().to_string()))
+            });
+            InputKind::Text { default }
+        };
+
+        found.push(TemplateInput {
+            name: name.to_string(),
+            kind,
+        });
+    }
+
+    found
+}
+
+/// Inputs declared anywhere in a YAML tree
+pub fn value_inputs(value: &Value) -> Vec<TemplateInput> {
+    let mut found = Vec::new();
+    collect_value_inputs(value, &mut found);
+    found
+}
+
+fn collect_value_inputs(value: &Value, found: &mut Vec<TemplateInput>) {
+    let mut push = |new: Vec<TemplateInput>| {
+        for input in new {
+            if !found.iter().any(|i| i.name == input.name) {
+                found.push(input);
+            }
+        }
+    };
+
+    match value {
+        Value::String(s) => push(inputs(s)),
+        Value::Sequence(seq) => {
+            for v in seq {
+                collect_value_inputs(v, found);
+            }
+        }
+        Value::Mapping(map) => match unquoted_placeholder(map) {
+            Some(placeholder) => push(inputs(&placeholder)),
+            None => {
+                for (k, v) in map {
+                    collect_value_inputs(k, found);
+                    collect_value_inputs(v, found);
+                }
+            }
+        },
+        Value::Tagged(tagged) => collect_value_inputs(&tagged.value, found),
+        _ => {}
+    }
+}
+
+/// `head | f1 | f2:"a|b"` → (head, [f1, f2:"a|b"]) respetando comillas
+fn split_filters(expr: &str) -> (&str, Vec<&str>) {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+
+    for (i, c) in expr.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '|' if !in_quotes => {
+                parts.push(expr[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(expr[start..].trim());
+
+    let head = parts.remove(0);
+    (head, parts)
+}
+
+fn unquote(s: &str) -> Option<String> {
+    let s = s.trim();
+    s.strip_prefix('"')
+        .and_then(|r| r.strip_suffix('"'))
+        .map(|r| r.to_string())
+}
+
+/// `date+7d-1w` → ("date", "+7d-1w") solo si el sufijo es aritmética válida
+fn split_date_offsets(head: &str) -> Option<(&str, &str)> {
+    let idx = head.find(['+', '-'])?;
+    let (name, offsets) = head.split_at(idx);
+    parse_offsets(offsets).map(|_| (name.trim(), offsets))
+}
+
+/// `+7d-1m` → [(7, 'd'), (-1, 'm')]
+fn parse_offsets(s: &str) -> Option<Vec<(i64, char)>> {
+    let mut result = Vec::new();
+    let mut rest = s.trim();
+
+    while !rest.is_empty() {
+        let sign = match rest.chars().next()? {
+            '+' => 1,
+            '-' => -1,
+            _ => return None,
+        };
+        rest = rest[1..].trim_start();
+        let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return None;
+        }
+        let amount: i64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit = rest.chars().next()?;
+        if !matches!(unit, 'd' | 'w' | 'm' | 'y') {
+            return None;
+        }
+        rest = rest[1..].trim_start();
+        result.push((sign * amount, unit));
+    }
+
+    (!result.is_empty()).then_some(result)
+}
+
+fn apply_offsets(date: NaiveDate, offsets: &str) -> anyhow::Result<NaiveDate> {
+    let mut date = date;
+    for (amount, unit) in parse_offsets(offsets).unwrap_or_default() {
+        date = date_expr::shift(date, amount, unit)
+            .ok_or_else(|| anyhow::anyhow!("Template: fecha fuera de rango"))?;
+    }
+    Ok(date)
+}
+
+fn is_truthy(value: &Val) -> bool {
+    match value {
+        Val::Date(_) => true,
+        Val::Data(Value::Null) => false,
+        Val::Data(Value::Bool(b)) => *b,
+        Val::Data(Value::String(s)) => !s.trim().is_empty(),
+        Val::Data(Value::Number(n)) => n.as_f64().is_some_and(|f| f != 0.0),
+        Val::Data(Value::Sequence(seq)) => !seq.is_empty(),
+        Val::Data(Value::Mapping(map)) => !map.is_empty(),
+        Val::Data(Value::Tagged(_)) => true,
+    }
+}
+
+/// Items to iterate: sequences, mappings (with `@key`) and comma-separated strings
+fn items_of(value: Value) -> Vec<(Option<Value>, Val)> {
+    match value {
+        Value::Sequence(seq) => seq.into_iter().map(|v| (None, Val::Data(v))).collect(),
+        Value::Mapping(map) => map
+            .into_iter()
+            .map(|(k, v)| (Some(k), Val::Data(v)))
+            .collect(),
+        Value::String(s) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(|p| (None, Val::Data(Value::String(p.to_string()))))
+            .collect(),
+        Value::Null => Vec::new(),
+        other => vec![(None, Val::Data(other))],
+    }
+}
+
+fn key_value(key: &str) -> Value {
+    Value::String(key.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> Context {
+        let mut vars = BTreeMap::new();
+        vars.insert("title".to_string(), "Reunión Semanal".to_string());
+        vars.insert("vacio".to_string(), String::new());
+        let mut ctx = Context::new(&vars)
+            .today(NaiveDate::from_ymd_opt(2026, 1, 30).unwrap())
+            .date_format("%Y-%m-%d");
+        ctx.set("asistentes", serde_yaml::from_str("[Ana, Luis]").unwrap());
+        ctx.set("cliente", serde_yaml::from_str("{nombre: Acme}").unwrap());
+        ctx
+    }
+
+    #[test]
+    fn test_variables_and_filters() {
+        let out = render(
+            "{{title}} | {{title | upper}} | {{title | slug}} | {{cliente.nombre}} | {{falta}} | {{falta | default:\"n/a\"}} | {{vacio | default:\"-\"}}",
+            &ctx(),
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "Reunión Semanal | REUNIÓN SEMANAL | reunion-semanal | Acme | {{falta}} | n/a | -"
+        );
+    }
+
+    #[test]
+    fn test_date_arithmetic_and_format() {
+        let out = render(
+            "{{date}} {{date+7d}} {{date-1m}} {{date+1w | date:\"%A\"}} {{today+1y | date:\"%d/%m/%Y\"}}",
+            &ctx(),
+        )
+        .unwrap();
+        assert_eq!(out, "2026-01-30 2026-02-06 2025-12-30 Friday 30/01/2027");
+    }
+
+    #[test]
+    fn test_if_and_each_blocks() {
+        let template = "# {{title}}\n{{#if asistentes}}\nAsistentes:\n{{#each asistentes}}\n- {{@index}} {{this}}{{#if @last}}.{{/if}}\n{{/each}}\n{{else}}\nSin asistentes\n{{/if}}\n{{#unless vacio}}vacío{{/unless}}\n";
+        let out = render(template, &ctx()).unwrap();
+        assert_eq!(
+            out,
+            "# Reunión Semanal\nAsistentes:\n- 0 Ana\n- 1 Luis.\nvacío\n"
+        );
+    }
+
+    #[test]
+    fn test_unclosed_block_is_an_error() {
+        assert!(render("{{#if title}}sin cierre", &ctx()).is_err());
+        assert!(render("{{#each x}}{{/if}}", &ctx()).is_err());
+        assert!(render("{{title | inexistente}}", &ctx()).is_err());
+    }
+
+    #[test]
+    fn test_template_inputs() {
+        let yaml: Value = serde_yaml::from_str(
+            "cliente: {{prompt:cliente}}\nestado: \"{{choice:estado|abierto, cerrado}}\"\n",
+        )
+        .unwrap();
+        let mut found = value_inputs(&yaml);
+        for input in inputs("{{prompt:cliente}} {{prompt:sala | default:\"B2\"}}") {
+            if !found.iter().any(|i| i.name == input.name) {
+                found.push(input);
+            }
+        }
+
+        assert_eq!(
+            found,
+            vec![
+                TemplateInput {
+                    name: "cliente".to_string(),
+                    kind: InputKind::Text { default: None },
+                },
+                TemplateInput {
+                    name: "estado".to_string(),
+                    kind: InputKind::Choice(vec!["abierto".to_string(), "cerrado".to_string()]),
+                },
+                TemplateInput {
+                    name: "sala".to_string(),
+                    kind: InputKind::Text {
+                        default: Some("B2".to_string())
+                    },
+                },
+            ]
+        );
+
+        let mut ctx = ctx();
+        ctx.set("cliente", Value::String("Acme".to_string()));
+        ctx.set("estado", Value::String("abierto".to_string()));
+        let out = render(
+            "{{prompt:cliente | upper}} {{choice:estado|abierto,cerrado}} {{cliente}} {{prompt:falta}}",
+            &ctx,
+        )
+        .unwrap();
+        assert_eq!(out, "ACME abierto Acme {{prompt:falta}}");
+    }
+
+    #[test]
+    fn test_render_yaml_tree() {
+        let yaml: Value = serde_yaml::from_str(
+            "date: {{date}}\ntags: [\"{{title | slug}}\"]\nmeta:\n  vence: \"{{date+7d}}\"\n  gente: \"{{asistentes}}\"\n",
+        )
+        .unwrap();
+        let rendered = render_value(yaml, &ctx()).unwrap();
+        let expected: Value = serde_yaml::from_str(
+            "date: \"2026-01-30\"\ntags: [reunion-semanal]\nmeta:\n  vence: \"2026-02-06\"\n  gente: [Ana, Luis]\n",
+        )
+        .unwrap();
+        assert_eq!(rendered, expected);
+    }
+}
@@ -0,0 +1,386 @@
+use super::{named_templates, read};
+use crate::core::frontmatter;
+use serde_yaml::{Mapping, Value};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Global base template, first layer of every cascade
+pub const BASE_TEMPLATE: &str = "_base.md";
+
+/// Per-directory template (same convention as the notebook's local template)
+pub const DIR_TEMPLATE: &str = "template.txt";
+
+const EXTENDS_KEY: &str = "extends";
+
+/// Template cascade for a note created in `note_dir`, from least to most specific:
+/// 1. `Templates/_base.md`
+/// 2. `template_path` (template del notebook o template con nombre)
+/// 3. `template.txt` de cada directorio desde la raíz del vault hasta `note_dir`
+///
+/// Each layer may declare `extends: <template>` to insert its parent before it.
+/// Frontmatter is merged with `frontmatter::merge` (the most specific wins) and bodies
+/// compose through `{{#block nombre}}...{{/block}}` (`{{super}}` = contenido del padre).
+/// Returns None if no layer exists.
+pub fn load_cascade(
+    templates_root: &Path,
+    notebook: &str,
+    template_path: &Path,
+    vault: &Path,
+    note_dir: &Path,
+) -> anyhow::Result<Option<(Mapping, String)>> {
+    let mut candidates = vec![
+        templates_root.join(BASE_TEMPLATE),
+        template_path.to_path_buf(),
+    ];
+    if let Ok(relative) = note_dir.strip_prefix(vault) {
+        let mut dir = vault.to_path_buf();
+        for component in relative.components() {
+            dir.push(component);
+            candidates.push(dir.join(DIR_TEMPLATE));
+        }
+    }
+
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    let mut layers: Vec<(Mapping, String)> = Vec::new();
+    for path in candidates {
+        if path.is_file() {
+            push_with_parents(
+                &path,
+                templates_root,
+                notebook,
+                &mut seen,
+                &mut Vec::new(),
+                &mut layers,
+            )?;
+        }
+    }
+
+    if layers.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(compose(layers)))
+}
+
+/// Add a template after its `extends:` chain
+fn push_with_parents(
+    path: &Path,
+    templates_root: &Path,
+    notebook: &str,
+    seen: &mut HashSet<PathBuf>,
+    stack: &mut Vec<PathBuf>,
+    layers: &mut Vec<(Mapping, String)>,
+) -> anyhow::Result<()> {
+    let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if stack.contains(&key) {
+        anyhow::bail!("Herencia de templates circular en: {}", path.display());
+    }
+    if seen.contains(&key) {
+        return Ok(());
+    }
+
+    let (mut fm, body) = read(path)?;
+    let parent = fm.remove(EXTENDS_KEY);
+
+    if let Some(parent) = parent {
+        let Value::String(name) = parent else {
+            anyhow::bail!(
+                "`extends` debe ser un nombre de template en: {}",
+                path.display()
+            );
+        };
+        let parent_path = find_parent(templates_root, notebook, name.trim()).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Template padre '{}' no encontrado (extends en {})",
+                name,
+                path.display()
+            )
+        })?;
+
+        stack.push(key.clone());
+        let result = push_with_parents(&parent_path, templates_root, notebook, seen, stack, layers);
+        stack.pop();
+        result?;
+    }
+
+    seen.insert(key);
+    layers.push((fm, body));
+    Ok(())
+}
+
+/// `extends: meeting` → template con nombre, `Templates/meeting.md` o path relativo a Templates
+fn find_parent(templates_root: &Path, notebook: &str, name: &str) -> Option<PathBuf> {
+    let stem = name.strip_suffix(".md").unwrap_or(name);
+
+    named_templates(templates_root, notebook)
+        .into_iter()
+        .find(|t| t.name.eq_ignore_ascii_case(stem))
+        .map(|t| t.path)
+        .or_else(|| {
+            [
+                templates_root.join(format!("{}.md", stem)),
+                templates_root.join(name),
+            ]
+            .into_iter()
+            .find(|p| p.is_file())
+        })
+}
+
+/// Merge layers from least to most specific
+pub fn compose(layers: Vec<(Mapping, String)>) -> (Mapping, String) {
+    let mut fm = Mapping::new();
+    let mut body = String::new();
+
+    for (layer_fm, layer_body) in layers {
+        fm = frontmatter::merge(fm, layer_fm);
+        body = compose_body(&body, &layer_body);
+    }
+
+    (fm, body)
+}
+
+/// Compose a child body over its parent:
+/// - hijo sin bloques: reemplaza al padre (si no está vacío)
+/// - hijo con bloques: reemplaza los bloques del mismo nombre en el padre;
+///   los bloques que el padre no tiene se agregan al final
+fn compose_body(parent: &str, child: &str) -> String {
+    let child_blocks = all_blocks(child);
+
+    if child_blocks.is_empty() {
+        return if child.trim().is_empty() {
+            parent.to_string()
+        } else {
+            child.to_string()
+        };
+    }
+    if parent.trim().is_empty() {
+        return child.to_string();
+    }
+
+    let parent_names: HashSet<String> = all_blocks(parent).into_iter().map(|b| b.0).collect();
+    let mut out = override_blocks(parent, &child_blocks);
+
+    for (name, content) in &child_blocks {
+        if !parent_names.contains(name) {
+            if !out.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str(&block_text(name, &content.replace(SUPER_TAG, "")));
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+const SUPER_TAG: &str = "{{super}}";
+
+/// Replace parent blocks overridden by the child (recursing into the ones that are not)
+fn override_blocks(parent: &str, child_blocks: &[(String, String)]) -> String {
+    let mut out = String::new();
+    let mut pos = 0;
+
+    for span in top_level_blocks(parent) {
+        out.push_str(&parent[pos..span.start]);
+        let inner = &parent[span.inner.clone()];
+        let lead = if inner.starts_with('\n') { "\n" } else { "" };
+
+        let content = match child_blocks.iter().find(|(name, _)| *name == span.name) {
+            Some((_, child)) => child.replace(SUPER_TAG, block_content(inner)),
+            None => override_blocks(block_content(inner), child_blocks),
+        };
+        out.push_str(&parent[span.start..span.inner.start]);
+        out.push_str(lead);
+        out.push_str(&content);
+        out.push_str(&parent[span.inner.end..span.end]);
+        pos = span.end;
+    }
+
+    out.push_str(&parent[pos..]);
+    out
+}
+
+/// Content of a block without the line break after `{{#block nombre}}`
+fn block_content(inner: &str) -> &str {
+    inner.strip_prefix('\n').unwrap_or(inner)
+}
+
+fn block_text(name: &str, content: &str) -> String {
+    let newline = if content.is_empty() || content.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    format!(
+        "{{{{#block {}}}}}\n{}{}{{{{/block}}}}",
+        name, content, newline
+    )
+}
+
+/// `{{#block nombre}}...{{/block}}` at any depth: (nombre, contenido)
+fn all_blocks(src: &str) -> Vec<(String, String)> {
+    let mut blocks = Vec::new();
+    for span in top_level_blocks(src) {
+        let inner = block_content(&src[span.inner.clone()]);
+        blocks.push((span.name.clone(), inner.to_string()));
+        blocks.extend(all_blocks(inner));
+    }
+    blocks
+}
+
+struct BlockSpan {
+    name: String,
+    /// Inicio de `{{#block}}`
+    start: usize,
+    /// Contenido entre las etiquetas
+    inner: std::ops::Range<usize>,
+    /// Fin de `{{/block}}`
+    end: usize,
+}
+
+fn top_level_blocks(src: &str) -> Vec<BlockSpan> {
+    let mut spans = Vec::new();
+    // (nombre, inicio del tag, inicio del contenido)
+    let mut stack: Vec<(String, usize, usize)> = Vec::new();
+    let mut pos = 0;
+
+    while let Some(start) = src[pos..].find("{{").map(|i| pos + i) {
+        let Some(end) = src[start + 2..].find("}}").map(|i| start + 2 + i + 2) else {
+            break;
+        };
+        let inner = src[start + 2..end - 2].trim();
+
+        if let Some(name) = inner.strip_prefix("#block") {
+            stack.push((name.trim().to_string(), start, end));
+        } else if inner == "/block" {
+            if let Some((name, tag_start, inner_start)) = stack.pop() {
+                if stack.is_empty() {
+                    spans.push(BlockSpan {
+                        name,
+                        start: tag_start,
+                        inner: inner_start..start,
+                        end,
+                    });
+                }
+            }
+        }
+        pos = end;
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn yaml(s: &str) -> Mapping {
+        serde_yaml::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_compose_blocks_and_super() {
+        let base = (
+            yaml("autor: Ana\nestado: borrador"),
+            "# {{title}}\n{{#block contenido}}\nTexto base\n{{/block}}\n{{#block pie}}\nFin\n{{/block}}\n".to_string(),
+        );
+        let notebook = (
+            yaml("estado: abierto"),
+            "{{#block contenido}}\n{{super}}Extra\n{{/block}}\n".to_string(),
+        );
+
+        let (fm, body) = compose(vec![base, notebook]);
+        assert_eq!(fm, yaml("autor: Ana\nestado: abierto"));
+        assert_eq!(
+            body,
+            "# {{title}}\n{{#block contenido}}\nTexto base\nExtra\n{{/block}}\n{{#block pie}}\nFin\n{{/block}}\n"
+        );
+
+        // Un hijo sin bloques reemplaza el cuerpo
+        let (_, body) = compose(vec![
+            (Mapping::new(), "base".to_string()),
+            (Mapping::new(), "hijo".to_string()),
+        ]);
+        assert_eq!(body, "hijo");
+    }
+
+    #[test]
+    fn test_load_cascade_with_dirs_and_extends() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        let templates = vault.join("Templates");
+        fs::create_dir_all(templates.join("Notas")).unwrap();
+        fs::create_dir_all(vault.join("Notas/proyecto/acme")).unwrap();
+
+        fs::write(
+            templates.join("_base.md"),
+            "---\nautor: Ana\ncreated: \"{{date}}\"\n---\n# {{title}}\n{{#block cuerpo}}\n{{/block}}\n",
+        )
+        .unwrap();
+        fs::write(
+            templates.join("reunion.md"),
+            "---\ntipo: reunion\n---\n{{#block cuerpo}}\n## Asistentes\n{{/block}}\n",
+        )
+        .unwrap();
+        fs::write(
+            templates.join("Notas/meeting.md"),
+            "---\nextends: reunion\nestado: abierto\n---\n",
+        )
+        .unwrap();
+        fs::write(
+            vault.join("Notas/proyecto/template.txt"),
+            "---\nproyecto: acme\n---\n{{#block cuerpo}}\n{{super}}## Cliente\n{{/block}}\n",
+        )
+        .unwrap();
+
+        let (fm, body) = load_cascade(
+            &templates,
+            "Notas",
+            &templates.join("Notas/meeting.md"),
+            vault,
+            &vault.join("Notas/proyecto/acme"),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            fm,
+            yaml(
+                "autor: Ana\ncreated: \"{{date}}\"\ntipo: reunion\nestado: abierto\nproyecto: acme"
+            )
+        );
+        assert_eq!(
+            body,
+            // El cuerpo conserva el salto de línea tras el frontmatter
+            "\n# {{title}}\n{{#block cuerpo}}\n## Asistentes\n## Cliente\n{{/block}}\n"
+        );
+
+        assert!(load_cascade(
+            &vault.join("Vacio"),
+            "Notas",
+            &vault.join("Vacio/Notas.md"),
+            vault,
+            &vault.join("Otro"),
+        )
+        .unwrap()
+        .is_none());
+    }
+
+    #[test]
+    fn test_circular_extends_is_an_error() {
+        let temp = TempDir::new().unwrap();
+        let templates = temp.path();
+        fs::write(templates.join("a.md"), "---\nextends: b\n---\n").unwrap();
+        fs::write(templates.join("b.md"), "---\nextends: a\n---\n").unwrap();
+
+        let result = load_cascade(
+            templates,
+            "Notas",
+            &templates.join("a.md"),
+            templates,
+            templates,
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,168 @@
+pub mod engine;
+pub mod inherit;
+
+pub use engine::{Context, InputKind, TemplateInput};
+pub use inherit::load_cascade;
+
+use crate::core::frontmatter;
+use serde_yaml::{Mapping, Value};
+use slug::slugify;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Which template to use when creating a note
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TemplateSelection {
+    /// Template del notebook (`Templates/<dir>.md` o `<dir>/template.txt`)
+    #[default]
+    Default,
+    /// Elegir con el selector fuzzy (`--pick-template`)
+    Pick,
+    /// Template con nombre (`--template meeting`)
+    Named(String),
+}
+
+/// Named template of a notebook
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedTemplate {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Read template file path if exists, else return empty mapping/body
+pub fn read(path: &Path) -> anyhow::Result<(Mapping, String)> {
+    if !path.exists() {
+        return Ok((Mapping::new(), String::new()));
+    }
+    let txt = fs::read_to_string(path)?;
+    frontmatter::extract(&txt)
+}
+
+/// Render template body (variables, filters, `{{#if}}` and `{{#each}}` blocks)
+pub fn render_body(body: &str, ctx: &Context) -> anyhow::Result<String> {
+    engine::render(body, ctx)
+}
+
+/// Default template of a notebook: `Templates/<notebook>.md`, else `<dir>/template.txt`
+pub fn default_path(templates_root: &Path, notebook: &str, notebook_dir: &Path) -> PathBuf {
+    let centralized = templates_root.join(format!("{}.md", notebook));
+    if centralized.exists() {
+        centralized
+    } else {
+        notebook_dir.join("template.txt")
+    }
+}
+
+/// Named templates available for a notebook:
+/// - `Templates/<notebook>/<nombre>.md` (del notebook)
+/// - `Templates/_shared/<nombre>.md` (compartidos por todos los notebooks)
+///
+/// Notebook templates win over shared ones with the same name.
+pub fn named_templates(templates_root: &Path, notebook: &str) -> Vec<NamedTemplate> {
+    let mut found: Vec<NamedTemplate> = Vec::new();
+
+    for dir in [
+        templates_root.join(notebook),
+        templates_root.join(SHARED_DIR),
+    ] {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut dir_templates: Vec<NamedTemplate> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && p.extension().and_then(|e| e.to_str()) == Some("md"))
+            .filter_map(|path| {
+                let name = path.file_stem()?.to_str()?.to_string();
+                Some(NamedTemplate { name, path })
+            })
+            .filter(|t| !found.iter().any(|f| f.name == t.name))
+            .collect();
+        dir_templates.sort_by(|a, b| a.name.cmp(&b.name));
+        found.extend(dir_templates);
+    }
+
+    found
+}
+
+/// Find a named template (case-insensitive, `1:1` matches `1-1.md`)
+pub fn find_named(templates_root: &Path, notebook: &str, name: &str) -> anyhow::Result<PathBuf> {
+    let available = named_templates(templates_root, notebook);
+    let wanted = slugify(name);
+
+    available
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(name) || slugify(&t.name) == wanted)
+        .map(|t| t.path.clone())
+        .ok_or_else(|| {
+            let names: Vec<&str> = available.iter().map(|t| t.name.as_str()).collect();
+            if names.is_empty() {
+                anyhow::anyhow!(
+                    "Template '{}' no encontrado: no hay templates en {}/{}/ ni {}/{}/",
+                    name,
+                    templates_root.display(),
+                    notebook,
+                    templates_root.display(),
+                    SHARED_DIR
+                )
+            } else {
+                anyhow::anyhow!(
+                    "Template '{}' no encontrado. Disponibles: {}",
+                    name,
+                    names.join(", ")
+                )
+            }
+        })
+}
+
+/// Values the template asks for (`{{prompt:x}}`, `{{choice:x|a,b}}`), frontmatter first
+pub fn inputs(fm: &Mapping, body: &str) -> Vec<TemplateInput> {
+    let mut found = engine::value_inputs(&Value::Mapping(fm.clone()));
+    for input in engine::inputs(body) {
+        if !found.iter().any(|i| i.name == input.name) {
+            found.push(input);
+        }
+    }
+    found
+}
+
+const SHARED_DIR: &str = "_shared";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_named_templates_per_notebook() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("Notas")).unwrap();
+        fs::create_dir_all(root.join("_shared")).unwrap();
+        fs::write(root.join("Notas.md"), "default").unwrap();
+        fs::write(root.join("Notas/meeting.md"), "notebook").unwrap();
+        fs::write(root.join("Notas/1-1.md"), "1:1").unwrap();
+        fs::write(root.join("_shared/meeting.md"), "shared").unwrap();
+        fs::write(root.join("_shared/bug.md"), "bug").unwrap();
+
+        let names: Vec<String> = named_templates(root, "Notas")
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["1-1", "meeting", "bug"]);
+
+        assert_eq!(
+            find_named(root, "Notas", "Meeting").unwrap(),
+            root.join("Notas/meeting.md")
+        );
+        assert_eq!(
+            find_named(root, "Notas", "1:1").unwrap(),
+            root.join("Notas/1-1.md")
+        );
+        assert_eq!(
+            find_named(root, "Diario", "bug").unwrap(),
+            root.join("_shared/bug.md")
+        );
+        assert!(find_named(root, "Notas", "falta").is_err());
+    }
+}
@@ -0,0 +1,554 @@
+pub mod render;
+
+use crate::core::frontmatter;
+use crate::tags::parser::{extract_primary_tag, TagPath};
+use crate::tags::tree::TagNode;
+use crate::utils::vault::VaultWalker;
+use crate::vault::links::{normalize, NoteResolver};
+use crate::vault::rewrite::relative_path;
+use crate::vault::scan::{self, Link, LinkKind};
+use render::{anchor, escape, url, Target};
+use serde_yaml::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Note exported as a page
+struct Page {
+    /// Nota original (path absoluto)
+    source: PathBuf,
+    /// Path de la página dentro del sitio: `Notas/dev/nota.html`
+    url: String,
+    title: String,
+    dir_tag: Option<TagPath>,
+    body: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ExportStats {
+    pub notes: usize,
+    pub tags: usize,
+    pub attachments: usize,
+    /// Notas con `publish: false`
+    pub unpublished: usize,
+}
+
+/// Static site of the vault
+struct Site<'a> {
+    vault: &'a Path,
+    /// Por path absoluto de la nota
+    pages: BTreeMap<PathBuf, Page>,
+    tree: TagNode,
+    resolver: NoteResolver,
+    /// Adjuntos del vault por nombre de archivo (en minúsculas)
+    attachments: HashMap<String, PathBuf>,
+}
+
+/// Export the notes as a static HTML site in `out`, keeping the vault layout
+/// (`Notas/dev/nota.md` → `out/Notas/dev/nota.html`).
+/// - `tag`: only notes whose dir-tag is that tag or a descendant
+/// - Notes with `publish: false` are skipped and links to them are shown as broken
+/// - Wikilinks and Markdown links point to the other pages; attachments are copied
+/// - `out/tags/<tag>.html`: one index page per tag of the dir-tag tree
+pub fn html(
+    vault: &Path,
+    templates_path: &Path,
+    tag: Option<&TagPath>,
+    out: &Path,
+) -> anyhow::Result<ExportStats> {
+    let mut stats = ExportStats::default();
+    let mut notes = Vec::new();
+    let mut pages = BTreeMap::new();
+
+    VaultWalker::new(vault)
+        .exclude_templates(templates_path)
+        .walk(|path, content| {
+            notes.push(path.to_path_buf());
+
+            let (fm, body) = frontmatter::extract(content).unwrap_or_default();
+            let dir_tag = extract_primary_tag(&body);
+            if let Some(filter) = tag {
+                if !dir_tag.as_ref().is_some_and(|t| t.starts_with(filter)) {
+                    return Ok(());
+                }
+            }
+            if fm.get("publish").and_then(Value::as_bool) == Some(false) {
+                stats.unpublished += 1;
+                return Ok(());
+            }
+
+            let rel = path.strip_prefix(vault).unwrap_or(path);
+            let title = fm
+                .get("title")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .or_else(|| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+                .unwrap_or_default();
+
+            pages.insert(
+                path.to_path_buf(),
+                Page {
+                    source: path.to_path_buf(),
+                    url: to_slash(&rel.with_extension("html")),
+                    title,
+                    dir_tag,
+                    body: strip_dir_tag(&body).to_string(),
+                },
+            );
+            Ok(())
+        })?;
+
+    fs::create_dir_all(out)?;
+    let out_canonical = out.canonicalize()?;
+
+    let mut tree = TagNode::new("root".to_string());
+    for page in pages.values() {
+        if let Some(tag) = &page.dir_tag {
+            tree.insert_path(&tag.0);
+        }
+    }
+
+    let site = Site {
+        vault,
+        resolver: NoteResolver::new(vault, &notes),
+        attachments: attachments_by_name(vault, &out_canonical)?,
+        pages,
+        tree,
+    };
+
+    let mut copied: BTreeSet<PathBuf> = BTreeSet::new();
+    for page in site.pages.values() {
+        let html = site.note_page(page, &mut copied);
+        write(&out.join(&page.url), &html)?;
+    }
+    stats.notes = site.pages.len();
+
+    let mut tag_paths = Vec::new();
+    collect_tag_paths(&site.tree, &mut Vec::new(), &mut tag_paths);
+    for tag in &tag_paths {
+        write(&out.join(tag_url(tag)), &site.tag_page(tag))?;
+    }
+    stats.tags = tag_paths.len();
+    write(&out.join("index.html"), &site.index_page(tag))?;
+
+    for attachment in &copied {
+        // Solo adjuntos del vault, y siempre dentro de `out`
+        let Ok(rel) = attachment.strip_prefix(vault) else {
+            continue;
+        };
+        let dest = normalize(&out.join(rel));
+        if !dest.starts_with(normalize(out)) {
+            anyhow::bail!("Adjunto fuera del directorio de salida: {}", attachment.display());
+        }
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(attachment, dest)?;
+    }
+    stats.attachments = copied.len();
+
+    Ok(stats)
+}
+
+impl Site<'_> {
+    fn note_page(&self, page: &Page, copied: &mut BTreeSet<PathBuf>) -> String {
+        let dir = parent_dir(&page.url);
+        let content = render::markdown(&page.body, |dest, wiki| {
+            self.resolve(page, &dir, dest, wiki, copied)
+        });
+
+        let header = match &page.dir_tag {
+            Some(tag) => self.breadcrumbs(&tag.0, &root_prefix(&page.url)),
+            None => String::new(),
+        };
+        render::page(
+            &page.title,
+            &root_prefix(&page.url),
+            &self.sidebar(&root_prefix(&page.url), Some(&page.url)),
+            &header,
+            &content,
+        )
+    }
+
+    /// Destination of a link or image written in `page`
+    fn resolve(
+        &self,
+        page: &Page,
+        dir: &Path,
+        dest: &str,
+        wiki: bool,
+        copied: &mut BTreeSet<PathBuf>,
+    ) -> Option<Target> {
+        if dest.contains("://") || dest.starts_with("mailto:") {
+            return Some(Target::File(dest.to_string()));
+        }
+        if let Some(heading) = dest.strip_prefix('#') {
+            return Some(Target::Page(format!("#{}", anchor(heading))));
+        }
+
+        let (target, heading) = match dest.split_once('#') {
+            Some((t, h)) => (t, Some(h)),
+            None => (dest, None),
+        };
+        let target = if wiki {
+            target.to_string()
+        } else {
+            scan::percent_decode(target)
+        };
+
+        let is_note = match Path::new(&target).extension() {
+            Some(ext) => ext.eq_ignore_ascii_case("md"),
+            None => true,
+        };
+
+        if is_note {
+            let note = if wiki {
+                self.resolver.resolve_name(&target, Some(&page.source))
+            } else {
+                let link = Link {
+                    kind: LinkKind::Markdown,
+                    target,
+                    heading: None,
+                    label: None,
+                    line: 0,
+                    target_span: 0..0,
+                };
+                self.resolver.resolve(&page.source, &link)
+            }?;
+            let linked = self.pages.get(&note)?;
+            let mut href = url(&relative_path(dir, Path::new(&linked.url)));
+            if let Some(heading) = heading {
+                href.push('#');
+                href.push_str(&anchor(heading));
+            }
+            return Some(Target::Page(href));
+        }
+
+        let file = if wiki {
+            let in_vault = normalize(&self.vault.join(target.trim_start_matches('/')));
+            if in_vault.starts_with(self.vault) && in_vault.is_file() {
+                Some(in_vault)
+            } else {
+                let name = Path::new(&target).file_name()?.to_string_lossy().to_lowercase();
+                self.attachments.get(&name).cloned()
+            }
+        } else {
+            let path = normalize(&page.source.parent()?.join(&target));
+            (path.starts_with(self.vault) && path.is_file()).then_some(path)
+        }?;
+
+        let rel = file.strip_prefix(self.vault).ok()?.to_path_buf();
+        copied.insert(file);
+        Some(Target::File(url(&relative_path(dir, &rel))))
+    }
+
+    /// Page of a tag: its subtags and the notes with exactly that dir-tag
+    fn tag_page(&self, tag: &[String]) -> String {
+        let page_url = tag_url(tag);
+        let root = root_prefix(&page_url);
+        let node = tag.iter().try_fold(&self.tree, |node, name| node.get_child(name));
+
+        let mut content = String::new();
+        if let Some(node) = node.filter(|n| !n.children.is_empty()) {
+            content.push_str("<h2>Subtags</h2>\n<ul>\n");
+            for name in node.get_children_names() {
+                let mut child = tag.to_vec();
+                child.push(name.clone());
+                content.push_str(&format!(
+                    "<li><a href=\"{}{}\">{}</a> ({})</li>\n",
+                    root,
+                    url(&tag_url(&child)),
+                    escape(&name),
+                    self.notes_under(&child).len()
+                ));
+            }
+            content.push_str("</ul>\n");
+        }
+
+        let notes = self.notes_with(Some(tag));
+        if !notes.is_empty() {
+            content.push_str("<h2>Notas</h2>\n");
+            content.push_str(&self.note_list(&notes, &root));
+        }
+
+        let parents = &tag[..tag.len() - 1];
+        render::page(
+            &format!("#{}", tag.join("/")),
+            &root,
+            &self.sidebar(&root, Some(&page_url)),
+            &self.breadcrumbs(parents, &root),
+            &content,
+        )
+    }
+
+    fn index_page(&self, filter: Option<&TagPath>) -> String {
+        let mut content = format!("<p>{} notas</p>\n", self.pages.len());
+
+        if !self.tree.children.is_empty() {
+            content.push_str("<h2>Tags</h2>\n<ul>\n");
+            for name in self.tree.get_children_names() {
+                let tag = vec![name.clone()];
+                content.push_str(&format!(
+                    "<li><a href=\"{}\">{}</a> ({})</li>\n",
+                    url(&tag_url(&tag)),
+                    escape(&name),
+                    self.notes_under(&tag).len()
+                ));
+            }
+            content.push_str("</ul>\n");
+        }
+
+        let untagged = self.notes_with(None);
+        if !untagged.is_empty() {
+            content.push_str("<h2>Otras notas</h2>\n");
+            content.push_str(&self.note_list(&untagged, ""));
+        }
+
+        let title = match filter {
+            Some(tag) => format!("#{}", tag.to_slash_string()),
+            None => "Vault".to_string(),
+        };
+        render::page(&title, "", &self.sidebar("", Some("index.html")), "", &content)
+    }
+
+    /// Navigation mirroring the dir-tag tree, with the notes of each tag
+    fn sidebar(&self, root: &str, current: Option<&str>) -> String {
+        let mut html = String::new();
+        self.sidebar_node(&self.tree, &mut Vec::new(), root, current, &mut html);
+
+        let untagged = self.notes_with(None);
+        if !untagged.is_empty() {
+            html.push_str("<ul>\n<li><span class=\"tag\">Otras notas</span>\n");
+            html.push_str(&self.sidebar_notes(&untagged, root, current));
+            html.push_str("</li>\n</ul>\n");
+        }
+        html
+    }
+
+    fn sidebar_node(
+        &self,
+        node: &TagNode,
+        path: &mut Vec<String>,
+        root: &str,
+        current: Option<&str>,
+        html: &mut String,
+    ) {
+        if node.children.is_empty() {
+            return;
+        }
+
+        html.push_str("<ul>\n");
+        for (name, child) in &node.children {
+            path.push(name.clone());
+            let href = tag_url(path);
+            html.push_str(&format!(
+                "<li><a class=\"tag{}\" href=\"{}{}\">{}</a>\n",
+                if current == Some(href.as_str()) { " current" } else { "" },
+                root,
+                url(&href),
+                escape(name)
+            ));
+            self.sidebar_node(child, path, root, current, html);
+            html.push_str(&self.sidebar_notes(&self.notes_with(Some(path)), root, current));
+            html.push_str("</li>\n");
+            path.pop();
+        }
+        html.push_str("</ul>\n");
+    }
+
+    fn sidebar_notes(&self, notes: &[&Page], root: &str, current: Option<&str>) -> String {
+        if notes.is_empty() {
+            return String::new();
+        }
+
+        let mut html = String::from("<ul>\n");
+        for page in notes {
+            html.push_str(&format!(
+                "<li><a{} href=\"{}{}\">{}</a></li>\n",
+                if current == Some(page.url.as_str()) { " class=\"current\"" } else { "" },
+                root,
+                url(&page.url),
+                escape(&page.title)
+            ));
+        }
+        html.push_str("</ul>\n");
+        html
+    }
+
+    fn note_list(&self, notes: &[&Page], root: &str) -> String {
+        let mut html = String::from("<ul>\n");
+        for page in notes {
+            html.push_str(&format!(
+                "<li><a href=\"{}{}\">{}</a></li>\n",
+                root,
+                url(&page.url),
+                escape(&page.title)
+            ));
+        }
+        html.push_str("</ul>\n");
+        html
+    }
+
+    /// `dev › magic-documents`, each part linking to its tag page
+    fn breadcrumbs(&self, tag: &[String], root: &str) -> String {
+        if tag.is_empty() {
+            return String::new();
+        }
+
+        let links: Vec<String> = (1..=tag.len())
+            .map(|n| {
+                format!(
+                    "<a href=\"{}{}\">{}</a>",
+                    root,
+                    url(&tag_url(&tag[..n])),
+                    escape(&tag[n - 1])
+                )
+            })
+            .collect();
+        format!("<p class=\"tags\">{}</p>\n", links.join(" › "))
+    }
+
+    /// Pages whose dir-tag is exactly `tag` (None: pages without dir-tag), by title
+    fn notes_with(&self, tag: Option<&[String]>) -> Vec<&Page> {
+        let mut notes: Vec<&Page> = self
+            .pages
+            .values()
+            .filter(|p| p.dir_tag.as_ref().map(|t| t.0.as_slice()) == tag)
+            .collect();
+        notes.sort_by_key(|p| p.title.to_lowercase());
+        notes
+    }
+
+    /// Pages whose dir-tag is `tag` or a descendant
+    fn notes_under(&self, tag: &[String]) -> Vec<&Page> {
+        let tag = TagPath(tag.to_vec());
+        self.pages
+            .values()
+            .filter(|p| p.dir_tag.as_ref().is_some_and(|t| t.starts_with(&tag)))
+            .collect()
+    }
+}
+
+/// The `{ #tag }` line is shown as breadcrumbs, not as text
+fn strip_dir_tag(body: &str) -> &str {
+    let trimmed = body.trim_start();
+    if extract_primary_tag(trimmed).is_some() {
+        if let Some(end) = trimmed.find(" }") {
+            return &trimmed[end + 2..];
+        }
+    }
+    body
+}
+
+/// Files that are not notes, by name. Skips hidden directories and the output directory.
+fn attachments_by_name(vault: &Path, out: &Path) -> anyhow::Result<HashMap<String, PathBuf>> {
+    fn walk(dir: &Path, out: &Path, found: &mut HashMap<String, PathBuf>) -> anyhow::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            if name.starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                if path.canonicalize().ok().as_deref() != Some(out) {
+                    walk(&path, out, found)?;
+                }
+            } else if path.extension().is_none_or(|e| !e.eq_ignore_ascii_case("md")) {
+                found.entry(name.to_lowercase()).or_insert(path);
+            }
+        }
+        Ok(())
+    }
+
+    let mut found = HashMap::new();
+    walk(vault, out, &mut found)?;
+    Ok(found)
+}
+
+fn collect_tag_paths(node: &TagNode, path: &mut Vec<String>, out: &mut Vec<Vec<String>>) {
+    for (name, child) in &node.children {
+        path.push(name.clone());
+        out.push(path.clone());
+        collect_tag_paths(child, path, out);
+        path.pop();
+    }
+}
+
+/// `tags/dev/magic-documents.html`
+fn tag_url(tag: &[String]) -> String {
+    format!("tags/{}.html", tag.join("/"))
+}
+
+fn parent_dir(page_url: &str) -> PathBuf {
+    Path::new(page_url).parent().map(Path::to_path_buf).unwrap_or_default()
+}
+
+/// `../../` for `Notas/dev/nota.html`
+fn root_prefix(page_url: &str) -> String {
+    "../".repeat(page_url.matches('/').count())
+}
+
+fn to_slash(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn write(path: &Path, content: &str) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_export_subtree() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path().join("vault");
+        let out = temp.path().join("site");
+        fs::create_dir_all(vault.join("Notas/dev/api")).unwrap();
+        fs::create_dir_all(vault.join("Notas/ops")).unwrap();
+        fs::create_dir_all(vault.join("adjuntos")).unwrap();
+        fs::write(
+            vault.join("Notas/dev/api/spec.md"),
+            "---\ntitle: Especificación\n---\n{ #dev/api }\n\n## Próximos pasos\n\n\
+             Ver [[plan#Fechas]], [[ops]], [[privada]] y [el plan](../plan.md).\n\n![[diagrama.png]] ![[../secreto.txt]]\n",
+        )
+        .unwrap();
+        fs::write(vault.join("Notas/dev/plan.md"), "{ #dev }\n\n## Fechas\n").unwrap();
+        fs::write(vault.join("Notas/dev/privada.md"), "---\npublish: false\n---\n{ #dev }\n").unwrap();
+        fs::write(vault.join("Notas/ops/ops.md"), "{ #ops }\n").unwrap();
+        fs::write(vault.join("adjuntos/diagrama.png"), "png").unwrap();
+        fs::write(temp.path().join("secreto.txt"), "fuera del vault").unwrap();
+
+        let stats = html(&vault, &vault.join("Templates"), Some(&TagPath::from_slash("dev")), &out).unwrap();
+        assert_eq!((stats.notes, stats.tags, stats.attachments, stats.unpublished), (2, 2, 1, 1));
+
+        let spec = fs::read_to_string(out.join("Notas/dev/api/spec.html")).unwrap();
+        assert!(spec.contains("<title>Especificación</title>"));
+        assert!(spec.contains(r#"<h2 id="proximos-pasos">"#));
+        assert!(spec.contains(r#"<a href="../plan.html#fechas">plan#Fechas</a>"#));
+        assert!(spec.contains(r#"<a href="../plan.html">el plan</a>"#));
+        assert!(spec.contains(r#"<span class="broken">ops</span>"#));
+        assert!(spec.contains(r#"<span class="broken">privada</span>"#));
+        assert!(spec.contains(r#"<img src="../../../adjuntos/diagrama.png""#));
+        assert!(spec.contains(r#"<a href="../../../tags/dev/api.html">api</a>"#));
+        assert!(!spec.contains("{ #dev/api }"));
+
+        assert!(out.join("adjuntos/diagrama.png").is_file());
+        assert!(!spec.contains("secreto.txt\""));
+        assert!(!out.join("Notas/dev/privada.html").exists());
+        assert!(!out.join("Notas/ops/ops.html").exists());
+        let tag = fs::read_to_string(out.join("tags/dev.html")).unwrap();
+        assert!(tag.contains(r#"<a href="../tags/dev/api.html">api</a> (1)"#));
+        assert!(tag.contains(r#"<a href="../Notas/dev/plan.html">plan</a>"#));
+        assert!(out.join("index.html").is_file());
+    }
+}
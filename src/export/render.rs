@@ -0,0 +1,191 @@
+use pulldown_cmark::{html, CowStr, Event, LinkType, Options, Parser, Tag, TagEnd};
+
+/// Where a link or image of a note points to in the site
+pub enum Target {
+    /// Otra página exportada (los `![[nota]]` se muestran como link)
+    Page(String),
+    /// Adjunto copiado o URL externa
+    File(String),
+}
+
+/// What replaced an opening link/image event, to close it the same way
+enum Open {
+    Keep,
+    Broken,
+    ImageAsLink,
+}
+
+/// Render a note body to HTML.
+/// `resolve(dest, is_wiki)` maps each link/image destination to the site; None marks it as broken.
+/// Headings get an `id` (slug of their text) so `[[nota#Título]]` can point to them.
+pub fn markdown(body: &str, mut resolve: impl FnMut(&str, bool) -> Option<Target>) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_WIKILINKS;
+
+    let mut events: Vec<Event> = Vec::new();
+    let mut open: Vec<Open> = Vec::new();
+    let mut heading_start: Option<usize> = None;
+
+    for event in Parser::new_ext(body, options) {
+        match event {
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => match resolve(&dest_url, is_wiki(link_type)) {
+                Some(Target::Page(url) | Target::File(url)) => {
+                    open.push(Open::Keep);
+                    events.push(Event::Start(Tag::Link {
+                        link_type,
+                        dest_url: url.into(),
+                        title,
+                        id,
+                    }));
+                }
+                None => {
+                    open.push(Open::Broken);
+                    events.push(Event::Html(r#"<span class="broken">"#.into()));
+                }
+            },
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => match resolve(&dest_url, is_wiki(link_type)) {
+                Some(Target::File(url)) => {
+                    open.push(Open::Keep);
+                    events.push(Event::Start(Tag::Image {
+                        link_type,
+                        dest_url: url.into(),
+                        title,
+                        id,
+                    }));
+                }
+                Some(Target::Page(url)) => {
+                    open.push(Open::ImageAsLink);
+                    events.push(Event::Start(Tag::Link {
+                        link_type,
+                        dest_url: url.into(),
+                        title,
+                        id,
+                    }));
+                }
+                None => {
+                    open.push(Open::Broken);
+                    events.push(Event::Html(r#"<span class="broken">"#.into()));
+                }
+            },
+            Event::End(end @ (TagEnd::Link | TagEnd::Image)) => match open.pop() {
+                Some(Open::Broken) => events.push(Event::Html("</span>".into())),
+                Some(Open::ImageAsLink) => events.push(Event::End(TagEnd::Link)),
+                _ => events.push(Event::End(end)),
+            },
+            Event::Start(Tag::Heading { .. }) => {
+                heading_start = Some(events.len());
+                events.push(event);
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some(start) = heading_start.take() {
+                    let text: String = events[start + 1..]
+                        .iter()
+                        .filter_map(|e| match e {
+                            Event::Text(t) | Event::Code(t) => Some(t.as_ref()),
+                            _ => None,
+                        })
+                        .collect();
+                    if let Event::Start(Tag::Heading { id, .. }) = &mut events[start] {
+                        if id.is_none() {
+                            *id = Some(CowStr::from(anchor(&text)));
+                        }
+                    }
+                }
+                events.push(event);
+            }
+            other => events.push(other),
+        }
+    }
+
+    let mut out = String::new();
+    html::push_html(&mut out, events.into_iter());
+    out
+}
+
+fn is_wiki(link_type: LinkType) -> bool {
+    matches!(link_type, LinkType::WikiLink { .. })
+}
+
+/// `id` of a heading: `## Próximos pasos` → `proximos-pasos`
+pub fn anchor(heading: &str) -> String {
+    slug::slugify(heading)
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Path relative to the page, usable in `href`/`src`
+pub fn url(path: &str) -> String {
+    path.replace('%', "%25")
+        .replace(' ', "%20")
+        .replace('#', "%23")
+        .replace('?', "%3F")
+        .replace('"', "%22")
+}
+
+/// Standalone page: `root` is the relative prefix to the site root (`../../`)
+pub fn page(title: &str, root: &str, sidebar: &str, header: &str, content: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="es">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>{css}</style>
+</head>
+<body>
+<nav class="sidebar">
+<a class="home" href="{root}index.html">Inicio</a>
+{sidebar}</nav>
+<main>
+{header}<h1>{title}</h1>
+{content}</main>
+</body>
+</html>
+"#,
+        title = escape(title),
+        css = CSS,
+        root = root,
+        sidebar = sidebar,
+        header = header,
+        content = content,
+    )
+}
+
+const CSS: &str = "
+body { margin: 0; display: flex; font-family: system-ui, sans-serif; line-height: 1.6; color: #222; }
+.sidebar { width: 16rem; flex-shrink: 0; padding: 1rem; border-right: 1px solid #ddd; background: #fafafa; min-height: 100vh; font-size: 0.9rem; }
+.sidebar ul { list-style: none; padding-left: 1rem; margin: 0; }
+.sidebar > ul { padding-left: 0; }
+.sidebar .tag { font-weight: 600; }
+.sidebar .current { font-weight: 700; }
+.home { display: block; margin-bottom: 1rem; font-weight: 700; }
+main { max-width: 48rem; padding: 1rem 2rem; }
+.tags a { margin-right: 0.5rem; }
+.broken { color: #b00; text-decoration: line-through dotted; }
+a { color: #0b5cad; text-decoration: none; }
+a:hover { text-decoration: underline; }
+pre { background: #f4f4f4; padding: 0.75rem; overflow-x: auto; }
+code { background: #f4f4f4; padding: 0 0.2rem; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #ddd; padding: 0.25rem 0.5rem; }
+img { max-width: 100%; }
+";
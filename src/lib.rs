@@ -1,5 +1,8 @@
+pub mod caldav;
 pub mod commands;
 pub mod core;
+pub mod export;
+pub mod search;
 pub mod tags;
 pub mod ui;
 pub mod utils;
@@ -12,75 +12,167 @@ fn main() -> anyhow::Result<()> {
         ValidatedArgs::Tman(action) => {
             let (_, vault) = load_config()?;
             match action {
-                TmanAction::List => commands::tman::list_tags(&vault, false)?,
-                TmanAction::Rename => commands::tman::rename_tags(&vault)?,
-                TmanAction::Find => commands::tman::find_by_tag(&vault)?,
+                TmanAction::List(format) => commands::tman::list_tags(&vault, false, format)?,
+                TmanAction::Rename {
+                    mode,
+                    from,
+                    to,
+                    recursive,
+                    yes,
+                } => commands::tman::rename_tags(
+                    &vault,
+                    &mode,
+                    from.as_deref(),
+                    to.as_deref(),
+                    recursive,
+                    yes,
+                )?,
+                TmanAction::Find { tag, format } => {
+                    commands::tman::find_by_tag(&vault, tag.as_deref(), format)?
+                }
                 TmanAction::Log => commands::tman::visual_selector()?,
             }
         }
         ValidatedArgs::Daily {
+            target,
+            rollover,
             editor,
             skip_timestamp,
+            template,
+            yes,
+        } => {
+            let (mut config, vault) = load_config()?;
+            if skip_timestamp {
+                config.timeprint = Some(false);
+            }
+            if rollover.is_some() {
+                config.rollover.enabled = rollover;
+            }
+            let editor_cmd = resolve_editor(&config, editor);
+            commands::daily::run(config, vault, editor_cmd, target, template, yes)?;
+        }
+        ValidatedArgs::Periodic {
+            period,
+            editor,
+            skip_timestamp,
+            template,
+            yes,
         } => {
             let (mut config, vault) = load_config()?;
             if skip_timestamp {
                 config.timeprint = Some(false);
             }
             let editor_cmd = resolve_editor(&config, editor);
-            commands::daily::run(config, vault, editor_cmd)?;
+            commands::periodic::run(config, vault, editor_cmd, period, template, yes)?;
         }
         ValidatedArgs::Last {
             count,
             editor,
             skip_timestamp,
+            format,
+        } => {
+            let (mut config, vault) = load_config()?;
+            if skip_timestamp {
+                config.timeprint = Some(false);
+            }
+            let editor_cmd = resolve_editor(&config, editor);
+            commands::last::run(vault, config, count, editor_cmd, format)?;
+        }
+        ValidatedArgs::Search {
+            query,
+            limit,
+            rebuild,
+            editor,
+            skip_timestamp,
+        } => {
+            let (mut config, vault) = load_config()?;
+            if skip_timestamp {
+                config.timeprint = Some(false);
+            }
+            let editor_cmd = resolve_editor(&config, editor);
+            commands::search::run(vault, config, &query, limit, rebuild, editor_cmd)?;
+        }
+        ValidatedArgs::Query { query, format } => {
+            let (config, vault) = load_config()?;
+            commands::query::run(&vault, &config, &query, format)?;
+        }
+        ValidatedArgs::Links {
+            note,
+            direction,
+            editor,
+            skip_timestamp,
         } => {
             let (mut config, vault) = load_config()?;
             if skip_timestamp {
                 config.timeprint = Some(false);
             }
             let editor_cmd = resolve_editor(&config, editor);
-            commands::last::run(vault, config, count, editor_cmd)?;
+            commands::links::run(vault, config, &note, direction, editor_cmd)?;
         }
         ValidatedArgs::Create {
             title,
             target_dir,
             editor,
             skip_timestamp,
+            template,
+            answers,
         } => {
             let (mut config, vault) = load_config()?;
             if skip_timestamp {
                 config.timeprint = Some(false);
             }
             let editor_cmd = resolve_editor(&config, editor);
-            commands::create::run(config, vault, title, target_dir, editor_cmd)?;
+            commands::create::run(config, vault, title, target_dir, editor_cmd, template, answers)?;
         }
-        ValidatedArgs::Retag { target, no_backup, no_alias } => {
+        ValidatedArgs::Retag {
+            target,
+            no_backup,
+            no_alias,
+            mode,
+        } => {
             let (config, vault) = load_config()?;
-            commands::retag::run(&vault, &config, &target, no_backup, no_alias)?;
+            commands::retag::run(&vault, &config, &target, no_backup, no_alias, &mode)?;
         }
-        ValidatedArgs::Redir { target, no_backup } => {
+        ValidatedArgs::Redir {
+            target,
+            no_backup,
+            pick_first,
+            mode,
+        } => {
             let (config, vault) = load_config()?;
-            commands::redir::run(&vault, &config, &target, no_backup)?;
+            commands::redir::run(&vault, &config, &target, no_backup, pick_first, &mode)?;
         }
         ValidatedArgs::Obsidian {
             title,
             editor,
             skip_timestamp,
+            template,
+            answers,
         } => {
             let (mut config, vault) = load_config()?;
             if skip_timestamp {
                 config.timeprint = Some(false);
             }
             let editor_cmd = resolve_editor(&config, editor);
-            commands::obsidian::run(&vault, config, title, editor_cmd)?;
+            commands::obsidian::run(&vault, config, title, editor_cmd, template, answers)?;
+        }
+        ValidatedArgs::Tasks {
+            mark_all,
+            full,
+            format,
+            filter,
+            yes,
+        } => {
+            let (config, vault) = load_config()?;
+            commands::todo::run(vault, config, mark_all, full, format, &filter, yes)?;
         }
-        ValidatedArgs::Tasks { mark_all, full } => {
+        ValidatedArgs::TasksSync { dry_run } => {
             let (config, vault) = load_config()?;
-            commands::todo::run(vault, config, mark_all, full)?;
+            commands::sync::run(&vault, &config, dry_run)?;
         }
-        ValidatedArgs::Cache { kind } => {
+        ValidatedArgs::Cache { kind, format } => {
             let (config, vault) = load_config()?;
-            commands::cache::run(&vault, &config, kind)?;
+            commands::cache::run(&vault, &config, kind, format)?;
         }
         ValidatedArgs::Alias { name, command } => {
             let aliases = mad::utils::alias::load_aliases()?;
@@ -94,9 +186,60 @@ fn main() -> anyhow::Result<()> {
             mad::utils::alias::save_aliases(&updated)?;
             println!("✅ Alias creado: {} → {}", name, command);
         }
-        ValidatedArgs::Rename { new_name, no_retag } => {
+        ValidatedArgs::Rename {
+            new_name,
+            no_retag,
+            mode,
+        } => {
             let (config, _vault) = load_config()?;
-            commands::rename::run(&config, &new_name, no_retag)?;
+            commands::rename::run(&config, &new_name, no_retag, &mode)?;
+        }
+        ValidatedArgs::Migrate { mode } => {
+            let (config, vault) = load_config()?;
+            commands::migrate::run(&vault, &config, &mode)?;
+        }
+        ValidatedArgs::Apply { plan } => {
+            let (_config, vault) = load_config()?;
+            commands::apply::run(&vault, &plan)?;
+        }
+        ValidatedArgs::Undo { list } => {
+            let (_config, vault) = load_config()?;
+            commands::undo::run(&vault, list)?;
+        }
+        ValidatedArgs::Backups(action) => {
+            let (config, vault) = load_config()?;
+            commands::backups::run(&vault, &config, action)?;
+        }
+        ValidatedArgs::Redo => {
+            let (_config, vault) = load_config()?;
+            commands::undo::redo(&vault)?;
+        }
+        ValidatedArgs::Capture {
+            text,
+            task,
+            target,
+            heading,
+        } => {
+            let (config, vault) = load_config()?;
+            commands::capture::run(&config, &vault, text, task, target, heading)?;
+        }
+        ValidatedArgs::Append {
+            note,
+            text,
+            heading,
+        } => {
+            let (config, vault) = load_config()?;
+            commands::capture::append(&config, &vault, &note, text, heading)?;
+        }
+        ValidatedArgs::Export { tag, out } => {
+            let (config, vault) = load_config()?;
+            commands::export::run(&vault, &config, tag.as_deref(), &out)?;
+        }
+        ValidatedArgs::Check { fix, format, mode } => {
+            let (config, vault) = load_config()?;
+            if commands::check::run(&vault, &config, fix, format, &mode)? {
+                std::process::exit(1);
+            }
         }
     }
 
@@ -0,0 +1,325 @@
+use crate::utils::vault::VaultWalker;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const INDEX_VERSION: u32 = 1;
+
+/// Documento indexado (path relativo al vault)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedDoc {
+    pub path: String,
+    pub mtime: u64,
+    /// Cantidad de tokens del documento (para normalizar el ranking)
+    pub len: u32,
+}
+
+/// Ocurrencias de un término en un documento
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Posting {
+    pub doc: u32,
+    pub positions: Vec<u32>,
+}
+
+/// Índice invertido persistente del vault
+/// - `docs`: id → documento
+/// - `postings`: término → documentos donde aparece (ordenado, permite búsqueda por prefijo)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchIndex {
+    version: u32,
+    vault: String,
+    next_id: u32,
+    pub(crate) docs: BTreeMap<u32, IndexedDoc>,
+    pub(crate) postings: BTreeMap<String, Vec<Posting>>,
+}
+
+/// Resumen de una actualización incremental
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RefreshStats {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+impl RefreshStats {
+    pub fn changed(&self) -> bool {
+        self.added + self.updated + self.removed > 0
+    }
+}
+
+impl SearchIndex {
+    /// Crea un índice vacío para el vault
+    pub fn new(vault: &Path) -> Self {
+        Self {
+            version: INDEX_VERSION,
+            vault: vault.to_string_lossy().to_string(),
+            next_id: 0,
+            docs: BTreeMap::new(),
+            postings: BTreeMap::new(),
+        }
+    }
+
+    /// Carga el índice desde disco.
+    /// Si no existe, es de otra versión o de otro vault, devuelve un índice vacío.
+    pub fn load(index_path: &Path, vault: &Path) -> Self {
+        if let Ok(content) = fs::read_to_string(index_path) {
+            if let Ok(index) = serde_json::from_str::<SearchIndex>(&content) {
+                if index.version == INDEX_VERSION && index.vault == vault.to_string_lossy() {
+                    return index;
+                }
+            }
+        }
+        Self::new(vault)
+    }
+
+    pub fn save(&self, index_path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = index_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string(self)?;
+        fs::write(index_path, json)?;
+        Ok(())
+    }
+
+    /// Cantidad de documentos indexados
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Actualiza el índice re-leyendo solo los archivos cuyo mtime cambió.
+    /// Los archivos que ya no existen se eliminan del índice.
+    pub fn refresh(&mut self, vault: &Path, templates_path: &Path) -> anyhow::Result<RefreshStats> {
+        let mut stats = RefreshStats::default();
+
+        let by_path: HashMap<String, (u32, u64)> = self
+            .docs
+            .iter()
+            .map(|(id, doc)| (doc.path.clone(), (*id, doc.mtime)))
+            .collect();
+
+        let mut seen: HashSet<u32> = HashSet::new();
+        let mut stale: HashSet<u32> = HashSet::new();
+        let mut pending: Vec<(PathBuf, String, u64, bool)> = Vec::new();
+
+        VaultWalker::new(vault)
+            .exclude_templates(templates_path)
+            .walk_paths(|path| {
+                let rel = relative_key(vault, path);
+                let mtime = file_mtime(path);
+                match by_path.get(&rel) {
+                    Some((id, indexed_mtime)) => {
+                        seen.insert(*id);
+                        if *indexed_mtime != mtime {
+                            stale.insert(*id);
+                            pending.push((path.to_path_buf(), rel, mtime, true));
+                        }
+                    }
+                    None => pending.push((path.to_path_buf(), rel, mtime, false)),
+                }
+                Ok(())
+            })?;
+
+        for id in self.docs.keys() {
+            if !seen.contains(id) {
+                stale.insert(*id);
+                stats.removed += 1;
+            }
+        }
+
+        self.remove_docs(&stale);
+
+        for (path, rel, mtime, existed) in pending {
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            self.add_doc(rel, mtime, &content);
+            if existed {
+                stats.updated += 1;
+            } else {
+                stats.added += 1;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Indexa un documento nuevo
+    pub fn add_doc(&mut self, rel_path: String, mtime: u64, content: &str) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let tokens = tokenize(content);
+        let mut positions: HashMap<String, Vec<u32>> = HashMap::new();
+        for (pos, token) in tokens.iter().enumerate() {
+            positions.entry(token.clone()).or_default().push(pos as u32);
+        }
+
+        for (term, positions) in positions {
+            self.postings
+                .entry(term)
+                .or_default()
+                .push(Posting { doc: id, positions });
+        }
+
+        self.docs.insert(
+            id,
+            IndexedDoc {
+                path: rel_path,
+                mtime,
+                len: tokens.len() as u32,
+            },
+        );
+    }
+
+    fn remove_docs(&mut self, ids: &HashSet<u32>) {
+        if ids.is_empty() {
+            return;
+        }
+        for id in ids {
+            self.docs.remove(id);
+        }
+        self.postings.retain(|_, list| {
+            list.retain(|p| !ids.contains(&p.doc));
+            !list.is_empty()
+        });
+    }
+
+    /// Postings de un término exacto
+    pub(crate) fn term_postings(&self, term: &str) -> &[Posting] {
+        self.postings.get(term).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// Términos que comienzan con `prefix`
+    pub(crate) fn prefix_terms<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = (&'a String, &'a Vec<Posting>)> + 'a {
+        self.postings
+            .range(prefix.to_string()..)
+            .take_while(move |(term, _)| term.starts_with(prefix))
+    }
+
+    /// Largo promedio de documento (en tokens)
+    pub(crate) fn avg_len(&self) -> f64 {
+        if self.docs.is_empty() {
+            return 0.0;
+        }
+        let total: u64 = self.docs.values().map(|d| d.len as u64).sum();
+        total as f64 / self.docs.len() as f64
+    }
+}
+
+/// Divide un texto en tokens normalizados (minúsculas, sin acentos)
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            for lower in c.to_lowercase() {
+                current.push(fold_accent(lower));
+            }
+        } else if !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+    }
+
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+/// Quita diacríticos comunes para que "mañana" coincida con "manana"
+pub(crate) fn fold_accent(c: char) -> char {
+    match c {
+        'á' | 'à' | 'ä' | 'â' | 'ã' => 'a',
+        'é' | 'è' | 'ë' | 'ê' => 'e',
+        'í' | 'ì' | 'ï' | 'î' => 'i',
+        'ó' | 'ò' | 'ö' | 'ô' | 'õ' => 'o',
+        'ú' | 'ù' | 'ü' | 'û' => 'u',
+        'ñ' => 'n',
+        'ç' => 'c',
+        other => other,
+    }
+}
+
+fn relative_key(vault: &Path, path: &Path) -> String {
+    path.strip_prefix(vault)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+fn file_mtime(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tokenize_folds_case_and_accents() {
+        let tokens = tokenize("Reunión de MAÑANA: api-v2");
+        assert_eq!(tokens, vec!["reunion", "de", "manana", "api", "v2"]);
+    }
+
+    #[test]
+    fn test_refresh_is_incremental() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        let templates = vault.join("Templates");
+
+        fs::write(vault.join("a.md"), "alpha beta").unwrap();
+        fs::write(vault.join("b.md"), "gamma").unwrap();
+
+        let mut index = SearchIndex::new(vault);
+        let stats = index.refresh(vault, &templates).unwrap();
+        assert_eq!(stats.added, 2);
+        assert_eq!(index.len(), 2);
+
+        // Sin cambios: no se re-indexa nada
+        let stats = index.refresh(vault, &templates).unwrap();
+        assert!(!stats.changed());
+
+        fs::remove_file(vault.join("b.md")).unwrap();
+        let stats = index.refresh(vault, &templates).unwrap();
+        assert_eq!(stats.removed, 1);
+        assert_eq!(index.len(), 1);
+        assert!(index.term_postings("gamma").is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path().join("vault");
+        fs::create_dir_all(&vault).unwrap();
+        let index_path = temp.path().join("search_index.json");
+
+        let mut index = SearchIndex::new(&vault);
+        index.add_doc("nota.md".to_string(), 1, "hola mundo");
+        index.save(&index_path).unwrap();
+
+        let loaded = SearchIndex::load(&index_path, &vault);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.term_postings("mundo").len(), 1);
+
+        // Otro vault → índice vacío
+        let other = SearchIndex::load(&index_path, temp.path());
+        assert!(other.is_empty());
+    }
+}
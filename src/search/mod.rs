@@ -0,0 +1,5 @@
+pub mod index;
+pub mod query;
+
+pub use index::SearchIndex;
+pub use query::{Clause, SearchHit};
@@ -0,0 +1,257 @@
+use super::index::{tokenize, Posting, SearchIndex};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+// Parámetros BM25
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Cláusula de búsqueda. Todas las cláusulas deben coincidir (AND).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Clause {
+    /// Término exacto: `palabra`
+    Term(String),
+    /// Prefijo: `pal*`
+    Prefix(String),
+    /// Frase: `"varias palabras seguidas"`
+    Phrase(Vec<String>),
+}
+
+/// Resultado de búsqueda ordenado por relevancia
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub path: PathBuf,
+    pub score: f64,
+}
+
+/// Parsea una consulta en cláusulas
+/// - `"frase exacta"` → Phrase
+/// - `pref*` → Prefix
+/// - resto → Term (normalizado igual que el índice)
+pub fn parse(query: &str) -> Vec<Clause> {
+    let mut clauses = Vec::new();
+    let mut rest = query;
+
+    while !rest.is_empty() {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+
+        if let Some(after_quote) = rest.strip_prefix('"') {
+            let (phrase, remaining) = match after_quote.find('"') {
+                Some(end) => (&after_quote[..end], &after_quote[end + 1..]),
+                None => (after_quote, ""),
+            };
+            let words = tokenize(phrase);
+            match words.len() {
+                0 => {}
+                1 => clauses.push(Clause::Term(words.into_iter().next().unwrap())),
+                _ => clauses.push(Clause::Phrase(words)),
+            }
+            rest = remaining;
+            continue;
+        }
+
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == '"')
+            .unwrap_or(rest.len());
+        let word = &rest[..end];
+        rest = &rest[end..];
+
+        let is_prefix = word.ends_with('*');
+        let words = tokenize(word);
+        let last = words.len().saturating_sub(1);
+        for (i, w) in words.into_iter().enumerate() {
+            if is_prefix && i == last {
+                clauses.push(Clause::Prefix(w));
+            } else {
+                clauses.push(Clause::Term(w));
+            }
+        }
+    }
+
+    clauses
+}
+
+impl SearchIndex {
+    /// Busca en el índice y retorna los documentos que cumplen todas las cláusulas,
+    /// ordenados por score BM25 (mayor primero).
+    pub fn search(&self, vault: &Path, query: &str) -> Vec<SearchHit> {
+        let clauses = parse(query);
+        if clauses.is_empty() || self.is_empty() {
+            return Vec::new();
+        }
+
+        let total_docs = self.len() as f64;
+        let avg_len = self.avg_len().max(1.0);
+
+        let mut candidates: Option<HashSet<u32>> = None;
+        let mut scores: HashMap<u32, f64> = HashMap::new();
+
+        for clause in &clauses {
+            // (doc → frecuencia) por cada término que aporta la cláusula
+            let groups: Vec<HashMap<u32, usize>> = match clause {
+                Clause::Term(term) => vec![frequencies(self.term_postings(term))],
+                Clause::Prefix(prefix) => self
+                    .prefix_terms(prefix)
+                    .map(|(_, postings)| frequencies(postings))
+                    .collect(),
+                Clause::Phrase(words) => vec![self.phrase_frequencies(words)],
+            };
+
+            let matched: HashSet<u32> = groups.iter().flat_map(|g| g.keys().copied()).collect();
+            candidates = Some(match candidates {
+                Some(prev) => prev.intersection(&matched).copied().collect(),
+                None => matched,
+            });
+
+            for group in &groups {
+                let df = group.len() as f64;
+                if df == 0.0 {
+                    continue;
+                }
+                let idf = (1.0 + (total_docs - df + 0.5) / (df + 0.5)).ln();
+                for (doc, tf) in group {
+                    let len = self.docs.get(doc).map(|d| d.len as f64).unwrap_or(avg_len);
+                    let tf = *tf as f64;
+                    let score = idf * (tf * (K1 + 1.0)) / (tf + K1 * (1.0 - B + B * len / avg_len));
+                    *scores.entry(*doc).or_insert(0.0) += score;
+                }
+            }
+        }
+
+        let candidates = candidates.unwrap_or_default();
+        let mut hits: Vec<SearchHit> = candidates
+            .into_iter()
+            .filter_map(|doc| {
+                let indexed = self.docs.get(&doc)?;
+                Some(SearchHit {
+                    path: vault.join(&indexed.path),
+                    score: scores.get(&doc).copied().unwrap_or(0.0),
+                })
+            })
+            .collect();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+
+        hits
+    }
+
+    /// Cantidad de ocurrencias de la frase (palabras en posiciones consecutivas) por documento
+    fn phrase_frequencies(&self, words: &[String]) -> HashMap<u32, usize> {
+        let mut result = HashMap::new();
+        let Some((first, others)) = words.split_first() else {
+            return result;
+        };
+
+        let others: Vec<HashMap<u32, HashSet<u32>>> = others
+            .iter()
+            .map(|w| {
+                self.term_postings(w)
+                    .iter()
+                    .map(|p| (p.doc, p.positions.iter().copied().collect()))
+                    .collect()
+            })
+            .collect();
+
+        for posting in self.term_postings(first) {
+            let count = posting
+                .positions
+                .iter()
+                .filter(|&&start| {
+                    others.iter().enumerate().all(|(offset, word)| {
+                        word.get(&posting.doc).is_some_and(|positions| {
+                            positions.contains(&(start + offset as u32 + 1))
+                        })
+                    })
+                })
+                .count();
+            if count > 0 {
+                result.insert(posting.doc, count);
+            }
+        }
+
+        result
+    }
+}
+
+fn frequencies(postings: &[Posting]) -> HashMap<u32, usize> {
+    postings
+        .iter()
+        .map(|p| (p.doc, p.positions.len()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_index() -> SearchIndex {
+        let mut index = SearchIndex::new(Path::new("/vault"));
+        index.add_doc(
+            "a.md".to_string(),
+            1,
+            "reunión con el cliente acme sobre la api",
+        );
+        index.add_doc(
+            "b.md".to_string(),
+            1,
+            "la api del cliente y el cliente nuevo",
+        );
+        index.add_doc("c.md".to_string(), 1, "notas sueltas de arquitectura");
+        index
+    }
+
+    fn paths(hits: &[SearchHit]) -> Vec<String> {
+        hits.iter()
+            .map(|h| h.path.file_name().unwrap().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_clauses() {
+        let clauses = parse(r#"api "cliente acme" arq*"#);
+        assert_eq!(
+            clauses,
+            vec![
+                Clause::Term("api".to_string()),
+                Clause::Phrase(vec!["cliente".to_string(), "acme".to_string()]),
+                Clause::Prefix("arq".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_search_terms_are_anded_and_ranked() {
+        let index = sample_index();
+        let hits = index.search(Path::new("/vault"), "cliente api");
+        // b.md menciona "cliente" dos veces
+        assert_eq!(paths(&hits), vec!["b.md", "a.md"]);
+    }
+
+    #[test]
+    fn test_search_phrase() {
+        let index = sample_index();
+        let hits = index.search(Path::new("/vault"), "\"cliente acme\"");
+        assert_eq!(paths(&hits), vec!["a.md"]);
+
+        let hits = index.search(Path::new("/vault"), "\"acme cliente\"");
+        assert!(hits.is_empty());
+    }
+
+    #[test]
+    fn test_search_prefix_and_accents() {
+        let index = sample_index();
+        let hits = index.search(Path::new("/vault"), "arqui*");
+        assert_eq!(paths(&hits), vec!["c.md"]);
+
+        let hits = index.search(Path::new("/vault"), "reunion");
+        assert_eq!(paths(&hits), vec!["a.md"]);
+    }
+}
@@ -1,61 +1,21 @@
 use super::tree::TagNode;
-use crate::vault::scan;
-use chrono::Local;
-use serde::{Deserialize, Serialize};
-use std::fs;
+use crate::vault::index::VaultIndex;
 use std::path::Path;
 
-#[derive(Debug, Serialize, Deserialize)]
-struct TagCache {
-    version: u32,
-    timestamp: i64,
-    root: TagNode,
+/// Secondary tag tree of the vault, read from the vault index
+pub fn load(vault: &Path, templates_path: &Path) -> anyhow::Result<TagNode> {
+    let index = VaultIndex::open(vault, templates_path)?;
+    Ok(collect(&index))
 }
 
-pub fn load(vault: &Path, config_dir: &Path) -> anyhow::Result<TagNode> {
-    let cache_path = config_dir.join("tags_cache.json");
-
-    if cache_path.exists() {
-        if let Ok(cache_content) = fs::read_to_string(&cache_path) {
-            if let Ok(cache) = serde_json::from_str::<TagCache>(&cache_content) {
-                return Ok(cache.root);
-            }
-        }
-    }
-
-    let root = collect(vault)?;
-    update(vault, config_dir, &root)?;
-    Ok(root)
-}
-
-pub fn update(_vault: &Path, config_dir: &Path, root: &TagNode) -> anyhow::Result<()> {
-    let cache_path = config_dir.join("tags_cache.json");
-
-    let cache = TagCache {
-        version: 1,
-        timestamp: Local::now().timestamp(),
-        root: root.clone(),
-    };
-
-    let cache_json = serde_json::to_string_pretty(&cache)?;
-    fs::write(&cache_path, cache_json)?;
-
-    Ok(())
-}
-
-pub fn collect(vault: &Path) -> anyhow::Result<TagNode> {
+pub fn collect(index: &VaultIndex) -> TagNode {
     let mut root = TagNode::new("root".to_string());
 
-    // Load config to get templates directory
-    let config = crate::core::config::Config::load_default()?;
-    let templates_path = vault.join(&config.templates_dir);
-
-    let items = scan::scan_tags(vault, &templates_path)?;
-    for item in items {
-        for tag_path in item.secondary_tags {
+    for (_, note) in index.iter() {
+        for tag_path in &note.secondary_tags {
             root.insert_path(&tag_path.0);
         }
     }
 
-    Ok(root)
+    root
 }
@@ -1,9 +1,10 @@
+use serde::{Deserialize, Serialize};
 use serde_yaml::{Mapping, Value};
 
 /// Representa un path de tag jerárquico (ej: ["proyecto", "cliente", "acme"])
 /// Cada elemento del array YAML es un tag independiente.
 /// La jerarquía se expresa con "/" dentro de cada string: "padre/hijo/nieto"
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TagPath(pub Vec<String>);
 
 impl TagPath {
@@ -14,7 +15,7 @@ impl TagPath {
         const TAG_KEYS: &[&str] = &["tags", "tag", "Tags", "Tag"];
 
         for key in TAG_KEYS {
-            if let Some(Value::Sequence(tag_list)) = fm.get(&Value::String((*key).to_string())) {
+            if let Some(Value::Sequence(tag_list)) = fm.get(Value::String((*key).to_string())) {
                 let mut result = Vec::new();
 
                 // Each array element is an INDEPENDENT tag
@@ -45,6 +46,18 @@ impl TagPath {
         Vec::new()
     }
 
+    /// Parsea "padre/hijo" (con o sin `#` inicial)
+    pub fn from_slash(tag: &str) -> Self {
+        TagPath(
+            tag.trim()
+                .trim_start_matches('#')
+                .split('/')
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty())
+                .collect(),
+        )
+    }
+
     /// Convierte el TagPath a formato slash-separated para serialización YAML
     pub fn to_slash_string(&self) -> String {
         self.0.join("/")
@@ -1,19 +1,7 @@
 use crate::tags::tree::TagNode;
-use crate::core::config::Config;
-use crate::vault::scan;
-use chrono::Local;
-use serde::{Deserialize, Serialize};
+use crate::vault::index::VaultIndex;
 use std::collections::{HashMap, HashSet};
-use std::fs;
-use std::path::{Path, PathBuf};
-
-#[derive(Debug, Serialize, Deserialize)]
-struct PrimaryTagCacheFile {
-    version: u32,
-    timestamp: i64,
-    root: TagNode,
-    dirs_by_tag: HashMap<String, Vec<String>>,
-}
+use std::path::Path;
 
 #[derive(Debug, Clone)]
 pub struct PrimaryTagCache {
@@ -21,63 +9,24 @@ pub struct PrimaryTagCache {
     pub dirs_by_tag: HashMap<String, Vec<String>>,
 }
 
-pub fn load(
-    vault: &Path,
-    config_dir: &Path,
-    templates_path: &Path,
-) -> anyhow::Result<PrimaryTagCache> {
-    let cache_path = Config::primary_cache_path().unwrap_or_else(|_| config_dir.join("primary_tags_cache.json"));
-
-    if cache_path.exists() {
-        if let Ok(cache_content) = fs::read_to_string(&cache_path) {
-            if let Ok(cache) = serde_json::from_str::<PrimaryTagCacheFile>(&cache_content) {
-                return Ok(PrimaryTagCache {
-                    root: cache.root,
-                    dirs_by_tag: cache.dirs_by_tag,
-                });
-            }
-        }
-    }
-
-    let cache = collect(vault, templates_path)?;
-    update(config_dir, &cache)?;
-    Ok(cache)
-}
-
-pub fn update(config_dir: &Path, cache: &PrimaryTagCache) -> anyhow::Result<()> {
-    let cache_path = Config::primary_cache_path().unwrap_or_else(|_| config_dir.join("primary_tags_cache.json"));
-
-    let cache_file = PrimaryTagCacheFile {
-        version: 1,
-        timestamp: Local::now().timestamp(),
-        root: cache.root.clone(),
-        dirs_by_tag: cache.dirs_by_tag.clone(),
-    };
-
-    let cache_json = serde_json::to_string_pretty(&cache_file)?;
-    fs::write(&cache_path, cache_json)?;
-
-    Ok(())
+/// Primary (dir) tag tree of the vault, read from the vault index
+pub fn load(vault: &Path, templates_path: &Path) -> anyhow::Result<PrimaryTagCache> {
+    let index = VaultIndex::open(vault, templates_path)?;
+    Ok(collect(&index))
 }
 
-pub fn collect(vault: &Path, templates_path: &Path) -> anyhow::Result<PrimaryTagCache> {
+pub fn collect(index: &VaultIndex) -> PrimaryTagCache {
     let mut root = TagNode::new("root".to_string());
     let mut dirs_by_tag: HashMap<String, HashSet<String>> = HashMap::new();
 
-    let items = scan::scan_tags(vault, templates_path)?;
-
-    for item in items {
-        if let Some(primary) = item.primary_tag {
+    for (rel, note) in &index.notes {
+        if let Some(primary) = &note.primary_tag {
             root.insert_path(&primary.0);
 
-            let dir = item
-                .path
+            let dir = Path::new(rel)
                 .parent()
-                .unwrap_or(vault)
-                .strip_prefix(vault)
-                .unwrap_or(item.path.parent().unwrap_or(vault))
-                .to_string_lossy()
-                .to_string();
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
 
             let key = primary.to_slash_string();
             dirs_by_tag.entry(key).or_default().insert(dir);
@@ -93,10 +42,5 @@ pub fn collect(vault: &Path, templates_path: &Path) -> anyhow::Result<PrimaryTag
         })
         .collect::<HashMap<String, Vec<String>>>();
 
-    Ok(PrimaryTagCache { root, dirs_by_tag })
-}
-
-#[allow(dead_code)]
-fn primary_cache_path(config_dir: &Path) -> PathBuf {
-    config_dir.join("primary_tags_cache.json")
+    PrimaryTagCache { root, dirs_by_tag }
 }
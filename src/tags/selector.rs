@@ -8,10 +8,9 @@ use std::path::Path;
 /// Selects a tag using hierarchical navigation.
 /// Returns slash-separated tag string (e.g., "padre/hijo/nieto")
 pub fn select_hierarchical(vault: &Path) -> anyhow::Result<String> {
-    let config_dir = crate::core::config::Config::config_dir()?;
     let config = Config::load_default()?;
     let templates_path = vault.join(&config.templates_dir);
-    let tag_tree = primary_cache::load(vault, &config_dir, &templates_path)?.root;
+    let tag_tree = primary_cache::load(vault, &templates_path)?.root;
 
     if tag_tree.children.is_empty() {
         println!("No se encontraron tags en el vault.");
@@ -111,10 +110,9 @@ pub fn select_hierarchical(vault: &Path) -> anyhow::Result<String> {
 /// Selects a tag using fuzzy search.
 /// Returns slash-separated tag string (e.g., "padre/hijo/nieto")
 pub fn select_with_fuzzy(vault: &Path) -> anyhow::Result<String> {
-    let config_dir = crate::core::config::Config::config_dir()?;
     let config = Config::load_default()?;
     let templates_path = vault.join(&config.templates_dir);
-    let tag_tree = primary_cache::load(vault, &config_dir, &templates_path)?.root;
+    let tag_tree = primary_cache::load(vault, &templates_path)?.root;
 
     if tag_tree.children.is_empty() {
         println!("No se encontraron tags en el vault.");
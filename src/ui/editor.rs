@@ -1,5 +1,7 @@
 use crate::core::frontmatter;
 use crate::tags;
+use crate::vault::plan::Plan;
+use crate::vault::rewrite::{self, LinkRewriter};
 use crossterm::{
     event::{self, Event, KeyCode, KeyModifiers},
     execute,
@@ -122,17 +124,17 @@ fn open_impl(
 
                     if !new_tag.is_empty() {
                         let current_text = textarea.lines().join("\n");
-                        if let Ok((mut fm, body)) = frontmatter::extract(&current_text) {
+                        if let Ok(mut doc) = frontmatter::Document::parse(&current_text) {
                             let mut existing_tags: Vec<String> = Vec::new();
+                            let mut tags_key = "tags";
                             for key in ["tags", "tag", "Tags", "Tag"] {
-                                if let Some(Value::Sequence(tag_list)) =
-                                    fm.get(&Value::String(key.to_string()))
-                                {
+                                if let Some(Value::Sequence(tag_list)) = doc.get(key) {
                                     for tag in tag_list {
                                         if let Value::String(t) = tag {
-                                            existing_tags.push(t.clone());
+                                            existing_tags.push(t);
                                         }
                                     }
+                                    tags_key = key;
                                     break;
                                 }
                             }
@@ -148,10 +150,8 @@ fn open_impl(
                                     .map(|t| Value::String(t.clone()))
                                     .collect(),
                             );
-                            fm.insert(Value::String("tags".to_string()), tags_value);
-
-                            let new_content =
-                                format!("---\n{}---{}", serde_yaml::to_string(&fm)?, body);
+                            doc.set(tags_key, tags_value)?;
+                            let new_content = doc.into_string();
 
                             let new_lines: Vec<String> =
                                 new_content.lines().map(|s| s.to_string()).collect();
@@ -184,8 +184,11 @@ fn open_impl(
                         .interact_opt()?;
 
                     if confirm.unwrap_or(false) {
-                        fs::remove_file(file_path)?;
-                        println!("\n✓ Archivo eliminado");
+                        // Con backup y en el journal (mad undo)
+                        let mut plan = Plan::new("delete", vault_root);
+                        plan.delete(file_path)?;
+                        plan.apply()?;
+                        println!("\n✓ Archivo eliminado (mad undo para recuperarlo)");
                         return Ok(false);
                     }
 
@@ -211,8 +214,19 @@ fn open_impl(
                             if new_path.exists() {
                                 println!("\n✗ Error: Ya existe un archivo con ese nombre");
                             } else {
-                                fs::rename(file_path, &new_path)?;
+                                // Links graph must be built before renaming
+                                let templates_path = crate::core::config::Config::load_default()
+                                    .map(|c| vault_root.join(c.templates_dir))
+                                    .unwrap_or_else(|_| vault_root.join("Templates"));
+                                let mut rewriter = LinkRewriter::new(vault_root, &templates_path)?;
+                                rewriter.record_move(file_path, &new_path);
+
+                                let mut plan = Plan::new("rename note", vault_root);
+                                plan.move_path(file_path, &new_path);
+                                let touched = rewriter.plan(&mut plan)?;
+                                plan.apply()?;
                                 println!("\n✓ Archivo renombrado");
+                                rewrite::print_summary(vault_root, &touched);
 
                                 // Update last note reference
                                 let _ =
@@ -31,12 +31,10 @@ pub fn input_with_esc(prompt: &str) -> anyhow::Result<Option<String>> {
                     println!();
                     return Ok(None);
                 }
-                KeyCode::Backspace => {
-                    if !input.is_empty() {
-                        input.pop();
-                        print!("\x08 \x08");
-                        io::stdout().flush()?;
-                    }
+                KeyCode::Backspace if !input.is_empty() => {
+                    input.pop();
+                    print!("\x08 \x08");
+                    io::stdout().flush()?;
                 }
                 KeyCode::Char(c)
                     if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT =>
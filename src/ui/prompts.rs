@@ -1,7 +1,28 @@
 use super::input::input_with_esc;
-use dialoguer::{theme::ColorfulTheme, Select};
+use crate::core::template::{InputKind, TemplateInput};
+use dialoguer::{theme::ColorfulTheme, FuzzySelect, Select};
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal};
 use std::path::Path;
 
+/// stdin is a terminal: prompts and the integrated editor need one
+pub fn is_tty() -> bool {
+    io::stdin().is_terminal()
+}
+
+/// Fail fast instead of waiting on a prompt nobody can answer (scripts, cron, pipes).
+/// `hint` says which flag answers it: "usa --tag"
+pub fn ensure_tty(hint: &str) -> anyhow::Result<()> {
+    require_tty(is_tty(), hint)
+}
+
+fn require_tty(tty: bool, hint: &str) -> anyhow::Result<()> {
+    if !tty {
+        anyhow::bail!("Este paso es interactivo y stdin no es una terminal: {}", hint);
+    }
+    Ok(())
+}
+
 /// Select or create a project interactively
 pub fn select_project(projects: &[String], projects_file: &Path) -> anyhow::Result<Option<String>> {
     let mut opts = projects.to_vec();
@@ -34,6 +55,7 @@ pub fn select_project(projects: &[String], projects_file: &Path) -> anyhow::Resu
 /// Select aliases interactively - enter sentences one by one, empty to finish
 /// Returns Ok(None) if user pressed ESC, Ok(Some(aliases)) otherwise
 pub fn select_aliases() -> anyhow::Result<Option<Vec<String>>> {
+    ensure_tty("usa --alias (una vez por alias) o --yes para crear la nota sin aliases")?;
     let mut aliases = Vec::new();
 
     println!("\nAliases (Enter vacío para terminar, ESC para cancelar):");
@@ -58,3 +80,83 @@ pub fn select_aliases() -> anyhow::Result<Option<Vec<String>>> {
 
     Ok(Some(aliases))
 }
+
+/// Pick a template by name (fuzzy). Returns Ok(None) if user pressed ESC
+pub fn select_template(names: &[String]) -> anyhow::Result<Option<usize>> {
+    ensure_tty("usa --template <nombre>")?;
+    let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Template (ESC para cancelar)")
+        .items(names)
+        .default(0)
+        .interact_opt()?;
+    Ok(selection)
+}
+
+/// Ask the values declared by a template (`{{prompt:x}}`, `{{choice:x|a,b}}`)
+/// Returns Ok(None) if user pressed ESC
+pub fn ask_template_inputs(
+    inputs: &[TemplateInput],
+) -> anyhow::Result<Option<BTreeMap<String, String>>> {
+    ensure_tty("usa --yes para aceptar los valores por defecto del template")?;
+    let mut values = BTreeMap::new();
+
+    for input in inputs {
+        let value = match &input.kind {
+            InputKind::Text { default } => {
+                let prompt = match default {
+                    Some(d) if !d.is_empty() => format!("{} [{}]", input.name, d),
+                    _ => input.name.clone(),
+                };
+                match input_with_esc(&prompt)? {
+                    Some(v) if v.trim().is_empty() => default.clone().unwrap_or_default(),
+                    Some(v) => v.trim().to_string(),
+                    None => return Ok(None),
+                }
+            }
+            InputKind::Choice(options) if !options.is_empty() => {
+                let selection = Select::with_theme(&ColorfulTheme::default())
+                    .with_prompt(&input.name)
+                    .items(options)
+                    .default(0)
+                    .interact_opt()?;
+                match selection {
+                    Some(idx) => options[idx].clone(),
+                    None => return Ok(None),
+                }
+            }
+            InputKind::Choice(_) => match input_with_esc(&input.name)? {
+                Some(v) => v.trim().to_string(),
+                None => return Ok(None),
+            },
+        };
+        values.insert(input.name.clone(), value);
+    }
+
+    Ok(Some(values))
+}
+
+/// `--yes`: the default of each text input (or empty) and the first option of each choice
+pub fn default_template_inputs(inputs: &[TemplateInput]) -> BTreeMap<String, String> {
+    inputs
+        .iter()
+        .map(|input| {
+            let value = match &input.kind {
+                InputKind::Text { default } => default.clone().unwrap_or_default(),
+                InputKind::Choice(options) => options.first().cloned().unwrap_or_default(),
+            };
+            (input.name.clone(), value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_require_tty_names_the_flag() {
+        assert!(require_tty(true, "usa --tag").is_ok());
+        let err = require_tty(false, "usa --tag").unwrap_err().to_string();
+        assert_eq!(err, "Este paso es interactivo y stdin no es una terminal: usa --tag");
+    }
+}
@@ -24,7 +24,7 @@ pub fn save_aliases(map: &HashMap<String, String>) -> anyhow::Result<()> {
 pub fn is_reserved_word(word: &str) -> bool {
     matches!(
         word,
-        "dialy" | "last" | "tag" | "retag" | "redir" | "cache" | "tasks" | "alias"
+        "dialy" | "week" | "month" | "quarter" | "year" | "capture" | "append" | "last" | "search" | "query" | "links" | "backlinks" | "tag" | "retag" | "redir" | "rename" | "migrate" | "apply" | "undo" | "redo" | "backups" | "check" | "export" | "cache" | "tasks" | "alias"
     )
 }
 
@@ -1,3 +1,9 @@
+use crate::core::date_expr;
+use crate::core::period::Period;
+use crate::core::task::Priority;
+use crate::core::template::TemplateSelection;
+use crate::utils::output::OutputFormat;
+use crate::vault::plan::PlanMode;
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
@@ -6,7 +12,7 @@ use std::path::PathBuf;
 #[command(
     name = "mad",
     about = "Magic Documents",
-    after_help = "USO:\n  mad <comando> [args]\n  mad [-t|--title] \"TITULO\" [DIR]\n\nComandos: dialy, last, tag, retag, redir, cache, tasks, alias\nPara ayuda: mad <comando> -h"
+    after_help = "USO:\n  mad <comando> [args]\n  mad [-t|--title] \"TITULO\" [DIR]\n\nComandos: dialy, week, month, quarter, year, capture, append, last, search, query, links, backlinks, tag, retag, redir, rename, migrate, apply, undo, redo, backups, check, export, cache, tasks, alias\nPara ayuda: mad <comando> -h"
 )]
 pub struct Args {
     #[command(subcommand)]
@@ -39,13 +45,137 @@ pub struct Args {
     /// No agregar timestamp al abrir nota existente
     #[arg(short = 'i', long = "no-id")]
     pub no_id: bool,
+
+    /// Template con nombre: --template meeting
+    #[arg(long = "template", value_name = "NOMBRE")]
+    pub template: Option<String>,
+
+    /// Elegir el template con el selector fuzzy
+    #[arg(long = "pick-template", conflicts_with = "template")]
+    pub pick_template: bool,
+
+    /// Dir-tag de la nota nueva (sin selector)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tag: Option<String>,
+
+    /// Alias de la nota nueva (repetible, sin prompt)
+    #[arg(long = "alias", value_name = "ALIAS")]
+    pub aliases: Vec<String>,
+
+    /// Responder que sí a las confirmaciones (y sin aliases ni valores del template: los por defecto)
+    #[arg(short = 'y', long = "yes", global = true)]
+    pub yes: bool,
+
+    /// Salida para scripts: json | tsv (last, query, tag list/find, tasks, cache, check)
+    #[arg(long = "format", value_name = "FORMATO", global = true)]
+    pub format: Option<String>,
+
+    /// Igual que --format json
+    #[arg(long = "json", global = true)]
+    pub json: bool,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Command {
     /// Daily note
     #[command(name = "dialy")]
-    Dialy,
+    Dialy {
+        /// Fecha: 2026-03-01, ayer, yesterday, "last friday", "lunes pasado"...
+        #[arg(value_name = "FECHA")]
+        date_pos: Option<String>,
+        /// Fecha (igual que el posicional)
+        #[arg(long = "date", value_name = "FECHA")]
+        date: Option<String>,
+        /// Daily note anterior a la última abierta
+        #[arg(long = "prev")]
+        prev: bool,
+        /// Daily note siguiente a la última abierta
+        #[arg(long = "next")]
+        next: bool,
+        /// Arrastrar tareas abiertas de daily notes anteriores (aunque esté desactivado en config)
+        #[arg(long = "rollover")]
+        rollover: bool,
+        /// No arrastrar tareas abiertas
+        #[arg(long = "no-rollover")]
+        no_rollover: bool,
+        /// Template con nombre: --template meeting
+        #[arg(long = "template", value_name = "NOMBRE")]
+        template: Option<String>,
+        /// Elegir el template con el selector fuzzy
+        #[arg(long = "pick-template", conflicts_with = "template")]
+        pick_template: bool,
+    },
+
+    /// Nota semanal
+    Week {
+        /// Template con nombre: --template meeting
+        #[arg(long = "template", value_name = "NOMBRE")]
+        template: Option<String>,
+        /// Elegir el template con el selector fuzzy
+        #[arg(long = "pick-template", conflicts_with = "template")]
+        pick_template: bool,
+    },
+
+    /// Nota mensual
+    Month {
+        /// Template con nombre: --template meeting
+        #[arg(long = "template", value_name = "NOMBRE")]
+        template: Option<String>,
+        /// Elegir el template con el selector fuzzy
+        #[arg(long = "pick-template", conflicts_with = "template")]
+        pick_template: bool,
+    },
+
+    /// Nota trimestral
+    Quarter {
+        /// Template con nombre: --template meeting
+        #[arg(long = "template", value_name = "NOMBRE")]
+        template: Option<String>,
+        /// Elegir el template con el selector fuzzy
+        #[arg(long = "pick-template", conflicts_with = "template")]
+        pick_template: bool,
+    },
+
+    /// Nota anual
+    Year {
+        /// Template con nombre: --template meeting
+        #[arg(long = "template", value_name = "NOMBRE")]
+        template: Option<String>,
+        /// Elegir el template con el selector fuzzy
+        #[arg(long = "pick-template", conflicts_with = "template")]
+        pick_template: bool,
+    },
+
+    /// Captura rápida (sin editor) en la daily note de hoy o en la nota inbox
+    Capture {
+        /// Texto a capturar ("-" lee stdin: una entrada por línea)
+        #[arg(value_name = "TEXTO", allow_hyphen_values = true)]
+        text: Option<String>,
+        /// Capturar como tarea `- [ ]`
+        #[arg(long = "task")]
+        task: bool,
+        /// En la nota inbox (capture.inbox, default: Inbox.md)
+        #[arg(long = "inbox")]
+        inbox: bool,
+        /// En la daily note de hoy (aunque capture.target sea "inbox")
+        #[arg(long = "daily")]
+        daily: bool,
+        /// Heading bajo el que se captura (se crea si falta)
+        #[arg(long = "heading", value_name = "HEADING")]
+        heading: Option<String>,
+    },
+
+    /// Agregar contenido a una nota (sin editor)
+    Append {
+        /// Path o nombre de la nota
+        note: String,
+        /// Contenido ("-" lee stdin)
+        #[arg(value_name = "TEXTO", allow_hyphen_values = true)]
+        text: Option<String>,
+        /// Heading bajo el que se agrega (se crea si falta; sin heading: al final)
+        #[arg(long = "heading", value_name = "HEADING")]
+        heading: Option<String>,
+    },
 
     /// Última nota o últimas N
     Last {
@@ -53,10 +183,55 @@ pub enum Command {
         count: Option<usize>,
     },
 
+    /// Búsqueda de texto completo en el vault
+    Search {
+        /// Consulta: términos, "frase exacta" o prefijo*
+        query: String,
+        /// Máximo de resultados a listar
+        #[arg(short = 'n', long = "limit", default_value_t = 50)]
+        limit: usize,
+        /// Regenerar el índice desde cero
+        #[arg(long = "rebuild")]
+        rebuild: bool,
+    },
+
+    /// Consulta estructurada: tag:proj/acme and status:open and modified>7d and not path:Archivo/**
+    Query {
+        /// Consulta (tag, dirtag, path, modified, tasks, links, backlinks, has o campos del frontmatter)
+        /// Salida con --format: paths (default) | table | json | tsv
+        query: String,
+    },
+
+    /// Enlaces salientes de una nota
+    Links {
+        /// Path o nombre de la nota
+        note: String,
+    },
+
+    /// Enlaces entrantes (backlinks) de una nota
+    Backlinks {
+        /// Path o nombre de la nota
+        note: String,
+    },
+
     /// Gestión de tags
     Tag {
         /// Acción: list (default) | rename | find | log
         action: Option<String>,
+        /// Tag para find (sin selector): proj/acme
+        #[arg(value_name = "TAG")]
+        tag: Option<String>,
+        /// rename: tag a renombrar (sin selector)
+        #[arg(long = "from", value_name = "TAG")]
+        from: Option<String>,
+        /// rename: tag nuevo
+        #[arg(long = "to", value_name = "TAG")]
+        to: Option<String>,
+        /// rename: renombrar también los sub-tags
+        #[arg(long = "recursive")]
+        recursive: bool,
+        #[command(flatten)]
+        plan: PlanArgs,
     },
 
     /// Re-tag archivo(s) según la ubicación del directorio
@@ -69,6 +244,8 @@ pub enum Command {
         /// No agregar alias al cambiar tag primario
         #[arg(long = "no-alias")]
         no_alias: bool,
+        #[command(flatten)]
+        plan: PlanArgs,
     },
 
     /// Mover archivo(s) a directorios según su dir-tag
@@ -78,6 +255,74 @@ pub enum Command {
         /// No crear archivos .bak
         #[arg(long = "no-bak")]
         no_bak: bool,
+        /// Con varios tags en el frontmatter, usar el primero (sin preguntar)
+        #[arg(long = "pick-first")]
+        pick_first: bool,
+        #[command(flatten)]
+        plan: PlanArgs,
+    },
+
+    /// Renombrar el directorio actual en el vault y en el workspace productivo
+    Rename {
+        /// Nuevo nombre del directorio
+        #[arg(value_name = "NUEVO_NOMBRE")]
+        new_name: String,
+        /// No re-taggear las notas del directorio renombrado
+        #[arg(long = "no-retag")]
+        no_retag: bool,
+        #[command(flatten)]
+        plan: PlanArgs,
+    },
+
+    /// Migrar tags en formato array (`[padre, hijo]`) a formato slash (`padre/hijo`)
+    Migrate {
+        #[command(flatten)]
+        plan: PlanArgs,
+    },
+
+    /// Aplicar un plan guardado con --plan-out
+    Apply {
+        /// Archivo JSON del plan
+        #[arg(value_name = "PLAN")]
+        plan: PathBuf,
+    },
+
+    /// Deshacer la última operación que modificó el vault
+    Undo {
+        /// Listar las operaciones del journal
+        #[arg(long = "list")]
+        list: bool,
+    },
+
+    /// Rehacer la última operación deshecha
+    Redo,
+
+    /// Backups de .arc/backups: list (default) | diff | restore | prune
+    Backups {
+        #[command(subcommand)]
+        action: Option<BackupsCommand>,
+    },
+
+    /// Exportar notas como sitio HTML estático
+    Export {
+        /// Formato: html
+        #[arg(value_name = "FORMATO")]
+        kind: String,
+        /// Solo las notas con este dir-tag (o sus descendientes)
+        #[arg(long = "tag")]
+        tag: Option<String>,
+        /// Directorio de salida
+        #[arg(long = "out", value_name = "DIR", default_value = "site")]
+        out: PathBuf,
+    },
+
+    /// Revisar el vault: dir-tags, frontmatter, enlaces rotos, duplicados, notas vacías
+    Check {
+        /// Corregir los dir-tags faltantes o incorrectos (como mad retag)
+        #[arg(long = "fix")]
+        fix: bool,
+        #[command(flatten)]
+        plan: PlanArgs,
     },
 
     /// Regenerar cache
@@ -94,6 +339,20 @@ pub enum Command {
         /// Vista completa con TUI
         #[arg(long = "full")]
         full: bool,
+        /// Solo tareas que vencen hasta FECHA (hoy, viernes, 2026-10-20...)
+        #[arg(long = "due", value_name = "FECHA")]
+        due: Option<String>,
+        /// Solo tareas vencidas
+        #[arg(long = "overdue")]
+        overdue: bool,
+        /// Prioridad mínima: highest | high | medium | low | lowest
+        #[arg(long = "priority", value_name = "PRIORIDAD")]
+        priority: Option<String>,
+        /// Solo tareas con este #tag (o sus sub-tags)
+        #[arg(long = "tag", value_name = "TAG")]
+        tag: Option<String>,
+        #[command(subcommand)]
+        action: Option<TasksCommand>,
     },
 
     /// Crear alias de comandos
@@ -105,14 +364,159 @@ pub enum Command {
     },
 }
 
+/// `--dry-run` y `--plan-out` de los comandos que modifican el vault en masa
+#[derive(clap::Args, Debug, Default)]
+pub struct PlanArgs {
+    /// Mostrar diffs y movimientos sin modificar el vault
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+    /// Guardar el plan en un JSON para revisarlo y aplicarlo con `mad apply` (implica --dry-run)
+    #[arg(long = "plan-out", value_name = "FILE")]
+    pub plan_out: Option<PathBuf>,
+}
+
+impl From<PlanArgs> for PlanMode {
+    fn from(args: PlanArgs) -> Self {
+        PlanMode {
+            dry_run: args.dry_run,
+            plan_out: args.plan_out,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BackupsCommand {
+    /// Listar backups (de una nota o de todo el vault)
+    List {
+        /// Path o nombre de la nota
+        note: Option<String>,
+    },
+    /// Diff entre un backup y la nota actual
+    Diff {
+        /// Backup (path, o relativo a .arc/backups)
+        backup: String,
+    },
+    /// Restaurar la nota desde un backup (se puede revertir con mad undo)
+    Restore {
+        /// Backup (path, o relativo a .arc/backups)
+        backup: String,
+    },
+    /// Eliminar backups viejos
+    Prune {
+        /// Edad mínima: 30d, 2w, 6m, 1y
+        #[arg(long = "older-than", value_name = "EDAD")]
+        older_than: Option<String>,
+        /// Conservar siempre los N backups más recientes de cada nota
+        #[arg(long = "keep", value_name = "N")]
+        keep: Option<usize>,
+        /// Listar sin eliminar
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TasksCommand {
+    /// Sincronizar en ambos sentidos con la colección CalDAV de [tasks.caldav]
+    Sync {
+        /// Mostrar los cambios sin modificar el vault ni el servidor
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug)]
+pub enum BackupsAction {
+    List {
+        note: Option<String>,
+    },
+    Diff {
+        backup: String,
+    },
+    Restore {
+        backup: String,
+    },
+    Prune {
+        /// (cantidad, unidad d/w/m/y)
+        older_than: Option<(i64, char)>,
+        keep: Option<usize>,
+        dry_run: bool,
+    },
+}
+
 #[derive(Debug)]
 pub enum TmanAction {
-    List,
-    Rename,
-    Find,
+    List(OutputFormat),
+    Rename {
+        mode: PlanMode,
+        /// `--from`/`--to`: sin selector ni prompt del tag nuevo
+        from: Option<String>,
+        to: Option<String>,
+        recursive: bool,
+        yes: bool,
+    },
+    Find {
+        tag: Option<String>,
+        format: OutputFormat,
+    },
     Log,
 }
 
+/// Respuestas por flags a los prompts de creación de notas
+#[derive(Debug, Default)]
+pub struct NoteAnswers {
+    /// `--tag`
+    pub tag: Option<String>,
+    /// `--alias`
+    pub aliases: Vec<String>,
+    /// `--yes`
+    pub yes: bool,
+}
+
+/// Filtros de `mad tasks` (se combinan con "y")
+#[derive(Debug, Default)]
+pub struct TaskFilter {
+    /// `--due`: expresión de fecha (ver `core::date_expr`)
+    pub due: Option<String>,
+    /// `--overdue`
+    pub overdue: bool,
+    /// `--priority`: prioridad mínima
+    pub priority: Option<Priority>,
+    /// `--tag`
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum LinkDirection {
+    Forward,
+    Backward,
+}
+
+/// Día a abrir con `mad dialy`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DailyTarget {
+    Today,
+    /// Expresión de fecha (ver `core::date_expr`)
+    Date(String),
+    Prev,
+    Next,
+}
+
+/// Nota destino de `mad capture` (sin flag: `capture.target` de la config)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureTarget {
+    Daily,
+    Inbox,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryFormat {
+    Paths,
+    Table,
+    Json,
+    Tsv,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum CacheKind {
     All,
@@ -125,12 +529,17 @@ impl Args {
         self.validate_inner(0)
     }
 
-    fn validate_inner(self, depth: usize) -> anyhow::Result<ValidatedArgs> {
+    fn validate_inner(mut self, depth: usize) -> anyhow::Result<ValidatedArgs> {
         if depth > 5 {
             anyhow::bail!("Alias recursivo detectado");
         }
+        let format = self.format.take();
+        let json = self.json;
+        let output = OutputArgs { format, json };
+
         // Handle quick (obsidian)
         if let Some(title) = self.quick {
+            output.reject()?;
             let editor = if self.editor_flag && self.editor_cmd.is_some() {
                 anyhow::bail!("No se pueden usar -e y --editor al mismo tiempo");
             } else if self.editor_flag {
@@ -140,10 +549,19 @@ impl Args {
             } else {
                 EditorMode::Default
             };
+            if self.tag.is_some() {
+                anyhow::bail!("--tag no aplica a -q: el tag se deriva del directorio");
+            }
             return Ok(ValidatedArgs::Obsidian {
                 title,
                 editor,
                 skip_timestamp: self.no_id,
+                template: parse_template(self.template, self.pick_template),
+                answers: NoteAnswers {
+                    tag: None,
+                    aliases: self.aliases,
+                    yes: self.yes,
+                },
             });
         }
 
@@ -153,41 +571,279 @@ impl Args {
             if self.title.is_some() || self.title_pos.is_some() || self.target_dir.is_some() {
                 anyhow::bail!("No se puede combinar un comando con título o directorio");
             }
+            if self.template.is_some() || self.pick_template {
+                anyhow::bail!("--template y --pick-template solo aplican al crear notas (usa: mad dialy --template NOMBRE)");
+            }
+            if self.tag.is_some() || !self.aliases.is_empty() {
+                anyhow::bail!("--tag y --alias solo aplican al crear notas: mad \"TITULO\" --tag proj/acme");
+            }
+
+            if !matches!(
+                cmd,
+                Command::Last { .. }
+                    | Command::Query { .. }
+                    | Command::Tag { .. }
+                    | Command::Check { .. }
+                    | Command::Cache { .. }
+                    | Command::Tasks { .. }
+            ) {
+                output.reject()?;
+            }
 
             return match cmd {
-                Command::Dialy => Ok(ValidatedArgs::Daily {
+                Command::Dialy {
+                    date_pos,
+                    date,
+                    prev,
+                    next,
+                    rollover,
+                    no_rollover,
+                    template,
+                    pick_template,
+                } => Ok(ValidatedArgs::Daily {
+                    target: parse_daily_target(date, date_pos, prev, next)?,
+                    rollover: match (rollover, no_rollover) {
+                        (true, true) => {
+                            anyhow::bail!("No se pueden usar --rollover y --no-rollover al mismo tiempo")
+                        }
+                        (true, false) => Some(true),
+                        (false, true) => Some(false),
+                        (false, false) => None,
+                    },
                     editor: resolve_editor(self.editor_flag, self.editor_cmd)?,
                     skip_timestamp: self.no_id,
+                    template: parse_template(template, pick_template),
+                    yes: self.yes,
+                }),
+                Command::Week {
+                    template,
+                    pick_template,
+                } => periodic(
+                    Period::Week,
+                    parse_template(template, pick_template),
+                    self.editor_flag,
+                    self.editor_cmd,
+                    self.no_id,
+                    self.yes,
+                ),
+                Command::Month {
+                    template,
+                    pick_template,
+                } => periodic(
+                    Period::Month,
+                    parse_template(template, pick_template),
+                    self.editor_flag,
+                    self.editor_cmd,
+                    self.no_id,
+                    self.yes,
+                ),
+                Command::Quarter {
+                    template,
+                    pick_template,
+                } => periodic(
+                    Period::Quarter,
+                    parse_template(template, pick_template),
+                    self.editor_flag,
+                    self.editor_cmd,
+                    self.no_id,
+                    self.yes,
+                ),
+                Command::Year {
+                    template,
+                    pick_template,
+                } => periodic(
+                    Period::Year,
+                    parse_template(template, pick_template),
+                    self.editor_flag,
+                    self.editor_cmd,
+                    self.no_id,
+                    self.yes,
+                ),
+                Command::Capture {
+                    text,
+                    task,
+                    inbox,
+                    daily,
+                    heading,
+                } => Ok(ValidatedArgs::Capture {
+                    text,
+                    task,
+                    target: match (inbox, daily) {
+                        (true, true) => {
+                            anyhow::bail!("No se pueden usar --inbox y --daily al mismo tiempo")
+                        }
+                        (true, false) => Some(CaptureTarget::Inbox),
+                        (false, true) => Some(CaptureTarget::Daily),
+                        (false, false) => None,
+                    },
+                    heading,
+                }),
+                Command::Append {
+                    note,
+                    text,
+                    heading,
+                } => Ok(ValidatedArgs::Append {
+                    note,
+                    text,
+                    heading,
                 }),
                 Command::Last { count } => Ok(ValidatedArgs::Last {
                     count: count.unwrap_or(1),
                     editor: resolve_editor(self.editor_flag, self.editor_cmd)?,
                     skip_timestamp: self.no_id,
+                    format: output.format()?,
+                }),
+                Command::Search {
+                    query,
+                    limit,
+                    rebuild,
+                } => Ok(ValidatedArgs::Search {
+                    query,
+                    limit,
+                    rebuild,
+                    editor: resolve_editor(self.editor_flag, self.editor_cmd)?,
+                    skip_timestamp: self.no_id,
+                }),
+                Command::Query { query } => Ok(ValidatedArgs::Query {
+                    query,
+                    format: output.query_format()?,
+                }),
+                Command::Links { note } => Ok(ValidatedArgs::Links {
+                    note,
+                    direction: LinkDirection::Forward,
+                    editor: resolve_editor(self.editor_flag, self.editor_cmd)?,
+                    skip_timestamp: self.no_id,
+                }),
+                Command::Backlinks { note } => Ok(ValidatedArgs::Links {
+                    note,
+                    direction: LinkDirection::Backward,
+                    editor: resolve_editor(self.editor_flag, self.editor_cmd)?,
+                    skip_timestamp: self.no_id,
                 }),
-                Command::Tag { action } => {
-                    let action = parse_tag_action(action.as_deref())?;
+                Command::Tag {
+                    action,
+                    tag,
+                    from,
+                    to,
+                    recursive,
+                    plan,
+                } => {
+                    let rename = RenameFlags {
+                        from,
+                        to,
+                        recursive,
+                        yes: self.yes,
+                    };
+                    let action = parse_tag_action(action.as_deref(), tag, rename, plan, output)?;
                     Ok(ValidatedArgs::Tman(action))
                 }
                 Command::Retag {
                     target,
                     no_bak,
                     no_alias,
+                    plan,
                 } => Ok(ValidatedArgs::Retag {
                     target,
                     no_backup: no_bak,
                     no_alias,
+                    mode: plan.into(),
                 }),
-                Command::Redir { target, no_bak } => Ok(ValidatedArgs::Redir {
+                Command::Redir {
+                    target,
+                    no_bak,
+                    pick_first,
+                    plan,
+                } => Ok(ValidatedArgs::Redir {
                     target,
                     no_backup: no_bak,
+                    pick_first,
+                    mode: plan.into(),
+                }),
+                Command::Rename {
+                    new_name,
+                    no_retag,
+                    plan,
+                } => Ok(ValidatedArgs::Rename {
+                    new_name,
+                    no_retag,
+                    mode: plan.into(),
                 }),
+                Command::Migrate { plan } => Ok(ValidatedArgs::Migrate { mode: plan.into() }),
+                Command::Apply { plan } => Ok(ValidatedArgs::Apply { plan }),
+                Command::Undo { list } => Ok(ValidatedArgs::Undo { list }),
+                Command::Backups { action } => {
+                    Ok(ValidatedArgs::Backups(parse_backups_action(action)?))
+                }
+                Command::Redo => Ok(ValidatedArgs::Redo),
+                Command::Export { kind, tag, out } => {
+                    if kind != "html" {
+                        anyhow::bail!("Formato de exportación desconocido: '{}'. Usa: html", kind);
+                    }
+                    Ok(ValidatedArgs::Export { tag, out })
+                }
+                Command::Check { fix, plan } => {
+                    let mode: PlanMode = plan.into();
+                    let format = output.format()?;
+                    if mode.is_dry_run() && !fix {
+                        anyhow::bail!("--dry-run y --plan-out solo se usan con --fix");
+                    }
+                    if mode.is_dry_run() && format.is_structured() {
+                        anyhow::bail!("--json y --format no se pueden combinar con --dry-run ni --plan-out");
+                    }
+                    Ok(ValidatedArgs::Check { fix, format, mode })
+                }
                 Command::Cache { kind } => Ok(ValidatedArgs::Cache {
                     kind: parse_cache_kind(kind.as_deref())?,
+                    format: output.format()?,
                 }),
-                Command::Tasks { force_check_everywhere, full } => Ok(ValidatedArgs::Tasks {
-                    mark_all: force_check_everywhere,
+                Command::Tasks {
+                    force_check_everywhere,
                     full,
-                }),
+                    due,
+                    overdue,
+                    priority,
+                    tag,
+                    action,
+                } => {
+                    let format = output.format()?;
+                    if let Some(TasksCommand::Sync { dry_run }) = action {
+                        if format.is_structured()
+                            || force_check_everywhere
+                            || full
+                            || due.is_some()
+                            || overdue
+                            || priority.is_some()
+                            || tag.is_some()
+                        {
+                            anyhow::bail!("mad tasks sync no admite filtros, --full, --force-check-everywhere ni --json/--format");
+                        }
+                        return Ok(ValidatedArgs::TasksSync { dry_run });
+                    }
+                    if format.is_structured() && (force_check_everywhere || full) {
+                        anyhow::bail!("--json y --format no se pueden combinar con --full ni --force-check-everywhere");
+                    }
+                    let priority = match priority {
+                        Some(raw) => Some(Priority::parse(&raw).ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "Prioridad desconocida: '{}'. Usa: highest, high, medium, low, lowest",
+                                raw
+                            )
+                        })?),
+                        None => None,
+                    };
+                    Ok(ValidatedArgs::Tasks {
+                        mark_all: force_check_everywhere,
+                        full,
+                        format,
+                        filter: TaskFilter {
+                            due,
+                            overdue,
+                            priority,
+                            tag,
+                        },
+                        yes: self.yes,
+                    })
+                }
                 Command::Alias { name, command } => Ok(ValidatedArgs::Alias { name, command }),
             };
         }
@@ -195,6 +851,7 @@ impl Args {
         // Create note (no command)
         let editor = resolve_editor(self.editor_flag, self.editor_cmd)?;
         let skip_timestamp = self.no_id;
+        let template = parse_template(self.template, self.pick_template);
 
         let title_flag = self.title.is_some();
         let title = self.title.or(self.title_pos);
@@ -211,7 +868,11 @@ impl Args {
                             args.remove(0);
                         }
                         args.insert(0, "mad".to_string());
-                        let expanded = Args::parse_from(args);
+                        let mut expanded = Args::parse_from(args);
+                        // `mad mialias --json`: los flags de salida pasan al comando expandido
+                        expanded.format = expanded.format.or(output.format);
+                        expanded.json |= output.json;
+                        expanded.yes |= self.yes;
                         return expanded.validate_inner(depth + 1);
                     }
                 }
@@ -221,6 +882,7 @@ impl Args {
         if title.is_none() {
             anyhow::bail!("Falta título o comando. Usa: mad <comando> -h");
         }
+        output.reject()?;
 
         // Enforce: single-word titles require --title
         if !title_flag {
@@ -231,11 +893,21 @@ impl Args {
             }
         }
 
+        if target_dir.is_some() && self.tag.is_some() {
+            anyhow::bail!("--tag no se puede combinar con DIR: el tag se deriva del directorio");
+        }
+
         Ok(ValidatedArgs::Create {
             title,
             target_dir,
             editor,
             skip_timestamp,
+            template,
+            answers: NoteAnswers {
+                tag: self.tag,
+                aliases: self.aliases,
+                yes: self.yes,
+            },
         })
     }
 }
@@ -253,11 +925,99 @@ fn resolve_editor(editor_flag: bool, editor_cmd: Option<String>) -> anyhow::Resu
     Ok(editor)
 }
 
-fn parse_tag_action(raw: Option<&str>) -> anyhow::Result<TmanAction> {
-    match raw.unwrap_or("list") {
-        "list" | "ls" => Ok(TmanAction::List),
-        "rename" | "rn" => Ok(TmanAction::Rename),
-        "find" | "search" => Ok(TmanAction::Find),
+fn periodic(
+    period: Period,
+    template: TemplateSelection,
+    editor_flag: bool,
+    editor_cmd: Option<String>,
+    no_id: bool,
+    yes: bool,
+) -> anyhow::Result<ValidatedArgs> {
+    Ok(ValidatedArgs::Periodic {
+        period,
+        editor: resolve_editor(editor_flag, editor_cmd)?,
+        skip_timestamp: no_id,
+        template,
+        yes,
+    })
+}
+
+/// `--template nombre` → template con nombre, `--pick-template` → selector
+fn parse_template(name: Option<String>, pick: bool) -> TemplateSelection {
+    match name {
+        Some(name) => TemplateSelection::Named(name.trim().to_string()),
+        None if pick => TemplateSelection::Pick,
+        None => TemplateSelection::Default,
+    }
+}
+
+fn parse_daily_target(
+    date: Option<String>,
+    date_pos: Option<String>,
+    prev: bool,
+    next: bool,
+) -> anyhow::Result<DailyTarget> {
+    if date.is_some() && date_pos.is_some() {
+        anyhow::bail!("Usa solo una de: FECHA, --date, --prev, --next");
+    }
+    match (date.or(date_pos), prev, next) {
+        (None, false, false) => Ok(DailyTarget::Today),
+        (Some(date), false, false) => Ok(DailyTarget::Date(date)),
+        (None, true, false) => Ok(DailyTarget::Prev),
+        (None, false, true) => Ok(DailyTarget::Next),
+        _ => anyhow::bail!("Usa solo una de: FECHA, --date, --prev, --next"),
+    }
+}
+
+/// `mad tag rename --from a/b --to c --recursive`
+struct RenameFlags {
+    from: Option<String>,
+    to: Option<String>,
+    recursive: bool,
+    yes: bool,
+}
+
+fn parse_tag_action(
+    raw: Option<&str>,
+    tag: Option<String>,
+    rename: RenameFlags,
+    plan: PlanArgs,
+    output: OutputArgs,
+) -> anyhow::Result<TmanAction> {
+    let action = raw.unwrap_or("list");
+    if !matches!(action, "rename" | "rn") && (plan.dry_run || plan.plan_out.is_some()) {
+        anyhow::bail!("--dry-run y --plan-out solo aplican a: mad tag rename");
+    }
+    if !matches!(action, "rename" | "rn")
+        && (rename.from.is_some() || rename.to.is_some() || rename.recursive)
+    {
+        anyhow::bail!("--from, --to y --recursive solo aplican a: mad tag rename");
+    }
+    if rename.from.is_some() != rename.to.is_some() {
+        anyhow::bail!("Usa --from y --to juntos: mad tag rename --from a/b --to c");
+    }
+    if rename.recursive && rename.from.is_none() {
+        anyhow::bail!("--recursive necesita --from y --to");
+    }
+    if tag.is_some() && !matches!(action, "find" | "search") {
+        anyhow::bail!("El argumento TAG solo aplica a: mad tag find <tag>");
+    }
+    if !matches!(action, "list" | "ls" | "find" | "search") {
+        output.reject()?;
+    }
+    match action {
+        "list" | "ls" => Ok(TmanAction::List(output.format()?)),
+        "rename" | "rn" => Ok(TmanAction::Rename {
+            mode: plan.into(),
+            from: rename.from,
+            to: rename.to,
+            recursive: rename.recursive,
+            yes: rename.yes,
+        }),
+        "find" | "search" => Ok(TmanAction::Find {
+            tag,
+            format: output.format()?,
+        }),
         "log" | "visual" | "telescope" => Ok(TmanAction::Log),
         other => anyhow::bail!(
             "Acción de tag desconocida: '{}'. Usa: list, rename, find, log",
@@ -266,6 +1026,81 @@ fn parse_tag_action(raw: Option<&str>) -> anyhow::Result<TmanAction> {
     }
 }
 
+fn parse_backups_action(cmd: Option<BackupsCommand>) -> anyhow::Result<BackupsAction> {
+    Ok(match cmd.unwrap_or(BackupsCommand::List { note: None }) {
+        BackupsCommand::List { note } => BackupsAction::List { note },
+        BackupsCommand::Diff { backup } => BackupsAction::Diff { backup },
+        BackupsCommand::Restore { backup } => BackupsAction::Restore { backup },
+        BackupsCommand::Prune {
+            older_than,
+            keep,
+            dry_run,
+        } => {
+            if older_than.is_none() && keep.is_none() {
+                anyhow::bail!("Usa --older-than y/o --keep (ej: mad backups prune --older-than 30d --keep 5)");
+            }
+            BackupsAction::Prune {
+                older_than: older_than.as_deref().map(parse_age).transpose()?,
+                keep,
+                dry_run,
+            }
+        }
+    })
+}
+
+/// `30d`, `2w`, `6m`, `1y` → (cantidad, unidad)
+fn parse_age(raw: &str) -> anyhow::Result<(i64, char)> {
+    let raw = raw.trim();
+    date_expr::parse_age(raw)
+        .ok_or_else(|| anyhow::anyhow!("Edad inválida: '{}'. Usa por ejemplo: 30d, 2w, 6m, 1y", raw))
+}
+
+/// `--format` y `--json` (globales)
+struct OutputArgs {
+    format: Option<String>,
+    json: bool,
+}
+
+impl OutputArgs {
+    /// `--json` is a shorthand for `--format json`; both together must agree
+    fn raw(&self) -> anyhow::Result<Option<&str>> {
+        match (self.format.as_deref(), self.json) {
+            (Some(format), true) if format != "json" => {
+                anyhow::bail!("No se pueden usar --json y --format {} al mismo tiempo", format)
+            }
+            (_, true) => Ok(Some("json")),
+            (format, false) => Ok(format),
+        }
+    }
+
+    fn format(&self) -> anyhow::Result<OutputFormat> {
+        match self.raw()?.unwrap_or("text") {
+            "text" | "texto" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "tsv" => Ok(OutputFormat::Tsv),
+            other => anyhow::bail!("Formato desconocido: '{}'. Usa: json, tsv", other),
+        }
+    }
+
+    fn query_format(&self) -> anyhow::Result<QueryFormat> {
+        match self.raw()?.unwrap_or("paths") {
+            "paths" | "text" | "texto" => Ok(QueryFormat::Paths),
+            "table" | "tabla" => Ok(QueryFormat::Table),
+            "json" => Ok(QueryFormat::Json),
+            "tsv" => Ok(QueryFormat::Tsv),
+            other => anyhow::bail!("Formato desconocido: '{}'. Usa: paths, table, json, tsv", other),
+        }
+    }
+
+    /// For commands without structured output
+    fn reject(&self) -> anyhow::Result<()> {
+        if self.format.is_some() || self.json {
+            anyhow::bail!("--json y --format solo aplican a: last, query, tag list, tag find, tasks, cache, check");
+        }
+        Ok(())
+    }
+}
+
 fn parse_cache_kind(raw: Option<&str>) -> anyhow::Result<CacheKind> {
     match raw.unwrap_or("all") {
         "all" => Ok(CacheKind::All),
@@ -291,37 +1126,91 @@ pub enum ValidatedArgs {
         target_dir: Option<PathBuf>,
         editor: EditorMode,
         skip_timestamp: bool,
+        template: TemplateSelection,
+        answers: NoteAnswers,
     },
     Daily {
+        target: DailyTarget,
+        rollover: Option<bool>,
+        editor: EditorMode,
+        skip_timestamp: bool,
+        template: TemplateSelection,
+        yes: bool,
+    },
+    Periodic {
+        period: Period,
         editor: EditorMode,
         skip_timestamp: bool,
+        template: TemplateSelection,
+        yes: bool,
+    },
+    Capture {
+        text: Option<String>,
+        task: bool,
+        target: Option<CaptureTarget>,
+        heading: Option<String>,
+    },
+    Append {
+        note: String,
+        text: Option<String>,
+        heading: Option<String>,
     },
     Last {
         count: usize,
         editor: EditorMode,
         skip_timestamp: bool,
+        format: OutputFormat,
+    },
+    Search {
+        query: String,
+        limit: usize,
+        rebuild: bool,
+        editor: EditorMode,
+        skip_timestamp: bool,
+    },
+    Query {
+        query: String,
+        format: QueryFormat,
+    },
+    Links {
+        note: String,
+        direction: LinkDirection,
+        editor: EditorMode,
+        skip_timestamp: bool,
     },
     Tman(TmanAction),
     Retag {
         target: String,
         no_backup: bool,
         no_alias: bool,
+        mode: PlanMode,
     },
     Redir {
         target: String,
         no_backup: bool,
+        pick_first: bool,
+        mode: PlanMode,
     },
     Obsidian {
         title: String,
         editor: EditorMode,
         skip_timestamp: bool,
+        template: TemplateSelection,
+        answers: NoteAnswers,
     },
     Cache {
         kind: CacheKind,
+        format: OutputFormat,
     },
     Tasks {
         mark_all: bool,
         full: bool,
+        format: OutputFormat,
+        filter: TaskFilter,
+        yes: bool,
+    },
+    TasksSync {
+        dry_run: bool,
     },
     Alias {
         name: String,
@@ -330,5 +1219,66 @@ pub enum ValidatedArgs {
     Rename {
         new_name: String,
         no_retag: bool,
+        mode: PlanMode,
+    },
+    Migrate {
+        mode: PlanMode,
+    },
+    Apply {
+        plan: PathBuf,
     },
+    Undo {
+        list: bool,
+    },
+    Redo,
+    Backups(BackupsAction),
+    Check {
+        fix: bool,
+        format: OutputFormat,
+        mode: PlanMode,
+    },
+    Export {
+        tag: Option<String>,
+        out: PathBuf,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> anyhow::Result<ValidatedArgs> {
+        Args::try_parse_from(args)?.validate()
+    }
+
+    #[test]
+    fn test_template_takes_a_name_and_pick_template_opens_the_selector() {
+        let args = parse(&["mad", "-t", "Title", "--template", "meeting", "--tag", "proj"]).unwrap();
+        assert!(matches!(
+            args,
+            ValidatedArgs::Create { title: Some(title), target_dir: None, template: TemplateSelection::Named(name), .. }
+                if title == "Title" && name == "meeting"
+        ));
+        let args = parse(&["mad", "--pick-template", "Mi nota"]).unwrap();
+        assert!(matches!(
+            args,
+            ValidatedArgs::Create { title: Some(title), template: TemplateSelection::Pick, .. } if title == "Mi nota"
+        ));
+        let args = parse(&["mad", "dialy", "--pick-template", "ayer"]).unwrap();
+        assert!(matches!(
+            args,
+            ValidatedArgs::Daily { target: DailyTarget::Date(date), template: TemplateSelection::Pick, .. } if date == "ayer"
+        ));
+        let args = parse(&["mad", "week", "--template", "retro"]).unwrap();
+        assert!(matches!(args, ValidatedArgs::Periodic { template: TemplateSelection::Named(name), .. } if name == "retro"));
+        assert!(parse(&["mad", "Mi nota", "--template", "a", "--pick-template"]).is_err());
+    }
+
+    #[test]
+    fn test_export_kind_is_not_the_output_format() {
+        let args = parse(&["mad", "export", "html", "--out", "site"]).unwrap();
+        assert!(matches!(args, ValidatedArgs::Export { out, tag: None } if out == std::path::Path::new("site")));
+        assert!(parse(&["mad", "export", "pdf"]).is_err());
+        assert!(parse(&["mad", "export", "html", "--json"]).is_err());
+    }
 }
@@ -0,0 +1,165 @@
+/// Line-level edit between two texts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Above this many cells the LCS table is skipped and the changed region is
+/// shown as a whole deletion followed by a whole insertion
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+const CONTEXT: usize = 3;
+
+/// Unified diff (`diff -u`) between two texts, with 3 lines of context.
+/// Returns an empty string if both texts are equal.
+pub fn unified(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    if old == new {
+        return String::new();
+    }
+
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&a, &b);
+
+    let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+    for hunk in hunks(&ops) {
+        let ops = &ops[hunk];
+        let (old_start, old_len) = span(ops, |op| match op {
+            Op::Equal(i, _) | Op::Delete(i) => Some(*i),
+            Op::Insert(_) => None,
+        });
+        let (new_start, new_len) = span(ops, |op| match op {
+            Op::Equal(_, j) | Op::Insert(j) => Some(*j),
+            Op::Delete(_) => None,
+        });
+        let old_start = if old_len == 0 { old_start } else { old_start + 1 };
+        let new_start = if new_len == 0 { new_start } else { new_start + 1 };
+
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_len, new_start, new_len
+        ));
+        for op in ops {
+            match *op {
+                Op::Equal(i, _) => out.push_str(&format!(" {}\n", a[i])),
+                Op::Delete(i) => out.push_str(&format!("-{}\n", a[i])),
+                Op::Insert(j) => out.push_str(&format!("+{}\n", b[j])),
+            }
+        }
+    }
+
+    if ops.iter().all(|op| matches!(op, Op::Equal(..))) {
+        // Solo cambió el salto de línea final
+        out.push_str("\\ Cambio en el salto de línea final\n");
+    }
+
+    out
+}
+
+fn diff_lines(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    let mut ops: Vec<Op> = (0..prefix).map(|i| Op::Equal(i, i)).collect();
+
+    if a_mid.len() * b_mid.len() > MAX_LCS_CELLS {
+        ops.extend((0..a_mid.len()).map(|i| Op::Delete(prefix + i)));
+        ops.extend((0..b_mid.len()).map(|j| Op::Insert(prefix + j)));
+    } else {
+        // lcs[i][j] = LCS de a_mid[i..] y b_mid[j..]
+        let (n, m) = (a_mid.len(), b_mid.len());
+        let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i * (m + 1) + j] = if a_mid[i] == b_mid[j] {
+                    lcs[(i + 1) * (m + 1) + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * (m + 1) + j].max(lcs[i * (m + 1) + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && a_mid[i] == b_mid[j] {
+                ops.push(Op::Equal(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j < m && (i == n || lcs[i * (m + 1) + j + 1] > lcs[(i + 1) * (m + 1) + j]) {
+                ops.push(Op::Insert(prefix + j));
+                j += 1;
+            } else {
+                ops.push(Op::Delete(prefix + i));
+                i += 1;
+            }
+        }
+    }
+
+    let (a_end, b_end) = (a.len() - suffix, b.len() - suffix);
+    ops.extend((0..suffix).map(|k| Op::Equal(a_end + k, b_end + k)));
+    ops
+}
+
+/// Ranges of `ops` to print: each change with up to 3 lines of context,
+/// merging hunks whose context overlaps
+fn hunks(ops: &[Op]) -> Vec<std::ops::Range<usize>> {
+    let mut ranges: Vec<std::ops::Range<usize>> = Vec::new();
+    for (idx, op) in ops.iter().enumerate() {
+        if matches!(op, Op::Equal(..)) {
+            continue;
+        }
+        let start = idx.saturating_sub(CONTEXT);
+        let end = (idx + CONTEXT + 1).min(ops.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.end => last.end = last.end.max(end),
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
+
+/// (first index, count) of the lines of one side in a hunk
+fn span(ops: &[Op], side: impl Fn(&Op) -> Option<usize>) -> (usize, usize) {
+    let lines: Vec<usize> = ops.iter().filter_map(&side).collect();
+    match lines.first() {
+        Some(&first) => (first, lines.len()),
+        // Hunk sin líneas de este lado: posición de la línea anterior
+        None => (0, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nb\nc\nd\nE\nf\ng\nh\ni\nj\nk\n";
+        assert_eq!(
+            unified(old, new, "a/x.md", "b/x.md"),
+            "--- a/x.md\n+++ b/x.md\n@@ -2,9 +2,10 @@\n b\n c\n d\n-e\n+E\n f\n g\n h\n i\n j\n+k\n"
+        );
+        assert_eq!(unified(old, old, "a", "b"), "");
+    }
+
+    #[test]
+    fn test_separate_hunks() {
+        let old: String = (1..=20).map(|i| format!("{}\n", i)).collect();
+        let new: String = (1..=20)
+            .filter(|i| *i != 19)
+            .map(|i| if i == 2 { "dos\n".to_string() } else { format!("{}\n", i) })
+            .collect();
+        let diff = unified(&old, &new, "a", "b");
+        assert!(diff.contains("@@ -1,5 +1,5 @@\n 1\n-2\n+dos\n 3\n 4\n 5\n"));
+        assert!(diff.contains("@@ -16,5 +16,4 @@\n 16\n 17\n 18\n-19\n 20\n"));
+    }
+}
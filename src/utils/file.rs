@@ -1,8 +1,10 @@
+use chrono::Local;
 use serde_yaml::Mapping;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-/// Write merged frontmatter (YAML) + body to file
+/// Write merged frontmatter (YAML) + body to a new file.
+/// Para editar notas existentes usar `frontmatter::Document`, que conserva el formato.
 pub fn write_note(path: &Path, fm: &Mapping, body: &str) -> anyhow::Result<()> {
     let mut out = String::new();
     if !fm.is_empty() {
@@ -17,6 +19,66 @@ pub fn write_note(path: &Path, fm: &Mapping, body: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Create backup in vault/.arc/backups/ with timestamp.
+/// Backups mirror the note's directory inside the vault:
+/// `Notas/dev/api.md` → `.arc/backups/Notas/dev/api_20260202_131045.md.bak`
+/// (`_2`, `_3`... si ya hay uno en el mismo segundo). Files outside the vault go flat.
+/// Returns the backup path
+pub fn create_backup(vault: &Path, file_path: &Path) -> anyhow::Result<PathBuf> {
+    let backup_dir = backups_dir(vault);
+
+    // Get filename without path
+    let filename = file_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid filename"))?;
+
+    let rel_dir = vault_relative(vault, file_path)
+        .and_then(|rel| rel.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    let target_dir = backup_dir.join(rel_dir);
+    fs::create_dir_all(&target_dir)?;
+
+    // Generate timestamp
+    let timestamp = Local::now().format("%Y%m%d_%H%M%S").to_string();
+
+    // Build backup filename: original_20260202_131045.md.bak
+    let (stem, ext) = match filename.strip_suffix(".md") {
+        Some(stem) => (stem, ".md.bak"),
+        None => (filename, ".bak"),
+    };
+    let mut backup_path = target_dir.join(format!("{}_{}{}", stem, timestamp, ext));
+    let mut n = 1;
+    while backup_path.exists() {
+        n += 1;
+        backup_path = target_dir.join(format!("{}_{}_{}{}", stem, timestamp, n, ext));
+    }
+
+    fs::copy(file_path, &backup_path)?;
+
+    Ok(backup_path)
+}
+
+pub fn backups_dir(vault: &Path) -> PathBuf {
+    vault.join(".arc").join("backups")
+}
+
+/// Path relative to the vault (also if one of them is canonical and the other is not)
+pub fn vault_relative(vault: &Path, path: &Path) -> Option<PathBuf> {
+    if let Ok(rel) = path.strip_prefix(vault) {
+        return Some(rel.to_path_buf());
+    }
+    let canonical_vault = vault.canonicalize().ok()?;
+    let canonical = match path.canonicalize() {
+        Ok(p) => p,
+        Err(_) => path.parent()?.canonicalize().ok()?.join(path.file_name()?),
+    };
+    canonical
+        .strip_prefix(&canonical_vault)
+        .ok()
+        .map(Path::to_path_buf)
+}
+
 /// Find notebook case-insensitive
 pub fn find_notebook_case_insensitive(vault: &Path, name: &str) -> Option<std::path::PathBuf> {
     let lower = name.to_lowercase();
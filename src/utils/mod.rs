@@ -1,4 +1,6 @@
 pub mod cli;
+pub mod diff;
 pub mod file;
 pub mod alias;
+pub mod output;
 pub mod vault;
@@ -0,0 +1,77 @@
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// Salida de los comandos que listan (`--json` / `--format json|tsv`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Texto para humanos (y prompts interactivos)
+    #[default]
+    Text,
+    Json,
+    Tsv,
+}
+
+impl OutputFormat {
+    /// JSON or TSV: no prompts, no emojis, one record per item
+    pub fn is_structured(self) -> bool {
+        self != OutputFormat::Text
+    }
+}
+
+/// Print `records` as a pretty JSON array, or one TSV line per record using `row`
+pub fn print<T: Serialize>(
+    format: OutputFormat,
+    records: &[T],
+    row: impl Fn(&T) -> Vec<String>,
+) -> anyhow::Result<()> {
+    match format {
+        OutputFormat::Json => print_json(records),
+        OutputFormat::Tsv | OutputFormat::Text => {
+            let lines: Vec<String> = records.iter().map(|r| tsv_line(&row(r))).collect();
+            print_lines(&lines)
+        }
+    }
+}
+
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> anyhow::Result<()> {
+    print_lines(&[serde_json::to_string_pretty(value)?])
+}
+
+/// Write to stdout; a closed pipe (`mad tasks --format tsv | head`) is not an error
+pub fn print_lines<S: AsRef<str>>(lines: &[S]) -> anyhow::Result<()> {
+    let mut out = io::stdout().lock();
+    for line in lines {
+        match writeln!(out, "{}", line.as_ref()) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
+
+/// Cells joined by tabs; tabs, newlines and backslashes are escaped so every record is one line
+pub fn tsv_line<S: AsRef<str>>(cells: &[S]) -> String {
+    cells
+        .iter()
+        .map(|cell| {
+            cell.as_ref()
+                .replace('\\', "\\\\")
+                .replace('\t', "\\t")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r")
+        })
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tsv_cells_stay_on_one_line() {
+        assert_eq!(tsv_line(&["a", "b c", ""]), "a\tb c\t");
+        assert_eq!(tsv_line(&["x\ty", "1\n2", "c:\\d"]), "x\\ty\t1\\n2\tc:\\\\d");
+    }
+}
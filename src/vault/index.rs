@@ -0,0 +1,462 @@
+use super::scan::{self, Link, LinkScanItem};
+use crate::core::config::Config;
+use crate::core::frontmatter;
+use crate::core::task::{ListItem, TaskLine};
+use crate::search::index::RefreshStats;
+use crate::tags::parser::TagPath;
+use crate::utils::vault::VaultWalker;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const INDEX_VERSION: u32 = 2;
+
+/// Heading `## Título` (línea 1-based en el archivo)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Heading {
+    pub level: usize,
+    pub text: String,
+    pub line: usize,
+}
+
+/// Tarea `- [ ] texto` (o `* [ ]`, `1. [ ]`, indentada, en una cita...).
+/// `status` es el carácter entre corchetes (' ', 'x', 'M'...)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedTask {
+    pub line: usize,
+    pub status: char,
+    pub text: String,
+    /// Línea de la tarea que la contiene (subtarea indentada)
+    pub parent: Option<usize>,
+}
+
+/// Metadatos indexados de una nota
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteMeta {
+    /// Nanosegundos desde epoch
+    pub mtime: u64,
+    pub size: u64,
+    pub primary_tag: Option<TagPath>,
+    /// Tags del frontmatter + #tags del cuerpo (incluye el primario)
+    pub secondary_tags: Vec<TagPath>,
+    pub frontmatter: Mapping,
+    pub headings: Vec<Heading>,
+    pub links: Vec<Link>,
+    pub tasks: Vec<IndexedTask>,
+}
+
+impl NoteMeta {
+    /// Parse a note. Malformed frontmatter is treated as empty.
+    pub fn parse(content: &str, mtime: u64, size: u64) -> Self {
+        let (fm, body) = frontmatter::extract(content).unwrap_or_default();
+        let (primary_tag, secondary_tags) = scan::extract_tags(&fm, &body);
+        let (headings, tasks) = parse_structure(content, &body);
+
+        Self {
+            mtime,
+            size,
+            primary_tag,
+            secondary_tags,
+            frontmatter: json_safe_mapping(fm),
+            headings,
+            links: scan::extract_links(content),
+            tasks,
+        }
+    }
+
+    /// Fecha de modificación del archivo al indexarlo
+    pub fn modified(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_nanos(self.mtime)
+    }
+}
+
+/// Índice persistente de metadatos del vault, keyed by path relativo.
+/// Se actualiza incrementalmente: solo se re-parsean los archivos cuyo
+/// mtime o tamaño cambió, y se eliminan los que ya no existen.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultIndex {
+    version: u32,
+    vault: String,
+    pub(crate) notes: BTreeMap<String, NoteMeta>,
+}
+
+impl VaultIndex {
+    /// Crea un índice vacío para el vault
+    pub fn new(vault: &Path) -> Self {
+        Self {
+            version: INDEX_VERSION,
+            vault: vault.to_string_lossy().to_string(),
+            notes: BTreeMap::new(),
+        }
+    }
+
+    /// Carga el índice desde disco.
+    /// Si no existe, es de otra versión o de otro vault, devuelve un índice vacío.
+    pub fn load(index_path: &Path, vault: &Path) -> Self {
+        if let Ok(content) = fs::read_to_string(index_path) {
+            if let Ok(index) = serde_json::from_str::<VaultIndex>(&content) {
+                if index.version == INDEX_VERSION && index.vault == vault.to_string_lossy() {
+                    return index;
+                }
+            }
+        }
+        Self::new(vault)
+    }
+
+    pub fn save(&self, index_path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = index_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string(self)?;
+        fs::write(index_path, json)?;
+        Ok(())
+    }
+
+    /// Carga el índice persistido, lo actualiza y lo guarda si cambió
+    pub fn open(vault: &Path, templates_path: &Path) -> anyhow::Result<Self> {
+        let index_path = Config::vault_index_path()?;
+        let mut index = Self::load(&index_path, vault);
+        if index.refresh(vault, templates_path)?.changed() {
+            index.save(&index_path)?;
+        }
+        Ok(index)
+    }
+
+    /// Regenera el índice desde cero y lo guarda
+    pub fn rebuild(vault: &Path, templates_path: &Path) -> anyhow::Result<Self> {
+        let index_path = Config::vault_index_path()?;
+        let mut index = Self::new(vault);
+        index.refresh(vault, templates_path)?;
+        index.save(&index_path)?;
+        Ok(index)
+    }
+
+    /// Cantidad de notas indexadas
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    /// Actualiza el índice re-parseando solo los archivos cuyo mtime o tamaño cambió.
+    /// Los archivos que ya no existen se eliminan del índice.
+    pub fn refresh(&mut self, vault: &Path, templates_path: &Path) -> anyhow::Result<RefreshStats> {
+        let mut stats = RefreshStats::default();
+        let mut seen: HashSet<String> = HashSet::new();
+        // (path, key, mtime, size, ya estaba indexado)
+        let mut pending: Vec<(PathBuf, String, u64, u64, bool)> = Vec::new();
+
+        VaultWalker::new(vault)
+            .exclude_templates(templates_path)
+            .walk_paths(|path| {
+                let Ok(metadata) = fs::metadata(path) else {
+                    return Ok(());
+                };
+                let rel = relative_key(vault, path);
+                let mtime = mtime_nanos(&metadata);
+                let size = metadata.len();
+
+                match self.notes.get(&rel) {
+                    Some(note) if note.mtime == mtime && note.size == size => {}
+                    Some(_) => pending.push((path.to_path_buf(), rel.clone(), mtime, size, true)),
+                    None => pending.push((path.to_path_buf(), rel.clone(), mtime, size, false)),
+                }
+                seen.insert(rel);
+                Ok(())
+            })?;
+
+        self.notes.retain(|rel, _| {
+            let keep = seen.contains(rel);
+            if !keep {
+                stats.removed += 1;
+            }
+            keep
+        });
+
+        for (path, rel, mtime, size, existed) in pending {
+            let Ok(content) = fs::read_to_string(&path) else {
+                if self.notes.remove(&rel).is_some() {
+                    stats.removed += 1;
+                }
+                continue;
+            };
+            self.notes
+                .insert(rel, NoteMeta::parse(&content, mtime, size));
+            if existed {
+                stats.updated += 1;
+            } else {
+                stats.added += 1;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Notas indexadas con su path absoluto, ordenadas por path
+    pub fn iter(&self) -> impl Iterator<Item = (PathBuf, &NoteMeta)> + '_ {
+        let vault = Path::new(&self.vault);
+        self.notes
+            .iter()
+            .map(move |(rel, note)| (vault.join(rel), note))
+    }
+
+    /// Metadatos de una nota por su path absoluto
+    pub fn get(&self, path: &Path) -> Option<&NoteMeta> {
+        let rel = path.strip_prefix(&self.vault).ok()?;
+        self.notes.get(&relative_key(Path::new(""), rel))
+    }
+
+    /// Links de cada nota, para construir un `LinkGraph` sin re-escanear el vault
+    pub fn link_items(&self) -> Vec<LinkScanItem> {
+        self.iter()
+            .map(|(path, note)| LinkScanItem {
+                path,
+                links: note.links.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Headings and tasks of the body (fenced code blocks are ignored).
+/// Line numbers refer to the whole file.
+fn parse_structure(content: &str, body: &str) -> (Vec<Heading>, Vec<IndexedTask>) {
+    let offset = if body.len() <= content.len() && content.ends_with(body) {
+        content[..content.len() - body.len()].matches('\n').count()
+    } else {
+        0
+    };
+
+    let mut headings = Vec::new();
+    let mut tasks = Vec::new();
+    let mut in_code_block = false;
+    // Items de lista abiertos: (citas, indentación, tarea que contiene al item)
+    let mut open_items: Vec<(usize, usize, Option<usize>)> = Vec::new();
+
+    for (idx, line) in body.split('\n').enumerate() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+
+        if in_code_block {
+            continue;
+        }
+
+        let line_number = offset + idx + 1;
+
+        if let Some(heading) = parse_heading(line) {
+            open_items.clear();
+            headings.push(Heading {
+                line: line_number,
+                ..heading
+            });
+        } else if let Some(item) = ListItem::parse(line) {
+            // El padre es el item abierto más cercano con menos indentación en la misma cita
+            while let Some(&(quote, indent, _)) = open_items.last() {
+                if quote == item.quote && indent < item.indent {
+                    break;
+                }
+                open_items.pop();
+            }
+            let parent = open_items.last().and_then(|&(_, _, task)| task);
+
+            let task = TaskLine::parse(line);
+            if let Some(task) = task {
+                tasks.push(IndexedTask {
+                    line: line_number,
+                    status: task.status,
+                    text: task.text.trim_end().to_string(),
+                    parent,
+                });
+            }
+            let contains = if task.is_some() { Some(line_number) } else { parent };
+            open_items.push((item.quote, item.indent, contains));
+        } else if !line.trim().is_empty() && !line.starts_with([' ', '\t', '>']) {
+            // Un párrafo sin indentar cierra las listas
+            open_items.clear();
+        }
+    }
+
+    (headings, tasks)
+}
+
+/// `## Título` → Heading (sin número de línea). `#tag` no es un heading.
+fn parse_heading(line: &str) -> Option<Heading> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') && !rest.starts_with('\t') {
+        return None;
+    }
+
+    Some(Heading {
+        level,
+        text: rest.trim().trim_end_matches('#').trim_end().to_string(),
+        line: 0,
+    })
+}
+
+/// JSON only allows string keys: stringify scalar keys and drop complex ones
+fn json_safe_mapping(map: Mapping) -> Mapping {
+    let mut out = Mapping::new();
+    for (k, v) in map {
+        let key = match k {
+            Value::String(s) => s,
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            _ => continue,
+        };
+        out.insert(Value::String(key), json_safe(v));
+    }
+    out
+}
+
+fn json_safe(value: Value) -> Value {
+    match value {
+        Value::Mapping(m) => Value::Mapping(json_safe_mapping(m)),
+        Value::Sequence(seq) => Value::Sequence(seq.into_iter().map(json_safe).collect()),
+        Value::Tagged(tagged) => json_safe(tagged.value),
+        other => other,
+    }
+}
+
+fn relative_key(vault: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(vault).unwrap_or(path);
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn mtime_nanos(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_note_metadata() {
+        let content = "---\ntags:\n  - proyecto/acme\nestado: abierto\n---\n{ #dev/api }\n# Reunión\n\n- [ ] llamar a [[Cliente]]\n- [x] enviar mail\n```\n# no es heading\n- [ ] tampoco tarea\n```\n## Notas #idea\n";
+        let note = NoteMeta::parse(content, 1, content.len() as u64);
+
+        assert_eq!(
+            note.primary_tag,
+            Some(TagPath(vec!["dev".to_string(), "api".to_string()]))
+        );
+        let tags: Vec<String> = note
+            .secondary_tags
+            .iter()
+            .map(|t| t.to_slash_string())
+            .collect();
+        assert!(tags.contains(&"proyecto/acme".to_string()));
+        assert!(tags.contains(&"idea".to_string()));
+        assert!(tags.contains(&"dev/api".to_string()));
+
+        assert_eq!(
+            note.frontmatter.get("estado"),
+            Some(&Value::String("abierto".to_string()))
+        );
+
+        assert_eq!(
+            note.headings,
+            vec![
+                Heading {
+                    level: 1,
+                    text: "Reunión".to_string(),
+                    line: 7
+                },
+                Heading {
+                    level: 2,
+                    text: "Notas #idea".to_string(),
+                    line: 15
+                },
+            ]
+        );
+
+        assert_eq!(note.tasks.len(), 2);
+        assert_eq!(note.tasks[0].line, 9);
+        assert_eq!(note.tasks[0].status, ' ');
+        assert_eq!(note.tasks[0].text, "llamar a [[Cliente]]");
+        assert_eq!(note.tasks[1].status, 'x');
+
+        assert_eq!(note.links.len(), 1);
+        assert_eq!(note.links[0].target, "Cliente");
+    }
+
+    #[test]
+    fn test_nested_and_alternate_tasks() {
+        let content = "# Tareas\n- [ ] padre\n  - nota\n    * [ ] nieta\n  1. [x] hija\n> [!todo]\n> + [ ] en callout\n>   - [ ] sub\ntexto\n  - [ ] suelta\n";
+        let note = NoteMeta::parse(content, 0, 0);
+        let tasks: Vec<(usize, char, &str, Option<usize>)> = note
+            .tasks
+            .iter()
+            .map(|t| (t.line, t.status, t.text.as_str(), t.parent))
+            .collect();
+        assert_eq!(
+            tasks,
+            vec![
+                (2, ' ', "padre", None),
+                (4, ' ', "nieta", Some(2)),
+                (5, 'x', "hija", Some(2)),
+                (7, ' ', "en callout", None),
+                (8, ' ', "sub", Some(7)),
+                (10, ' ', "suelta", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_refresh_is_incremental() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        let templates = vault.join("Templates");
+        fs::create_dir_all(vault.join("Notas")).unwrap();
+        fs::create_dir_all(&templates).unwrap();
+
+        fs::write(vault.join("Notas/a.md"), "# A\n").unwrap();
+        fs::write(vault.join("b.md"), "- [ ] tarea\n").unwrap();
+        fs::write(templates.join("t.md"), "# Template\n").unwrap();
+
+        let mut index = VaultIndex::new(vault);
+        let stats = index.refresh(vault, &templates).unwrap();
+        assert_eq!((stats.added, stats.updated, stats.removed), (2, 0, 0));
+        assert!(index.get(&vault.join("Notas/a.md")).is_some());
+
+        let stats = index.refresh(vault, &templates).unwrap();
+        assert!(!stats.changed());
+
+        fs::write(vault.join("b.md"), "- [ ] tarea\n- [ ] otra\n").unwrap();
+        fs::remove_file(vault.join("Notas/a.md")).unwrap();
+        fs::write(vault.join("c.md"), "nueva\n").unwrap();
+
+        let stats = index.refresh(vault, &templates).unwrap();
+        assert_eq!((stats.added, stats.updated, stats.removed), (1, 1, 1));
+        assert_eq!(index.get(&vault.join("b.md")).unwrap().tasks.len(), 2);
+        assert!(index.get(&vault.join("Notas/a.md")).is_none());
+
+        // Round-trip por JSON
+        let index_path = temp.path().join("index.json");
+        index.save(&index_path).unwrap();
+        let loaded = VaultIndex::load(&index_path, vault);
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.get(&vault.join("b.md")).unwrap().tasks.len(), 2);
+    }
+}
@@ -0,0 +1,182 @@
+use super::plan::Plan;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Applied operation, stored in `.arc/journal/<id>.json`.
+/// The plan keeps the content before/after each edit, so undo does not depend on the backups.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// `20261016_224142_123`: sorts chronologically
+    pub id: String,
+    pub date: String,
+    pub undone: bool,
+    pub plan: Plan,
+    /// Backup of each edited, moved or deleted file
+    #[serde(default)]
+    pub backups: BTreeMap<PathBuf, PathBuf>,
+}
+
+/// Operations kept in the journal (las más viejas se descartan al registrar una nueva)
+pub const MAX_ENTRIES: usize = 200;
+
+pub fn journal_dir(vault: &Path) -> PathBuf {
+    vault.join(".arc").join("journal")
+}
+
+/// Record an applied plan. Undone entries are dropped: after a new operation
+/// there is nothing left to redo.
+pub fn record(vault: &Path, plan: &Plan, backups: &HashMap<PathBuf, PathBuf>) -> anyhow::Result<Entry> {
+    let dir = journal_dir(vault);
+    fs::create_dir_all(&dir)?;
+
+    for (path, entry) in list(vault)? {
+        if entry.undone {
+            fs::remove_file(path)?;
+        }
+    }
+
+    let now = Local::now();
+    let mut id = now.format("%Y%m%d_%H%M%S_%3f").to_string();
+    let mut n = 1;
+    while dir.join(format!("{}.json", id)).exists() {
+        n += 1;
+        id = format!("{}_{}", now.format("%Y%m%d_%H%M%S_%3f"), n);
+    }
+
+    let entry = Entry {
+        id,
+        date: now.format("%Y-%m-%d %H:%M:%S").to_string(),
+        undone: false,
+        plan: plan.clone(),
+        backups: backups.clone().into_iter().collect(),
+    };
+    save(&dir.join(format!("{}.json", entry.id)), &entry)?;
+    prune(vault, MAX_ENTRIES)?;
+    Ok(entry)
+}
+
+/// Delete the oldest entries, keeping the last `keep`. Returns how many were deleted.
+pub fn prune(vault: &Path, keep: usize) -> anyhow::Result<usize> {
+    let entries = list(vault)?;
+    let excess = entries.len().saturating_sub(keep);
+    for (path, _) in &entries[..excess] {
+        fs::remove_file(path)?;
+    }
+    Ok(excess)
+}
+
+/// Journal entries, oldest first
+pub fn list(vault: &Path) -> anyhow::Result<Vec<(PathBuf, Entry)>> {
+    let dir = journal_dir(vault);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    for res in fs::read_dir(&dir)? {
+        let path = res?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let raw = fs::read_to_string(&path)?;
+        let entry: Entry = serde_json::from_str(&raw)
+            .map_err(|e| anyhow::anyhow!("Entrada de journal inválida {}: {}", path.display(), e))?;
+        entries.push((path, entry));
+    }
+    entries.sort_by(|a, b| a.1.id.cmp(&b.1.id));
+    Ok(entries)
+}
+
+/// Revert the last applied operation. None if there is nothing to undo.
+pub fn undo(vault: &Path) -> anyhow::Result<Option<Entry>> {
+    let Some((path, mut entry)) = list(vault)?.into_iter().rev().find(|(_, e)| !e.undone) else {
+        return Ok(None);
+    };
+
+    entry.plan.inverse().execute()?;
+    entry.undone = true;
+    save(&path, &entry)?;
+    Ok(Some(entry))
+}
+
+/// Re-apply the last undone operation. None if there is nothing to redo.
+pub fn redo(vault: &Path) -> anyhow::Result<Option<Entry>> {
+    let Some((path, mut entry)) = list(vault)?.into_iter().find(|(_, e)| e.undone) else {
+        return Ok(None);
+    };
+
+    let backups = entry.plan.execute()?;
+    entry.undone = false;
+    entry.backups.extend(backups);
+    save(&path, &entry)?;
+    Ok(Some(entry))
+}
+
+fn save(path: &Path, entry: &Entry) -> anyhow::Result<()> {
+    fs::write(path, serde_json::to_string_pretty(entry)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::plan::Change;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_undo_redo_moves_and_edits() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::create_dir_all(vault.join("a")).unwrap();
+        fs::write(vault.join("a/n.md"), "uno\n").unwrap();
+        fs::write(vault.join("otra.md"), "[[a/n]]\n").unwrap();
+
+        let mut plan = Plan::new("redir", vault);
+        plan.create_dir(&vault.join("b/c"));
+        plan.move_path(&vault.join("a/n.md"), &vault.join("b/c/n.md"));
+        plan.edit(&vault.join("otra.md"), "[[b/c/n]]\n".to_string()).unwrap();
+        plan.apply().unwrap();
+
+        let entries = list(vault).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].1.backups.len(), 2);
+
+        let undone = undo(vault).unwrap().unwrap();
+        assert_eq!(undone.plan.command, "redir");
+        assert_eq!(fs::read_to_string(vault.join("a/n.md")).unwrap(), "uno\n");
+        assert_eq!(fs::read_to_string(vault.join("otra.md")).unwrap(), "[[a/n]]\n");
+        assert!(!vault.join("b").exists());
+        assert!(undo(vault).unwrap().is_none());
+
+        redo(vault).unwrap().unwrap();
+        assert!(vault.join("b/c/n.md").exists());
+        assert_eq!(fs::read_to_string(vault.join("otra.md")).unwrap(), "[[b/c/n]]\n");
+        assert!(redo(vault).unwrap().is_none());
+
+        // Undo falla sin tocar nada si el archivo cambió después de la operación
+        fs::write(vault.join("otra.md"), "editado\n").unwrap();
+        assert!(undo(vault).is_err());
+        assert!(vault.join("b/c/n.md").exists());
+        assert!(!vault.join("a/n.md").exists());
+    }
+
+    #[test]
+    fn test_prune_keeps_newest_entries() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        for i in 0..3 {
+            let mut plan = Plan::new("capture", vault);
+            plan.create(&vault.join(format!("{}.md", i)), "x\n".to_string());
+            plan.apply().unwrap();
+        }
+
+        assert_eq!(prune(vault, 2).unwrap(), 1);
+        let entries = list(vault).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(matches!(&entries[0].1.plan.changes[0], Change::Create { path, .. } if path.ends_with("1.md")));
+        assert_eq!(prune(vault, 2).unwrap(), 0);
+    }
+}
@@ -0,0 +1,259 @@
+use super::scan::{self, Link, LinkKind};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+/// Resolves link targets to note paths, following Obsidian rules:
+/// - `[[nombre]]` → nota con ese nombre en cualquier carpeta
+/// - `[[carpeta/nombre]]` → path relativo al vault
+/// - `[texto](../path.md)` → path relativo a la nota que contiene el link
+pub struct NoteResolver {
+    vault: PathBuf,
+    by_stem: HashMap<String, Vec<PathBuf>>,
+    by_rel: HashMap<String, PathBuf>,
+}
+
+impl NoteResolver {
+    pub fn new<'a>(vault: &Path, notes: impl IntoIterator<Item = &'a PathBuf>) -> Self {
+        let mut by_stem: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        let mut by_rel = HashMap::new();
+
+        for path in notes {
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                by_stem
+                    .entry(stem.to_lowercase())
+                    .or_default()
+                    .push(path.clone());
+            }
+            let rel = path.strip_prefix(vault).unwrap_or(path);
+            by_rel.insert(rel_key(rel), path.clone());
+        }
+
+        // Ante nombres duplicados se prefiere el path más corto (como Obsidian)
+        for paths in by_stem.values_mut() {
+            paths.sort_by(|a, b| {
+                a.components()
+                    .count()
+                    .cmp(&b.components().count())
+                    .then_with(|| a.cmp(b))
+            });
+        }
+
+        Self {
+            vault: vault.to_path_buf(),
+            by_stem,
+            by_rel,
+        }
+    }
+
+    /// Resolve a link found in `source`
+    pub fn resolve(&self, source: &Path, link: &Link) -> Option<PathBuf> {
+        match link.kind {
+            LinkKind::Wiki => self.resolve_name(&link.target, Some(source)),
+            LinkKind::Markdown => {
+                let target = with_md_extension(&link.target);
+                let from_source = source
+                    .parent()
+                    .map(|dir| normalize(&dir.join(&target)))
+                    .and_then(|p| {
+                        p.strip_prefix(&self.vault)
+                            .ok()
+                            .and_then(|rel| self.by_rel.get(&rel_key(rel)))
+                            .cloned()
+                    });
+                from_source.or_else(|| {
+                    let rel = normalize(Path::new(target.trim_start_matches('/')));
+                    self.by_rel.get(&rel_key(&rel)).cloned()
+                })
+            }
+        }
+    }
+
+    /// Resolve a note name (`nombre` or `carpeta/nombre`, with or without `.md`).
+    /// If several notes share the name, prefers the one next to `source`.
+    pub fn resolve_name(&self, name: &str, source: Option<&Path>) -> Option<PathBuf> {
+        let name = name.trim();
+        if name.contains('/') {
+            let rel = normalize(Path::new(&with_md_extension(name.trim_start_matches('/'))));
+            if let Some(found) = self.by_rel.get(&rel_key(&rel)) {
+                return Some(found.clone());
+            }
+        }
+
+        let stem = Path::new(name)
+            .file_name()
+            .and_then(|s| s.to_str())
+            .map(|s| s.strip_suffix(".md").unwrap_or(s))?;
+        let candidates = self.by_stem.get(&stem.to_lowercase())?;
+
+        if let Some(dir) = source.and_then(|s| s.parent()) {
+            if let Some(sibling) = candidates.iter().find(|c| c.parent() == Some(dir)) {
+                return Some(sibling.clone());
+            }
+        }
+
+        candidates.first().cloned()
+    }
+}
+
+/// Link with its resolved destination (None = link roto)
+#[derive(Clone, Debug)]
+pub struct ResolvedLink {
+    pub link: Link,
+    pub target: Option<PathBuf>,
+}
+
+/// Incoming link: `source` links to the note through `link`
+#[derive(Clone, Debug)]
+pub struct Backlink {
+    pub source: PathBuf,
+    pub link: Link,
+}
+
+/// Forward/backward link index of the vault
+pub struct LinkGraph {
+    pub forward: BTreeMap<PathBuf, Vec<ResolvedLink>>,
+    pub backward: BTreeMap<PathBuf, Vec<Backlink>>,
+    pub resolver: NoteResolver,
+}
+
+impl LinkGraph {
+    /// Scan the vault and build the link index
+    pub fn build(vault: &Path, templates_path: &Path) -> anyhow::Result<Self> {
+        let items = scan::scan_links(vault, templates_path)?;
+        Ok(Self::from_items(vault, items))
+    }
+
+    pub fn from_items(vault: &Path, items: Vec<scan::LinkScanItem>) -> Self {
+        let resolver = NoteResolver::new(vault, items.iter().map(|i| &i.path));
+
+        let mut forward = BTreeMap::new();
+        let mut backward: BTreeMap<PathBuf, Vec<Backlink>> = BTreeMap::new();
+
+        for item in items {
+            let resolved: Vec<ResolvedLink> = item
+                .links
+                .into_iter()
+                .map(|link| {
+                    let target = resolver.resolve(&item.path, &link);
+                    if let Some(ref t) = target {
+                        backward.entry(t.clone()).or_default().push(Backlink {
+                            source: item.path.clone(),
+                            link: link.clone(),
+                        });
+                    }
+                    ResolvedLink { link, target }
+                })
+                .collect();
+            forward.insert(item.path, resolved);
+        }
+
+        Self {
+            forward,
+            backward,
+            resolver,
+        }
+    }
+
+    /// Outgoing links of a note
+    pub fn links_from(&self, note: &Path) -> &[ResolvedLink] {
+        self.forward.get(note).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// Incoming links of a note
+    pub fn backlinks_to(&self, note: &Path) -> &[Backlink] {
+        self.backward.get(note).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+}
+
+fn with_md_extension(target: &str) -> String {
+    if Path::new(target)
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("md"))
+    {
+        target.to_string()
+    } else {
+        format!("{}.md", target)
+    }
+}
+
+/// Normalize `.` and `..` components without touching the filesystem
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+fn rel_key(rel: &Path) -> String {
+    rel.to_string_lossy().replace('\\', "/").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_link_graph_forward_and_backward() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::create_dir_all(vault.join("Notas/dev")).unwrap();
+        fs::create_dir_all(vault.join("Diario")).unwrap();
+
+        fs::write(vault.join("Notas/dev/api.md"), "# API\n").unwrap();
+        fs::write(
+            vault.join("Notas/dev/index.md"),
+            "[[API]] y [diario](../../Diario/2026-01-01.md) y [[falta]]\n",
+        )
+        .unwrap();
+        fs::write(
+            vault.join("Diario/2026-01-01.md"),
+            "Hoy: [[Notas/dev/api|la api]]\n",
+        )
+        .unwrap();
+
+        let graph = LinkGraph::build(vault, &vault.join("Templates")).unwrap();
+
+        let index = vault.join("Notas/dev/index.md");
+        let links = graph.links_from(&index);
+        assert_eq!(links.len(), 3);
+        assert_eq!(
+            links[0].target.as_deref(),
+            Some(vault.join("Notas/dev/api.md").as_path())
+        );
+        assert_eq!(
+            links[1].target.as_deref(),
+            Some(vault.join("Diario/2026-01-01.md").as_path())
+        );
+        assert!(links[2].target.is_none());
+
+        let backlinks = graph.backlinks_to(&vault.join("Notas/dev/api.md"));
+        let mut sources: Vec<PathBuf> = backlinks.iter().map(|b| b.source.clone()).collect();
+        sources.sort();
+        assert_eq!(sources, vec![vault.join("Diario/2026-01-01.md"), index]);
+    }
+
+    #[test]
+    fn test_resolve_name_prefers_sibling() {
+        let vault = Path::new("/vault");
+        let notes = vec![vault.join("a/readme.md"), vault.join("b/c/readme.md")];
+        let resolver = NoteResolver::new(vault, &notes);
+
+        assert_eq!(
+            resolver.resolve_name("readme", None),
+            Some(notes[0].clone())
+        );
+        assert_eq!(
+            resolver.resolve_name("README", Some(&vault.join("b/c/otra.md"))),
+            Some(notes[1].clone())
+        );
+    }
+}
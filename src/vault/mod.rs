@@ -1 +1,7 @@
+pub mod index;
+pub mod journal;
+pub mod links;
+pub mod plan;
+pub mod query;
+pub mod rewrite;
 pub mod scan;
@@ -0,0 +1,541 @@
+use super::journal;
+use crate::utils::{diff, file};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Planned change to the vault (or to a mapped working directory)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Change {
+    CreateDir {
+        path: PathBuf,
+    },
+    /// Only empty directories (inverse of `CreateDir`)
+    RemoveDir {
+        path: PathBuf,
+    },
+    Create {
+        path: PathBuf,
+        after: String,
+    },
+    Delete {
+        path: PathBuf,
+        before: String,
+        backup: bool,
+    },
+    Edit {
+        path: PathBuf,
+        before: String,
+        after: String,
+        backup: bool,
+    },
+    Move {
+        from: PathBuf,
+        to: PathBuf,
+        backup: bool,
+    },
+}
+
+/// Alias added to a note's frontmatter (informative, the edit is in `changes`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewAlias {
+    pub path: PathBuf,
+    pub alias: String,
+}
+
+/// Changes collected by a bulk command before touching the disk.
+///
+/// Commands record edits and moves in order; reads go through the plan so that
+/// later steps see the result of earlier ones (e.g. retag after a rename):
+/// ```ignore
+/// let mut plan = Plan::new("redir", vault);
+/// plan.move_path(&old, &new);
+/// rewriter.plan(&mut plan)?;
+/// plan.finish(&mode)?;
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Plan {
+    pub command: String,
+    pub vault: PathBuf,
+    /// Backup for the changes recorded from now on
+    pub backup: bool,
+    pub changes: Vec<Change>,
+    #[serde(default)]
+    pub aliases: Vec<NewAlias>,
+}
+
+/// `--dry-run` / `--plan-out FILE` (implies dry-run)
+#[derive(Debug, Clone, Default)]
+pub struct PlanMode {
+    pub dry_run: bool,
+    pub plan_out: Option<PathBuf>,
+}
+
+impl PlanMode {
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run || self.plan_out.is_some()
+    }
+}
+
+impl Plan {
+    pub fn new(command: &str, vault: &Path) -> Self {
+        Self {
+            command: command.to_string(),
+            vault: vault.to_path_buf(),
+            backup: true,
+            changes: Vec::new(),
+            aliases: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Content of `path` after the planned changes
+    pub fn read(&self, path: &Path) -> anyhow::Result<String> {
+        self.read_at(path, self.changes.len())
+    }
+
+    /// Whether `path` exists after the planned changes
+    pub fn exists(&self, path: &Path) -> bool {
+        self.exists_at(path, self.changes.len())
+    }
+
+    /// Plan writing `after` to `path`. Returns false if the content does not change.
+    pub fn edit(&mut self, path: &Path, after: String) -> anyhow::Result<bool> {
+        let before = self.read(path)?;
+        if before == after {
+            return Ok(false);
+        }
+        self.changes.push(Change::Edit {
+            path: path.to_path_buf(),
+            before,
+            after,
+            backup: self.backup,
+        });
+        Ok(true)
+    }
+
+    /// Plan creating a directory and each missing parent (one change per directory,
+    /// so that undo removes exactly the ones created)
+    pub fn create_dir(&mut self, path: &Path) {
+        let mut missing = Vec::new();
+        let mut dir = Some(path);
+        while let Some(d) = dir.filter(|d| !d.as_os_str().is_empty() && !self.exists(d)) {
+            missing.push(d.to_path_buf());
+            dir = d.parent();
+        }
+        for path in missing.into_iter().rev() {
+            self.changes.push(Change::CreateDir { path });
+        }
+    }
+
+    /// Plan creating a new file (and its missing directories)
+    pub fn create(&mut self, path: &Path, after: String) {
+        if let Some(parent) = path.parent() {
+            self.create_dir(parent);
+        }
+        self.changes.push(Change::Create {
+            path: path.to_path_buf(),
+            after,
+        });
+    }
+
+    /// Plan deleting a file
+    pub fn delete(&mut self, path: &Path) -> anyhow::Result<()> {
+        let before = self.read(path)?;
+        self.changes.push(Change::Delete {
+            path: path.to_path_buf(),
+            before,
+            backup: self.backup,
+        });
+        Ok(())
+    }
+
+    /// Plan moving a file or directory
+    pub fn move_path(&mut self, from: &Path, to: &Path) {
+        self.changes.push(Change::Move {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+            backup: self.backup,
+        });
+    }
+
+    pub fn note_alias(&mut self, path: &Path, alias: &str) {
+        self.aliases.push(NewAlias {
+            path: path.to_path_buf(),
+            alias: alias.to_string(),
+        });
+    }
+
+    /// Content of `path` after the first `upto` changes
+    fn read_at(&self, path: &Path, upto: usize) -> anyhow::Result<String> {
+        let mut path = path.to_path_buf();
+        for change in self.changes[..upto].iter().rev() {
+            match change {
+                Change::Edit {
+                    path: edited,
+                    after,
+                    ..
+                }
+                | Change::Create {
+                    path: edited,
+                    after,
+                } if *edited == path => return Ok(after.clone()),
+                Change::Delete { path: deleted, .. } if *deleted == path => {
+                    anyhow::bail!("No se pudo leer {}: eliminado en el plan", path.display())
+                }
+                Change::Move { from, to, .. } => {
+                    if let Ok(rel) = path.strip_prefix(to) {
+                        path = join(from, rel);
+                    }
+                }
+                _ => {}
+            }
+        }
+        fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("No se pudo leer {}: {}", path.display(), e))
+    }
+
+    /// Whether `path` exists after the first `upto` changes
+    fn exists_at(&self, path: &Path, upto: usize) -> bool {
+        let mut path = path.to_path_buf();
+        for change in self.changes[..upto].iter().rev() {
+            match change {
+                Change::Edit { path: edited, .. } | Change::Create { path: edited, .. }
+                    if *edited == path =>
+                {
+                    return true
+                }
+                Change::Delete { path: deleted, .. } if *deleted == path => return false,
+                Change::CreateDir { path: created } if created.starts_with(&path) => return true,
+                Change::RemoveDir { path: removed } if path.starts_with(removed) => return false,
+                Change::Move { from, to, .. } => {
+                    if let Ok(rel) = path.strip_prefix(to) {
+                        path = join(from, rel);
+                    } else if path.starts_with(from) {
+                        return false;
+                    }
+                }
+                _ => {}
+            }
+        }
+        path.exists()
+    }
+
+    /// Check that the vault is still in the state the plan was built from
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (i, change) in self.changes.iter().enumerate() {
+            match change {
+                Change::Edit { path, before, .. } | Change::Delete { path, before, .. } => {
+                    if self.read_at(path, i).ok().as_deref() != Some(before.as_str()) {
+                        anyhow::bail!(
+                            "El archivo cambió desde que se generó el plan: {}",
+                            path.display()
+                        );
+                    }
+                }
+                Change::Move { from, to, .. } => {
+                    if !self.exists_at(from, i) {
+                        anyhow::bail!("Origen no encontrado: {}", from.display());
+                    }
+                    if self.exists_at(to, i) {
+                        anyhow::bail!("Destino ya existe: {}", to.display());
+                    }
+                }
+                Change::Create { path, .. } => {
+                    if self.exists_at(path, i) {
+                        anyhow::bail!("Archivo ya existe: {}", path.display());
+                    }
+                }
+                Change::RemoveDir { path } => {
+                    if !self.exists_at(path, i) {
+                        anyhow::bail!("Directorio no encontrado: {}", path.display());
+                    }
+                }
+                Change::CreateDir { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply every change in order and record the operation in the journal (`mad undo`).
+    /// Returns the backup of each edited, moved or deleted file (first backup per path).
+    pub fn apply(&self) -> anyhow::Result<HashMap<PathBuf, PathBuf>> {
+        let backups = self.execute()?;
+        if !self.is_empty() {
+            if let Err(e) = journal::record(&self.vault, self, &backups) {
+                eprintln!("⚠️  No se pudo registrar la operación en el journal: {}", e);
+            }
+        }
+        Ok(backups)
+    }
+
+    /// Apply every change without journaling. All or nothing: if a change fails,
+    /// the ones already applied are reverted.
+    pub(crate) fn execute(&self) -> anyhow::Result<HashMap<PathBuf, PathBuf>> {
+        self.validate()?;
+
+        let mut backups: HashMap<PathBuf, PathBuf> = HashMap::new();
+        for (i, change) in self.changes.iter().enumerate() {
+            if let Err(e) = self.apply_change(change, &mut backups) {
+                let mut applied = self.clone();
+                applied.changes.truncate(i);
+                if let Err(rollback) = applied.inverse().execute() {
+                    eprintln!("⚠️  No se pudieron revertir los cambios aplicados: {}", rollback);
+                }
+                return Err(e);
+            }
+        }
+        Ok(backups)
+    }
+
+    fn apply_change(
+        &self,
+        change: &Change,
+        backups: &mut HashMap<PathBuf, PathBuf>,
+    ) -> anyhow::Result<()> {
+        match change {
+            Change::CreateDir { path } => fs::create_dir_all(path)?,
+            Change::RemoveDir { path } => fs::remove_dir(path).map_err(|e| {
+                anyhow::anyhow!("No se pudo eliminar el directorio {}: {}", path.display(), e)
+            })?,
+            Change::Create { path, after } => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(path, after)?;
+            }
+            Change::Delete { path, backup, .. } => {
+                if *backup && !backups.contains_key(path) {
+                    backups.insert(path.clone(), file::create_backup(&self.vault, path)?);
+                }
+                fs::remove_file(path)?;
+            }
+            Change::Edit {
+                path,
+                after,
+                backup,
+                ..
+            } => {
+                if *backup && !backups.contains_key(path) {
+                    backups.insert(path.clone(), file::create_backup(&self.vault, path)?);
+                }
+                fs::write(path, after)?;
+            }
+            Change::Move { from, to, backup } => {
+                if *backup && from.is_file() {
+                    backups.insert(to.clone(), file::create_backup(&self.vault, from)?);
+                }
+                if let Some(parent) = to.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::rename(from, to).map_err(|e| {
+                    anyhow::anyhow!(
+                        "Error moviendo '{}' → '{}': {}",
+                        from.display(),
+                        to.display(),
+                        e
+                    )
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Plan that reverts this one (changes inverted, in reverse order, without backups)
+    pub fn inverse(&self) -> Plan {
+        let changes = self
+            .changes
+            .iter()
+            .rev()
+            .map(|change| match change.clone() {
+                Change::CreateDir { path } => Change::RemoveDir { path },
+                Change::RemoveDir { path } => Change::CreateDir { path },
+                Change::Create { path, after } => Change::Delete {
+                    path,
+                    before: after,
+                    backup: false,
+                },
+                Change::Delete { path, before, .. } => Change::Create {
+                    path,
+                    after: before,
+                },
+                Change::Edit {
+                    path,
+                    before,
+                    after,
+                    ..
+                } => Change::Edit {
+                    path,
+                    before: after,
+                    after: before,
+                    backup: false,
+                },
+                Change::Move { from, to, .. } => Change::Move {
+                    from: to,
+                    to: from,
+                    backup: false,
+                },
+            })
+            .collect();
+
+        Plan {
+            command: self.command.clone(),
+            vault: self.vault.clone(),
+            backup: false,
+            changes,
+            aliases: Vec::new(),
+        }
+    }
+
+    /// Print moves, new aliases and a unified diff of each edit
+    pub fn print(&self) {
+        println!("\n📋 Plan '{}': {} cambios", self.command, self.changes.len());
+        self.print_changes();
+
+        if !self.aliases.is_empty() {
+            println!("\nAliases nuevos:");
+            for alias in &self.aliases {
+                println!("  🏷️  {}: {}", self.display(&alias.path), alias.alias);
+            }
+        }
+
+        for change in &self.changes {
+            if let Change::Edit {
+                path,
+                before,
+                after,
+                ..
+            } = change
+            {
+                let name = self.display(path);
+                println!();
+                print!(
+                    "{}",
+                    diff::unified(before, after, &format!("a/{}", name), &format!("b/{}", name))
+                );
+            }
+        }
+    }
+
+    /// Dry-run: print the plan (and save it with `--plan-out`). Otherwise apply it.
+    /// Returns true if the vault was modified.
+    pub fn finish(self, mode: &PlanMode) -> anyhow::Result<bool> {
+        if !mode.is_dry_run() {
+            self.apply()?;
+            return Ok(!self.is_empty());
+        }
+
+        self.print();
+        if let Some(out) = &mode.plan_out {
+            self.save(out)?;
+            println!("\n💾 Plan guardado en {} (aplicar con: mad apply {})", out.display(), out.display());
+        }
+        println!("\n🔍 Dry-run: no se modificó el vault");
+        Ok(false)
+    }
+
+    /// One line per change
+    pub fn print_changes(&self) {
+        for change in &self.changes {
+            match change {
+                Change::CreateDir { path } => println!("  📁 {}", self.display(path)),
+                Change::RemoveDir { path } => println!("  🗑️  {}/", self.display(path)),
+                Change::Create { path, .. } => println!("  ➕ {}", self.display(path)),
+                Change::Delete { path, .. } => println!("  🗑️  {}", self.display(path)),
+                Change::Move { from, to, .. } => {
+                    println!("  🚚 {} → {}", self.display(from), self.display(to))
+                }
+                Change::Edit { path, .. } => println!("  ✏️  {}", self.display(path)),
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let raw = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("No se pudo leer el plan {}: {}", path.display(), e))?;
+        Ok(serde_json::from_str(&raw)?)
+    }
+
+    pub(crate) fn display(&self, path: &Path) -> String {
+        path.strip_prefix(&self.vault)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+}
+
+fn join(base: &Path, rel: &Path) -> PathBuf {
+    if rel.as_os_str().is_empty() {
+        base.to_path_buf()
+    } else {
+        base.join(rel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_plan_reads_through_moves_and_edits() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::create_dir_all(vault.join("a")).unwrap();
+        fs::write(vault.join("a/n.md"), "uno\n").unwrap();
+
+        let mut plan = Plan::new("test", vault);
+        plan.create_dir(&vault.join("b"));
+        plan.move_path(&vault.join("a"), &vault.join("b/a"));
+        assert!(plan.edit(&vault.join("b/a/n.md"), "dos\n".to_string()).unwrap());
+        assert!(!plan.edit(&vault.join("b/a/n.md"), "dos\n".to_string()).unwrap());
+
+        assert_eq!(plan.read(&vault.join("b/a/n.md")).unwrap(), "dos\n");
+        assert!(plan.exists(&vault.join("b/a/n.md")));
+        assert!(!plan.exists(&vault.join("a/n.md")));
+
+        // Dry-run no toca el disco
+        let out = vault.join("plan.json");
+        let mode = PlanMode {
+            dry_run: false,
+            plan_out: Some(out.clone()),
+        };
+        assert!(!plan.clone().finish(&mode).unwrap());
+        assert!(vault.join("a/n.md").exists());
+        assert!(!vault.join("b").exists());
+
+        let loaded = Plan::load(&out).unwrap();
+        assert_eq!(loaded.changes, plan.changes);
+        let backups = loaded.apply().unwrap();
+        assert_eq!(fs::read_to_string(vault.join("b/a/n.md")).unwrap(), "dos\n");
+        assert!(!vault.join("a").exists());
+        assert!(backups[&vault.join("b/a/n.md")].exists());
+    }
+
+    #[test]
+    fn test_stale_plan_is_rejected() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::write(vault.join("n.md"), "uno\n").unwrap();
+
+        let mut plan = Plan::new("test", vault);
+        plan.edit(&vault.join("n.md"), "dos\n".to_string()).unwrap();
+        fs::write(vault.join("n.md"), "editado a mano\n").unwrap();
+
+        assert!(plan.apply().is_err());
+        assert_eq!(
+            fs::read_to_string(vault.join("n.md")).unwrap(),
+            "editado a mano\n"
+        );
+    }
+}
@@ -0,0 +1,538 @@
+use super::index::{NoteMeta, VaultIndex};
+use super::links::LinkGraph;
+use crate::core::date_expr;
+use crate::tags::parser::TagPath;
+use chrono::{DateTime, Local, NaiveDate};
+use serde_yaml::Value;
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+/// Query over the vault index:
+/// `tag:proj/acme and status:open and modified>7d and not path:Archivo/**`
+/// - `and` (también implícito entre condiciones), `or`, `not`, paréntesis
+/// - `tag:x` cualquier tag (incluye descendientes), `dirtag:x` solo el dir-tag
+/// - `path:glob` (`*`, `**`, `?`; sin comodines también matchea el directorio)
+/// - `modified>7d` hace más de 7 días, `modified<7d` en los últimos 7 días,
+///   `modified>=2026-01-01` desde esa fecha (acepta las expresiones de `date_expr`)
+/// - `tasks`, `tasks.open`, `tasks.done`, `links`, `backlinks` con `:`, `>`, `>=`, `<`, `<=`
+/// - `links:nota` enlaza a la nota, `has:campo` (o `has:links`, `has:tasks`...)
+/// - cualquier otro campo se busca en el frontmatter: `status:open`, `priority>=2`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Cond(Cond),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cond {
+    Tag(TagPath),
+    DirTag(TagPath),
+    Path(String),
+    /// Fecha de modificación (ya normalizada: `>7d` → `< hoy-7`)
+    Modified(Op, NaiveDate),
+    Count(Counter, Op, usize),
+    LinksTo(String),
+    Has(String),
+    Field(String, Op, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Counter {
+    Tasks,
+    OpenTasks,
+    DoneTasks,
+    Links,
+    Backlinks,
+}
+
+/// Note that matched a query, with the values shown by `mad query`
+#[derive(Debug, Clone)]
+pub struct QueryHit<'a> {
+    pub path: PathBuf,
+    pub meta: &'a NoteMeta,
+    pub links: usize,
+    pub backlinks: usize,
+}
+
+impl Op {
+    fn holds(self, ord: Ordering) -> bool {
+        match self {
+            Op::Eq => ord == Ordering::Equal,
+            Op::Gt => ord == Ordering::Greater,
+            Op::Ge => ord != Ordering::Less,
+            Op::Lt => ord == Ordering::Less,
+            Op::Le => ord != Ordering::Greater,
+        }
+    }
+
+    /// `a > b` ⇔ `b < a`
+    fn flip(self) -> Op {
+        match self {
+            Op::Eq => Op::Eq,
+            Op::Gt => Op::Lt,
+            Op::Ge => Op::Le,
+            Op::Lt => Op::Gt,
+            Op::Le => Op::Ge,
+        }
+    }
+}
+
+/// Parse a query. Dates are resolved relative to `today`.
+pub fn parse(input: &str, today: NaiveDate) -> anyhow::Result<Expr> {
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        anyhow::bail!("Consulta vacía");
+    }
+
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        today,
+    };
+    let expr = parser.or()?;
+    if let Some(token) = parser.peek() {
+        anyhow::bail!("Token inesperado en la consulta: '{}'", token.text());
+    }
+    Ok(expr)
+}
+
+/// Notes of the index that match `expr`, sorted by path
+pub fn run<'a>(vault: &Path, index: &'a VaultIndex, expr: &Expr) -> Vec<QueryHit<'a>> {
+    let graph = LinkGraph::from_items(vault, index.link_items());
+
+    index
+        .iter()
+        .map(|(path, meta)| QueryHit {
+            links: graph.links_from(&path).len(),
+            backlinks: graph.backlinks_to(&path).len(),
+            path,
+            meta,
+        })
+        .filter(|hit| expr.matches(vault, hit, &graph))
+        .collect()
+}
+
+impl Expr {
+    fn matches(&self, vault: &Path, hit: &QueryHit, graph: &LinkGraph) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(vault, hit, graph) && b.matches(vault, hit, graph),
+            Expr::Or(a, b) => a.matches(vault, hit, graph) || b.matches(vault, hit, graph),
+            Expr::Not(e) => !e.matches(vault, hit, graph),
+            Expr::Cond(cond) => cond.matches(vault, hit, graph),
+        }
+    }
+}
+
+impl Cond {
+    fn matches(&self, vault: &Path, hit: &QueryHit, graph: &LinkGraph) -> bool {
+        let meta = hit.meta;
+        match self {
+            Cond::Tag(tag) => meta.secondary_tags.iter().any(|t| t.starts_with(tag)),
+            Cond::DirTag(tag) => meta.primary_tag.as_ref().is_some_and(|t| t.starts_with(tag)),
+            Cond::Path(pattern) => {
+                let rel = hit.path.strip_prefix(vault).unwrap_or(&hit.path);
+                let rel = rel.to_string_lossy().replace('\\', "/");
+                path_matches(pattern, &rel)
+            }
+            Cond::Modified(op, date) => {
+                let modified: DateTime<Local> = meta.modified().into();
+                op.holds(modified.date_naive().cmp(date))
+            }
+            Cond::Count(counter, op, n) => {
+                let count = match counter {
+                    Counter::Tasks => meta.tasks.len(),
+                    Counter::OpenTasks => meta.tasks.iter().filter(|t| t.status == ' ').count(),
+                    Counter::DoneTasks => meta
+                        .tasks
+                        .iter()
+                        .filter(|t| t.status.eq_ignore_ascii_case(&'x'))
+                        .count(),
+                    Counter::Links => hit.links,
+                    Counter::Backlinks => hit.backlinks,
+                };
+                op.holds(count.cmp(n))
+            }
+            Cond::LinksTo(name) => match graph.resolver.resolve_name(name, Some(&hit.path)) {
+                Some(target) => graph
+                    .links_from(&hit.path)
+                    .iter()
+                    .any(|l| l.target.as_ref() == Some(&target)),
+                None => false,
+            },
+            Cond::Has(key) => match key.as_str() {
+                "tasks" => !meta.tasks.is_empty(),
+                "links" => hit.links > 0,
+                "backlinks" => hit.backlinks > 0,
+                "dirtag" => meta.primary_tag.is_some(),
+                "tags" => !meta.secondary_tags.is_empty(),
+                _ => field(meta, key).is_some_and(|v| !v.is_null()),
+            },
+            Cond::Field(key, op, value) => match field(meta, key) {
+                Some(Value::Sequence(items)) => items.iter().any(|v| scalar_matches(v, *op, value)),
+                Some(v) => scalar_matches(v, *op, value),
+                None => false,
+            },
+        }
+    }
+}
+
+/// Frontmatter value; keys are compared case-insensitively
+fn field<'a>(meta: &'a NoteMeta, key: &str) -> Option<&'a Value> {
+    meta.frontmatter
+        .iter()
+        .find(|(k, _)| k.as_str().is_some_and(|k| k.eq_ignore_ascii_case(key)))
+        .map(|(_, v)| v)
+}
+
+/// Compare a frontmatter scalar with the query value: as dates, numbers or text
+fn scalar_matches(value: &Value, op: Op, query: &str) -> bool {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => return false,
+    };
+
+    if op == Op::Eq {
+        return text.to_lowercase() == query.to_lowercase();
+    }
+
+    let ord = match (parse_date(&text), parse_date(query)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => match (text.parse::<f64>(), query.parse::<f64>()) {
+            (Ok(a), Ok(b)) => match a.partial_cmp(&b) {
+                Some(ord) => ord,
+                None => return false,
+            },
+            _ => text.to_lowercase().cmp(&query.to_lowercase()),
+        },
+    };
+    op.holds(ord)
+}
+
+/// `2026-01-02` or the date part of `2026-01-02 10:00`
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    let date = text.get(..10).unwrap_or(text);
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// Glob over the vault-relative path. Without wildcards, also matches everything
+/// below that directory (`path:Notas/dev`).
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    if !pattern.contains(['*', '?']) {
+        return path == pattern
+            || path
+                .strip_prefix(pattern)
+                .is_some_and(|rest| rest.starts_with('/'));
+    }
+    glob_match(pattern.as_bytes(), path.as_bytes())
+}
+
+/// `**` any characters, `*` any except `/`, `?` one character except `/`
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', b'/', rest @ ..] => {
+            // `**/` también puede no consumir directorios
+            glob_match(rest, text)
+                || (0..text.len()).any(|i| text[i] == b'/' && glob_match(rest, &text[i + 1..]))
+        }
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob_match(rest, &text[i..])),
+        [b'?', rest @ ..] => {
+            text.first().is_some_and(|&c| c != b'/') && glob_match(rest, &text[1..])
+        }
+        [c, rest @ ..] => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Word(String),
+}
+
+impl Token {
+    fn text(&self) -> &str {
+        match self {
+            Token::Open => "(",
+            Token::Close => ")",
+            Token::Word(w) => w,
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+}
+
+/// Split into words and parentheses. `"..."` keeps spaces (also inside a word:
+/// `title:"mi nota"`).
+fn tokenize(input: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    chars.next();
+                    if c == '"' {
+                        let mut closed = false;
+                        for c in chars.by_ref() {
+                            if c == '"' {
+                                closed = true;
+                                break;
+                            }
+                            word.push(c);
+                        }
+                        if !closed {
+                            anyhow::bail!("Comillas sin cerrar en la consulta");
+                        }
+                    } else {
+                        word.push(c);
+                    }
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    today: NaiveDate,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.and()?;
+        while self.peek().is_some_and(|t| t.is_keyword("or")) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    /// `a and b`, or `a b`
+    fn and(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.unary()?;
+        loop {
+            match self.peek() {
+                Some(t) if t.is_keyword("and") => self.pos += 1,
+                Some(t) if t.is_keyword("or") || *t == Token::Close => break,
+                Some(_) => {}
+                None => break,
+            }
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> anyhow::Result<Expr> {
+        match self.next() {
+            Some(t) if t.is_keyword("not") => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => anyhow::bail!("Falta ')' en la consulta"),
+                }
+            }
+            Some(Token::Word(word)) => Ok(Expr::Cond(parse_cond(&word, self.today)?)),
+            Some(Token::Close) => anyhow::bail!("')' inesperado en la consulta"),
+            None => anyhow::bail!("La consulta termina de forma inesperada"),
+        }
+    }
+}
+
+/// `campo:valor`, `campo=valor`, `campo>valor`, `campo>=valor`...
+fn parse_cond(word: &str, today: NaiveDate) -> anyhow::Result<Cond> {
+    let Some(at) = word.find([':', '=', '<', '>']) else {
+        anyhow::bail!(
+            "Condición inválida: '{}'. Usa campo:valor (ej: tag:proj, status:open, modified>7d)",
+            word
+        );
+    };
+
+    let field = word[..at].to_lowercase();
+    let rest = &word[at..];
+    let (op, value) = if let Some(v) = rest.strip_prefix(">=") {
+        (Op::Ge, v)
+    } else if let Some(v) = rest.strip_prefix("<=") {
+        (Op::Le, v)
+    } else if let Some(v) = rest.strip_prefix('>') {
+        (Op::Gt, v)
+    } else if let Some(v) = rest.strip_prefix('<') {
+        (Op::Lt, v)
+    } else {
+        (Op::Eq, &rest[1..])
+    };
+
+    if field.is_empty() || value.is_empty() {
+        anyhow::bail!("Condición inválida: '{}'", word);
+    }
+
+    let only_eq = |cond: Cond| {
+        if op == Op::Eq {
+            Ok(cond)
+        } else {
+            anyhow::bail!("'{}' solo admite ':' (condición: '{}')", field, word)
+        }
+    };
+
+    match field.as_str() {
+        "tag" => only_eq(Cond::Tag(TagPath::from_slash(value))),
+        "dirtag" => only_eq(Cond::DirTag(TagPath::from_slash(value))),
+        "path" => only_eq(Cond::Path(value.to_string())),
+        "has" => only_eq(Cond::Has(value.to_lowercase())),
+        "links" if value.parse::<usize>().is_err() => only_eq(Cond::LinksTo(value.to_string())),
+        "modified" => parse_modified(op, value, today),
+        "tasks" | "tasks.open" | "tasks.done" | "links" | "backlinks" => {
+            let counter = match field.as_str() {
+                "tasks" => Counter::Tasks,
+                "tasks.open" => Counter::OpenTasks,
+                "tasks.done" => Counter::DoneTasks,
+                "links" => Counter::Links,
+                _ => Counter::Backlinks,
+            };
+            let n = value
+                .parse()
+                .map_err(|_| anyhow::anyhow!("'{}' espera un número: '{}'", field, word))?;
+            Ok(Cond::Count(counter, op, n))
+        }
+        _ => Ok(Cond::Field(word[..at].to_string(), op, value.to_string())),
+    }
+}
+
+/// `7d`, `2w`, `6m`, `1y` are ages (`>7d`: hace más de 7 días); anything else is a date
+fn parse_modified(op: Op, value: &str, today: NaiveDate) -> anyhow::Result<Cond> {
+    if let Some((amount, unit)) = date_expr::parse_age(value) {
+        let date = date_expr::shift(today, -amount, unit)
+            .ok_or_else(|| anyhow::anyhow!("Fecha fuera de rango: '{}'", value))?;
+        return Ok(Cond::Modified(op.flip(), date));
+    }
+
+    let date = date_expr::parse(value, today)
+        .map_err(|e| anyhow::anyhow!("Fecha inválida en modified: {}", e))?;
+    Ok(Cond::Modified(op, date))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 10).unwrap()
+    }
+
+    #[test]
+    fn test_parse_precedence_and_ages() {
+        let expr = parse("tag:proj/acme status:open or not (path:Archivo/**)", today()).unwrap();
+        let Expr::Or(left, right) = expr else {
+            panic!("or debe tener menor precedencia que and");
+        };
+        assert!(matches!(*left, Expr::And(..)));
+        assert_eq!(
+            *right,
+            Expr::Not(Box::new(Expr::Cond(Cond::Path("Archivo/**".to_string()))))
+        );
+
+        let expr = parse("modified>7d", today()).unwrap();
+        let expected = NaiveDate::from_ymd_opt(2026, 3, 3).unwrap();
+        assert_eq!(expr, Expr::Cond(Cond::Modified(Op::Lt, expected)));
+
+        assert!(parse("tag>a", today()).is_err());
+        assert!(parse("(tag:a", today()).is_err());
+        assert!(parse("tasks>muchas", today()).is_err());
+    }
+
+    #[test]
+    fn test_glob() {
+        assert!(path_matches("Archivo/**", "Archivo/2025/x.md"));
+        assert!(path_matches("**/x.md", "x.md"));
+        assert!(path_matches("Notas/*/api.md", "Notas/dev/api.md"));
+        assert!(!path_matches("Notas/*.md", "Notas/dev/api.md"));
+        assert!(path_matches("Notas/dev", "Notas/dev/api.md"));
+        assert!(!path_matches("Notas/de", "Notas/dev/api.md"));
+    }
+
+    #[test]
+    fn test_run_filters_notes() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::create_dir_all(vault.join("Notas/proj/acme/api")).unwrap();
+        fs::create_dir_all(vault.join("Archivo")).unwrap();
+        fs::write(
+            vault.join("Notas/proj/acme/api/spec.md"),
+            "---\nstatus: open\npriority: 2\n---\n{ #proj/acme/api }\n\n- [ ] uno\n- [x] dos\n[[plan]]\n",
+        )
+        .unwrap();
+        fs::write(vault.join("Notas/proj/acme/plan.md"), "---\nstatus: done\n---\n{ #proj/acme }\n").unwrap();
+        fs::write(vault.join("Archivo/viejo.md"), "---\nstatus: open\n---\n#proj/acme\n").unwrap();
+
+        let mut index = VaultIndex::new(vault);
+        index.refresh(vault, &vault.join("Templates")).unwrap();
+
+        let names = |query: &str| -> Vec<String> {
+            let expr = parse(query, Local::now().date_naive()).unwrap();
+            run(vault, &index, &expr)
+                .iter()
+                .map(|h| h.path.file_stem().unwrap().to_string_lossy().to_string())
+                .collect()
+        };
+
+        assert_eq!(names("tag:proj/acme status:open and not path:Archivo/**"), ["spec"]);
+        assert_eq!(names("tag:proj/acme"), ["viejo", "spec", "plan"]);
+        assert_eq!(names("dirtag:proj"), ["spec", "plan"]);
+        assert_eq!(names("tasks.open:1 and priority>=2 and links:plan"), ["spec"]);
+        assert_eq!(names("backlinks>0 or STATUS:Done"), ["plan"]);
+        assert!(names("modified>1d").is_empty());
+        assert_eq!(names("modified<=0d and not has:status").len(), 0);
+    }
+}
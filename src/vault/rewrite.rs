@@ -0,0 +1,391 @@
+use super::links::{normalize, LinkGraph};
+use super::plan::Plan;
+use super::scan::{Link, LinkKind};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+
+/// Rewrites links after notes are moved or renamed inside the vault.
+///
+/// The link graph must be built BEFORE the move (links still resolve to the old paths):
+/// ```ignore
+/// let mut rewriter = LinkRewriter::new(vault, &templates_path)?;
+/// fs::rename(&old, &new)?;
+/// rewriter.record_move(&old, &new);
+/// let touched = rewriter.apply(true)?;
+/// ```
+/// With a [`Plan`], moves are only recorded and `plan()` reads through it instead of the disk.
+pub struct LinkRewriter {
+    vault: PathBuf,
+    canonical_vault: Option<PathBuf>,
+    graph: LinkGraph,
+    moves: HashMap<PathBuf, PathBuf>,
+}
+
+/// File whose links were rewritten
+#[derive(Debug, Clone)]
+pub struct RewrittenFile {
+    pub path: PathBuf,
+    pub links: usize,
+    pub backup: Option<PathBuf>,
+}
+
+impl LinkRewriter {
+    pub fn new(vault: &Path, templates_path: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            vault: vault.to_path_buf(),
+            canonical_vault: vault.canonicalize().ok(),
+            graph: LinkGraph::build(vault, templates_path)?,
+            moves: HashMap::new(),
+        })
+    }
+
+    /// Record a moved note (absolute or cwd-canonical paths are accepted)
+    pub fn record_move(&mut self, from: &Path, to: &Path) {
+        let from = self.vault_key(from);
+        let to = self.vault_key(to);
+        if from != to {
+            self.moves.insert(from, to);
+        }
+    }
+
+    /// Record every note under a moved directory
+    pub fn record_dir_move(&mut self, from_dir: &Path, to_dir: &Path) {
+        let from_dir = self.vault_key(from_dir);
+        let to_dir = self.vault_key(to_dir);
+
+        let moved: Vec<(PathBuf, PathBuf)> = self
+            .graph
+            .forward
+            .keys()
+            .filter_map(|note| {
+                let rel = note.strip_prefix(&from_dir).ok()?;
+                Some((note.clone(), to_dir.join(rel)))
+            })
+            .collect();
+
+        self.moves.extend(moved);
+    }
+
+    /// Rewrite every link affected by the recorded moves (already done on disk).
+    /// Returns the files that were modified.
+    pub fn apply(&self, backup: bool) -> anyhow::Result<Vec<RewrittenFile>> {
+        let mut plan = Plan::new("links", &self.vault);
+        plan.backup = backup;
+        let mut touched = self.plan(&mut plan)?;
+        let backups = plan.apply()?;
+        for t in &mut touched {
+            t.backup = backups.get(&t.path).cloned();
+        }
+        Ok(touched)
+    }
+
+    /// Add to `plan` the edits for every link affected by the recorded moves:
+    /// - links (wiki or Markdown) pointing to a moved note
+    /// - relative Markdown links inside a moved note
+    ///
+    /// Returns the files that will be modified.
+    pub fn plan(&self, plan: &mut Plan) -> anyhow::Result<Vec<RewrittenFile>> {
+        let mut touched = Vec::new();
+        if self.moves.is_empty() {
+            return Ok(touched);
+        }
+
+        for (source, links) in &self.graph.forward {
+            let new_source = self.moves.get(source).unwrap_or(source);
+            let source_moved = new_source != source;
+
+            let affected: Vec<_> = links
+                .iter()
+                .filter_map(|resolved| {
+                    let target = resolved.target.as_ref()?;
+                    let new_target = self.moves.get(target).unwrap_or(target);
+                    let relative_md = source_moved && resolved.link.kind == LinkKind::Markdown;
+                    (new_target != target || relative_md).then_some((resolved, target, new_target))
+                })
+                .collect();
+
+            if affected.is_empty() {
+                continue;
+            }
+
+            let Ok(content) = plan.read(new_source) else {
+                continue;
+            };
+            let mut lines: Vec<String> = content.split('\n').map(|s| s.to_string()).collect();
+
+            // línea → [(span, nuevo destino)]
+            let mut edits: BTreeMap<usize, Vec<(Range<usize>, String)>> = BTreeMap::new();
+
+            for (resolved, target, new_target) in affected {
+                let link = &resolved.link;
+                let Some(line) = lines.get(link.line.wrapping_sub(1)) else {
+                    continue;
+                };
+                let Some(raw) = line.get(link.target_span.clone()) else {
+                    continue;
+                };
+                let Some(new_text) =
+                    self.new_link_text(link, raw, line, source, new_source, target, new_target)
+                else {
+                    continue;
+                };
+                if new_text != raw {
+                    edits
+                        .entry(link.line)
+                        .or_default()
+                        .push((link.target_span.clone(), new_text));
+                }
+            }
+
+            let mut count = 0;
+            for (line_number, mut spans) in edits {
+                // De derecha a izquierda para no invalidar los offsets
+                spans.sort_by_key(|(span, _)| std::cmp::Reverse(span.start));
+                let line = &mut lines[line_number - 1];
+                for (span, text) in spans {
+                    line.replace_range(span, &text);
+                    count += 1;
+                }
+            }
+
+            if count == 0 {
+                continue;
+            }
+
+            plan.edit(new_source, lines.join("\n"))?;
+            touched.push(RewrittenFile {
+                path: new_source.clone(),
+                links: count,
+                backup: None,
+            });
+        }
+
+        Ok(touched)
+    }
+
+    /// New text for the link target, keeping the style it was written in
+    #[allow(clippy::too_many_arguments)]
+    fn new_link_text(
+        &self,
+        link: &Link,
+        raw: &str,
+        line: &str,
+        source: &Path,
+        new_source: &Path,
+        target: &Path,
+        new_target: &Path,
+    ) -> Option<String> {
+        let keep_ext = raw.to_lowercase().ends_with(".md");
+
+        match link.kind {
+            LinkKind::Wiki => {
+                if raw.contains('/') {
+                    // [[carpeta/nota]] → path relativo al vault
+                    let rel = new_target.strip_prefix(&self.vault).ok()?;
+                    let rel = to_slash(rel);
+                    let leading = if raw.starts_with('/') { "/" } else { "" };
+                    Some(format!("{}{}", leading, strip_md(&rel, keep_ext)))
+                } else {
+                    // [[nota]] → solo cambia si cambió el nombre
+                    let old_stem = target.file_stem()?.to_str()?;
+                    let new_stem = new_target.file_stem()?.to_str()?;
+                    if old_stem == new_stem {
+                        return None;
+                    }
+                    Some(if keep_ext {
+                        format!("{}.md", new_stem)
+                    } else {
+                        new_stem.to_string()
+                    })
+                }
+            }
+            LinkKind::Markdown => {
+                let relative_style = source
+                    .parent()
+                    .map(|dir| normalize(&dir.join(with_md(&link.target))) == *target)
+                    .unwrap_or(false);
+
+                let path = if relative_style {
+                    relative_path(new_source.parent()?, new_target)
+                } else {
+                    let rel = to_slash(new_target.strip_prefix(&self.vault).ok()?);
+                    if raw.starts_with('/') {
+                        format!("/{}", rel)
+                    } else {
+                        rel
+                    }
+                };
+
+                let path = strip_md(&path, keep_ext);
+                let start = link.target_span.start;
+                let in_angle = start > 0 && line.as_bytes().get(start - 1) == Some(&b'<');
+
+                if !in_angle && (raw.contains('%') || path.contains(' ')) {
+                    Some(path.replace(' ', "%20"))
+                } else {
+                    Some(path)
+                }
+            }
+        }
+    }
+
+    /// Convert a path to the form used by the graph keys (`vault.join(rel)`)
+    fn vault_key(&self, path: &Path) -> PathBuf {
+        if path.starts_with(&self.vault) {
+            return path.to_path_buf();
+        }
+        if let Some(canonical_vault) = &self.canonical_vault {
+            if let Ok(rel) = path.strip_prefix(canonical_vault) {
+                return self.vault.join(rel);
+            }
+        }
+        path.to_path_buf()
+    }
+}
+
+/// Print the list of files whose links were rewritten
+pub fn print_summary(vault: &Path, touched: &[RewrittenFile]) {
+    if touched.is_empty() {
+        return;
+    }
+
+    let total: usize = touched.iter().map(|t| t.links).sum();
+    println!(
+        "\n🔗 {} links actualizados en {} archivos:",
+        total,
+        touched.len()
+    );
+    for t in touched {
+        let relative = t.path.strip_prefix(vault).unwrap_or(&t.path);
+        println!("  ✏️  {} ({})", relative.display(), t.links);
+    }
+}
+
+/// Relative path from directory `from` to file `to` (`../otra/nota.md`)
+pub fn relative_path(from: &Path, to: &Path) -> String {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+
+    let common = from
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts: Vec<String> = Vec::new();
+    for _ in common..from.len() {
+        parts.push("..".to_string());
+    }
+    for c in &to[common..] {
+        parts.push(c.as_os_str().to_string_lossy().to_string());
+    }
+
+    parts.join("/")
+}
+
+fn to_slash(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn with_md(target: &str) -> String {
+    if target.to_lowercase().ends_with(".md") {
+        target.to_string()
+    } else {
+        format!("{}.md", target)
+    }
+}
+
+fn strip_md(path: &str, keep_ext: bool) -> String {
+    if keep_ext {
+        path.to_string()
+    } else {
+        path.strip_suffix(".md").unwrap_or(path).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_relative_path() {
+        assert_eq!(
+            relative_path(Path::new("/v/a/b"), Path::new("/v/c/n.md")),
+            "../../c/n.md"
+        );
+        assert_eq!(
+            relative_path(Path::new("/v/a"), Path::new("/v/a/n.md")),
+            "n.md"
+        );
+    }
+
+    #[test]
+    fn test_rename_note_rewrites_incoming_links() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        let templates = vault.join("Templates");
+
+        fs::write(vault.join("vieja.md"), "# Vieja\n").unwrap();
+        fs::write(
+            vault.join("otra.md"),
+            "Ver [[vieja]], [[vieja#Intro|alias]] y [link](vieja.md)\n```\n[[vieja]]\n```\n",
+        )
+        .unwrap();
+
+        let mut rewriter = LinkRewriter::new(vault, &templates).unwrap();
+        fs::rename(vault.join("vieja.md"), vault.join("nueva.md")).unwrap();
+        rewriter.record_move(&vault.join("vieja.md"), &vault.join("nueva.md"));
+        let touched = rewriter.apply(true).unwrap();
+
+        assert_eq!(touched.len(), 1);
+        assert_eq!(touched[0].links, 3);
+        assert!(touched[0].backup.as_ref().unwrap().exists());
+
+        let content = fs::read_to_string(vault.join("otra.md")).unwrap();
+        assert_eq!(
+            content,
+            "Ver [[nueva]], [[nueva#Intro|alias]] y [link](nueva.md)\n```\n[[vieja]]\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_dir_move_rewrites_paths_and_relative_links() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        let templates = vault.join("Templates");
+        fs::create_dir_all(vault.join("Notas/old")).unwrap();
+
+        fs::write(
+            vault.join("Notas/old/x.md"),
+            "Volver a [raíz](../../y.md)\n",
+        )
+        .unwrap();
+        fs::write(
+            vault.join("y.md"),
+            "[x](Notas/old/x.md) y [[Notas/old/x]] y [[x]]\n",
+        )
+        .unwrap();
+
+        let mut rewriter = LinkRewriter::new(vault, &templates).unwrap();
+        fs::create_dir_all(vault.join("Notas/deep")).unwrap();
+        fs::rename(vault.join("Notas/old"), vault.join("Notas/deep/new")).unwrap();
+        rewriter.record_dir_move(&vault.join("Notas/old"), &vault.join("Notas/deep/new"));
+        let touched = rewriter.apply(false).unwrap();
+
+        assert_eq!(touched.len(), 2);
+        assert_eq!(
+            fs::read_to_string(vault.join("y.md")).unwrap(),
+            "[x](Notas/deep/new/x.md) y [[Notas/deep/new/x]] y [[x]]\n"
+        );
+        assert_eq!(
+            fs::read_to_string(vault.join("Notas/deep/new/x.md")).unwrap(),
+            "Volver a [raíz](../../../y.md)\n"
+        );
+    }
+}
@@ -1,7 +1,10 @@
 use crate::core::frontmatter;
 use crate::tags::parser::{extract_primary_tag, TagPath};
 use crate::utils::vault::VaultWalker;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
 use std::collections::HashSet;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
 #[derive(Clone, Debug)]
@@ -22,16 +25,7 @@ pub fn scan_tags(vault: &Path, templates_path: &Path) -> anyhow::Result<Vec<Scan
         .exclude_templates(templates_path)
         .walk(|path, content| {
             let (fm, body) = frontmatter::extract(content).unwrap_or_default();
-
-            let primary = extract_primary_tag(&body);
-            let mut secondary = TagPath::from_frontmatter(&fm);
-            secondary.extend(extract_body_tags(&body));
-
-            if let Some(primary_tag) = primary.as_ref() {
-                secondary.push(primary_tag.clone());
-            }
-
-            let secondary = dedupe_tags(secondary);
+            let (primary, secondary) = extract_tags(&fm, &body);
 
             items.push(ScanItem {
                 path: path.to_path_buf(),
@@ -44,6 +38,257 @@ pub fn scan_tags(vault: &Path, templates_path: &Path) -> anyhow::Result<Vec<Scan
     Ok(items)
 }
 
+/// Primary tag and secondary tags (frontmatter + body #tags, including primary)
+pub fn extract_tags(fm: &Mapping, body: &str) -> (Option<TagPath>, Vec<TagPath>) {
+    let primary = extract_primary_tag(body);
+    let mut secondary = TagPath::from_frontmatter(fm);
+    secondary.extend(extract_body_tags(body));
+
+    if let Some(primary_tag) = primary.as_ref() {
+        secondary.push(primary_tag.clone());
+    }
+
+    (primary, dedupe_tags(secondary))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkKind {
+    /// `[[nota]]`, `[[nota|label]]`, `![[nota]]`
+    Wiki,
+    /// `[texto](path.md)`
+    Markdown,
+}
+
+/// Link found in a note body
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Link {
+    pub kind: LinkKind,
+    /// Destino tal como está escrito (sin `#heading` ni label)
+    pub target: String,
+    pub heading: Option<String>,
+    pub label: Option<String>,
+    /// Número de línea en el archivo (1-based)
+    pub line: usize,
+    /// Bytes del destino tal como está escrito en la línea (para reescribirlo)
+    pub target_span: Range<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LinkScanItem {
+    pub path: PathBuf,
+    pub links: Vec<Link>,
+}
+
+/// Scan the vault and return the links of every file.
+/// - Wikilinks: `[[nota]]`, `[[nota|label]]`, `[[nota#heading]]`
+/// - Markdown links to local notes: `[texto](path.md)`
+/// - Ignores links inside fenced code blocks and links to attachments.
+pub fn scan_links(vault: &Path, templates_path: &Path) -> anyhow::Result<Vec<LinkScanItem>> {
+    let mut items = Vec::new();
+
+    VaultWalker::new(vault)
+        .exclude_templates(templates_path)
+        .walk(|path, content| {
+            items.push(LinkScanItem {
+                path: path.to_path_buf(),
+                links: extract_links(content),
+            });
+            Ok(())
+        })?;
+
+    Ok(items)
+}
+
+/// Extract links from a full note (frontmatter is skipped).
+/// Line numbers refer to the whole file.
+pub fn extract_links(content: &str) -> Vec<Link> {
+    let (_, body) = frontmatter::extract(content).unwrap_or_default();
+    let offset = if body.len() <= content.len() && content.ends_with(body.as_str()) {
+        content[..content.len() - body.len()].matches('\n').count()
+    } else {
+        0
+    };
+
+    let mut links = Vec::new();
+    let mut in_code_block = false;
+
+    for (idx, line) in body.split('\n').enumerate() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+
+        if in_code_block {
+            continue;
+        }
+
+        for mut link in extract_links_from_line(line) {
+            link.line = offset + idx + 1;
+            links.push(link);
+        }
+    }
+
+    links
+}
+
+/// Extract wikilinks and Markdown links from a single line.
+/// Returned links have `line` set to 0; `target_span` is relative to `line`.
+pub fn extract_links_from_line(line: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut i = 0;
+
+    while i < line.len() {
+        let rest = &line[i..];
+
+        if let Some(inner) = rest.strip_prefix("[[") {
+            if let Some(end) = inner.find("]]") {
+                if let Some(mut link) = parse_wikilink(&inner[..end]) {
+                    link.target_span = shift(link.target_span, i + 2);
+                    links.push(link);
+                }
+                i += 2 + end + 2;
+                continue;
+            }
+        } else if rest.starts_with('[') {
+            if let Some((link, consumed)) = parse_markdown_link(rest) {
+                if let Some(mut link) = link {
+                    link.target_span = shift(link.target_span, i);
+                    links.push(link);
+                }
+                i += consumed;
+                continue;
+            }
+        }
+
+        i += rest.chars().next().map(|c| c.len_utf8()).unwrap_or(1);
+    }
+
+    links
+}
+
+/// Parse the inside of `[[...]]`
+fn parse_wikilink(inner: &str) -> Option<Link> {
+    let (target_part, label) = match inner.split_once('|') {
+        Some((t, l)) => (t, Some(l.trim().to_string())),
+        None => (inner, None),
+    };
+
+    let (target, heading) = match target_part.split_once('#') {
+        Some((t, h)) => (t.trim(), Some(h.trim().to_string())),
+        None => (target_part.trim(), None),
+    };
+
+    if target.is_empty() || !is_note_target(target) {
+        return None;
+    }
+
+    let start = offset_in(inner, target);
+    Some(Link {
+        kind: LinkKind::Wiki,
+        target: target.to_string(),
+        heading,
+        label,
+        line: 0,
+        target_span: start..start + target.len(),
+    })
+}
+
+/// Parse `[texto](destino)` at the start of `s`.
+/// Returns the link (None if it is not a local note) and the bytes consumed.
+fn parse_markdown_link(s: &str) -> Option<(Option<Link>, usize)> {
+    let close = s.find("](")?;
+    let text = &s[1..close];
+    if text.contains('[') {
+        return None;
+    }
+
+    let after = &s[close + 2..];
+    let end = after.find(')')?;
+    let raw = after[..end].trim();
+    let consumed = close + 2 + end + 1;
+
+    let raw = match raw.strip_prefix('<') {
+        Some(r) => r.split('>').next().unwrap_or(r),
+        // `[x](destino "título")`
+        None => raw.split_whitespace().next().unwrap_or(""),
+    };
+
+    if raw.is_empty() || raw.starts_with('#') || raw.contains("://") || raw.starts_with("mailto:") {
+        return Some((None, consumed));
+    }
+
+    let decoded = percent_decode(raw);
+    let (target, heading) = match decoded.split_once('#') {
+        Some((t, h)) => (t.to_string(), Some(h.to_string())),
+        None => (decoded, None),
+    };
+
+    if !is_note_target(&target) {
+        return Some((None, consumed));
+    }
+
+    let raw_path = raw.split('#').next().unwrap_or(raw);
+    let start = offset_in(s, raw_path);
+    let link = Link {
+        kind: LinkKind::Markdown,
+        target,
+        heading,
+        label: Some(text.to_string()),
+        line: 0,
+        target_span: start..start + raw_path.len(),
+    };
+    Some((Some(link), consumed))
+}
+
+/// Byte offset of `part` inside `outer` (`part` must be a subslice of `outer`)
+fn offset_in(outer: &str, part: &str) -> usize {
+    part.as_ptr() as usize - outer.as_ptr() as usize
+}
+
+fn shift(range: Range<usize>, by: usize) -> Range<usize> {
+    range.start + by..range.end + by
+}
+
+/// Links to attachments (images, PDFs, canvases...) are not note links
+fn is_note_target(target: &str) -> bool {
+    const ATTACHMENT_EXTS: &[&str] = &[
+        "png", "jpg", "jpeg", "gif", "svg", "webp", "bmp", "pdf", "canvas", "excalidraw", "mp3",
+        "wav", "ogg", "m4a", "mp4", "webm", "mov", "zip", "txt", "csv", "json",
+    ];
+
+    match Path::new(target).extension().and_then(|e| e.to_str()) {
+        Some(ext) => !ATTACHMENT_EXTS.contains(&ext.to_lowercase().as_str()),
+        None => true,
+    }
+}
+
+/// Decode `%XX` sequences (Obsidian writes `Mi%20Nota.md`)
+pub(crate) fn percent_decode(s: &str) -> String {
+    fn hex(b: u8) -> Option<u8> {
+        (b as char).to_digit(16).map(|d| d as u8)
+    }
+
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let (Some(hi), Some(lo)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                out.push(hi * 16 + lo);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).to_string()
+}
+
 fn extract_body_tags(body: &str) -> Vec<TagPath> {
     let mut tags = Vec::new();
     let mut in_code_block = false;
@@ -69,7 +314,7 @@ fn extract_body_tags(body: &str) -> Vec<TagPath> {
     tags
 }
 
-fn extract_hash_tags_from_line(line: &str) -> Vec<TagPath> {
+pub(crate) fn extract_hash_tags_from_line(line: &str) -> Vec<TagPath> {
     let mut tags = Vec::new();
     let bytes = line.as_bytes();
     let mut i = 0;
@@ -121,3 +366,46 @@ fn dedupe_tags(tags: Vec<TagPath>) -> Vec<TagPath> {
 
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_wikilinks() {
+        let links = extract_links_from_line("Ver [[Nota A]], [[Nota B|otra]] y ![[Nota C#Resumen]]");
+        assert_eq!(links.len(), 3);
+        assert_eq!(links[0].target, "Nota A");
+        assert_eq!(links[1].label.as_deref(), Some("otra"));
+        assert_eq!(links[2].target, "Nota C");
+        assert_eq!(links[2].heading.as_deref(), Some("Resumen"));
+        assert!(links.iter().all(|l| l.kind == LinkKind::Wiki));
+    }
+
+    #[test]
+    fn test_link_target_spans() {
+        let line = "x [[ Nota A |a]] y [doc](<dev/Mi Nota.md#api>)";
+        let links = extract_links_from_line(line);
+        assert_eq!(&line[links[0].target_span.clone()], "Nota A");
+        assert_eq!(&line[links[1].target_span.clone()], "dev/Mi Nota.md");
+    }
+
+    #[test]
+    fn test_extract_markdown_links() {
+        let links = extract_links_from_line(
+            "[doc](../dev/Mi%20Nota.md#api) [web](https://x.com) ![img](a.png) [ancla](#x)",
+        );
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].kind, LinkKind::Markdown);
+        assert_eq!(links[0].target, "../dev/Mi Nota.md");
+        assert_eq!(links[0].heading.as_deref(), Some("api"));
+    }
+
+    #[test]
+    fn test_extract_links_skips_code_blocks_and_counts_lines() {
+        let content = "---\ntags: [a]\n---\n[[uno]]\n```\n[[dos]]\n```\n[tres](tres.md)\n";
+        let links = extract_links(content);
+        let targets: Vec<(&str, usize)> = links.iter().map(|l| (l.target.as_str(), l.line)).collect();
+        assert_eq!(targets, vec![("uno", 4), ("tres.md", 8)]);
+    }
+}